@@ -0,0 +1,121 @@
+use alloc::vec::Vec;
+use core::num::NonZeroUsize;
+
+use lru::LruCache;
+
+/// A store of compiled bytecode which keeps the bytecode in memory.
+///
+/// This is the storage of all in-memory bytecode caches: it backs
+/// [`EvalCache`](crate::context::EvalCache), the cache of
+/// [`CachingLoader`](crate::loader::CachingLoader) and can be used as the store of a
+/// [`BytecodeCacheLoader`](crate::loader::BytecodeCacheLoader). A cache created with
+/// [`MemoryBytecodeCache::with_capacity`] holds at most the given number of entries, evicting
+/// the least recently used one when full.
+#[derive(Debug, Clone)]
+pub struct MemoryBytecodeCache {
+    entries: LruCache<Vec<u8>, Vec<u8>>,
+    capacity: Option<usize>,
+    evictions: u64,
+}
+
+impl Default for MemoryBytecodeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Without the loader only `EvalCache` uses the store, which is not public then.
+#[cfg_attr(not(feature = "loader"), allow(dead_code))]
+impl MemoryBytecodeCache {
+    /// Create an empty cache without a limit on the number of entries.
+    pub fn new() -> Self {
+        Self {
+            entries: LruCache::unbounded(),
+            capacity: None,
+            evictions: 0,
+        }
+    }
+
+    /// Create an empty cache holding at most `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let entries = NonZeroUsize::new(capacity)
+            .map(LruCache::new)
+            .unwrap_or_else(LruCache::unbounded);
+        Self {
+            entries,
+            capacity: Some(capacity),
+            evictions: 0,
+        }
+    }
+
+    /// Returns the maximum number of entries the cache holds, if limited.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of entries removed to keep the cache within its capacity.
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+
+    /// Returns the bytecode stored under the key, marking it as the most recently used entry.
+    pub fn get(&mut self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    /// Store the bytecode under the key, evicting the least recently used entry if the cache
+    /// is full.
+    pub fn insert(&mut self, key: Vec<u8>, bytecode: Vec<u8>) {
+        if self.capacity == Some(0) {
+            return;
+        }
+        let replaced = self.entries.contains(&key);
+        if self.entries.push(key, bytecode).is_some() && !replaced {
+            self.evictions += 1;
+        }
+    }
+
+    /// Remove the entry with the key, returning whether there was one.
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        self.entries.pop(key).is_some()
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lru_eviction() {
+        let mut cache = MemoryBytecodeCache::with_capacity(2);
+        cache.insert(b"a".to_vec(), vec![1]);
+        cache.insert(b"b".to_vec(), vec![2]);
+        assert_eq!(cache.get(b"a"), Some(&[1][..]));
+        cache.insert(b"c".to_vec(), vec![3]);
+        assert_eq!(cache.get(b"b"), None);
+        assert_eq!(cache.get(b"a"), Some(&[1][..]));
+        // Replacing an entry isn't an eviction.
+        cache.insert(b"c".to_vec(), vec![4]);
+        assert_eq!(cache.get(b"c"), Some(&[4][..]));
+        assert_eq!((cache.len(), cache.evictions()), (2, 1));
+
+        let mut cache = MemoryBytecodeCache::with_capacity(0);
+        cache.insert(b"a".to_vec(), vec![1]);
+        assert!(cache.is_empty());
+    }
+}
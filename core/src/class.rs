@@ -1,12 +1,17 @@
 //! JavaScript classes defined from Rust.
+//!
+//! Note that classes can't overload JavaScript operators: the bundled QuickJS-NG engine does not
+//! implement the operator overloading proposal (`Operators.create`). Expose operations as methods
+//! instead, e.g. `a.add(b)` instead of `a + b`.
 
 use crate::{
+    atom::PredefinedAtom,
     function::Params,
     qjs::{self},
     value::Constructor,
-    Ctx, Error, FromJs, IntoJs, JsLifetime, Object, Result, Value,
+    Atom, Ctx, Error, FromJs, Function, IntoJs, JsLifetime, Object, Result, Value,
 };
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 use core::{hash::Hash, marker::PhantomData, mem, ops::Deref, ptr::NonNull};
 
 mod cell;
@@ -23,6 +28,16 @@ pub use trace::{Trace, Tracer};
 pub mod impl_;
 
 /// The trait which allows Rust types to be used from JavaScript.
+///
+/// # Exotic properties
+///
+/// Classes with [`Self::EXOTIC`] set can provide properties dynamically through the hooks
+/// [`Self::get_own_property`], [`Self::get_own_property_names`], [`Self::delete_property`],
+/// [`Self::define_own_property`] and [`Self::has_property`], which describe the own properties
+/// of an instance. Classes which also set [`Self::HAS_PROPERTY_HOOKS`] can intercept reads and
+/// assignments with [`Self::get_property`] and [`Self::set_property`]. All hooks have defaults,
+/// so a class only overrides the ones it needs.
+#[doc(alias = "JsClassExotic")]
 pub trait JsClass<'js>: Trace<'js> + JsLifetime<'js> + Sized {
     /// The name the constructor has in JavaScript
     const NAME: &'static str;
@@ -30,6 +45,28 @@ pub trait JsClass<'js>: Trace<'js> + JsLifetime<'js> + Sized {
     /// Is this class a function.
     const CALLABLE: bool = false;
 
+    /// Can instances of this class be called with `new`.
+    ///
+    /// Only has an effect if [`Self::CALLABLE`] is also true, the call is then forwarded to
+    /// [`Self::call`] with [`Params::is_constructor`] returning true.
+    const CONSTRUCTIBLE: bool = false;
+
+    /// Are the properties of this class backed by the exotic property hooks like
+    /// [`Self::get_own_property`].
+    ///
+    /// This allows Rust data to be accessed without copying it into JavaScript values, the
+    /// `array-like` example exposes a `Vec` which can be indexed like an array this way.
+    ///
+    /// Has no effect if [`Self::CALLABLE`] is true.
+    const EXOTIC: bool = false;
+
+    /// Are reads and assignments of properties intercepted by [`Self::get_property`] and
+    /// [`Self::set_property`].
+    ///
+    /// Only has an effect if [`Self::EXOTIC`] is also true. Otherwise QuickJS reads and assigns
+    /// properties itself, using the own property hooks.
+    const HAS_PROPERTY_HOOKS: bool = false;
+
     /// Can the type be mutated while a JavaScript value.
     ///
     /// This should either be [`Readable`] or [`Writable`].
@@ -45,12 +82,118 @@ pub trait JsClass<'js>: Trace<'js> + JsLifetime<'js> + Sized {
 
     /// The function which will be called if [`Self::CALLABLE`] is true and an an object with this
     /// class is called as if it is a function.
+    ///
+    /// Use [`Params::parse`] to convert the arguments into typed values.
     fn call<'a>(this: &JsCell<'js, Self>, params: Params<'a, 'js>) -> Result<Value<'js>> {
         let _ = this;
         Ok(Value::new_undefined(params.ctx().clone()))
     }
+
+    /// Returns the value of a dynamic own property if [`Self::EXOTIC`] is true.
+    ///
+    /// Only called for properties which are not defined on the object itself. Returned values
+    /// behave like writable, enumerable and configurable data properties and shadow properties of
+    /// the prototype.
+    fn get_own_property(
+        this: &JsCell<'js, Self>,
+        ctx: &Ctx<'js>,
+        atom: Atom<'js>,
+    ) -> Result<Option<Value<'js>>> {
+        let _ = (this, ctx, atom);
+        Ok(None)
+    }
+
+    /// Returns the names of the dynamic own properties if [`Self::EXOTIC`] is true.
+    ///
+    /// The names are listed after the properties defined on the object itself, and
+    /// [`Self::get_own_property`] must return a value for each of them.
+    fn get_own_property_names(this: &JsCell<'js, Self>, ctx: &Ctx<'js>) -> Result<Vec<Atom<'js>>> {
+        let _ = (this, ctx);
+        Ok(Vec::new())
+    }
+
+    /// Deletes a dynamic own property if [`Self::EXOTIC`] is true.
+    ///
+    /// Returns false if the property can't be deleted.
+    fn delete_property(this: &JsCell<'js, Self>, ctx: &Ctx<'js>, atom: Atom<'js>) -> Result<bool> {
+        let _ = (this, ctx, atom);
+        Ok(true)
+    }
+
+    /// Defines or assigns a dynamic own property if [`Self::EXOTIC`] is true.
+    ///
+    /// Returns true if the property was handled, false to define a regular property on the object
+    /// instead. Accessor properties are always defined as regular properties.
+    fn define_own_property(
+        this: &JsCell<'js, Self>,
+        ctx: &Ctx<'js>,
+        atom: Atom<'js>,
+        value: Value<'js>,
+    ) -> Result<bool> {
+        let _ = (this, ctx, atom, value);
+        Ok(false)
+    }
+
+    /// Returns if a dynamic own property exists if [`Self::EXOTIC`] is true.
+    ///
+    /// Properties defined on the object itself and its prototype are checked if this returns
+    /// false. Defaults to checking [`Self::get_own_property`].
+    fn has_property(this: &JsCell<'js, Self>, ctx: &Ctx<'js>, atom: Atom<'js>) -> Result<bool> {
+        Self::get_own_property(this, ctx, atom).map(|x| x.is_some())
+    }
+
+    /// Reads a property if [`Self::EXOTIC`] and [`Self::HAS_PROPERTY_HOOKS`] are true.
+    ///
+    /// Called for every read of a property which is not defined on the object itself, including
+    /// reads from objects which have the instance in their prototype chain. `receiver` is the
+    /// object the property is read from. Returns `None` to continue with the default lookup,
+    /// which checks [`Self::get_own_property`] and then the prototype chain.
+    fn get_property(
+        this: &JsCell<'js, Self>,
+        ctx: &Ctx<'js>,
+        atom: Atom<'js>,
+        receiver: Value<'js>,
+    ) -> Result<Option<Value<'js>>> {
+        let _ = (this, ctx, atom, receiver);
+        Ok(None)
+    }
+
+    /// Assigns a property if [`Self::EXOTIC`] and [`Self::HAS_PROPERTY_HOOKS`] are true.
+    ///
+    /// Called for every assignment of a property which is not defined on the object itself,
+    /// including assignments to objects which have the instance in their prototype chain.
+    /// `receiver` is the object the property is assigned to. Returns true if the assignment was
+    /// handled, false to continue with the default assignment, which calls setters found in the
+    /// prototype chain or defines the property on the receiver, through
+    /// [`Self::define_own_property`] if the receiver is the instance.
+    fn set_property(
+        this: &JsCell<'js, Self>,
+        ctx: &Ctx<'js>,
+        atom: Atom<'js>,
+        value: Value<'js>,
+        receiver: Value<'js>,
+    ) -> Result<bool> {
+        let _ = (this, ctx, atom, value, receiver);
+        Ok(false)
+    }
 }
 
+/// A [`JsClass`] which can be shared between runtimes running on different threads.
+///
+/// Class ids are allocated per runtime and stored with the runtime, so no global state is shared
+/// between runtimes using the same class. This trait only adds the `Send + Sync` bounds required
+/// to move the class data along with a runtime, or to share data between runtimes on different
+/// threads. It is implemented for every class which satisfies these bounds.
+///
+/// Only available with the `"parallel"` feature: without it runtimes can't be moved between
+/// threads, so requiring this bound is rejected at compile time.
+#[cfg(feature = "parallel")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "parallel")))]
+pub trait JsClassSend<'js>: JsClass<'js> + Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<'js, C: JsClass<'js> + Send + Sync> JsClassSend<'js> for C {}
+
 /// A object which is instance of a Rust class.
 #[repr(transparent)]
 pub struct Class<'js, C: JsClass<'js>>(pub(crate) Object<'js>, PhantomData<C>);
@@ -94,13 +237,7 @@ impl<'js, C: JsClass<'js>> Deref for Class<'js, C> {
 impl<'js, C: JsClass<'js>> Class<'js, C> {
     /// Create a class from a Rust object.
     pub fn instance(ctx: Ctx<'js>, value: C) -> Result<Class<'js, C>> {
-        let id = unsafe {
-            if C::CALLABLE {
-                ctx.get_opaque().get_callable_id()
-            } else {
-                ctx.get_opaque().get_class_id()
-            }
-        };
+        let id = unsafe { ctx.get_opaque().get_class_id_of::<C>() };
 
         let prototype = Self::prototype(&ctx)?;
 
@@ -111,6 +248,9 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
 
         let ptr = Box::into_raw(Box::new(ClassCell::new(value)));
         unsafe { qjs::JS_SetOpaque(val, ptr.cast()) };
+        if C::CALLABLE && C::CONSTRUCTIBLE {
+            unsafe { qjs::JS_SetConstructorBit(ctx.as_ptr(), val, true) };
+        }
         Ok(Self(
             unsafe { Object::from_js_value(ctx, val) },
             PhantomData,
@@ -119,13 +259,7 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
 
     /// Create a class from a Rust object with a given prototype.
     pub fn instance_proto(value: C, proto: Object<'js>) -> Result<Class<'js, C>> {
-        let id = unsafe {
-            if C::CALLABLE {
-                proto.ctx().get_opaque().get_callable_id()
-            } else {
-                proto.ctx().get_opaque().get_class_id()
-            }
-        };
+        let id = unsafe { proto.ctx().get_opaque().get_class_id_of::<C>() };
 
         let val = unsafe {
             proto.ctx.handle_exception(qjs::JS_NewObjectProtoClass(
@@ -136,6 +270,9 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
         };
         let ptr = Box::into_raw(Box::new(ClassCell::new(value)));
         unsafe { qjs::JS_SetOpaque(val, ptr.cast()) };
+        if C::CALLABLE && C::CONSTRUCTIBLE {
+            unsafe { qjs::JS_SetConstructorBit(proto.ctx().as_ptr(), val, true) };
+        }
         Ok(Self(
             unsafe { Object::from_js_value(proto.ctx.clone(), val) },
             PhantomData,
@@ -149,6 +286,82 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
         unsafe { ctx.get_opaque().get_or_insert_prototype::<C>(ctx) }
     }
 
+    /// Replaces the prototype used for new instances of the class.
+    ///
+    /// Only instances created after this call use the new prototype, existing instances keep the
+    /// prototype they were created with. Like the prototype itself this affects all contexts of
+    /// the runtime.
+    pub fn set_prototype(ctx: &Ctx<'js>, proto: Option<Object<'js>>) {
+        unsafe { ctx.get_opaque().set_prototype::<C>(proto) }
+    }
+
+    /// Returns the constructor of the class.
+    ///
+    /// Returns the constructor linked to the prototype of the class if there is one, otherwise
+    /// creates it from the class definition. Returns `None` if the class has no constructor.
+    pub fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        if let Some(proto) = Self::prototype(ctx)? {
+            let desc = proto.own_property_descriptor(PredefinedAtom::Constructor)?;
+            if let Some(constructor) = desc
+                .and_then(|desc| desc.value)
+                .and_then(|value| Constructor::from_js(ctx, value).ok())
+            {
+                return Ok(Some(constructor));
+            }
+        }
+        Self::create_constructor(ctx)
+    }
+
+    /// Returns the prototype of the class like [`Class::prototype`], but returns an error if the
+    /// class has no prototype.
+    pub fn get_prototype(ctx: &Ctx<'js>) -> Result<Object<'js>> {
+        Self::prototype(ctx)?.ok_or_else(|| {
+            Error::new_into_js_message(C::NAME, "Object", "The class has no prototype")
+        })
+    }
+
+    /// Returns the constructor of the class like [`Class::constructor`], but returns an error if
+    /// the class has no constructor.
+    pub fn get_constructor(ctx: &Ctx<'js>) -> Result<Function<'js>> {
+        Self::constructor(ctx)?
+            .map(Constructor::into_inner)
+            .ok_or_else(|| {
+                Error::new_into_js_message(C::NAME, "Function", "The class has no constructor")
+            })
+    }
+
+    /// Sets the prototype of the constructor of the class.
+    ///
+    /// The constructor inherits the static properties of `proto`, like a class inherits the
+    /// static properties of the class it extends.
+    pub fn set_prototype_to(ctx: &Ctx<'js>, proto: Object<'js>) -> Result<()> {
+        Self::get_constructor(ctx)?.set_prototype(Some(&proto))
+    }
+
+    /// Sets the prototype of the prototype object of the class.
+    ///
+    /// Instances inherit the properties of `proto` and `instanceof` returns true for
+    /// constructors with `proto` as their `prototype`, which makes instances compatible with an
+    /// interface defined in JavaScript without extending it. This affects existing instances as
+    /// well as all contexts of the runtime.
+    pub fn set_instance_proto(ctx: &Ctx<'js>, proto: Object<'js>) -> Result<()> {
+        Self::get_prototype(ctx)?.set_prototype(Some(&proto))
+    }
+
+    /// Registers the class with the runtime of the context by creating its prototype.
+    ///
+    /// Class ids and prototypes are shared by all contexts of a runtime, so a class only has to
+    /// be registered once. Registering is optional as [`Class::instance`] registers the class on
+    /// first use.
+    pub fn register(ctx: &Ctx<'js>) -> Result<()> {
+        Self::prototype(ctx).map(|_| ())
+    }
+
+    /// Returns if the class is registered with the runtime of the context.
+    pub fn is_registered(ctx: &Ctx<'js>) -> bool {
+        unsafe { ctx.get_opaque().has_prototype::<C>() }
+    }
+
     /// Create a constructor for the current class using its definition.
     pub fn create_constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
         C::constructor(ctx)
@@ -225,13 +438,7 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
     /// returns a pointer to the class object.
     #[inline]
     pub(crate) fn get_class_ptr(&self) -> NonNull<ClassCell<JsCell<'js, C>>> {
-        let id = unsafe {
-            if C::CALLABLE {
-                self.ctx.get_opaque().get_callable_id()
-            } else {
-                self.ctx.get_opaque().get_class_id()
-            }
-        };
+        let id = unsafe { self.ctx.get_opaque().get_class_id_of::<C>() };
 
         let ptr = unsafe { qjs::JS_GetOpaque2(self.0.ctx.as_ptr(), self.0 .0.as_js_value(), id) };
 
@@ -274,23 +481,24 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
     pub fn from_object(object: &Object<'js>) -> Option<Self> {
         object.into_class().ok()
     }
+
+    /// Returns if the value is an instance of this class.
+    ///
+    /// Unlike the JavaScript `instanceof` operator this doesn't look at the prototype chain, so it
+    /// is true exactly when the value can be converted into this class.
+    #[inline]
+    pub fn is_instance(value: &Value<'js>) -> bool {
+        value.as_object().is_some_and(Object::instance_of::<C>)
+    }
 }
 
 impl<'js> Object<'js> {
     /// Returns if the object is of a certain Rust class.
     pub fn instance_of<C: JsClass<'js>>(&self) -> bool {
-        let id = unsafe {
-            if C::CALLABLE {
-                self.ctx.get_opaque().get_callable_id()
-            } else {
-                self.ctx.get_opaque().get_class_id()
-            }
-        };
+        let id = unsafe { self.ctx.get_opaque().get_class_id_of::<C>() };
 
         // This checks if the class is of the right class id.
-        let Some(x) = NonNull::new(unsafe {
-            qjs::JS_GetOpaque2(self.0.ctx.as_ptr(), self.0.as_js_value(), id)
-        }) else {
+        let Some(x) = NonNull::new(unsafe { qjs::JS_GetOpaque(self.0.as_js_value(), id) }) else {
             return false;
         };
 
@@ -330,6 +538,18 @@ impl<'js> Object<'js> {
     }
 }
 
+impl<'js> Value<'js> {
+    /// Turn the value into the class if it is an instance of that class, returns the value back
+    /// otherwise.
+    pub fn downcast<C: JsClass<'js>>(self) -> core::result::Result<Class<'js, C>, Value<'js>> {
+        if Class::<C>::is_instance(&self) {
+            Ok(Class(self.into_object().unwrap(), PhantomData))
+        } else {
+            Err(self)
+        }
+    }
+}
+
 impl<'js, C: JsClass<'js>> FromJs<'js> for Class<'js, C> {
     fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
         Self::from_value(&value)
@@ -350,11 +570,12 @@ mod test {
     };
 
     use crate::{
-        class::{JsClass, Readable, Trace, Tracer, Writable},
-        function::This,
-        test_with,
+        class::{JsCell, JsClass, Readable, Trace, Tracer, Writable},
+        function::{Opt, Params, This},
+        qjs, test_with,
         value::Constructor,
-        CatchResultExt, Class, Context, FromJs, Function, IntoJs, JsLifetime, Object, Runtime,
+        Atom, CatchResultExt, Class, Context, Ctx, FromJs, Function, IntoJs, JsLifetime, Object,
+        Runtime, Value,
     };
 
     /// Test circular references.
@@ -526,6 +747,32 @@ mod test {
         })
     }
 
+    #[test]
+    fn downcast() {
+        test_with(|ctx| {
+            Class::<Vec3>::define(&ctx.globals()).unwrap();
+
+            let v: Value = ctx.eval("new Vec3(1,2,3)").unwrap();
+            assert!(Class::<Vec3>::is_instance(&v));
+            let v = v.downcast::<Vec3>().unwrap();
+            approx::assert_abs_diff_eq!(v.borrow().y, 2.0);
+
+            let obj: Value = ctx.eval("({ x: 1 })").unwrap();
+            assert!(!Class::<Vec3>::is_instance(&obj));
+            let Err(obj) = obj.downcast::<Vec3>() else {
+                panic!("object was downcast to Vec3");
+            };
+            assert_eq!(obj.as_object().unwrap().get::<_, i32>("x").unwrap(), 1);
+
+            let func = Function::new(ctx.clone(), || 1).unwrap().into_value();
+            assert!(func.downcast::<Vec3>().is_err());
+            assert!(Value::new_int(ctx.clone(), 1).downcast::<Vec3>().is_err());
+
+            // A failed check doesn't leave an exception behind.
+            assert!(!unsafe { qjs::JS_HasException(ctx.as_ptr()) });
+        })
+    }
+
     #[test]
     fn extend_class() {
         test_with(|ctx| {
@@ -554,6 +801,131 @@ mod test {
         })
     }
 
+    #[test]
+    fn subclass_round_trip() {
+        test_with(|ctx| {
+            Class::<Vec3>::define(&ctx.globals()).unwrap();
+            let sum = Function::new(ctx.clone(), |v: Class<Vec3>| {
+                let v = v.borrow();
+                v.x + v.y + v.z
+            })
+            .unwrap();
+            ctx.globals().set("sum", sum).unwrap();
+
+            let v = ctx
+                .eval::<Class<Vec3>, _>(
+                    r"
+                    class Scaled extends Vec3 {
+                        add(other) {
+                            return super.add(other).add(other);
+                        }
+                    }
+                    function NoProto() {}
+                    NoProto.prototype = 1;
+
+                    const s = new Scaled(1, 2, 3);
+                    if (!(s instanceof Scaled) || !(s instanceof Vec3)) throw new Error('instanceof');
+                    if (sum(s) !== 6) throw new Error('sum');
+                    if (Object.getPrototypeOf(Reflect.construct(Vec3, [0, 0, 0], NoProto)) !== Vec3.prototype)
+                        throw new Error('fallback prototype');
+                    s.add(new Vec3(1, 1, 1));
+                    s
+                ",
+                )
+                .catch(&ctx)
+                .unwrap();
+
+            assert!(v.instance_of::<Vec3>());
+            let v = v.borrow();
+            approx::assert_abs_diff_eq!(v.x, 1.0);
+            approx::assert_abs_diff_eq!(v.z, 3.0);
+
+            let v: Vec3 = ctx.eval("s.add(new Vec3(1, 1, 1))").catch(&ctx).unwrap();
+            approx::assert_abs_diff_eq!(v.x, 3.0);
+            approx::assert_abs_diff_eq!(v.z, 5.0);
+        })
+    }
+
+    #[test]
+    fn replace_prototype() {
+        test_with(|ctx| {
+            Class::<Vec3>::define(&ctx.globals()).unwrap();
+            let constructor = Class::<Vec3>::constructor(&ctx).unwrap().unwrap();
+            let global: Constructor = ctx.globals().get("Vec3").unwrap();
+            assert_eq!(constructor.as_value(), global.as_value());
+
+            let proto = Class::<Vec3>::prototype(&ctx).unwrap().unwrap();
+            ctx.globals().set("proto", proto).unwrap();
+            let patched: bool = ctx
+                .eval(
+                    r"
+                    globalThis.old = new Vec3(1, 2, 3);
+                    const add = proto.add;
+                    proto.add = function(other) { return add.call(add.call(this, other), other) };
+                    globalThis.v = old.add(new Vec3(1, 1, 1));
+                    proto === Vec3.prototype && v.add === proto.add
+                    ",
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert!(patched);
+            let v: Vec3 = ctx.globals().get("v").unwrap();
+            approx::assert_abs_diff_eq!(v.x, 3.0);
+
+            let custom: Object = ctx
+                .eval("({ kind() { return 'custom' }, __proto__: proto })")
+                .unwrap();
+            Class::<Vec3>::set_prototype(&ctx, Some(custom));
+            let instance = Class::instance(ctx.clone(), Vec3::new(0.0, 0.0, 0.0)).unwrap();
+            ctx.globals().set("instance", instance).unwrap();
+            let kinds: Vec<Option<String>> = ctx
+                .eval("[instance.kind?.(), old.kind?.()]")
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(kinds, [Some("custom".to_string()), None]);
+        })
+    }
+
+    #[test]
+    fn interface_prototype() {
+        test_with(|ctx| {
+            Class::<Vec3>::define(&ctx.globals()).unwrap();
+            let constructor = Class::<Vec3>::get_constructor(&ctx).unwrap();
+            let proto = Class::<Vec3>::get_prototype(&ctx).unwrap();
+            let global: Value = ctx.globals().get("Vec3").unwrap();
+            assert_eq!(constructor.as_value(), &global);
+            assert_eq!(proto, constructor.get::<_, Object>("prototype").unwrap());
+
+            let shape: Object = ctx
+                .eval(
+                    r"
+                    globalThis.old = new Vec3(1, 2, 3);
+                    class Shape {
+                        static kind() { return 'shape' }
+                        area() { return 0 }
+                    }
+                    Shape
+                    ",
+                )
+                .unwrap();
+            Class::<Vec3>::set_instance_proto(&ctx, shape.get("prototype").unwrap()).unwrap();
+            Class::<Vec3>::set_prototype_to(&ctx, shape).unwrap();
+            let res: String = ctx
+                .eval(
+                    r"
+                    const v = new Vec3(1, 2, 3);
+                    [
+                        v instanceof Shape, old instanceof Shape, v instanceof Vec3,
+                        v.area(), v.add(old) instanceof Shape, Vec3.kind(),
+                    ].join()
+                    ",
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "true,true,true,0,true,shape");
+        })
+    }
+
     #[test]
     fn get_prototype() {
         pub struct X;
@@ -674,4 +1046,403 @@ mod test {
                 .unwrap();
         })
     }
+    #[test]
+    fn callable_instance() {
+        pub struct Template {
+            prefix: String,
+            calls: usize,
+        }
+
+        impl<'js> Trace<'js> for Template {
+            fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+        }
+
+        unsafe impl<'js> JsLifetime<'js> for Template {
+            type Changed<'to> = Template;
+        }
+
+        impl<'js> JsClass<'js> for Template {
+            const NAME: &'static str = "Template";
+
+            const CALLABLE: bool = true;
+
+            const CONSTRUCTIBLE: bool = true;
+
+            type Mutable = Writable;
+
+            fn constructor(_ctx: &crate::Ctx<'js>) -> crate::Result<Option<Constructor<'js>>> {
+                Ok(None)
+            }
+
+            fn call<'a>(
+                this: &JsCell<'js, Self>,
+                params: Params<'a, 'js>,
+            ) -> crate::Result<crate::Value<'js>> {
+                let ctx = params.ctx().clone();
+                if params.is_constructor() {
+                    let object = Object::new(ctx.clone())?;
+                    object.set("prefix", this.borrow().prefix.clone())?;
+                    return Ok(object.into_value());
+                }
+                let (Opt(arg),): (Opt<String>,) = params.parse()?;
+                let mut this = this.borrow_mut();
+                this.calls += 1;
+                format!("{}{}", this.prefix, arg.unwrap_or_default()).into_js(&ctx)
+            }
+        }
+
+        test_with(|ctx| {
+            let tmpl = Class::instance(
+                ctx.clone(),
+                Template {
+                    prefix: "hello ".to_string(),
+                    calls: 0,
+                },
+            )
+            .unwrap();
+            ctx.globals().set("tmpl", tmpl.clone()).unwrap();
+
+            let res: String = ctx.eval(r#"tmpl("x")"#).catch(&ctx).unwrap();
+            assert_eq!(res, "hello x");
+            let res: String = ctx.eval(r#"tmpl()"#).catch(&ctx).unwrap();
+            assert_eq!(res, "hello ");
+            assert_eq!(tmpl.borrow().calls, 2);
+
+            let res: String = ctx.eval(r#"new tmpl().prefix"#).catch(&ctx).unwrap();
+            assert_eq!(res, "hello ");
+            let ty: String = ctx.eval(r#"typeof tmpl"#).catch(&ctx).unwrap();
+            assert_eq!(ty, "function");
+        })
+    }
+
+    #[test]
+    fn non_callable_instance() {
+        test_with(|ctx| {
+            ctx.globals()
+                .set("v", Class::instance(ctx.clone(), Vec3::new(1.0, 2.0, 3.0)))
+                .unwrap();
+            let res: String = ctx
+                .eval(
+                    r#"
+                    try {
+                        v();
+                        "no error"
+                    } catch (e) {
+                        `${e.name}: ${e.message}`
+                    }
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert!(res.starts_with("TypeError"), "{res}");
+            assert!(res.contains("not a function"), "{res}");
+        })
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn send_class_multiple_runtimes() {
+        use super::JsClassSend;
+
+        fn run<C>(value: C) -> C
+        where
+            for<'js> C: JsClassSend<'js> + IntoJs<'js> + FromJs<'js> + 'static,
+        {
+            let rt = Runtime::new().unwrap();
+            let ctx = Context::full(&rt).unwrap();
+            ctx.with(|ctx| {
+                ctx.globals().set("v", value).unwrap();
+                ctx.eval("v.add(v)").catch(&ctx).unwrap()
+            })
+        }
+
+        let threads: Vec<_> = (0..2)
+            .map(|i| std::thread::spawn(move || run(Vec3::new(i as f32, 0.0, 0.0))))
+            .collect();
+        let res: Vec<_> = threads.into_iter().map(|x| x.join().unwrap().x).collect();
+        assert_eq!(res, [0.0, 2.0]);
+    }
+
+    #[test]
+    fn exotic_properties() {
+        pub struct Dict(std::collections::BTreeMap<String, String>);
+
+        impl<'js> Trace<'js> for Dict {
+            fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+        }
+
+        unsafe impl<'js> JsLifetime<'js> for Dict {
+            type Changed<'to> = Dict;
+        }
+
+        impl<'js> JsClass<'js> for Dict {
+            const NAME: &'static str = "Dict";
+
+            const EXOTIC: bool = true;
+
+            type Mutable = Writable;
+
+            fn prototype(ctx: &Ctx<'js>) -> crate::Result<Option<Object<'js>>> {
+                let proto = Object::new(ctx.clone())?;
+                let func =
+                    Function::new(ctx.clone(), |this: This<Class<Dict>>| this.borrow().0.len())?;
+                proto.set("size", func)?;
+                Ok(Some(proto))
+            }
+
+            fn constructor(_ctx: &Ctx<'js>) -> crate::Result<Option<Constructor<'js>>> {
+                Ok(None)
+            }
+
+            fn get_own_property(
+                this: &JsCell<'js, Self>,
+                ctx: &Ctx<'js>,
+                atom: Atom<'js>,
+            ) -> crate::Result<Option<Value<'js>>> {
+                let key = atom.to_string()?;
+                this.borrow()
+                    .0
+                    .get(&key)
+                    .map(|x| x.as_str().into_js(ctx))
+                    .transpose()
+            }
+
+            fn get_own_property_names(
+                this: &JsCell<'js, Self>,
+                ctx: &Ctx<'js>,
+            ) -> crate::Result<Vec<Atom<'js>>> {
+                this.borrow()
+                    .0
+                    .keys()
+                    .map(|x| Atom::from_str(ctx.clone(), x))
+                    .collect()
+            }
+
+            fn delete_property(
+                this: &JsCell<'js, Self>,
+                _ctx: &Ctx<'js>,
+                atom: Atom<'js>,
+            ) -> crate::Result<bool> {
+                this.borrow_mut().0.remove(&atom.to_string()?);
+                Ok(true)
+            }
+
+            fn define_own_property(
+                this: &JsCell<'js, Self>,
+                _ctx: &Ctx<'js>,
+                atom: Atom<'js>,
+                value: Value<'js>,
+            ) -> crate::Result<bool> {
+                let Some(value) = value.as_string() else {
+                    return Ok(false);
+                };
+                this.borrow_mut()
+                    .0
+                    .insert(atom.to_string()?, value.to_string()?);
+                Ok(true)
+            }
+        }
+
+        test_with(|ctx| {
+            let dict = Dict(
+                [("a", "1"), ("b", "2")]
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            );
+            let dict = Class::instance(ctx.clone(), dict).unwrap();
+            ctx.globals().set("d", dict.clone()).unwrap();
+
+            let res: String = ctx
+                .eval(r#"`${d.a},${d.b},${Object.keys(d).join("")},${"a" in d},${"c" in d}`"#)
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "1,2,ab,true,false");
+
+            let res: String = ctx
+                .eval(r#"delete d.a; d.c = "3"; d.n = 4; Object.keys(d).join("")"#)
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "nbc");
+            assert_eq!(dict.borrow().0.len(), 2);
+            assert_eq!(dict.borrow().0["c"], "3");
+
+            // Prototype methods are visible unless shadowed by a dynamic property.
+            let res: String = ctx
+                .eval(r#"`${"size" in d},${d.size()}`"#)
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "true,2");
+            let res: String = ctx
+                .eval(r#"d.size = "big"; `${typeof d.size},${d.n}`"#)
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "string,4");
+        })
+    }
+
+    #[test]
+    fn exotic_get_set_property() {
+        pub struct Recorder {
+            values: std::collections::BTreeMap<String, i32>,
+            foreign_receivers: usize,
+        }
+
+        impl<'js> Trace<'js> for Recorder {
+            fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+        }
+
+        unsafe impl<'js> JsLifetime<'js> for Recorder {
+            type Changed<'to> = Recorder;
+        }
+
+        impl<'js> JsClass<'js> for Recorder {
+            const NAME: &'static str = "Recorder";
+
+            const EXOTIC: bool = true;
+
+            const HAS_PROPERTY_HOOKS: bool = true;
+
+            type Mutable = Writable;
+
+            fn prototype(ctx: &Ctx<'js>) -> crate::Result<Option<Object<'js>>> {
+                ctx.eval(
+                    r#"Object.defineProperties({}, {
+                        who: { get() { return this.tag } },
+                        ro: { value: 1 },
+                    })"#,
+                )
+                .map(Some)
+            }
+
+            fn constructor(_ctx: &Ctx<'js>) -> crate::Result<Option<Constructor<'js>>> {
+                Ok(None)
+            }
+
+            fn get_property(
+                this: &JsCell<'js, Self>,
+                ctx: &Ctx<'js>,
+                atom: Atom<'js>,
+                receiver: Value<'js>,
+            ) -> crate::Result<Option<Value<'js>>> {
+                let name = atom.to_string()?;
+                let Some(key) = name.strip_prefix("dyn_") else {
+                    return Ok(None);
+                };
+                let own = Class::<Self>::from_value(&receiver).is_ok();
+                let value = this.borrow().values.get(key).copied().unwrap_or(-1);
+                format!("{key}={value},{own}").into_js(ctx).map(Some)
+            }
+
+            fn set_property(
+                this: &JsCell<'js, Self>,
+                _ctx: &Ctx<'js>,
+                atom: Atom<'js>,
+                value: Value<'js>,
+                receiver: Value<'js>,
+            ) -> crate::Result<bool> {
+                let name = atom.to_string()?;
+                let Some(key) = name.strip_prefix("dyn_") else {
+                    return Ok(false);
+                };
+                let mut this = this.borrow_mut();
+                if Class::<Self>::from_value(&receiver).is_err() {
+                    this.foreign_receivers += 1;
+                }
+                this.values.insert(key.into(), value.get::<i32>()?);
+                Ok(true)
+            }
+        }
+
+        test_with(|ctx| {
+            let recorder = Recorder {
+                values: Default::default(),
+                foreign_receivers: 0,
+            };
+            let recorder = Class::instance(ctx.clone(), recorder).unwrap();
+            ctx.globals().set("r", recorder.clone()).unwrap();
+
+            let res: String = ctx
+                .eval(r#"r.dyn_a = 5; r.plain = 1; [r.dyn_a, r.dyn_b, r.plain, Object.keys(r)].join(";")"#)
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "a=5,true;b=-1,true;1;plain");
+            assert_eq!(recorder.borrow().values["a"], 5);
+
+            // The hooks and the default lookup receive the object the property is accessed on.
+            let res: String = ctx
+                .eval(
+                    r#"
+                    const child = Object.create(r);
+                    child.tag = "child";
+                    child.dyn_c = 3;
+                    [child.who, child.dyn_c, Object.keys(child)].join(";")
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "child;c=3,false;tag");
+            assert_eq!(recorder.borrow().foreign_receivers, 1);
+
+            // Assignments which aren't handled follow the usual rules.
+            let sloppy = crate::context::EvalOptions {
+                strict: false,
+                ..Default::default()
+            };
+            let res: String = ctx
+                .eval_with_options(r#"r.ro = 2; r.tag = "r"; [r.ro, r.who].join()"#, sloppy)
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "1,r");
+            let err = ctx.eval::<(), _>("r.ro = 2").catch(&ctx).unwrap_err();
+            assert!(err.to_string().contains("read-only"), "{err}");
+
+            // The default lookup is done by QuickJS, so proxies in the prototype chain are used.
+            let res: String = ctx
+                .eval(
+                    r#"
+                    let trapped = [];
+                    Object.setPrototypeOf(Object.getPrototypeOf(r), new Proxy({}, {
+                        get: (target, key, receiver) => `get ${key},${receiver === r}`,
+                        set: (target, key, value, receiver) => trapped.push(key, value) > 0,
+                    }));
+                    r.viaProxy = 4;
+                    [r.viaProxy, trapped, Object.keys(r)].join(";")
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "get viaProxy,true;viaProxy,4;plain,tag");
+        })
+    }
+
+    #[test]
+    fn shared_between_contexts() {
+        let rt = Runtime::new().unwrap();
+        let ctx_a = Context::full(&rt).unwrap();
+        let ctx_b = Context::full(&rt).unwrap();
+
+        let v = ctx_a.with(|ctx| {
+            assert!(!Class::<Vec3>::is_registered(&ctx));
+            Class::<Vec3>::register(&ctx).unwrap();
+            assert!(Class::<Vec3>::is_registered(&ctx));
+            crate::Persistent::save(
+                &ctx,
+                Class::instance(ctx.clone(), Vec3::new(1.0, 2.0, 3.0)).unwrap(),
+            )
+        });
+
+        ctx_b.with(|ctx| {
+            assert!(Class::<Vec3>::is_registered(&ctx));
+            let a = v.restore(&ctx).unwrap();
+            let b = Class::instance(ctx.clone(), Vec3::new(1.0, 1.0, 1.0)).unwrap();
+            assert!(a.instance_of::<Vec3>());
+            assert_eq!(a.get_prototype(), b.get_prototype());
+
+            ctx.globals().set("a", a).unwrap();
+            ctx.globals().set("b", b).unwrap();
+            let v: Vec3 = ctx.eval("a.add(b)").catch(&ctx).unwrap();
+            approx::assert_abs_diff_eq!(v.z, 4.0);
+        });
+    }
 }
@@ -1,7 +1,9 @@
 use super::{JsClass, Tracer};
-use crate::{class::JsCell, function::Params, qjs, runtime::opaque::Opaque, Value};
+use crate::{
+    class::JsCell, function::Params, qjs, runtime::opaque::Opaque, Atom, Ctx, Object, Value,
+};
 use alloc::boxed::Box;
-use core::{any::TypeId, panic::AssertUnwindSafe, ptr::NonNull};
+use core::{any::TypeId, mem, panic::AssertUnwindSafe, ptr::NonNull};
 
 /// FFI finalizer, destroying the object once it is delete by the Gc.
 pub(crate) unsafe extern "C" fn class_finalizer(rt: *mut qjs::JSRuntime, val: qjs::JSValue) {
@@ -61,6 +63,245 @@ pub(crate) unsafe extern "C" fn call(
     (ptr.as_ref().v_table.call)(ptr, ctx, function, this, argc, argv, flags)
 }
 
+/// FFI finalizer of classes with exotic behavior, destroying the object once it is deleted by
+/// the Gc.
+///
+/// Used by both exotic class ids, with and without property hooks, so the cell is looked up
+/// with the class id of the object itself instead of one stored in the runtime opaque.
+pub(crate) unsafe extern "C" fn exotic_finalizer(_rt: *mut qjs::JSRuntime, val: qjs::JSValue) {
+    let ptr = exotic_cell(val);
+    (ptr.as_ref().v_table.finalizer)(ptr)
+}
+
+/// FFI tracing function for classes with exotic behavior.
+pub(crate) unsafe extern "C" fn exotic_trace(
+    rt: *mut qjs::JSRuntime,
+    val: qjs::JSValue,
+    mark_func: qjs::JS_MarkFunc,
+) {
+    let ptr = exotic_cell(val);
+    let tracer = Tracer::from_ffi(rt, mark_func);
+    (ptr.as_ref().v_table.trace)(ptr, tracer)
+}
+
+/// The exotic methods of classes with exotic behavior, forwarding to the hooks in the v table.
+///
+/// Reads and assignments are left to QuickJS, which uses the own property hooks.
+pub(crate) static EXOTIC_METHODS: qjs::JSClassExoticMethods = qjs::JSClassExoticMethods {
+    get_own_property: Some(exotic_get_own_property),
+    get_own_property_names: Some(exotic_get_own_property_names),
+    delete_property: Some(exotic_delete_property),
+    define_own_property: Some(exotic_define_own_property),
+    has_property: Some(exotic_has_property),
+    get_property: None,
+    set_property: None,
+};
+
+/// The exotic methods of classes with [`JsClass::HAS_PROPERTY_HOOKS`], which also intercept
+/// reads and assignments.
+pub(crate) static EXOTIC_HOOK_METHODS: qjs::JSClassExoticMethods = qjs::JSClassExoticMethods {
+    get_property: Some(exotic_get_property),
+    set_property: Some(exotic_set_property),
+    ..EXOTIC_METHODS
+};
+
+/// Returns the class cell of an instance of an exotic class, with or without property hooks.
+unsafe fn exotic_cell(obj: qjs::JSValue) -> NonNull<ClassCell<()>> {
+    let ptr = qjs::JS_GetOpaque(obj, qjs::JS_GetClassID(obj));
+    NonNull::new(ptr).unwrap().cast::<ClassCell<()>>()
+}
+
+unsafe fn exotic_v_table(obj: qjs::JSValue) -> &'static ExoticVTable {
+    &exotic_cell(obj).as_ref().v_table.exotic
+}
+
+unsafe extern "C" fn exotic_get_own_property(
+    ctx: *mut qjs::JSContext,
+    desc: *mut qjs::JSPropertyDescriptor,
+    obj: qjs::JSValue,
+    prop: qjs::JSAtom,
+) -> qjs::c_int {
+    (exotic_v_table(obj).get_own_property)(ctx, desc, obj, prop)
+}
+
+unsafe extern "C" fn exotic_get_own_property_names(
+    ctx: *mut qjs::JSContext,
+    ptab: *mut *mut qjs::JSPropertyEnum,
+    plen: *mut u32,
+    obj: qjs::JSValue,
+) -> qjs::c_int {
+    (exotic_v_table(obj).get_own_property_names)(ctx, ptab, plen, obj)
+}
+
+unsafe extern "C" fn exotic_delete_property(
+    ctx: *mut qjs::JSContext,
+    obj: qjs::JSValue,
+    prop: qjs::JSAtom,
+) -> qjs::c_int {
+    (exotic_v_table(obj).delete_property)(ctx, obj, prop)
+}
+
+unsafe extern "C" fn exotic_define_own_property(
+    ctx: *mut qjs::JSContext,
+    obj: qjs::JSValue,
+    prop: qjs::JSAtom,
+    val: qjs::JSValue,
+    getter: qjs::JSValue,
+    setter: qjs::JSValue,
+    flags: qjs::c_int,
+) -> qjs::c_int {
+    const ACCESSOR: qjs::c_int = (qjs::JS_PROP_HAS_GET | qjs::JS_PROP_HAS_SET) as qjs::c_int;
+    if flags & qjs::JS_PROP_HAS_VALUE as qjs::c_int != 0 && flags & ACCESSOR == 0 {
+        let res = (exotic_v_table(obj).define_own_property)(ctx, obj, prop, val);
+        if res != 0 {
+            return res;
+        }
+    }
+    qjs::JS_DefineProperty(
+        ctx,
+        obj,
+        prop,
+        val,
+        getter,
+        setter,
+        flags | qjs::JS_PROP_NO_EXOTIC as qjs::c_int,
+    )
+}
+
+unsafe extern "C" fn exotic_has_property(
+    ctx: *mut qjs::JSContext,
+    obj: qjs::JSValue,
+    prop: qjs::JSAtom,
+) -> qjs::c_int {
+    let res = (exotic_v_table(obj).has_property)(ctx, obj, prop);
+    if res != 0 {
+        return res;
+    }
+    // Fall back to the regular properties and the prototype chain.
+    let res = qjs::JS_GetOwnProperty(ctx, core::ptr::null_mut(), obj, prop);
+    if res != 0 {
+        return res;
+    }
+    let proto = qjs::JS_GetPrototype(ctx, obj);
+    if qjs::JS_IsException(proto) {
+        return -1;
+    }
+    let res = if qjs::JS_IsObject(proto) {
+        qjs::JS_HasProperty(ctx, proto, prop)
+    } else {
+        0
+    };
+    qjs::JS_FreeValue(ctx, proto);
+    res
+}
+
+unsafe extern "C" fn exotic_get_property(
+    ctx: *mut qjs::JSContext,
+    obj: qjs::JSValue,
+    prop: qjs::JSAtom,
+    receiver: qjs::JSValue,
+) -> qjs::JSValue {
+    (exotic_v_table(obj).get_property)(ctx, obj, prop, receiver)
+}
+
+unsafe extern "C" fn exotic_set_property(
+    ctx: *mut qjs::JSContext,
+    obj: qjs::JSValue,
+    prop: qjs::JSAtom,
+    val: qjs::JSValue,
+    receiver: qjs::JSValue,
+    flags: qjs::c_int,
+) -> qjs::c_int {
+    (exotic_v_table(obj).set_property)(ctx, obj, prop, val, receiver, flags)
+}
+
+/// Continue reading a property not found by the hooks with the prototype, calling getters with
+/// the receiver like QuickJS does for exotic objects without the `get_property` method.
+unsafe fn get_from_prototype<'js>(
+    obj: &Object<'js>,
+    prop: qjs::JSAtom,
+    receiver: &Value<'js>,
+) -> crate::Result<Value<'js>> {
+    let ctx = obj.ctx();
+    let Some(proto) = obj.get_prototype() else {
+        return Ok(Value::new_undefined(ctx.clone()));
+    };
+    let value = qjs::rquickjs_get_property_receiver(
+        ctx.as_ptr(),
+        proto.as_js_value(),
+        prop,
+        receiver.as_js_value(),
+    );
+    let value = ctx.handle_exception(value)?;
+    Ok(Value::from_js_value(ctx.clone(), value))
+}
+
+/// Continue an assignment not handled by the hooks like QuickJS does for exotic objects without
+/// the `set_property` method.
+///
+/// `has_own` is whether [`JsClass::get_own_property`] returned a value, dynamic own properties
+/// are writable data properties.
+unsafe fn set_default<'js>(
+    obj: &Object<'js>,
+    prop: qjs::JSAtom,
+    value: &Value<'js>,
+    receiver: &Value<'js>,
+    has_own: bool,
+    flags: qjs::c_int,
+) -> crate::Result<qjs::c_int> {
+    let ctx = obj.ctx();
+    let res = if has_own && receiver.as_object() == Some(obj) {
+        qjs::JS_DefineProperty(
+            ctx.as_ptr(),
+            obj.as_js_value(),
+            prop,
+            value.as_js_value(),
+            qjs::JS_UNDEFINED,
+            qjs::JS_UNDEFINED,
+            qjs::JS_PROP_HAS_VALUE as qjs::c_int,
+        )
+    } else {
+        // A found property ends the lookup, the property is then created on the receiver. This
+        // is what QuickJS does when the prototype chain ends, so an empty object without a
+        // prototype is used in place of the chain.
+        let proto = match obj.get_prototype() {
+            Some(proto) if !has_own => proto,
+            _ => {
+                let empty = qjs::JS_NewObjectProto(ctx.as_ptr(), qjs::JS_NULL);
+                Object::from_js_value(ctx.clone(), ctx.handle_exception(empty)?)
+            }
+        };
+        qjs::rquickjs_set_property_receiver(
+            ctx.as_ptr(),
+            proto.as_js_value(),
+            prop,
+            value.clone().into_js_value(),
+            receiver.as_js_value(),
+            flags,
+        )
+    };
+    if res < 0 {
+        return Err(ctx.raise_exception());
+    }
+    Ok(res)
+}
+
+/// Run a hook returning an integer, turning errors and panics into exceptions.
+unsafe fn handle_exotic<'js, F>(ctx: &Ctx<'js>, f: F) -> qjs::c_int
+where
+    F: FnOnce() -> crate::Result<qjs::c_int>,
+{
+    let res = ctx.handle_panic(AssertUnwindSafe(|| match f() {
+        Ok(x) => qjs::JS_MKVAL(qjs::JS_TAG_INT, x),
+        Err(e) => e.throw(ctx),
+    }));
+    if qjs::JS_IsException(res) {
+        -1
+    } else {
+        qjs::JS_VALUE_GET_INT(res)
+    }
+}
+
 pub(crate) type FinalizerFunc = unsafe fn(this: NonNull<ClassCell<()>>);
 pub(crate) type TraceFunc =
     for<'a> unsafe fn(this: NonNull<ClassCell<()>>, tracer: Tracer<'a, 'static>);
@@ -76,11 +317,201 @@ pub(crate) type CallFunc = for<'a> unsafe fn(
 
 pub(crate) type TypeIdFn = fn() -> TypeId;
 
+pub(crate) struct ExoticVTable {
+    get_own_property: unsafe fn(
+        ctx: *mut qjs::JSContext,
+        desc: *mut qjs::JSPropertyDescriptor,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+    ) -> qjs::c_int,
+    get_own_property_names: unsafe fn(
+        ctx: *mut qjs::JSContext,
+        ptab: *mut *mut qjs::JSPropertyEnum,
+        plen: *mut u32,
+        obj: qjs::JSValue,
+    ) -> qjs::c_int,
+    delete_property:
+        unsafe fn(ctx: *mut qjs::JSContext, obj: qjs::JSValue, prop: qjs::JSAtom) -> qjs::c_int,
+    define_own_property: unsafe fn(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+        val: qjs::JSValue,
+    ) -> qjs::c_int,
+    has_property:
+        unsafe fn(ctx: *mut qjs::JSContext, obj: qjs::JSValue, prop: qjs::JSAtom) -> qjs::c_int,
+    get_property: unsafe fn(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+        receiver: qjs::JSValue,
+    ) -> qjs::JSValue,
+    set_property: unsafe fn(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+        val: qjs::JSValue,
+        receiver: qjs::JSValue,
+        flags: qjs::c_int,
+    ) -> qjs::c_int,
+}
+
+impl ExoticVTable {
+    unsafe fn cell<'a, 'js, C: JsClass<'js>>(obj: qjs::JSValue) -> &'a JsCell<'js, C> {
+        &exotic_cell(obj)
+            .cast::<ClassCell<JsCell<'js, C>>>()
+            .as_ref()
+            .data
+    }
+
+    unsafe fn get_own_property_impl<'js, C: JsClass<'js>>(
+        ctx: *mut qjs::JSContext,
+        desc: *mut qjs::JSPropertyDescriptor,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+    ) -> qjs::c_int {
+        let ctx = Ctx::from_ptr(ctx);
+        handle_exotic(&ctx, || {
+            let atom = Atom::from_atom_val_dup(ctx.clone(), prop);
+            let Some(value) = C::get_own_property(Self::cell::<C>(obj), &ctx, atom)? else {
+                return Ok(0);
+            };
+            if let Some(desc) = desc.as_mut() {
+                desc.flags = qjs::JS_PROP_C_W_E as qjs::c_int;
+                desc.value = value.into_js_value();
+                desc.getter = qjs::JS_UNDEFINED;
+                desc.setter = qjs::JS_UNDEFINED;
+            }
+            Ok(1)
+        })
+    }
+
+    unsafe fn get_own_property_names_impl<'js, C: JsClass<'js>>(
+        ctx: *mut qjs::JSContext,
+        ptab: *mut *mut qjs::JSPropertyEnum,
+        plen: *mut u32,
+        obj: qjs::JSValue,
+    ) -> qjs::c_int {
+        let ctx = Ctx::from_ptr(ctx);
+        handle_exotic(&ctx, || {
+            let names = C::get_own_property_names(Self::cell::<C>(obj), &ctx)?;
+            // Allocate at least a single element as QuickJS frees the table even if it is empty.
+            let tab = qjs::js_malloc(
+                ctx.as_ptr(),
+                (mem::size_of::<qjs::JSPropertyEnum>() * names.len().max(1)) as _,
+            )
+            .cast::<qjs::JSPropertyEnum>();
+            if tab.is_null() {
+                return Err(crate::Error::Allocation);
+            }
+            let len = names.len();
+            for (idx, name) in names.iter().enumerate() {
+                // The table owns a reference to each atom.
+                tab.add(idx).write(qjs::JSPropertyEnum {
+                    is_enumerable: true,
+                    atom: qjs::JS_DupAtom(ctx.as_ptr(), name.atom),
+                });
+            }
+            *ptab = tab;
+            *plen = len as u32;
+            Ok(0)
+        })
+    }
+
+    unsafe fn delete_property_impl<'js, C: JsClass<'js>>(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+    ) -> qjs::c_int {
+        let ctx = Ctx::from_ptr(ctx);
+        handle_exotic(&ctx, || {
+            let atom = Atom::from_atom_val_dup(ctx.clone(), prop);
+            C::delete_property(Self::cell::<C>(obj), &ctx, atom).map(qjs::c_int::from)
+        })
+    }
+
+    unsafe fn define_own_property_impl<'js, C: JsClass<'js>>(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+        val: qjs::JSValue,
+    ) -> qjs::c_int {
+        let ctx = Ctx::from_ptr(ctx);
+        handle_exotic(&ctx, || {
+            let atom = Atom::from_atom_val_dup(ctx.clone(), prop);
+            let value = Value::from_js_value_const(ctx.clone(), val);
+            C::define_own_property(Self::cell::<C>(obj), &ctx, atom, value).map(qjs::c_int::from)
+        })
+    }
+
+    unsafe fn has_property_impl<'js, C: JsClass<'js>>(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+    ) -> qjs::c_int {
+        let ctx = Ctx::from_ptr(ctx);
+        handle_exotic(&ctx, || {
+            let atom = Atom::from_atom_val_dup(ctx.clone(), prop);
+            C::has_property(Self::cell::<C>(obj), &ctx, atom).map(qjs::c_int::from)
+        })
+    }
+
+    unsafe fn get_property_impl<'js, C: JsClass<'js>>(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+        receiver: qjs::JSValue,
+    ) -> qjs::JSValue {
+        let ctx = Ctx::from_ptr(ctx);
+        ctx.handle_panic(AssertUnwindSafe(|| {
+            let res = (|| {
+                let cell = Self::cell::<C>(obj);
+                let atom = Atom::from_atom_val_dup(ctx.clone(), prop);
+                let receiver = Value::from_js_value_const(ctx.clone(), receiver);
+                if let Some(value) = C::get_property(cell, &ctx, atom.clone(), receiver.clone())? {
+                    return Ok(value);
+                }
+                if let Some(value) = C::get_own_property(cell, &ctx, atom)? {
+                    return Ok(value);
+                }
+                let obj = Object(Value::from_js_value_const(ctx.clone(), obj));
+                get_from_prototype(&obj, prop, &receiver)
+            })();
+            res.map(Value::into_js_value)
+                .unwrap_or_else(|e| e.throw(&ctx))
+        }))
+    }
+
+    unsafe fn set_property_impl<'js, C: JsClass<'js>>(
+        ctx: *mut qjs::JSContext,
+        obj: qjs::JSValue,
+        prop: qjs::JSAtom,
+        val: qjs::JSValue,
+        receiver: qjs::JSValue,
+        flags: qjs::c_int,
+    ) -> qjs::c_int {
+        let ctx = Ctx::from_ptr(ctx);
+        handle_exotic(&ctx, || {
+            let cell = Self::cell::<C>(obj);
+            let atom = Atom::from_atom_val_dup(ctx.clone(), prop);
+            let value = Value::from_js_value_const(ctx.clone(), val);
+            let receiver = Value::from_js_value_const(ctx.clone(), receiver);
+            if C::set_property(cell, &ctx, atom.clone(), value.clone(), receiver.clone())? {
+                return Ok(1);
+            }
+            let has_own = C::get_own_property(cell, &ctx, atom)?.is_some();
+            let obj = Object(Value::from_js_value_const(ctx.clone(), obj));
+            set_default(&obj, prop, &value, &receiver, has_own, flags)
+        })
+    }
+}
+
 pub(crate) struct VTable {
     id_fn: TypeIdFn,
     finalizer: FinalizerFunc,
     trace: TraceFunc,
     call: CallFunc,
+    exotic: ExoticVTable,
 }
 
 impl VTable {
@@ -130,6 +561,15 @@ impl VTable {
                 finalizer: VTable::finalizer_impl::<'js, C>,
                 trace: VTable::trace_impl::<C>,
                 call: VTable::call_impl::<C>,
+                exotic: ExoticVTable {
+                    get_own_property: ExoticVTable::get_own_property_impl::<C>,
+                    get_own_property_names: ExoticVTable::get_own_property_names_impl::<C>,
+                    delete_property: ExoticVTable::delete_property_impl::<C>,
+                    define_own_property: ExoticVTable::define_own_property_impl::<C>,
+                    has_property: ExoticVTable::has_property_impl::<C>,
+                    get_property: ExoticVTable::get_property_impl::<C>,
+                    set_property: ExoticVTable::set_property_impl::<C>,
+                },
             };
         }
         &<C as HasVTable>::VTABLE
@@ -1,6 +1,12 @@
 //! Helper classes and functions for use inside the macros.
 
-use crate::{value::Constructor, Ctx, Object, Result};
+use crate::{
+    class::{JsCell, JsClass},
+    function::Params,
+    value::Constructor,
+    Ctx, Exception, Object, Result, Value,
+};
+use alloc::format;
 use core::marker::PhantomData;
 
 /// Trait used for borrow specialization for implementing methods without access to the class.
@@ -17,6 +23,20 @@ pub trait ConstructorCreator<'js, T>: Sized {
     }
 }
 
+/// Trait used for borrow specialization for implementing the call handler of callable classes
+/// without access to the class.
+///
+/// The default implementation is used by callable classes without a `#[qjs(call)]` method and
+/// throws a `TypeError`, like calling any other object which isn't a function.
+pub trait CallImplementor<'js, T: JsClass<'js>>: Sized {
+    fn call<'a>(&self, _this: &JsCell<'js, T>, params: Params<'a, 'js>) -> Result<Value<'js>> {
+        Err(Exception::throw_type(
+            params.ctx(),
+            &format!("{} is not a function", T::NAME),
+        ))
+    }
+}
+
 /// A helper type for borrow specialization
 #[derive(Default)]
 pub struct MethodImpl<T>(PhantomData<T>);
@@ -37,6 +57,16 @@ impl<T> ConstructorCreate<T> {
     }
 }
 
+/// A helper type for borrow specialization
+#[derive(Default)]
+pub struct CallImpl<T>(PhantomData<T>);
+
+impl<T> CallImpl<T> {
+    pub fn new() -> Self {
+        CallImpl(PhantomData)
+    }
+}
+
 /// Specialization isn't stabilized yet so in the macro we can't normally have a default
 /// implementation for class prototypes if it doesn't have an associated impl item.
 ///
@@ -61,6 +91,8 @@ impl<T> MethodImplementor<T> for &MethodImpl<T> {}
 
 impl<'js, T> ConstructorCreator<'js, T> for &ConstructorCreate<T> {}
 
+impl<'js, T: JsClass<'js>> CallImplementor<'js, T> for &CallImpl<T> {}
+
 /// A helper struct to implement [`FromJs`](crate::FromJs) for types which implement [`Clone`].
 pub struct CloneWrapper<'a, T>(pub &'a T);
 /// A helper trait to implement [`FromJs`](crate::FromJs) for types which implement [`Clone`].
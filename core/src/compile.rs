@@ -0,0 +1,285 @@
+//! Compiling modules to bytecode ahead of time, for example in a build script.
+//!
+//! [`compile_module`] compiles a module in a throwaway runtime and returns its bytecode prefixed
+//! with a header recording the QuickJS version, the version of this crate and the endianness
+//! it was compiled with. [`Module::load_bytecode`] checks the header before loading the module,
+//! as bytecode is only compatible with the exact version it was compiled by.
+//!
+//! ```no_run
+//! // build.rs
+//! use std::{env, fs, path::Path};
+//!
+//! fn main() {
+//!     let source = fs::read_to_string("js/main.js").unwrap();
+//!     let bytecode = rquickjs::compile::compile_module("main", source).unwrap();
+//!     let out = Path::new(&env::var("OUT_DIR").unwrap()).join("main.qjsbc");
+//!     fs::write(out, bytecode).unwrap();
+//!     println!("cargo:rerun-if-changed=js/main.js");
+//! }
+//! ```
+//!
+//! The crate then includes the bytecode and loads it at runtime:
+//!
+//! ```ignore
+//! static MAIN: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/main.qjsbc"));
+//!
+//! let module = unsafe { Module::load_bytecode(ctx.clone(), MAIN) }?;
+//! ```
+
+use crate::{
+    loader::{Loader, Resolver},
+    module::Declared,
+    qjs, CatchResultExt, Context, Ctx, Error, Module, Result, Runtime, StdString, WriteOptions,
+};
+use alloc::{borrow::ToOwned, format, string::ToString as _, vec::Vec};
+use core::ffi::CStr;
+
+/// The magic bytes starting the header of compiled bytecode.
+const MAGIC: &[u8; 4] = b"RQBC";
+
+/// Returns the version recorded in the header of compiled bytecode.
+fn bytecode_version() -> StdString {
+    let quickjs = unsafe { CStr::from_ptr(qjs::JS_GetVersion()) };
+    let endianness = if cfg!(target_endian = "little") {
+        "le"
+    } else {
+        "be"
+    };
+    format!(
+        "quickjs-ng {} rquickjs {} {endianness}",
+        quickjs.to_string_lossy(),
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+/// Resolves every import to an empty module while compiling.
+///
+/// Imports are resolved and linked when the module is loaded, but compiling a module already
+/// loads the modules it imports.
+struct ImportStubs;
+
+impl Resolver for ImportStubs {
+    fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, _base: &str, name: &str) -> Result<StdString> {
+        Ok(name.to_owned())
+    }
+}
+
+impl Loader for ImportStubs {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
+        Module::declare(ctx.clone(), name, "")
+    }
+}
+
+/// Compile the source of a module to bytecode which can be loaded with
+/// [`Module::load_bytecode`].
+///
+/// The module is compiled in a new runtime, so this can be called from anywhere, including
+/// build scripts. The modules it imports are not compiled, they are resolved and loaded by the
+/// loader of the runtime loading the bytecode. An exception thrown while compiling,
+/// like a syntax error, is returned as an [`Error::Loading`] containing its message.
+pub fn compile_module<N, S>(name: N, source: S) -> Result<Vec<u8>>
+where
+    N: Into<StdString>,
+    S: Into<Vec<u8>>,
+{
+    let bytecode = compile_modules_raw([(name, source)])?
+        .pop()
+        .expect("a module should be compiled");
+
+    let version = bytecode_version();
+    let mut res = Vec::with_capacity(MAGIC.len() + 1 + version.len() + bytecode.len());
+    res.extend_from_slice(MAGIC);
+    res.push(version.len() as u8);
+    res.extend_from_slice(version.as_bytes());
+    res.extend_from_slice(&bytecode);
+    Ok(res)
+}
+
+/// Compile the sources of modules to raw bytecode without a header, in the order of the
+/// `(name, source)` pairs.
+///
+/// The bytecode can be loaded with [`Module::load`], which unlike [`Module::load_bytecode`]
+/// doesn't check that it was compiled by the same version, so this is meant for bytecode
+/// embedded into the binary which loads it, like with the `embed!` macro. Imports and errors
+/// are handled like with [`compile_module`], all modules are compiled in the same runtime.
+pub fn compile_modules_raw<I, N, S>(modules: I) -> Result<Vec<Vec<u8>>>
+where
+    I: IntoIterator<Item = (N, S)>,
+    N: Into<StdString>,
+    S: Into<Vec<u8>>,
+{
+    let rt = Runtime::new()?;
+    rt.set_loader(ImportStubs, ImportStubs);
+    let ctx = Context::full(&rt)?;
+    ctx.with(|ctx| {
+        modules
+            .into_iter()
+            .map(|(name, source)| {
+                let name = name.into();
+                Module::declare(ctx.clone(), name.as_str(), source)
+                    .and_then(|module| module.write(WriteOptions::default()))
+                    .catch(&ctx)
+                    .map_err(|error| Error::new_loading_message(&name, error.to_string()))
+            })
+            .collect()
+    })
+}
+
+/// Returns the bytecode following the header, checking the version recorded in the header.
+fn strip_header(bytes: &[u8]) -> Result<&[u8]> {
+    let expected = bytecode_version();
+    let error = |found| Error::BytecodeVersion {
+        expected: expected.clone(),
+        found,
+    };
+    let rest = bytes.strip_prefix(MAGIC).ok_or_else(|| error(None))?;
+    let (&len, rest) = rest.split_first().ok_or_else(|| error(None))?;
+    if rest.len() < len as usize {
+        return Err(error(None));
+    }
+    let (version, bytecode) = rest.split_at(len as usize);
+    if version != expected.as_bytes() {
+        return Err(error(Some(
+            StdString::from_utf8_lossy(version).into_owned(),
+        )));
+    }
+    Ok(bytecode)
+}
+
+impl<'js> Module<'js> {
+    /// Load a module from bytecode created by [`compile_module`].
+    ///
+    /// Returns [`Error::BytecodeVersion`] if the bytecode was compiled by a different version
+    /// of QuickJS or this crate, for a platform with a different endianness, or wasn't created
+    /// by [`compile_module`].
+    ///
+    /// # Safety
+    /// Like [`Module::load`] the header doesn't guarantee that the bytecode is valid, the bytes
+    /// must come from a trusted source and must not be modified.
+    pub unsafe fn load_bytecode(ctx: Ctx<'js>, bytes: &[u8]) -> Result<Module<'js, Declared>> {
+        let bytecode = strip_header(bytes)?;
+        unsafe { Module::load(ctx, bytecode) }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::{
+        loader::{BuiltinResolver, ModuleLoader},
+        module::NativeModule,
+    };
+    use std::{fs, string::String};
+
+    #[test]
+    fn round_trip() {
+        let bytecode = compile_module(
+            "math",
+            r#"
+                import { helper } from "./helper.js";
+                export function add(a, b) { return a + b; }
+                export const uses = typeof helper;
+            "#,
+        )
+        .unwrap();
+        assert!(bytecode.starts_with(MAGIC));
+
+        // Round trip through a file like a build script writing to `OUT_DIR`.
+        let path = std::env::temp_dir().join(std::format!(
+            "rquickjs-compile-{}.qjsbc",
+            std::process::id()
+        ));
+        fs::write(&path, &bytecode).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Imports are resolved by the loader of the runtime loading the bytecode.
+        let helper = NativeModule::builder("helper.js")
+            .export_fn("helper", || ())
+            .finish()
+            .unwrap();
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            BuiltinResolver::default().with_module("helper.js"),
+            ModuleLoader::default().with_native(helper),
+        );
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let module = unsafe { Module::load_bytecode(ctx.clone(), &bytes) }
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(module.name::<String>().unwrap(), "math");
+            let (module, promise) = module.eval().catch(&ctx).unwrap();
+            promise.finish::<()>().catch(&ctx).unwrap();
+            assert_eq!(module.get::<_, String>("uses").unwrap(), "function");
+            let add: crate::Function = module.get("add").unwrap();
+            assert_eq!(add.call::<_, i32>((40, 2)).unwrap(), 42);
+        });
+
+        let bytecode = compile_module("standalone", "export const answer = 6 * 7;").unwrap();
+        ctx.with(|ctx| {
+            let module = unsafe { Module::load_bytecode(ctx.clone(), &bytecode) }.unwrap();
+            let (module, promise) = module.eval().catch(&ctx).unwrap();
+            promise.finish::<()>().catch(&ctx).unwrap();
+            assert_eq!(module.get::<_, i32>("answer").unwrap(), 42);
+        });
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let mut bytecode = compile_module("test", "export const a = 1;").unwrap();
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            // Pretend the bytecode was compiled with another version.
+            let version = MAGIC.len() + 1;
+            bytecode[version..version + 10].copy_from_slice(b"quickjs-xx");
+            let err = unsafe { Module::load_bytecode(ctx.clone(), &bytecode) }.unwrap_err();
+            assert!(
+                matches!(&err, Error::BytecodeVersion { found: Some(found), .. } if found.starts_with("quickjs-xx")),
+                "{err}"
+            );
+
+            // Raw bytecode without the header.
+            let raw = Module::declare(ctx.clone(), "raw", "export const a = 1;")
+                .unwrap()
+                .write(WriteOptions::default())
+                .unwrap();
+            let err = unsafe { Module::load_bytecode(ctx.clone(), &raw) }.unwrap_err();
+            assert!(matches!(err, Error::BytecodeVersion { found: None, .. }));
+        });
+    }
+
+    #[test]
+    fn raw_modules() {
+        let modules = compile_modules_raw([
+            ("a", "import { b } from 'b'; export const a = b + 1;"),
+            ("b", "export const b = 41;"),
+        ])
+        .unwrap();
+        assert_eq!(modules.len(), 2);
+        assert!(!modules[0].starts_with(MAGIC));
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let b = unsafe { Module::load(ctx.clone(), &modules[1]) }.unwrap();
+            b.eval().catch(&ctx).unwrap();
+            let a = unsafe { Module::load(ctx.clone(), &modules[0]) }.unwrap();
+            let (a, promise) = a.eval().catch(&ctx).unwrap();
+            promise.finish::<()>().catch(&ctx).unwrap();
+            assert_eq!(a.get::<_, i32>("a").unwrap(), 42);
+        });
+
+        let err = compile_modules_raw([("ok", "1"), ("broken", "export const = 1;")]).unwrap_err();
+        assert!(err.to_string().contains("broken"), "{err}");
+    }
+
+    #[test]
+    fn syntax_error() {
+        let err = compile_module("broken", "export const = 1;").unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("broken"), "{message}");
+        assert!(message.contains("variable name expected"), "{message}");
+    }
+}
@@ -3,7 +3,11 @@
 mod base;
 mod builder;
 mod ctx;
+#[cfg(feature = "std")]
+mod eval_cache;
+mod fork;
 mod owner;
+mod sandbox;
 
 #[cfg(feature = "futures")]
 mod r#async;
@@ -25,6 +29,9 @@ pub trait MultiWith<'js> {
 pub use base::Context;
 pub use builder::{intrinsic, ContextBuilder, Intrinsic};
 pub use ctx::{Ctx, EvalOptions};
+#[cfg(feature = "std")]
+pub use eval_cache::{CacheStats, EvalCache};
+pub use sandbox::SandboxPolicy;
 
 #[cfg(feature = "futures")]
-pub use r#async::AsyncContext;
+pub use r#async::{AsyncContext, Scope};
@@ -3,9 +3,14 @@ use super::{
     owner::{ContextOwner, DropContext},
     ContextBuilder, Intrinsic,
 };
-use crate::{markers::ParallelSend, qjs, runtime::AsyncRuntime, Ctx, Error, Result};
+use crate::{
+    markers::ParallelSend,
+    qjs,
+    runtime::{opaque::Opaque, AsyncRuntime, CancellationToken},
+    Ctx, Error, Result,
+};
 use alloc::boxed::Box;
-use core::{future::Future, mem, pin::Pin, ptr::NonNull};
+use core::{future::Future, marker::PhantomData, mem, pin::Pin, ptr::NonNull};
 
 mod future;
 
@@ -89,6 +94,13 @@ macro_rules! async_with{
     };
 }
 
+/// The scope of the future of [`AsyncContext::with_scoped`].
+///
+/// The marker tells the compiler that the context outlives the values borrowed by the future,
+/// so the future can hold both, while the borrowed values still don't outlive the context and
+/// can't be moved into spawned futures or JavaScript functions.
+pub struct Scope<'js, 'a>(PhantomData<&'a &'js ()>);
+
 impl DropContext for AsyncRuntime {
     unsafe fn drop_context(&self, ctx: NonNull<qjs::JSContext>) {
         //TODO
@@ -129,9 +141,12 @@ impl DropContext for AsyncRuntime {
 /// An asynchronous single execution context with its own global variables and stack.
 ///
 /// Can share objects with other contexts of the same runtime.
+///
+/// Once the last handle to the context is dropped, all futures spawned inside the context with
+/// [`Ctx::spawn`] are aborted, including the ones of [`Async`](crate::function::Async) functions.
+/// Aborted futures are dropped without being polled again.
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
-#[derive(Clone)]
-pub struct AsyncContext(pub(crate) ContextOwner<AsyncRuntime>);
+pub struct AsyncContext(pub(crate) ContextOwner<AsyncRuntime>, CancellationToken);
 
 impl AsyncContext {
     /// Create a async context form a raw context pointer.
@@ -141,7 +156,14 @@ impl AsyncContext {
     /// The context must also have valid reference count, one which can be decremented when this
     /// object is dropped without going negative.
     pub unsafe fn from_raw(ctx: NonNull<qjs::JSContext>, rt: AsyncRuntime) -> Self {
-        AsyncContext(ContextOwner::new(ctx, rt))
+        let opaque = Opaque::from_runtime_ptr(qjs::JS_GetRuntime(ctx.as_ptr()));
+        let token = opaque.cancellation_token(ctx.as_ptr());
+        Self::from_parts(ContextOwner::new(ctx, rt), token)
+    }
+
+    fn from_parts(owner: ContextOwner<AsyncRuntime>, token: CancellationToken) -> Self {
+        token.acquire();
+        AsyncContext(owner, token)
     }
 
     /// Creates a base context with only the required functions registered.
@@ -160,11 +182,16 @@ impl AsyncContext {
             .ok_or(Error::Allocation)?;
         unsafe { qjs::JS_AddIntrinsicBaseObjects(ctx.as_ptr()) };
         unsafe { I::add_intrinsic(ctx) };
+        let token = CancellationToken::new();
+        guard
+            .runtime
+            .get_opaque()
+            .set_cancellation_token(ctx.as_ptr(), token.clone());
         let res = unsafe { ContextOwner::new(ctx, runtime.clone()) };
         guard.drop_pending();
         mem::drop(guard);
 
-        Ok(AsyncContext(res))
+        Ok(AsyncContext::from_parts(res, token))
     }
 
     /// Creates a context with all standard available intrinsics registered.
@@ -174,12 +201,17 @@ impl AsyncContext {
         let guard = runtime.inner.lock().await;
         let ctx = NonNull::new(unsafe { qjs::JS_NewContext(guard.runtime.rt.as_ptr()) })
             .ok_or(Error::Allocation)?;
+        let token = CancellationToken::new();
+        guard
+            .runtime
+            .get_opaque()
+            .set_cancellation_token(ctx.as_ptr(), token.clone());
         let res = unsafe { ContextOwner::new(ctx, runtime.clone()) };
         // Explicitly drop the guard to ensure it is valid during the entire use of runtime
         guard.drop_pending();
         mem::drop(guard);
 
-        Ok(AsyncContext(res))
+        Ok(AsyncContext::from_parts(res, token))
     }
 
     /// Create a context builder for creating a context with a specific set of intrinsics
@@ -192,10 +224,30 @@ impl AsyncContext {
         self.0.rt()
     }
 
+    /// Returns the token which aborts the futures spawned inside this context once cancelled.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.1.clone()
+    }
+
+    /// Abort all futures spawned inside this context.
+    ///
+    /// Futures spawned inside the context afterwards are aborted right away. Jobs already queued
+    /// by the context are still executed by the runtime, QuickJS can't remove them.
+    pub fn abort(&self) {
+        self.1.cancel()
+    }
+
     /// A entry point for manipulating and using JavaScript objects and scripts.
     ///
     /// This function is rather limited in what environment it can capture. If you need to borrow
-    /// the environment in the closure use the [`async_with!`] macro.
+    /// the environment in the closure use the [`async_with!`] macro, which works as a scoped
+    /// variant of this function.
+    ///
+    /// The macro can't be replaced by a function: the returned future borrows both from the
+    /// environment and from `'js`, and a signature relating the two lifetimes would also allow
+    /// moving borrowed values into futures spawned with [`Ctx::spawn`] or into functions stored
+    /// in JavaScript, which can outlive the call. The macro instead keeps the lifetimes unrelated
+    /// so borrowed values can only be used within the future itself.
     ///
     /// Unfortunately it is currently impossible to have closures return a generic future which has a higher
     /// rank trait bound lifetime. So, to allow closures to work, the closure must return a boxed
@@ -209,10 +261,62 @@ impl AsyncContext {
         WithFuture::new(self, f)
     }
 
+    /// A entry point for manipulating and using JavaScript objects and scripts with a future
+    /// which borrows from the environment.
+    ///
+    /// This is the function form of the [`async_with!`] macro. The closure is called with a
+    /// [`Scope`] marker, which relates the lifetime of the context to the lifetime `'a` of the
+    /// borrowed values, and returns the boxed future. Like with the macro, borrowed values can
+    /// be used by the future itself but can't be moved into futures spawned with [`Ctx::spawn`]
+    /// or into functions stored in JavaScript, which could outlive the call.
+    ///
+    /// ```
+    /// # use rquickjs::{AsyncContext, AsyncRuntime};
+    /// # async fn run() {
+    /// # let rt = AsyncRuntime::new().unwrap();
+    /// # let ctx = AsyncContext::full(&rt).await.unwrap();
+    /// let name = String::from("world");
+    /// let name = name.as_str();
+    /// let greeting: String = ctx
+    ///     .with_scoped(|ctx, _scope| {
+    ///         Box::pin(async move {
+    ///             ctx.globals().set("name", name).unwrap();
+    ///             ctx.eval("`hello ${name}`").unwrap()
+    ///         })
+    ///     })
+    ///     .await;
+    /// assert_eq!(greeting, "hello world");
+    /// # }
+    /// ```
+    pub async fn with_scoped<'a, F, R>(&'a self, f: F) -> R
+    where
+        F: for<'js> FnOnce(Ctx<'js>, Scope<'js, 'a>) -> Pin<Box<dyn Future<Output = R> + 'a>>
+            + ParallelSend,
+        R: ParallelSend + 'static,
+    {
+        /// SAFETY: The lifetime of a `Ctx` only brands the values of a context, the context
+        /// itself lives as long as `self` and is locked while the future runs, like with
+        /// [`async_with!`]. The future is only polled within the returned future, which can't
+        /// outlive `'a`, and nothing borrowed can escape it as the borrowed values can't be
+        /// moved into anything requiring `'js`. The future isn't send, but it is only run while
+        /// the runtime is locked.
+        unsafe fn uplift<'a, 'b, R>(
+            f: Pin<Box<dyn Future<Output = R> + 'a>>,
+        ) -> Pin<Box<dyn Future<Output = R> + 'b + Send>> {
+            mem::transmute(f)
+        }
+
+        WithFuture::new(self, move |ctx| {
+            let ctx = unsafe { mem::transmute::<Ctx<'_>, Ctx<'a>>(ctx) };
+            unsafe { uplift(f(ctx, Scope(PhantomData))) }
+        })
+        .await
+    }
+
     /// A entry point for manipulating and using JavaScript objects and scripts.
     ///
     /// This closure can't return a future, if you need to await JavaScript promises prefer the
-    /// [`async_with!`] macro.
+    /// [`async_with!`] macro or [`AsyncContext::with_scoped`].
     pub async fn with<F, R>(&self, f: F) -> R
     where
         F: for<'js> FnOnce(Ctx<'js>) -> R + ParallelSend,
@@ -227,6 +331,18 @@ impl AsyncContext {
     }
 }
 
+impl Clone for AsyncContext {
+    fn clone(&self) -> Self {
+        AsyncContext::from_parts(self.0.clone(), self.1.clone())
+    }
+}
+
+impl Drop for AsyncContext {
+    fn drop(&mut self) {
+        self.1.release();
+    }
+}
+
 // Since the reference to runtime is behind a Arc this object is send
 #[cfg(feature = "parallel")]
 unsafe impl Send for AsyncContext {}
@@ -238,7 +354,7 @@ unsafe impl Sync for AsyncContext {}
 
 #[cfg(test)]
 mod test {
-    use crate::{AsyncContext, AsyncRuntime};
+    use crate::{AsyncContext, AsyncRuntime, Promise};
 
     #[tokio::test]
     async fn base_asyc_context() {
@@ -275,6 +391,106 @@ mod test {
             .await;
     }
 
+    #[tokio::test]
+    async fn borrow_environment() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        let source = String::from("Promise.resolve(40).then((x) => x + 2)");
+        let source_ref = source.as_str();
+        let mut results = Vec::new();
+        let results_ref = &mut results;
+        async_with!(ctx => |ctx| {
+            let promise: Promise = ctx.eval(source_ref).unwrap();
+            results_ref.push(promise.into_future::<i32>().await.unwrap());
+        })
+        .await;
+        assert_eq!(results, [42]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn borrow_environment_stress() {
+        let rt = AsyncRuntime::new().unwrap();
+        let mut handles = Vec::new();
+        for i in 0..64 {
+            let ctx = AsyncContext::full(&rt).await.unwrap();
+            handles.push(tokio::spawn(async move {
+                let name = format!("task{i}");
+                let name_ref = name.as_str();
+                for _ in 0..8 {
+                    async_with!(ctx => |ctx| {
+                        let promise: Promise = ctx
+                            .eval(format!("Promise.resolve().then(() => '{name_ref}'.length)"))
+                            .unwrap();
+                        let len: usize = promise.into_future().await.unwrap();
+                        assert_eq!(len, name_ref.len());
+                    })
+                    .await;
+                    tokio::task::yield_now().await;
+                }
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn with_scoped_borrow_environment() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        let source = String::from("Promise.resolve(40).then((x) => x + 2)");
+        let source_ref = source.as_str();
+        let mut results = Vec::new();
+        let results_ref = &mut results;
+        let len = ctx
+            .with_scoped(|ctx, _scope| {
+                Box::pin(async move {
+                    let promise: Promise = ctx.eval(source_ref).unwrap();
+                    results_ref.push(promise.into_future::<i32>().await.unwrap());
+                    results_ref.len()
+                })
+            })
+            .await;
+        assert_eq!(len, 1);
+        assert_eq!(results, [42]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn with_scoped_stress() {
+        let rt = AsyncRuntime::new().unwrap();
+        let mut handles = Vec::new();
+        for i in 0..64 {
+            let ctx = AsyncContext::full(&rt).await.unwrap();
+            handles.push(tokio::spawn(async move {
+                let name = format!("task{i}");
+                let name_ref = name.as_str();
+                for _ in 0..8 {
+                    let len: usize = ctx
+                        .with_scoped(|ctx, _scope| {
+                            Box::pin(async move {
+                                let promise: Promise = ctx
+                                    .eval(format!(
+                                        "Promise.resolve().then(() => '{name_ref}'.length)"
+                                    ))
+                                    .unwrap();
+                                promise.into_future().await.unwrap()
+                            })
+                        })
+                        .await;
+                    assert_eq!(len, name_ref.len());
+                    tokio::task::yield_now().await;
+                }
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    }
+
     #[cfg(feature = "parallel")]
     #[tokio::test]
     async fn parallel_drop() {
@@ -313,4 +529,98 @@ mod test {
             })
             .await;
     }
+
+    #[tokio::test]
+    async fn abort_on_drop() {
+        use std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            time::Duration,
+        };
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let counter_ref = counter.clone();
+        let handle = ctx
+            .with(move |ctx| {
+                ctx.clone().spawn(async move {
+                    loop {
+                        let count = counter_ref.fetch_add(1, Ordering::SeqCst) + 1;
+                        ctx.globals().set("count", count).unwrap();
+                        tokio::time::sleep(Duration::from_millis(1)).await;
+                    }
+                })
+            })
+            .await;
+
+        // The loop never finishes so the runtime never becomes idle.
+        assert!(tokio::time::timeout(Duration::from_millis(50), rt.idle())
+            .await
+            .is_err());
+        assert!(counter.load(Ordering::SeqCst) > 0);
+        assert!(!handle.is_finished());
+
+        let token = ctx.cancellation_token();
+        let ctx_clone = ctx.clone();
+        drop(ctx);
+        assert!(!token.is_cancelled());
+        drop(ctx_clone);
+        assert!(token.is_cancelled());
+
+        tokio::time::timeout(Duration::from_secs(1), rt.idle())
+            .await
+            .unwrap();
+        assert!(handle.is_finished());
+        let count = counter.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        rt.idle().await;
+        assert_eq!(counter.load(Ordering::SeqCst), count);
+    }
+
+    #[tokio::test]
+    async fn abort() {
+        use crate::{function::Async, Function};
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        let (handle, aborted) = ctx
+            .with(|ctx| {
+                let handle = ctx.spawn(std::future::pending());
+                let aborted = ctx.spawn(std::future::pending());
+                aborted.abort();
+                (handle, aborted)
+            })
+            .await;
+        // Runs until the remaining future is aborted.
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(20), rt.idle())
+                .await
+                .is_err()
+        );
+        assert!(aborted.is_finished());
+        assert!(!handle.is_finished());
+
+        ctx.abort();
+        rt.idle().await;
+        assert!(handle.is_finished());
+
+        // Futures of async functions called after aborting are dropped right away.
+        ctx.with(|ctx| {
+            let func = Function::new(
+                ctx.clone(),
+                Async(|| async { std::future::pending::<crate::Result<()>>().await }),
+            )
+            .unwrap();
+            ctx.globals().set("func", func).unwrap();
+            ctx.eval::<(), _>("func()").unwrap();
+        })
+        .await;
+        rt.idle().await;
+        assert!(!rt.is_job_pending().await);
+    }
 }
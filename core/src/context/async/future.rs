@@ -112,6 +112,11 @@ where
             };
 
             let opaque = lock.runtime.get_opaque();
+            if !opaque.auto_drive() {
+                // Jobs and spawned futures are only run when driven manually.
+                this.state = WithFutureState::FutureCreated { future };
+                break Poll::Pending;
+            }
             match opaque.poll(cx) {
                 SchedularPoll::Empty => {
                     // if the schedular is empty that means the future is waiting on an external or
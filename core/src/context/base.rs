@@ -1,10 +1,14 @@
+#[cfg(feature = "std")]
+use super::EvalCache;
 use super::{
     ctx::RefCountHeader,
-    intrinsic,
+    fork, intrinsic,
     owner::{ContextOwner, DropContext},
-    ContextBuilder, Intrinsic,
+    sandbox, ContextBuilder, Intrinsic, SandboxPolicy,
 };
 use crate::{qjs, Ctx, Error, Result, Runtime};
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
 use core::{mem, ptr::NonNull};
 
 impl DropContext for Runtime {
@@ -27,6 +31,13 @@ impl DropContext for Runtime {
             }
         };
         guard.update_stack_top();
+        #[cfg(feature = "std")]
+        {
+            let p = unsafe { &*(ctx.as_ptr() as *const RefCountHeader) };
+            if p.ref_count <= 1 {
+                guard.get_opaque().set_eval_cache(ctx.as_ptr(), None);
+            }
+        }
         unsafe { qjs::JS_FreeContext(ctx.as_ptr()) }
         // Explicitly drop the guard to ensure it is valid during the entire use of runtime
         mem::drop(guard);
@@ -91,6 +102,26 @@ impl Context {
         Ok(Context(res))
     }
 
+    /// Creates a context for running untrusted code, restricted according to the policy.
+    ///
+    /// The context has all standard intrinsics registered, after which the global `eval`
+    /// function and the constructors compiling source code, like `Function`, are removed unless
+    /// allowed by the policy. The constructors are replaced by functions which throw, so
+    /// `instanceof Function` keeps working. Globals not in
+    /// [`SandboxPolicy::allowed_globals`] are removed and the global object and built-ins are
+    /// frozen if [`SandboxPolicy::frozen_global`] is set.
+    ///
+    /// Scripts and modules can still be evaluated from Rust with [`Ctx::eval`] and the module
+    /// loader of the runtime. Note that declaring `var` or `function` at the top level of a script
+    /// defines properties on the global object, which fails if the global object is frozen.
+    pub fn new_sandboxed(runtime: &Runtime, policy: SandboxPolicy) -> Result<Self> {
+        // The eval intrinsic is also required to evaluate scripts from Rust, so only the global
+        // function is removed.
+        let context = Self::custom::<intrinsic::All>(runtime)?;
+        context.with(|ctx| sandbox::restrict(&ctx, &policy))?;
+        Ok(context)
+    }
+
     /// Create a context builder for creating a context with a specific set of intrinsics
     pub fn builder() -> ContextBuilder<()> {
         ContextBuilder::default()
@@ -106,6 +137,61 @@ impl Context {
         unsafe { qjs::JS_GetRuntime(self.0.ctx().as_ptr()) }
     }
 
+    /// Create a new context on the given runtime with a copy of the global environment of this
+    /// context.
+    ///
+    /// The new context has all standard intrinsics registered, like [`Context::full`], after
+    /// which all enumerable own properties of the global object of this context which don't exist
+    /// on the new global object are copied onto it. Values are copied as follows:
+    ///
+    /// - Primitive values like numbers, strings and big integers are copied as is.
+    /// - Plain objects and arrays are cloned deeply, shared references and cycles between them
+    ///   are preserved. Only their enumerable own string properties are copied.
+    /// - Functions, class instances, errors, promises, symbols and other objects which aren't
+    ///   plain are skipped, as are properties or array elements containing them.
+    ///
+    /// Property attributes like frozen or non-writable properties are not preserved and
+    /// `let`/`const` declarations from the top level of scripts are not part of the global
+    /// object so they aren't copied either.
+    ///
+    /// Returns [`Error::UnrelatedRuntime`] if the runtime is not the runtime of this context.
+    pub fn fork(&self, runtime: &Runtime) -> Result<Context> {
+        if self.get_runtime_ptr() != runtime.inner.lock().rt.as_ptr() {
+            return Err(Error::UnrelatedRuntime);
+        }
+        let forked = Context::full(runtime)?;
+        let guard = runtime.inner.lock();
+        guard.update_stack_top();
+        let src = unsafe { Ctx::new(self) };
+        let dst = unsafe { Ctx::new(&forked) };
+        fork::copy_globals(&src, &dst)?;
+        mem::drop((src, dst));
+        // Explicitly drop the guard to ensure it is valid during the entire use of runtime
+        mem::drop(guard);
+        Ok(forked)
+    }
+
+    /// Attach a cache of compiled bytecode which is used by [`Ctx::eval`] and
+    /// [`Ctx::eval_with_options`] in this context.
+    ///
+    /// Returns the previously attached cache, if any.
+    #[cfg(feature = "std")]
+    pub fn set_eval_cache(&self, cache: Arc<EvalCache>) -> Option<Arc<EvalCache>> {
+        let guard = self.0.rt().inner.lock();
+        guard
+            .get_opaque()
+            .set_eval_cache(self.0.ctx().as_ptr(), Some(cache))
+    }
+
+    /// Detach the cache of compiled bytecode from this context.
+    #[cfg(feature = "std")]
+    pub fn remove_eval_cache(&self) -> Option<Arc<EvalCache>> {
+        let guard = self.0.rt().inner.lock();
+        guard
+            .get_opaque()
+            .set_eval_cache(self.0.ctx().as_ptr(), None)
+    }
+
     /// A entry point for manipulating and using JavaScript objects and scripts.
     /// The api is structured this way to avoid repeated locking the runtime when ever
     /// any function is called. This way the runtime is locked once before executing the callback.
@@ -120,9 +206,26 @@ impl Context {
     {
         let guard = self.0.rt().inner.lock();
         guard.update_stack_top();
+        guard.drop_pending();
         let ctx = unsafe { Ctx::new(self) };
         f(ctx)
     }
+
+    /// Like [`Context::with`] but doesn't block if the runtime is currently locked.
+    ///
+    /// Returns [`Error::WouldBlock`] if the runtime is locked, including when it is locked by the
+    /// calling thread, for example when called from within another call to [`Context::with`] on
+    /// a context of the same runtime.
+    pub fn try_with<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(Ctx) -> R,
+    {
+        let guard = self.0.rt().inner.try_lock_now().ok_or(Error::WouldBlock)?;
+        guard.update_stack_top();
+        guard.drop_pending();
+        let ctx = unsafe { Ctx::new(self) };
+        Ok(f(ctx))
+    }
 }
 
 // Since the reference to runtime is behind a Arc this object is send
@@ -166,6 +269,179 @@ mod test {
         });
     }
 
+    #[test]
+    fn eval_cache() {
+        use crate::context::{CacheStats, EvalCache, EvalOptions};
+
+        let cache = Arc::new(EvalCache::new());
+        let rt = Runtime::new().unwrap();
+        let ctx1 = Context::full(&rt).unwrap();
+        let ctx2 = Context::full(&rt).unwrap();
+        assert!(ctx1.set_eval_cache(cache.clone()).is_none());
+        ctx2.set_eval_cache(cache.clone());
+
+        let src = "globalThis.count = (globalThis.count ?? 0) + 1";
+        ctx1.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>(src).unwrap(), 1);
+            assert_eq!(ctx.eval::<i32, _>(src).unwrap(), 2);
+            let promise = ctx.eval_promise("await 1").unwrap();
+            assert_eq!(
+                promise
+                    .finish::<Object>()
+                    .unwrap()
+                    .get::<_, i32>("value")
+                    .unwrap(),
+                1
+            );
+        });
+        ctx2.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>(src).unwrap(), 1);
+            let options = EvalOptions {
+                global: false,
+                ..Default::default()
+            };
+            ctx.eval_with_options::<Promise, _>("export const a = 1", options)
+                .unwrap()
+                .finish::<()>()
+                .unwrap();
+            assert!(ctx.eval::<(), _>("syntax error").is_err());
+        });
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 4,
+                entries: 3,
+                evictions: 0,
+            }
+        );
+
+        cache.invalidate_all();
+        assert_eq!(cache.stats().entries, 0);
+        assert!(ctx1.remove_eval_cache().is_some());
+        ctx1.with(|ctx| assert_eq!(ctx.eval::<i32, _>(src).unwrap(), 3));
+        assert_eq!(cache.stats().misses, 4);
+
+        // The name can't run into the source.
+        assert_ne!(
+            EvalCache::key(b"bc", b"a", 0),
+            EvalCache::key(b"c", b"ab", 0)
+        );
+    }
+
+    #[test]
+    fn bytecode_cache() {
+        use crate::context::{CacheStats, EvalCache};
+
+        let rt = Runtime::new().unwrap();
+        let cache = rt.enable_bytecode_cache(2);
+        assert_eq!(cache.capacity(), Some(2));
+
+        for _ in 0..2 {
+            let ctx = Context::full(&rt).unwrap();
+            ctx.with(|ctx| {
+                assert_eq!(ctx.eval_cached::<i32, _>("a", "1").unwrap(), 1);
+                assert_eq!(ctx.eval_cached::<i32, _>("b", "2").unwrap(), 2);
+                // Plain evaluation doesn't use the runtime cache.
+                assert_eq!(ctx.eval::<i32, _>("3").unwrap(), 3);
+            });
+        }
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 2,
+                entries: 2,
+                evictions: 0,
+            }
+        );
+
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            // `b` was used least recently and is evicted.
+            assert_eq!(ctx.eval_cached::<i32, _>("a", "1").unwrap(), 1);
+            assert_eq!(ctx.eval_cached::<i32, _>("c", "3").unwrap(), 3);
+            assert_eq!(ctx.eval_cached::<i32, _>("a", "1").unwrap(), 1);
+            assert_eq!(ctx.eval_cached::<i32, _>("b", "2").unwrap(), 2);
+        });
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 4,
+                misses: 4,
+                entries: 2,
+                evictions: 2,
+            }
+        );
+
+        // A cache attached to the context takes precedence.
+        let own = Arc::new(EvalCache::new());
+        ctx.set_eval_cache(own.clone());
+        ctx.with(|ctx| assert_eq!(ctx.eval_cached::<i32, _>("a", "1").unwrap(), 1));
+        assert_eq!(own.stats().misses, 1);
+        assert_eq!(cache.stats().misses, 4);
+
+        assert!(rt.disable_bytecode_cache().is_some());
+        ctx.remove_eval_cache();
+        ctx.with(|ctx| assert_eq!(ctx.eval_cached::<i32, _>("a", "1").unwrap(), 1));
+        assert_eq!(cache.stats().hits, 4);
+    }
+
+    #[test]
+    fn fork() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                    var num = 1;
+                    var config = { name: "foo", list: [1, "a", () => 1], nested: { ok: true } };
+                    config.self = config;
+                    var func = () => 2;
+                    var date = new Date();
+                    class Foo {}
+                    var foo = new Foo();
+                    globalThis.big = 10n;
+                    let hidden = 3;
+                "#,
+            )
+            .unwrap();
+        });
+
+        let forked = ctx.fork(&rt).unwrap();
+        forked.with(|ctx| {
+            let res: bool = ctx
+                .eval(
+                    r#"
+                        num === 1 &&
+                        big === 10n &&
+                        config.name === "foo" &&
+                        config.list.length === 3 &&
+                        config.list[1] === "a" &&
+                        config.list[2] === undefined &&
+                        config.nested.ok &&
+                        config.self === config &&
+                        Object.getPrototypeOf(config) === Object.prototype &&
+                        typeof func === "undefined" &&
+                        typeof date === "undefined" &&
+                        typeof foo === "undefined" &&
+                        typeof hidden === "undefined"
+                    "#,
+                )
+                .unwrap();
+            assert!(res);
+            ctx.eval::<(), _>("config.name = 'bar'; num = 2").unwrap();
+        });
+        ctx.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>("num").unwrap(), 1);
+            let name: std::string::String = ctx.eval("config.name").unwrap();
+            assert_eq!(name, "foo");
+        });
+
+        let rt2 = Runtime::new().unwrap();
+        assert!(matches!(ctx.fork(&rt2), Err(Error::UnrelatedRuntime)));
+    }
+
     #[test]
     fn base() {
         let rt = Runtime::new().unwrap();
@@ -215,6 +491,22 @@ mod test {
         });
     }
 
+    #[test]
+    fn try_with() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let ctx_2 = Context::full(&rt).unwrap();
+        ctx.with(|_| {
+            assert!(matches!(ctx_2.try_with(|_| ()), Err(Error::WouldBlock)));
+            assert!(matches!(rt.try_lock(), Err(Error::WouldBlock)));
+        });
+        let res: i32 = ctx_2.try_with(|ctx| ctx.eval("1 + 1").unwrap()).unwrap();
+        assert_eq!(res, 2);
+        let lock = rt.try_lock().unwrap();
+        assert!(matches!(ctx.try_with(|_| ()), Err(Error::WouldBlock)));
+        drop(lock);
+    }
+
     #[test]
     #[cfg(feature = "parallel")]
     fn parallel() {
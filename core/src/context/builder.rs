@@ -6,24 +6,52 @@ use crate::{qjs, util::Sealed, Context, Result, Runtime};
 
 /// The internal trait to add JS builtins
 pub trait Intrinsic: Sealed {
+    /// The intrinsics which are added, as the flags used by the `with_*` methods of
+    /// [`ContextBuilder`].
+    #[doc(hidden)]
+    const FLAGS: u16;
+
     /// # Safety
     /// Do not need implement it yourself instead you may use predefined intrinsics from [`intrinsic`] module.
     unsafe fn add_intrinsic(ctx: NonNull<qjs::JSContext>);
 }
 
 /// Used for building a [`Context`](struct.Context.html) with a specific set of intrinsics
-pub struct ContextBuilder<I>(PhantomData<I>);
+///
+/// Intrinsics can either be selected at compile time with [`ContextBuilder::with`] or at runtime
+/// with the `with_*` methods like [`ContextBuilder::with_date`]. The base objects (`Object`,
+/// `Function`, `Array`, `String`, `Error`, etc.) are always added as rquickjs depends on them.
+///
+/// Some intrinsics depend on others:
+/// - Evaluating scripts and modules, including [`Ctx::eval`](crate::Ctx::eval), requires
+///   [`intrinsic::Eval`].
+/// - Regular expression literals and the `RegExp` constructor require
+///   [`intrinsic::RegExpCompiler`], which is also added by [`intrinsic::RegExp`].
+/// - `String.prototype.match`, `matchAll`, `replace` and `split` only work with regular
+///   expression arguments if [`intrinsic::RegExp`] is added.
+/// - `async` functions, modules and [`Promise`](crate::Promise) require [`intrinsic::Promise`].
+/// - `ArrayBuffer`, typed arrays and `Atomics` require [`intrinsic::TypedArrays`].
+///
+/// Every intrinsic is only added once, even if it is selected both ways. The compile time
+/// selection can't be undone at runtime: calling a `with_*` method with `false` has no effect
+/// for intrinsics which are added by [`ContextBuilder::with`].
+pub struct ContextBuilder<I> {
+    marker: PhantomData<I>,
+    flags: u16,
+}
 
 macro_rules! intrinsic_impls {
-    (@builtin: $($(#[$meta:meta])* $name:ident $func:ident $(($($args:expr),*))*,)*) => {
+    (@builtin: $($(#[$meta:meta])* $name:ident $func:ident => $flags:expr,)*) => {
         $(
             $(#[$meta])*
             pub struct $name;
             impl crate::util::Sealed for $name { }
 
             impl Intrinsic for $name {
+                const FLAGS: u16 = $flags;
+
                 unsafe fn add_intrinsic(ctx: NonNull<qjs::JSContext>) {
-                    qjs::$func(ctx.as_ptr() $(, $($args),*)*);
+                    qjs::$func(ctx.as_ptr());
                 }
             }
         )*
@@ -37,8 +65,11 @@ macro_rules! intrinsic_impls {
             where
                 $($name: Intrinsic,)*
             {
-                unsafe fn add_intrinsic(_ctx: NonNull<qjs::JSContext>) {
-                    $($name::add_intrinsic(_ctx);)*
+                const FLAGS: u16 = 0 $(| $name::FLAGS)*;
+
+                unsafe fn add_intrinsic(ctx: NonNull<qjs::JSContext>) {
+                    // Every intrinsic of nested tuples is only added once.
+                    add_flagged(ctx.as_ptr(), Self::FLAGS, 0);
                 }
             }
         )*
@@ -49,34 +80,34 @@ macro_rules! intrinsic_impls {
 ///
 /// You can select just you need only. If `lto = true` any unused code will be drop by link-time optimizer.
 pub mod intrinsic {
-    use super::{qjs, Intrinsic, NonNull};
+    use super::{flag::*, qjs, Intrinsic, NonNull};
 
     intrinsic_impls! {
         @builtin:
         /// Add Date object support
-        Date JS_AddIntrinsicDate,
+        Date JS_AddIntrinsicDate => DATE,
         /// Add evaluation support
-        Eval JS_AddIntrinsicEval,
+        Eval JS_AddIntrinsicEval => EVAL,
         /// Add RegExp compiler
-        RegExpCompiler JS_AddIntrinsicRegExpCompiler,
+        RegExpCompiler JS_AddIntrinsicRegExpCompiler => REGEXP_COMPILER,
         /// Add RegExp object support
-        RegExp JS_AddIntrinsicRegExp,
+        RegExp JS_AddIntrinsicRegExp => REGEXP | REGEXP_COMPILER,
         /// Add JSON parse and stringify
-        Json JS_AddIntrinsicJSON,
+        Json JS_AddIntrinsicJSON => JSON,
         /// Add Proxy object support
-        Proxy JS_AddIntrinsicProxy,
+        Proxy JS_AddIntrinsicProxy => PROXY,
         /// Add MapSet object support
-        MapSet JS_AddIntrinsicMapSet,
+        MapSet JS_AddIntrinsicMapSet => MAP_SET,
         /// Add Typed Arrays support
-        TypedArrays JS_AddIntrinsicTypedArrays,
+        TypedArrays JS_AddIntrinsicTypedArrays => TYPED_ARRAYS,
         /// Add Promise object support
-        Promise JS_AddIntrinsicPromise,
+        Promise JS_AddIntrinsicPromise => PROMISE,
         /// Add BigInt support
-        BigInt JS_AddIntrinsicBigInt,
+        BigInt JS_AddIntrinsicBigInt => BIG_INT,
         /// Add Performance support
-        Performance JS_AddPerformance,
+        Performance JS_AddPerformance => PERFORMANCE,
         /// Add WeakRef support
-        WeakRef JS_AddIntrinsicWeakRef,
+        WeakRef JS_AddIntrinsicWeakRef => WEAK_REF,
     }
 
     /// Add none intrinsics
@@ -123,22 +154,119 @@ intrinsic_impls! {
 
 impl Default for ContextBuilder<()> {
     fn default() -> Self {
-        ContextBuilder(PhantomData)
+        ContextBuilder {
+            marker: PhantomData,
+            flags: 0,
+        }
+    }
+}
+
+macro_rules! intrinsic_flags {
+    ($($(#[$meta:meta])* $method:ident $flag:ident $func:ident,)*) => {
+        mod flag {
+            intrinsic_flags!(@flag 0, $($flag,)*);
+        }
+
+        const INTRINSIC_FLAGS: &[(u16, unsafe extern "C" fn(*mut qjs::JSContext))] =
+            &[$((flag::$flag, qjs::$func),)*];
+
+        impl<I: Intrinsic> ContextBuilder<I> {
+            $(
+                $(#[$meta])*
+                pub fn $method(self, enable: bool) -> Self {
+                    self.set_flag(flag::$flag, enable)
+                }
+            )*
+        }
+    };
+    (@flag $idx:expr, $flag:ident, $($rest:ident,)*) => {
+        pub const $flag: u16 = 1 << $idx;
+        intrinsic_flags!(@flag $idx + 1, $($rest,)*);
+    };
+    (@flag $idx:expr,) => {};
+}
+
+intrinsic_flags! {
+    /// Select if `Date` is added.
+    with_date DATE JS_AddIntrinsicDate,
+    /// Select if script evaluation is supported.
+    with_eval EVAL JS_AddIntrinsicEval,
+    /// Select if regular expressions can be compiled.
+    with_regexp_compiler REGEXP_COMPILER JS_AddIntrinsicRegExpCompiler,
+    /// Select if `RegExp` is added, this also adds the regular expression compiler.
+    with_regexp REGEXP JS_AddIntrinsicRegExp,
+    /// Select if `JSON` is added.
+    with_json JSON JS_AddIntrinsicJSON,
+    /// Select if `Proxy` and `Reflect` are added.
+    with_proxy PROXY JS_AddIntrinsicProxy,
+    /// Select if `Map`, `Set`, `WeakMap` and `WeakSet` are added.
+    with_map_set MAP_SET JS_AddIntrinsicMapSet,
+    /// Select if `ArrayBuffer` and the typed arrays are added.
+    with_typed_arrays TYPED_ARRAYS JS_AddIntrinsicTypedArrays,
+    /// Select if `Promise` is added.
+    with_promise PROMISE JS_AddIntrinsicPromise,
+    /// Select if `BigInt` is added.
+    with_big_int BIG_INT JS_AddIntrinsicBigInt,
+    /// Select if `performance` is added.
+    with_performance PERFORMANCE JS_AddPerformance,
+    /// Select if `WeakRef` and `FinalizationRegistry` are added.
+    with_weak_ref WEAK_REF JS_AddIntrinsicWeakRef,
+}
+
+/// Add the intrinsics selected by `flags` to the context, skipping the already `added` ones.
+///
+/// # Safety
+/// The context must be a valid context which is not currently in use.
+unsafe fn add_flagged(ctx: *mut qjs::JSContext, flags: u16, added: u16) {
+    let mut flags = flags & !added;
+    if (flags | added) & flag::REGEXP != 0 {
+        // `RegExp` adds the compiler itself.
+        flags &= !flag::REGEXP_COMPILER;
+    }
+    for (flag, add) in INTRINSIC_FLAGS {
+        if flags & flag != 0 {
+            add(ctx);
+        }
     }
 }
 
 impl<I: Intrinsic> ContextBuilder<I> {
+    /// Add an intrinsic selected at compile time.
     pub fn with<J: Intrinsic>(self) -> ContextBuilder<(I, J)> {
-        ContextBuilder(PhantomData)
+        ContextBuilder {
+            marker: PhantomData,
+            flags: self.flags,
+        }
+    }
+
+    /// Select an intrinsic at runtime, intrinsics added with [`ContextBuilder::with`] can't be
+    /// deselected.
+    fn set_flag(mut self, flag: u16, enable: bool) -> Self {
+        if enable {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+        self
     }
 
+    /// Build a context with the selected intrinsics.
     pub fn build(self, runtime: &Runtime) -> Result<Context> {
-        Context::custom::<I>(runtime)
+        let context = Context::custom::<I>(runtime)?;
+        let flags = self.flags;
+        context.with(|ctx| unsafe { add_flagged(ctx.as_ptr(), flags, I::FLAGS) });
+        Ok(context)
     }
 
+    /// Build an async context with the selected intrinsics.
     #[cfg(feature = "futures")]
     pub async fn build_async(self, runtime: &AsyncRuntime) -> Result<AsyncContext> {
-        AsyncContext::custom::<I>(runtime).await
+        let context = AsyncContext::custom::<I>(runtime).await?;
+        let flags = self.flags;
+        context
+            .with(|ctx| unsafe { add_flagged(ctx.as_ptr(), flags, I::FLAGS) })
+            .await;
+        Ok(context)
     }
 }
 
@@ -156,4 +284,48 @@ mod tests {
         let result: usize = ctx.with(|ctx| ctx.eval("1+1")).unwrap();
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn runtime_selected_intrinsics() {
+        let rt = crate::Runtime::new().unwrap();
+        let ctx = Context::builder()
+            .with_eval(true)
+            .with_json(true)
+            .with_date(true)
+            .with_date(false)
+            .build(&rt)
+            .unwrap();
+        ctx.with(|ctx| {
+            let ty: String = ctx.eval("typeof JSON.stringify").unwrap();
+            assert_eq!(ty, "function");
+            let ty: String = ctx.eval("typeof Date").unwrap();
+            assert_eq!(ty, "undefined");
+            let ty: String = ctx.eval("typeof RegExp").unwrap();
+            assert_eq!(ty, "undefined");
+        });
+    }
+
+    #[test]
+    fn compile_time_intrinsics_take_precedence() {
+        let rt = crate::Runtime::new().unwrap();
+        let ctx = Context::builder()
+            .with::<(intrinsic::Eval, (intrinsic::Date, intrinsic::RegExp))>()
+            .with::<intrinsic::Date>()
+            .with_date(false)
+            .with_regexp(true)
+            .with_regexp_compiler(true)
+            .with_json(true)
+            .build(&rt)
+            .unwrap();
+        ctx.with(|ctx| {
+            let ty: String = ctx.eval("typeof Date").unwrap();
+            assert_eq!(ty, "function");
+            let res: bool = ctx.eval("/a+/.test('baa') && JSON.parse('true')").unwrap();
+            assert!(res);
+        });
+        assert_eq!(
+            <(intrinsic::Eval, (intrinsic::Date, intrinsic::RegExp))>::FLAGS,
+            flag::EVAL | flag::DATE | flag::REGEXP | flag::REGEXP_COMPILER
+        );
+    }
 }
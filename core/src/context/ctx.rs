@@ -1,7 +1,13 @@
 #[cfg(feature = "futures")]
 use core::future::Future;
 
-use alloc::{boxed::Box, ffi::CString, vec::Vec};
+use alloc::{
+    boxed::Box,
+    ffi::CString,
+    format,
+    string::{String as StdString, ToString as _},
+    vec::Vec,
+};
 use core::{
     any::Any,
     ffi::CStr,
@@ -13,16 +19,26 @@ use core::{
 #[cfg(feature = "std")]
 use std::{fs, path::Path};
 
-#[cfg(feature = "futures")]
-use crate::AsyncContext;
 use crate::{
+    function::Rest,
+    interop::console::LogLevel,
     markers::Invariant,
     qjs,
     runtime::{opaque::Opaque, UserDataError, UserDataGuard},
+    util::is_identifier,
     Atom, Error, FromJs, Function, IntoJs, JsLifetime, Object, Promise, Result, String, Value,
 };
+#[cfg(feature = "futures")]
+use crate::{
+    runtime::{ExecutorSpawner, JobHandle},
+    AsyncContext,
+};
+#[cfg(feature = "futures")]
+use alloc::sync::Arc;
 
 use super::Context;
+#[cfg(feature = "std")]
+use super::EvalCache;
 
 /// Eval options.
 #[non_exhaustive]
@@ -35,6 +51,12 @@ pub struct EvalOptions {
     pub backtrace_barrier: bool,
     /// Support top-level-await.
     pub promise: bool,
+    /// Name of the evaluated source, defaults to `eval_script`.
+    ///
+    /// Used as the file name in backtraces and as the base for resolving the specifiers of
+    /// modules imported with [`Module::import`](crate::Module::import) while the source is
+    /// evaluated.
+    pub source_name: Option<StdString>,
 }
 
 impl EvalOptions {
@@ -68,6 +90,7 @@ impl Default for EvalOptions {
             strict: true,
             backtrace_barrier: false,
             promise: false,
+            source_name: None,
         }
     }
 }
@@ -97,6 +120,22 @@ impl<'js> Drop for Ctx<'js> {
 
 unsafe impl Send for Ctx<'_> {}
 
+/// Keeps the name of a source on the stack of sources being evaluated until dropped.
+struct SourceNameGuard<'a, 'js>(&'a Ctx<'js>);
+
+impl<'a, 'js> SourceNameGuard<'a, 'js> {
+    fn new(ctx: &'a Ctx<'js>, name: CString) -> Self {
+        unsafe { ctx.get_opaque().push_source_name(name) };
+        SourceNameGuard(ctx)
+    }
+}
+
+impl Drop for SourceNameGuard<'_, '_> {
+    fn drop(&mut self) {
+        unsafe { self.0.get_opaque().pop_source_name() };
+    }
+}
+
 #[repr(C)] // Ensure C-compatible memory layout
 pub(crate) struct RefCountHeader {
     pub ref_count: i32, // `int` in C is usually equivalent to `i32` in Rust
@@ -139,6 +178,8 @@ impl<'js> Ctx<'js> {
         file_name: &CStr,
         flag: i32,
     ) -> Result<qjs::JSValue> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::eval(file_name);
         let src = source.into();
         let len = src.len();
         let src = CString::new(src)?;
@@ -177,14 +218,259 @@ impl<'js> Ctx<'js> {
         source: S,
         options: EvalOptions,
     ) -> Result<V> {
-        let file_name = c"eval_script";
+        let file_name = match options.source_name {
+            Some(ref name) => CString::new(name.as_str())?,
+            None => CString::from(c"eval_script"),
+        };
+        let flag = options.to_flag();
+
+        V::from_js(self, unsafe {
+            let _guard = SourceNameGuard::new(self, file_name.clone());
+            #[cfg(feature = "std")]
+            let val = match self.get_opaque().eval_cache(self.as_ptr()) {
+                Some(cache) => self.eval_raw_cached(&cache, source, &file_name, flag)?,
+                None => self.eval_raw(source, &file_name, flag)?,
+            };
+            #[cfg(not(feature = "std"))]
+            let val = self.eval_raw(source, &file_name, flag)?;
+            Value::from_js_value(self.clone(), val)
+        })
+    }
+
+    /// Evaluate a script in global context, re-using compiled bytecode if the same source was
+    /// evaluated under the same name before.
+    ///
+    /// Uses the cache attached to the context with
+    /// [`Context::set_eval_cache`](crate::Context::set_eval_cache), otherwise the cache of the
+    /// runtime enabled with [`Runtime::enable_bytecode_cache`](crate::Runtime::enable_bytecode_cache).
+    /// Without either cache the script is compiled every time like with [`Ctx::eval`].
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context};
+    /// let rt = Runtime::new().unwrap();
+    /// let cache = rt.enable_bytecode_cache(64);
+    /// for _ in 0..3 {
+    ///     let ctx = Context::full(&rt).unwrap();
+    ///     ctx.with(|ctx| {
+    ///         let res: i32 = ctx.eval_cached("sum.js", "[1, 2, 3].reduce((a, b) => a + b)").unwrap();
+    ///         assert_eq!(res, 6);
+    ///     });
+    /// }
+    /// assert_eq!(cache.stats().hits, 2);
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub fn eval_cached<V: FromJs<'js>, S: Into<Vec<u8>>>(
+        &self,
+        name: &str,
+        source: S,
+    ) -> Result<V> {
+        let file_name = CString::new(name)?;
+        let flag = EvalOptions::default().to_flag();
 
         V::from_js(self, unsafe {
-            let val = self.eval_raw(source, file_name, options.to_flag())?;
+            let _guard = SourceNameGuard::new(self, file_name.clone());
+            let opaque = self.get_opaque();
+            let val = match opaque
+                .eval_cache(self.as_ptr())
+                .or_else(|| opaque.bytecode_cache())
+            {
+                Some(cache) => self.eval_raw_cached(&cache, source, &file_name, flag)?,
+                None => self.eval_raw(source, &file_name, flag)?,
+            };
             Value::from_js_value(self.clone(), val)
         })
     }
 
+    /// Evaluate a script in global context, interrupting it once the execution timeout set with
+    /// [`Runtime::set_execution_timeout`](crate::Runtime::set_execution_timeout) elapsed.
+    ///
+    /// Returns [`Error::Timeout`] if the script was interrupted. The interrupt can't be caught by
+    /// the script, but changes it made before, like values stored on the global object, are kept
+    /// and the context can be used to evaluate other scripts afterwards. Without a timeout this is
+    /// the same as [`Ctx::eval`].
+    ///
+    /// Scripts evaluated from within the script, like with a nested call of this function, share
+    /// the deadline of the outermost evaluation.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Error};
+    /// # use std::time::Duration;
+    /// let rt = Runtime::new().unwrap();
+    /// rt.set_execution_timeout(Some(Duration::from_millis(10)));
+    /// let ctx = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| {
+    ///     let res = ctx.eval_catching_interrupt::<(), _>("globalThis.output = [1]; for(;;) {}");
+    ///     assert!(matches!(res, Err(Error::Timeout)));
+    ///     let output: Vec<i32> = ctx.globals().get("output").unwrap();
+    ///     assert_eq!(output, [1]);
+    /// });
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub fn eval_catching_interrupt<V: FromJs<'js>, S: Into<Vec<u8>>>(
+        &self,
+        source: S,
+    ) -> Result<V> {
+        struct DeadlineGuard<'a, 'js>(Option<&'a Opaque<'js>>);
+
+        impl Drop for DeadlineGuard<'_, '_> {
+            fn drop(&mut self) {
+                if let Some(opaque) = self.0 {
+                    opaque.stop_deadline();
+                }
+            }
+        }
+
+        let opaque = unsafe { self.get_opaque() };
+        let mut guard = DeadlineGuard(opaque.start_deadline().then_some(opaque));
+        let res = self.eval(source);
+        let timed_out = guard.0.take().is_some_and(Opaque::stop_deadline);
+        match res {
+            Err(Error::Exception) if timed_out => {
+                // Clear the uncatchable interrupt error so the context can be used again.
+                self.catch();
+                Err(Error::Timeout)
+            }
+            res => res,
+        }
+    }
+
+    /// Evaluate a script with the properties of `vars` in scope as local variables.
+    ///
+    /// The source is evaluated inside a function which has the own enumerable string keys of
+    /// `vars` as parameters and is called with their values, so the variables, as well as any
+    /// `var` declarations of the script, don't leak into the global object. Returns the completion
+    /// value of the script like [`Ctx::eval`].
+    ///
+    /// The source is evaluated with the `eval` function the context was created with, so this
+    /// also works in a context created with [`Context::new_sandboxed`] and is not affected by
+    /// scripts replacing the global `eval`. The source is always evaluated as a script without
+    /// top-level await support, the `global` and `promise` options are ignored.
+    ///
+    /// Returns an error if a key of `vars` isn't a valid JavaScript identifier or is a reserved
+    /// word like `this`, `eval` or `arguments`, which would collide with the names in scope of
+    /// the evaluated source.
+    ///
+    /// ```
+    /// # use rquickjs::{Context, Object, Runtime};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let vars = Object::new(ctx.clone()).unwrap();
+    /// vars.set("name", "world").unwrap();
+    /// let greeting: String = ctx
+    ///     .eval_scoped("`hello ${name}`", vars, Default::default())
+    ///     .unwrap();
+    /// assert_eq!(greeting, "hello world");
+    /// assert!(!ctx.globals().contains_key("name").unwrap());
+    /// # });
+    /// ```
+    pub fn eval_scoped<V: FromJs<'js>, S: Into<Vec<u8>>>(
+        &self,
+        source: S,
+        vars: Object<'js>,
+        options: EvalOptions,
+    ) -> Result<V> {
+        let mut names = Vec::new();
+        let mut values = Vec::new();
+        for prop in vars.props::<StdString, Value>() {
+            let (name, value) = prop?;
+            if !is_variable_name(&name) {
+                return Err(Error::new_into_js_message(
+                    "object",
+                    "scope",
+                    format!("`{name}` is not a valid variable name"),
+                ));
+            }
+            names.push(name);
+            values.push(value);
+        }
+        let source = StdString::from_utf8(source.into())
+            .map_err(|e| Error::new_into_js_message("bytes", "source", e.to_string()))?;
+        let eval = unsafe {
+            let eval = qjs::rquickjs_get_intrinsic_eval(self.as_ptr());
+            Function::from_js_value(self.clone(), eval)
+        };
+
+        // The source is passed as an additional argument and evaluated with a direct eval, which
+        // keeps the completion value of the script and gives it access to the parameters. The
+        // call is only a direct eval if `eval` is the intrinsic of the context, so it is passed
+        // as a parameter as well, which requires the wrapper to be sloppy code. Strict mode is
+        // enabled by the evaluated source instead.
+        let wrapper = format!(
+            "(function ({names}eval) {{ return eval(arguments[{len}]); }})",
+            names = names
+                .iter()
+                .map(|name| format!("{name}, "))
+                .collect::<StdString>(),
+            len = names.len() + 1,
+        );
+        let strict = options.strict;
+        let func: Function = self.eval_with_options(
+            wrapper,
+            EvalOptions {
+                global: true,
+                strict: false,
+                promise: false,
+                ..options
+            },
+        )?;
+        let source = if strict {
+            format!("'use strict'; {source}")
+        } else {
+            source
+        };
+        values.push(eval.into_value());
+        values.push(String::from_str(self.clone(), &source)?.into_value());
+        func.call((Rest(values),))
+    }
+
+    /// Evaluate a script re-using bytecode from the cache if the same source was compiled before.
+    #[cfg(feature = "std")]
+    unsafe fn eval_raw_cached<S: Into<Vec<u8>>>(
+        &self,
+        cache: &EvalCache,
+        source: S,
+        file_name: &CStr,
+        flag: i32,
+    ) -> Result<qjs::JSValue> {
+        let source = source.into();
+        let key = EvalCache::key(&source, file_name.to_bytes(), flag);
+        let func = match cache.get(&key) {
+            Some(bytecode) => self.handle_exception(qjs::JS_ReadObject(
+                self.as_ptr(),
+                bytecode.as_ptr(),
+                bytecode.len() as _,
+                qjs::JS_READ_OBJ_BYTECODE as i32,
+            ))?,
+            None => {
+                let func = self.eval_raw(
+                    source,
+                    file_name,
+                    flag | qjs::JS_EVAL_FLAG_COMPILE_ONLY as i32,
+                )?;
+                let mut len = MaybeUninit::uninit();
+                let buf = qjs::JS_WriteObject(
+                    self.as_ptr(),
+                    len.as_mut_ptr(),
+                    func,
+                    qjs::JS_WRITE_OBJ_BYTECODE as i32,
+                );
+                if buf.is_null() {
+                    qjs::JS_FreeValue(self.as_ptr(), func);
+                    return Err(self.raise_exception());
+                }
+                let bytecode = core::slice::from_raw_parts(buf, len.assume_init() as _).to_vec();
+                qjs::js_free(self.as_ptr(), buf as _);
+                cache.insert(key, bytecode);
+                func
+            }
+        };
+        // Takes ownership of the compiled function.
+        self.handle_exception(qjs::JS_EvalFunction(self.as_ptr(), func))
+    }
+
     #[cfg(feature = "std")]
     /// Evaluate a script directly from a file.
     pub fn eval_file<V: FromJs<'js>, P: AsRef<Path>>(&self, path: P) -> Result<V> {
@@ -207,6 +493,7 @@ impl<'js> Ctx<'js> {
         )?;
 
         V::from_js(self, unsafe {
+            let _guard = SourceNameGuard::new(self, file_name.clone());
             let val = self.eval_raw(buffer, file_name.as_c_str(), options.to_flag())?;
             Value::from_js_value(self.clone(), val)
         })
@@ -220,6 +507,34 @@ impl<'js> Ctx<'js> {
         }
     }
 
+    /// Install a `console` global whose `debug`, `log`, `info`, `warn` and `error` methods call
+    /// the handler with the level and the arguments of the call.
+    ///
+    /// The arguments are passed unformatted, use
+    /// [`format_args`](crate::interop::console::format_args) to format them like `console.log`
+    /// does in other environments. Errors returned by the handler are thrown by the called method.
+    ///
+    /// # Example
+    /// ```
+    /// # use rquickjs::{Runtime, Context, interop::console::format_args};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| {
+    ///     ctx.set_console(|level, args| {
+    ///         println!("[{level}] {}", format_args(&args)?);
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    ///     ctx.eval::<(), _>("console.info('value:', { a: [1, 2] })").unwrap();
+    /// });
+    /// ```
+    pub fn set_console<F>(&self, handler: F) -> Result<()>
+    where
+        F: Fn(LogLevel, Vec<Value<'js>>) -> Result<()> + 'js,
+    {
+        crate::interop::console::register_console(self, handler)
+    }
+
     /// Returns the last raised JavaScript exception, if there is no exception the JavaScript value `null` is returned.
     ///
     /// # Usage
@@ -242,9 +557,14 @@ impl<'js> Ctx<'js> {
         }
     }
 
-    /// Throws a JavaScript value as a new exception.
-    /// Always returns `Error::Exception`;
-    pub fn throw(&self, value: Value<'js>) -> Error {
+    /// Throws a value converted to JavaScript as a new exception.
+    ///
+    /// Returns `Error::Exception`, or the error of the conversion if it failed.
+    pub fn throw<V: IntoJs<'js>>(&self, value: V) -> Error {
+        let value = match value.into_js(self) {
+            Ok(value) => value,
+            Err(error) => return error,
+        };
         unsafe {
             let v = value.into_js_value();
             qjs::JS_Throw(self.ctx.as_ptr(), v);
@@ -273,6 +593,21 @@ impl<'js> Ctx<'js> {
         }
     }
 
+    /// Parse json directly into a Rust type.
+    ///
+    /// The json is parsed by QuickJS so numbers and strings follow JavaScript semantics, the
+    /// resulting JavaScript value is only kept alive while it is deserialized into `T`.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "serde")))]
+    pub fn parse_json_typed<T, S>(&self, json: S) -> Result<T>
+    where
+        T: ::serde::de::DeserializeOwned,
+        S: Into<Vec<u8>>,
+    {
+        let value = self.json_parse(json)?;
+        crate::serde::from_value(value)
+    }
+
     /// Stringify a JavaScript value into its JSON representation
     pub fn json_stringify<V>(&self, value: V) -> Result<Option<String<'js>>>
     where
@@ -373,6 +708,16 @@ impl<'js> Ctx<'js> {
         })
     }
 
+    /// Creates a promise which is already resolved with the value, or rejected with the error.
+    ///
+    /// See [`Promise::from_result`].
+    pub fn promise_from_result<T>(&self, res: Result<T>) -> Result<Promise<'js>>
+    where
+        T: IntoJs<'js>,
+    {
+        Promise::from_result(self, res)
+    }
+
     /// Executes a quickjs job.
     ///
     /// Returns wether a job was actually executed.
@@ -380,7 +725,7 @@ impl<'js> Ctx<'js> {
     pub fn execute_pending_job(&self) -> bool {
         let mut ptr = MaybeUninit::<*mut qjs::JSContext>::uninit();
         let rt = unsafe { qjs::JS_GetRuntime(self.ctx.as_ptr()) };
-        let res = unsafe { qjs::JS_ExecutePendingJob(rt, ptr.as_mut_ptr()) };
+        let res = unsafe { crate::runtime::raw::RawRuntime::execute_job(rt, ptr.as_mut_ptr()) };
         res != 0
     }
 
@@ -389,13 +734,43 @@ impl<'js> Ctx<'js> {
     }
 
     /// Spawn future using configured async runtime
+    ///
+    /// The future doesn't need to be [`Send`] without the `"parallel"` feature.
+    ///
+    /// The returned handle can be used to abort the future. The future is also aborted when the
+    /// [`AsyncContext`](crate::AsyncContext) it was spawned in is aborted or dropped.
     #[cfg(feature = "futures")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
-    pub fn spawn<F>(&self, future: F)
+    pub fn spawn<F>(&self, future: F) -> JobHandle
     where
         F: Future<Output = ()> + 'js,
     {
-        unsafe { self.get_opaque().push(future) }
+        unsafe { self.get_opaque().push(self.ctx.as_ptr(), future) }
+    }
+
+    /// Spawn a future using the configured async runtime and return a promise which settles with
+    /// its output.
+    ///
+    /// Outputs which are `Err` or fail to convert reject the promise, errors which aren't
+    /// JavaScript exceptions are converted into one. The future keeps the context alive, if the
+    /// runtime is dropped before the future completes the future is dropped and the promise is
+    /// never settled.
+    #[cfg(feature = "futures")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+    pub fn spawn_promise<F, T>(&self, future: F) -> Result<Promise<'js>>
+    where
+        F: Future<Output = T> + 'js,
+        T: IntoJs<'js>,
+    {
+        Promise::wrap_future(self, future)
+    }
+
+    /// Returns the executor of the runtime, if it was created with
+    /// [`AsyncRuntime::new_with_spawner`](crate::AsyncRuntime::new_with_spawner).
+    #[cfg(feature = "futures")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+    pub fn executor(&self) -> Option<Arc<dyn ExecutorSpawner>> {
+        unsafe { self.get_opaque().executor() }
     }
 
     /// Create a new `Ctx` from a pointer to the context and a invariant lifetime.
@@ -445,7 +820,12 @@ impl<'js> Ctx<'js> {
     /// This runs the cyclic reference collector cycle, types which are not part of a reference cycle
     /// will be freed the momement their reference count becomes zero.
     pub fn run_gc(&self) {
-        unsafe { qjs::JS_RunGC(qjs::JS_GetRuntime(self.ctx.as_ptr())) }
+        unsafe {
+            let rt = qjs::JS_GetRuntime(self.ctx.as_ptr());
+            #[cfg(feature = "tracing")]
+            let _span = crate::trace::GcSpan::enter(rt);
+            qjs::JS_RunGC(rt)
+        }
     }
 
     /// Store a type in the runtime which can be retrieved later with `Ctx::userdata`.
@@ -490,6 +870,62 @@ impl<'js> Ctx<'js> {
     }
 }
 
+/// Returns whether a name can be used as the name of a function parameter.
+fn is_variable_name(name: &str) -> bool {
+    const RESERVED: &[&str] = &[
+        "arguments",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "eval",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ];
+
+    is_identifier(name) && !RESERVED.contains(&name)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{CatchResultExt, JsLifetime};
@@ -647,6 +1083,247 @@ mod test {
         })
     }
 
+    #[cfg(feature = "futures")]
+    #[tokio::test]
+    async fn spawn_promise() {
+        use crate::{async_with, AsyncContext, AsyncRuntime, Error, Module, Result};
+        use std::time::Duration;
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            let globals = ctx.globals();
+            let promise = ctx
+                .spawn_promise(async {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    Result::Ok(42)
+                })
+                .unwrap();
+            globals.set("resolved", promise).unwrap();
+            let promise = ctx
+                .spawn_promise(async { Result::<()>::Err(Error::new_from_js("foo", "bar")) })
+                .unwrap();
+            globals.set("rejected", promise).unwrap();
+
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                globalThis.value = await resolved;
+                globalThis.error = await rejected.catch((e) => e.message);
+                "#,
+            )
+            .unwrap()
+            .into_future::<()>()
+            .await
+            .unwrap();
+
+            assert_eq!(globals.get::<_, i32>("value").unwrap(), 42);
+            let error: String = globals.get("error").unwrap();
+            assert!(error.contains("foo"), "{error}");
+        })
+        .await
+    }
+
+    #[test]
+    fn promise_from_result() {
+        use crate::{promise::PromiseState, CaughtError, Context, Error, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            let promise = ctx.promise_from_result(Ok("foo")).unwrap();
+            assert!(
+                matches!(promise.state(), PromiseState::Fulfilled(v) if v.get::<String>().unwrap() == "foo")
+            );
+            assert_eq!(promise.finish::<String>().unwrap(), "foo");
+
+            let err = ctx.eval::<(), _>("throw 1").unwrap_err();
+            let promise = ctx.promise_from_result::<()>(Err(err)).unwrap();
+            assert!(matches!(promise.state(), PromiseState::Rejected(v) if v.as_int() == Some(1)));
+            let err = promise.finish::<()>().catch(&ctx).unwrap_err();
+            assert!(matches!(err, CaughtError::Value(v) if v.as_int() == Some(1)));
+
+            let promise = ctx
+                .promise_from_result::<()>(Err(Error::new_from_js("foo", "bar")))
+                .unwrap();
+            let err = promise.finish::<()>().catch(&ctx).unwrap_err();
+            assert!(err.is_exception());
+        })
+    }
+
+    #[test]
+    fn eval_catching_interrupt() {
+        use crate::{Context, Error, Runtime};
+        use std::time::Duration;
+
+        let runtime = Runtime::new().unwrap();
+        runtime.set_execution_timeout(Some(Duration::from_millis(20)));
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            ctx.globals().set("output", Vec::<i32>::new()).unwrap();
+            for i in 0..2 {
+                let res = ctx.eval_catching_interrupt::<(), _>(format!(
+                    r#"
+                    output.push({i});
+                    try {{
+                        for(;;) {{}}
+                    }} catch(e) {{
+                        output.push("caught");
+                    }}
+                    "#
+                ));
+                assert!(matches!(res, Err(Error::Timeout)), "{res:?}");
+            }
+            let output: Vec<i32> = ctx.globals().get("output").unwrap();
+            assert_eq!(output, [0, 1]);
+
+            // The context is usable after the timeout and the deadline is per evaluation.
+            let res: i32 = ctx.eval_catching_interrupt("output.length + 1").unwrap();
+            assert_eq!(res, 3);
+            // Plain evaluations are not interrupted.
+            let res: i32 = ctx
+                .eval("let start = Date.now(); while(Date.now() - start < 40) {}; 1")
+                .unwrap();
+            assert_eq!(res, 1);
+        });
+
+        // Interrupt handlers still apply and are not reported as timeouts.
+        runtime.set_interrupt_handler(Some(Box::new(|| true)));
+        ctx.with(|ctx| {
+            let res = ctx.eval_catching_interrupt::<(), _>("for(;;) {}");
+            assert!(matches!(res, Err(Error::Exception)), "{res:?}");
+            ctx.catch();
+        });
+        runtime.set_interrupt_handler(None);
+        runtime.set_execution_timeout(None);
+        ctx.with(|ctx| {
+            let res: i32 = ctx
+                .eval_catching_interrupt("let i = 0; while(i < 100000) { i++ }; i")
+                .unwrap();
+            assert_eq!(res, 100000);
+        });
+    }
+
+    #[test]
+    fn eval_scoped() {
+        use crate::{Context, Error, Object, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            let vars = Object::new(ctx.clone()).unwrap();
+            vars.set("a", 1).unwrap();
+            vars.set("$b", 2).unwrap();
+            let res: i32 = ctx
+                .eval_scoped("var c = a + $b; c * 2", vars.clone(), Default::default())
+                .unwrap();
+            assert_eq!(res, 6);
+            for name in ["a", "$b", "c"] {
+                assert!(!ctx.globals().contains_key(name).unwrap());
+            }
+
+            // Globals are still visible and can be shadowed.
+            ctx.globals().set("a", 10).unwrap();
+            let res: i32 = ctx.eval("a").unwrap();
+            assert_eq!(res, 10);
+            let res: i32 = ctx
+                .eval_scoped("a + Math.max($b, 1)", vars.clone(), Default::default())
+                .unwrap();
+            assert_eq!(res, 3);
+
+            let empty = Object::new(ctx.clone()).unwrap();
+            let res: i32 = ctx
+                .eval_scoped("if (true) { 4 }", empty, Default::default())
+                .unwrap();
+            assert_eq!(res, 4);
+
+            let err = ctx
+                .eval_scoped::<(), _>("throw new Error('inner')", vars, Default::default())
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("inner"));
+
+            for name in ["not valid", "1a", "this", "arguments", "eval", ""] {
+                let vars = Object::new(ctx.clone()).unwrap();
+                vars.set(name, 1).unwrap();
+                let res = ctx.eval_scoped::<(), _>("1", vars, Default::default());
+                assert!(matches!(res, Err(Error::IntoJs { .. })), "{name}: {res:?}");
+            }
+        });
+    }
+
+    #[test]
+    fn eval_scoped_intrinsic_eval() {
+        use crate::{
+            context::{EvalOptions, SandboxPolicy},
+            Context, Object, Runtime,
+        };
+
+        let runtime = Runtime::new().unwrap();
+        let sandbox = Context::new_sandboxed(&runtime, SandboxPolicy::default()).unwrap();
+        let full = Context::full(&runtime).unwrap();
+        full.with(|ctx| {
+            ctx.eval::<(), _>("globalThis.eval = (src) => { globalThis.leaked = src; return 0 }")
+                .unwrap();
+        });
+        for context in [sandbox, full] {
+            context.with(|ctx| {
+                let vars = Object::new(ctx.clone()).unwrap();
+                vars.set("a", 1).unwrap();
+                let res: i32 = ctx
+                    .eval_scoped("var b = a + 1; b * 2", vars.clone(), Default::default())
+                    .catch(&ctx)
+                    .unwrap();
+                assert_eq!(res, 4);
+                assert!(!ctx.globals().contains_key("leaked").unwrap());
+                let res: String = ctx
+                    .eval_scoped(
+                        "'a' +\n\"b\" + `${a}` // }); ",
+                        vars.clone(),
+                        Default::default(),
+                    )
+                    .catch(&ctx)
+                    .unwrap();
+                assert_eq!(res, "ab1");
+
+                let res: bool = ctx
+                    .eval_scoped(
+                        "(function () { return this === undefined })()",
+                        vars,
+                        EvalOptions {
+                            strict: true,
+                            ..Default::default()
+                        },
+                    )
+                    .catch(&ctx)
+                    .unwrap();
+                assert!(res);
+            });
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_json_typed() {
+        use crate::{Context, Runtime};
+        use std::collections::BTreeMap;
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            let v: BTreeMap<String, (u64, bool, Option<String>)> = ctx
+                .parse_json_typed(r#"{ "a": [1e12, true, null], "b": [0, false, "foo"] }"#)
+                .unwrap();
+            assert_eq!(v["a"], (1_000_000_000_000, true, None));
+            assert_eq!(v["b"], (0, false, Some("foo".to_string())));
+
+            let err = ctx.parse_json_typed::<u32, _>("{ a:").unwrap_err();
+            assert!(err.is_exception());
+        })
+    }
+
     #[test]
     fn json_stringify() {
         use crate::{Array, Context, Object, Runtime};
@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::bytecode_cache::MemoryBytecodeCache;
+
+/// Statistics of an [`EvalCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of evaluations which re-used cached bytecode.
+    pub hits: u64,
+    /// Number of evaluations which had to compile the source.
+    pub misses: u64,
+    /// Number of compiled sources currently in the cache.
+    pub entries: usize,
+    /// Number of compiled sources removed to keep the cache within its capacity.
+    pub evictions: u64,
+}
+
+/// A cache of compiled bytecode for scripts evaluated with [`Ctx::eval`](crate::Ctx::eval).
+///
+/// Attach it to a context with [`Context::set_eval_cache`](crate::Context::set_eval_cache).
+/// Entries are keyed on the source together with its name and the evaluation flags, so the same
+/// cache can be shared between multiple contexts and runtimes. The whole source is part of the
+/// key, a hash of it could collide and run the bytecode of another script.
+///
+/// A cache created with [`EvalCache::with_capacity`] holds at most the given number of compiled
+/// sources, evicting the least recently used one when full.
+///
+/// The entries are behind a single mutex instead of a concurrent map: every lookup updates the
+/// order of the least recently used entries, so it needs exclusive access anyway. The lock is
+/// only held to look up or store the bytecode, never while a script is compiled or run.
+#[derive(Debug, Default)]
+pub struct EvalCache {
+    entries: Mutex<MemoryBytecodeCache>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl EvalCache {
+    /// Create a new empty cache without a limit on the number of entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new empty cache holding at most `capacity` compiled sources.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(MemoryBytecodeCache::with_capacity(capacity)),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the maximum number of compiled sources the cache holds, if limited.
+    pub fn capacity(&self) -> Option<usize> {
+        self.lock().capacity()
+    }
+
+    /// Remove all compiled bytecode from the cache.
+    pub fn invalidate_all(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.lock();
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: entries.len(),
+            evictions: entries.evictions(),
+        }
+    }
+
+    pub(crate) fn key(source: &[u8], name: &[u8], flag: i32) -> Vec<u8> {
+        // The length of the name separates it from the source.
+        let mut key = Vec::with_capacity(12 + name.len() + source.len());
+        key.extend_from_slice(&flag.to_le_bytes());
+        key.extend_from_slice(&(name.len() as u64).to_le_bytes());
+        key.extend_from_slice(name);
+        key.extend_from_slice(source);
+        key
+    }
+
+    pub(crate) fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let bytecode = self.lock().get(key).map(<[u8]>::to_vec);
+        if bytecode.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        bytecode
+    }
+
+    pub(crate) fn insert(&self, key: Vec<u8>, bytecode: Vec<u8>) {
+        self.lock().insert(key, bytecode);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MemoryBytecodeCache> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::{atom::PredefinedAtom, Array, Atom, Ctx, Object, Result, Type, Value};
+
+/// Copies the enumerable own properties of the global object of `src` onto the global object
+/// of `dst`, both contexts must be part of the same runtime.
+pub(crate) fn copy_globals<'js>(src: &Ctx<'js>, dst: &Ctx<'js>) -> Result<()> {
+    let mut cloner = Cloner {
+        dst: dst.clone(),
+        object_proto: src.globals().get::<_, Object>("Object")?.get("prototype")?,
+        cloned: HashMap::new(),
+    };
+    let globals = dst.globals();
+    for prop in src.globals().props::<Atom, Value>() {
+        let (key, value) = prop?;
+        // Don't replace the intrinsics of the new context.
+        if globals.contains_key(key.clone())? {
+            continue;
+        }
+        if let Some(value) = cloner.clone_value(value)? {
+            globals.set(key, value)?;
+        }
+    }
+    Ok(())
+}
+
+struct Cloner<'js> {
+    dst: Ctx<'js>,
+    object_proto: Value<'js>,
+    /// Objects already cloned, used to preserve shared references and cycles.
+    cloned: HashMap<Value<'js>, Value<'js>>,
+}
+
+impl<'js> Cloner<'js> {
+    /// Returns `None` if the value can't be cloned.
+    fn clone_value(&mut self, value: Value<'js>) -> Result<Option<Value<'js>>> {
+        match value.type_of() {
+            Type::Undefined
+            | Type::Null
+            | Type::Bool
+            | Type::Int
+            | Type::Float
+            | Type::String
+            | Type::BigInt => {
+                // Primitives are owned by the runtime so they can be shared between contexts.
+                Ok(Some(unsafe {
+                    Value::from_js_value_const(self.dst.clone(), value.as_js_value())
+                }))
+            }
+            Type::Array => {
+                if let Some(cloned) = self.cloned.get(&value) {
+                    return Ok(Some(cloned.clone()));
+                }
+                let array = Array::new(self.dst.clone())?;
+                self.cloned
+                    .insert(value.clone(), array.clone().into_value());
+                let src = value.into_array().unwrap();
+                for (idx, item) in src.iter::<Value>().enumerate() {
+                    if let Some(item) = self.clone_value(item?)? {
+                        array.set(idx, item)?;
+                    }
+                }
+                // Skipped trailing elements would otherwise shorten the array.
+                array.as_object().set(PredefinedAtom::Length, src.len())?;
+                Ok(Some(array.into_value()))
+            }
+            Type::Object => {
+                if let Some(cloned) = self.cloned.get(&value) {
+                    return Ok(Some(cloned.clone()));
+                }
+                let object = value.as_object().unwrap();
+                let cloned = Object::new(self.dst.clone())?;
+                match object.get_prototype() {
+                    // Only plain objects are cloned, not instances of classes.
+                    Some(proto) if proto.as_value() != &self.object_proto => return Ok(None),
+                    Some(_) => {}
+                    None => cloned.set_prototype(None)?,
+                }
+                self.cloned
+                    .insert(value.clone(), cloned.clone().into_value());
+                for prop in object.props::<Atom, Value>() {
+                    let (key, value) = prop?;
+                    if let Some(value) = self.clone_value(value)? {
+                        cloned.set(key, value)?;
+                    }
+                }
+                Ok(Some(cloned.into_value()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use core::ptr::NonNull;
+use core::{marker::PhantomData, ptr::NonNull};
 
-use crate::qjs;
+use crate::{markers::ThreadMarker, qjs};
 
 #[cfg(feature = "parallel")]
 use std::sync::Arc;
@@ -21,18 +21,25 @@ pub(crate) struct ContextOwner<R: DropContext> {
     #[cfg(feature = "parallel")]
     pub(crate) ctx: Arc<NonNull<qjs::JSContext>>,
     pub(crate) rt: R,
+    _thread: ThreadMarker,
 }
 
 impl<R: DropContext> ContextOwner<R> {
     #[cfg(not(feature = "parallel"))]
     pub(crate) unsafe fn new(ctx: NonNull<qjs::JSContext>, rt: R) -> Self {
-        Self { ctx, rt }
+        Self {
+            ctx,
+            rt,
+            _thread: PhantomData,
+        }
     }
     #[cfg(feature = "parallel")]
+    #[allow(clippy::arc_with_non_send_sync)]
     pub(crate) unsafe fn new(ctx: NonNull<qjs::JSContext>, rt: R) -> Self {
         Self {
             ctx: Arc::new(ctx),
             rt,
+            _thread: PhantomData,
         }
     }
 
@@ -56,7 +63,11 @@ impl<R: DropContext> Clone for ContextOwner<R> {
     fn clone(&self) -> Self {
         let ctx = unsafe { NonNull::new_unchecked(qjs::JS_DupContext(self.ctx.as_ptr())) };
         let rt = self.rt.clone();
-        Self { ctx, rt }
+        Self {
+            ctx,
+            rt,
+            _thread: PhantomData,
+        }
     }
 }
 
@@ -66,6 +77,7 @@ impl<R: DropContext> Clone for ContextOwner<R> {
         Self {
             ctx: self.ctx.clone(),
             rt: self.rt.clone(),
+            _thread: PhantomData,
         }
     }
 }
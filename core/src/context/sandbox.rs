@@ -0,0 +1,265 @@
+#[cfg(not(feature = "std"))]
+use hashbrown::HashSet;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+use crate::{
+    object::Property, qjs, Atom, Ctx, Exception, Filter, Function, Object, Result, StdString, Value,
+};
+use alloc::vec::Vec;
+
+/// The restrictions of a context created with
+/// [`Context::new_sandboxed`](crate::Context::new_sandboxed).
+///
+/// The default policy is the most restrictive one: `eval` and the `Function` constructor are
+/// removed and the global object and built-ins are frozen, while all other standard globals are
+/// kept.
+#[derive(Debug, Clone)]
+pub struct SandboxPolicy {
+    /// Keep the global `eval` function.
+    pub allow_eval: bool,
+    /// Keep the constructors compiling source code at runtime, `Function` and the constructors
+    /// of async and generator functions.
+    pub allow_function_constructor: bool,
+    /// Freeze the global object and all built-in objects and prototypes reachable from it, which
+    /// prevents scripts from replacing built-ins or polluting prototypes.
+    pub frozen_global: bool,
+    /// The names of the global properties to keep, all other configurable globals are removed.
+    /// `None` keeps all globals not removed by the other options.
+    pub allowed_globals: Option<HashSet<StdString>>,
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self {
+            allow_eval: false,
+            allow_function_constructor: false,
+            frozen_global: true,
+            allowed_globals: None,
+        }
+    }
+}
+
+/// Restricts the globals of a context with all intrinsics according to the policy.
+pub(crate) fn restrict<'js>(ctx: &Ctx<'js>, policy: &SandboxPolicy) -> Result<()> {
+    let globals = ctx.globals();
+    let function_proto: Object = globals.get::<_, Object>("Function")?.get("prototype")?;
+    // Intrinsics which aren't reachable from the global object, retrieved before removing any
+    // globals.
+    let hidden: Vec<Value> = ctx.eval(
+        r#"[
+            async function () {},
+            function* () {},
+            async function* () {},
+            [][Symbol.iterator](),
+            ""[Symbol.iterator](),
+            new Map()[Symbol.iterator](),
+            new Set()[Symbol.iterator](),
+            /a/[Symbol.matchAll](""),
+        ]"#,
+    )?;
+
+    if !policy.allow_function_constructor {
+        let function = disabled_constructor(ctx, "Function", &function_proto)?;
+        globals.set("Function", function)?;
+        for (value, name) in hidden[..3].iter().zip([
+            "AsyncFunction",
+            "GeneratorFunction",
+            "AsyncGeneratorFunction",
+        ]) {
+            if let Some(proto) = value.as_object().and_then(Object::get_prototype) {
+                disabled_constructor(ctx, name, &proto)?;
+            }
+        }
+    }
+
+    if !policy.allow_eval {
+        globals.remove("eval")?;
+    }
+
+    if let Some(allowed) = &policy.allowed_globals {
+        for (key, desc) in globals.own_properties_full::<Atom>(Filter::new().string())? {
+            // `undefined`, `NaN` and `Infinity` can't be removed.
+            if !desc.configurable || allowed.contains(key.to_string()?.as_str()) {
+                continue;
+            }
+            globals.remove(key)?;
+        }
+    }
+
+    if policy.frozen_global {
+        let mut freezer = Freezer {
+            frozen: HashSet::new(),
+        };
+        freezer.freeze(globals.into_value())?;
+        for value in hidden {
+            freezer.freeze(value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the constructor of the prototype with a function which throws when called.
+fn disabled_constructor<'js>(
+    ctx: &Ctx<'js>,
+    name: &str,
+    proto: &Object<'js>,
+) -> Result<Function<'js>> {
+    let function = Function::new(ctx.clone(), |ctx: Ctx<'js>| -> Result<()> {
+        Err(Exception::throw_type(
+            &ctx,
+            "Compiling code at runtime is not allowed in the sandbox",
+        ))
+    })?
+    .with_name(name)?
+    .with_constructor(true);
+    function.prop("prototype", Property::from(proto.clone()))?;
+    proto.prop(
+        "constructor",
+        Property::from(function.clone()).writable().configurable(),
+    )?;
+    Ok(function)
+}
+
+struct Freezer<'js> {
+    frozen: HashSet<Value<'js>>,
+}
+
+impl<'js> Freezer<'js> {
+    /// Freezes the object, its properties and its prototype chain.
+    fn freeze(&mut self, value: Value<'js>) -> Result<()> {
+        let Some(object) = value.as_object().cloned() else {
+            return Ok(());
+        };
+        if !self.frozen.insert(value) {
+            return Ok(());
+        }
+        let ctx = object.ctx();
+        if unsafe { qjs::JS_FreezeObject(ctx.as_ptr(), object.as_js_value()) } < 0 {
+            return Err(ctx.raise_exception());
+        }
+        for (_, desc) in object.own_properties_full::<Atom>(Filter::new().string().symbol())? {
+            if let Some(value) = desc.value {
+                self.freeze(value)?;
+            }
+            if let Some(get) = desc.get {
+                self.freeze(get.into_value())?;
+            }
+            if let Some(set) = desc.set {
+                self.freeze(set.into_value())?;
+            }
+        }
+        if let Some(proto) = object.get_prototype() {
+            self.freeze(proto.into_value())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SandboxPolicy;
+    use crate::{CatchResultExt, Context, Runtime, StdString};
+
+    fn sandboxed(policy: SandboxPolicy) -> Context {
+        let rt = Runtime::new().unwrap();
+        Context::new_sandboxed(&rt, policy).unwrap()
+    }
+
+    #[test]
+    fn no_code_generation() {
+        let ctx = sandboxed(SandboxPolicy::default());
+        ctx.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>("1 + 2").unwrap(), 3);
+            for escape in [
+                "eval('1')",
+                "globalThis.eval('1')",
+                "Function('return this')()",
+                "new Function('return this')()",
+                "(() => {}).constructor('return this')()",
+                "Object.getPrototypeOf(async function () {}).constructor('return 1')",
+                "(function* () {}).constructor('yield 1')",
+                "(async function* () {}).constructor('yield 1')",
+                "Reflect.construct(Function, ['return 1'])",
+            ] {
+                let err = ctx.eval::<(), _>(escape).catch(&ctx).unwrap_err();
+                assert!(err.is_exception(), "{escape}: {err}");
+            }
+            // Functions still work like before.
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                    const f = function () {};
+                    [
+                        f instanceof Function,
+                        (async () => {}) instanceof Function,
+                        typeof eval,
+                        f.call.name,
+                    ].join()
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "true,true,undefined,call");
+        })
+    }
+
+    #[test]
+    fn frozen_builtins() {
+        let ctx = sandboxed(SandboxPolicy::default());
+        ctx.with(|ctx| {
+            for pollution in [
+                "Object.prototype.polluted = true",
+                "({}).__proto__.polluted = true",
+                "Array.prototype.push = null",
+                "JSON.parse = null",
+                "globalThis.Math = null",
+                "globalThis.leaked = 1",
+                "Object.getPrototypeOf([][Symbol.iterator]()).next = null",
+                "Object.getPrototypeOf(function* () {}).prototype.next = null",
+                "delete Object.prototype.toString",
+            ] {
+                let err = ctx.eval::<(), _>(pollution).catch(&ctx).unwrap_err();
+                assert!(err.is_exception(), "{pollution}: {err}");
+            }
+            assert!(!ctx
+                .eval::<bool, _>("'polluted' in {} || Object.isExtensible(globalThis)")
+                .unwrap());
+            // Own objects are not affected.
+            assert_eq!(
+                ctx.eval::<i32, _>("const o = { a: 1 }; o.a = 2; o.a")
+                    .unwrap(),
+                2
+            );
+        })
+    }
+
+    #[test]
+    fn allowed_globals() {
+        let ctx = sandboxed(SandboxPolicy {
+            allow_eval: true,
+            allow_function_constructor: true,
+            frozen_global: false,
+            allowed_globals: Some(
+                ["globalThis", "Math", "eval", "Function"]
+                    .map(StdString::from)
+                    .into(),
+            ),
+        });
+        ctx.with(|ctx| {
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                    [
+                        typeof Math, typeof JSON, typeof Object, typeof Proxy,
+                        eval("1 + 1"), Function("return 3")(), typeof undefined,
+                    ].join()
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "object,undefined,undefined,undefined,2,3,undefined");
+            ctx.eval::<(), _>("globalThis.added = 1").unwrap();
+        })
+    }
+}
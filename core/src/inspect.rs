@@ -0,0 +1,659 @@
+//! Formatting JavaScript values for debugging, like `util.inspect` in Node.js.
+//!
+//! Unlike `JSON.stringify` the output shows functions, `undefined`, symbols and circular
+//! references, and accessors are shown as `[Getter]` or `[Setter]` instead of being invoked.
+//!
+//! ```
+//! # use rquickjs::{inspect::{inspect, InspectOptions}, Context, Runtime, Value};
+//! # let rt = Runtime::new().unwrap();
+//! # let ctx = Context::full(&rt).unwrap();
+//! # ctx.with(|ctx| {
+//! let value: Value = ctx
+//!     .eval("const a = { f() {}, n: undefined, s: Symbol('s') }; a.a = a; a")
+//!     .unwrap();
+//! assert_eq!(
+//!     inspect(ctx, value, InspectOptions::default()).unwrap(),
+//!     "<ref *1> { f: [Function: f], n: undefined, s: Symbol(s), a: [Circular *1] }"
+//! );
+//! # })
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString as _},
+    vec::Vec,
+};
+
+use crate::util::{is_identifier, quote};
+use crate::{
+    atom::PredefinedAtom, function::This, object::PropertyDescriptor, promise::PromiseState, Atom,
+    Coerced, Ctx, Filter, Function, Object, Result, Type, Value,
+};
+
+/// Options of [`inspect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InspectOptions {
+    /// How many levels of nested objects are formatted, deeper objects are shown as `[Object]`.
+    pub depth: usize,
+    /// Whether to style the output with ANSI color codes.
+    pub colors: bool,
+    /// The maximum number of array elements shown, the rest is summarized as `... n more items`.
+    pub max_array_length: usize,
+    /// The maximum number of characters of strings shown, the rest is summarized as
+    /// `... n more characters`.
+    pub max_string_length: usize,
+}
+
+impl Default for InspectOptions {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            colors: false,
+            max_array_length: 100,
+            max_string_length: 10000,
+        }
+    }
+}
+
+/// Format a value for debugging.
+///
+/// The format follows `util.inspect` in Node.js: strings are quoted, objects are shown as
+/// `{ key: value }` prefixed with the name of their class, arrays as `[ ... ]` and functions as
+/// `[Function: name]`. Objects which don't fit on a line of 80 characters are split over multiple
+/// lines. Maps, sets and typed arrays are shown as plain objects.
+pub fn inspect<'js>(ctx: Ctx<'js>, value: Value<'js>, options: InspectOptions) -> Result<String> {
+    let function_to_string = Function::prototype(ctx).get(PredefinedAtom::ToString)?;
+    Inspector {
+        options,
+        function_to_string,
+        seen: Vec::new(),
+        circular: Vec::new(),
+        indentation: 0,
+    }
+    .format_value(&value, 0)
+}
+
+const BREAK_LENGTH: usize = 80;
+
+#[derive(Clone, Copy)]
+enum Style {
+    Special,
+    Number,
+    Undefined,
+    Null,
+    String,
+    Symbol,
+    Date,
+    RegExp,
+}
+
+impl Style {
+    fn codes(self) -> (u8, u8) {
+        match self {
+            Style::Special => (36, 39),
+            Style::Number => (33, 39),
+            Style::Undefined => (90, 39),
+            Style::Null => (1, 22),
+            Style::String | Style::Symbol => (32, 39),
+            Style::Date => (35, 39),
+            Style::RegExp => (31, 39),
+        }
+    }
+}
+
+struct Inspector<'js> {
+    options: InspectOptions,
+    function_to_string: Function<'js>,
+    /// The objects currently being formatted, to detect circular references.
+    seen: Vec<Object<'js>>,
+    /// The objects referenced circularly, numbered by their position.
+    circular: Vec<Object<'js>>,
+    indentation: usize,
+}
+
+impl<'js> Inspector<'js> {
+    fn stylize(&self, text: &str, style: Style) -> String {
+        if self.options.colors {
+            let (open, close) = style.codes();
+            format!("\x1b[{open}m{text}\x1b[{close}m")
+        } else {
+            text.into()
+        }
+    }
+
+    fn format_value(&mut self, value: &Value<'js>, level: usize) -> Result<String> {
+        Ok(match value.type_of() {
+            Type::Uninitialized | Type::Undefined => self.stylize("undefined", Style::Undefined),
+            Type::Null => self.stylize("null", Style::Null),
+            Type::Bool | Type::Int => {
+                let text = value.get::<Coerced<String>>()?.0;
+                self.stylize(&text, Style::Number)
+            }
+            Type::Float => {
+                let float = value.as_float().unwrap_or_default();
+                let text = if float == 0.0 && float.is_sign_negative() {
+                    "-0".into()
+                } else {
+                    value.get::<Coerced<String>>()?.0
+                };
+                self.stylize(&text, Style::Number)
+            }
+            Type::BigInt => {
+                let text = value.get::<Coerced<String>>()?.0;
+                self.stylize(&format!("{text}n"), Style::Number)
+            }
+            Type::String => {
+                let text = value.get::<String>()?;
+                match text.char_indices().nth(self.options.max_string_length) {
+                    Some((end, _)) => {
+                        let more = text[end..].chars().count();
+                        let shown = self.stylize(&quote(&text[..end]), Style::String);
+                        format!("{shown}... {more} more character{}", plural(more))
+                    }
+                    None => self.stylize(&quote(&text), Style::String),
+                }
+            }
+            Type::Symbol => {
+                let text = format_symbol(value)?;
+                self.stylize(&text, Style::Symbol)
+            }
+            Type::Module => self.stylize("[Module]", Style::Special),
+            Type::Unknown => self.stylize("[Unknown]", Style::Special),
+            Type::Array
+            | Type::Constructor
+            | Type::Function
+            | Type::Promise
+            | Type::Exception
+            | Type::Object => {
+                let object = value.as_object().expect("value is an object").clone();
+                self.format_object(object, level)?
+            }
+        })
+    }
+
+    fn format_object(&mut self, object: Object<'js>, level: usize) -> Result<String> {
+        if self.seen.contains(&object) {
+            let index = match self.circular.iter().position(|o| o == &object) {
+                Some(index) => index,
+                None => {
+                    self.circular.push(object);
+                    self.circular.len() - 1
+                }
+            };
+            return Ok(self.stylize(&format!("[Circular *{}]", index + 1), Style::Special));
+        }
+
+        let constructor = constructor_name(&object)?;
+        let array = object.as_array().cloned();
+        let array_len = array.as_ref().map(|array| array.len()).unwrap_or(0);
+        let (base, braces, standalone) =
+            self.format_base(&object, constructor.as_deref(), array_len)?;
+
+        let props = object
+            .own_properties_full::<Atom>(Filter::new().string().symbol().enum_only())?
+            .into_iter()
+            .filter(|(key, _)| array.is_none() || !is_index(key, array_len))
+            .collect::<Vec<_>>();
+        let promise_state = object.as_promise().map(|promise| promise.state());
+
+        if props.is_empty() && array_len == 0 && promise_state.is_none() {
+            return Ok(if standalone {
+                base
+            } else if base.is_empty() {
+                format!("{}{}", braces.0, braces.1)
+            } else {
+                format!("{base} {}{}", braces.0, braces.1)
+            });
+        }
+        if level > self.options.depth {
+            let name = match (&constructor, &array) {
+                (Some(name), _) => name.as_str(),
+                (None, Some(_)) => "Array",
+                (None, None) => "Object",
+            };
+            return Ok(self.stylize(&format!("[{name}]"), Style::Special));
+        }
+
+        self.seen.push(object.clone());
+        self.indentation += 2;
+        let mut output = Vec::new();
+
+        if let Some(state) = promise_state {
+            output.push(match state {
+                PromiseState::Pending => self.stylize("<pending>", Style::Special),
+                PromiseState::Fulfilled(value) => self.format_value(&value, level + 1)?,
+                PromiseState::Rejected(value) => {
+                    let value = self.format_value(&value, level + 1)?;
+                    format!("{} {value}", self.stylize("<rejected>", Style::Special))
+                }
+            });
+        }
+
+        let shown = array_len.min(self.options.max_array_length);
+        let mut index = 0;
+        while index < shown {
+            match object.own_property_descriptor(index as u32)? {
+                Some(desc) => {
+                    output.push(self.format_property(&desc, level)?);
+                    index += 1;
+                }
+                None => {
+                    let start = index;
+                    while index < shown && object.own_property_descriptor(index as u32)?.is_none() {
+                        index += 1;
+                    }
+                    let holes = index - start;
+                    let text = format!("<{holes} empty item{}>", plural(holes));
+                    output.push(self.stylize(&text, Style::Undefined));
+                }
+            }
+        }
+        if array_len > shown {
+            let more = array_len - shown;
+            output.push(format!("... {more} more item{}", plural(more)));
+        }
+
+        for (key, desc) in props {
+            let key = self.format_key(&key)?;
+            let value = self.format_property(&desc, level)?;
+            output.push(format!("{key}: {value}"));
+        }
+
+        self.indentation -= 2;
+        self.seen.pop();
+
+        let mut res = self.reduce(&output, &base, braces);
+        if let Some(index) = self.circular.iter().position(|o| o == &object) {
+            let reference = self.stylize(&format!("<ref *{}>", index + 1), Style::Special);
+            res = format!("{reference} {res}");
+        }
+        Ok(res)
+    }
+
+    /// Returns the description shown in front of the entries of the object, its braces and
+    /// whether the description is shown without braces if there are no entries.
+    fn format_base(
+        &self,
+        object: &Object<'js>,
+        constructor: Option<&str>,
+        array_len: usize,
+    ) -> Result<(String, (&'static str, &'static str), bool)> {
+        if object.is_array() {
+            let base = match constructor {
+                Some("Array") => String::new(),
+                Some(name) => format!("{name}({array_len})"),
+                None => format!("[Array({array_len}): null prototype]"),
+            };
+            return Ok((base, ("[", "]"), false));
+        }
+
+        let mut standalone = true;
+        let base = if let Some(function) = object.as_function() {
+            let name = own_string(object, PredefinedAtom::Name)?.filter(|name| !name.is_empty());
+            // Throws for proxies of functions, which are shown as plain functions.
+            let source = match self.function_to_string.call((This(function.clone()),)) {
+                Ok(source) => source,
+                Err(_) => {
+                    object.ctx().catch();
+                    String::new()
+                }
+            };
+            let text = match (source.starts_with("class"), name) {
+                (true, Some(name)) => format!("[class {name}]"),
+                (true, None) => "[class (anonymous)]".into(),
+                (false, name) => {
+                    let kind = constructor.unwrap_or("Function");
+                    match name {
+                        Some(name) => format!("[{kind}: {name}]"),
+                        None => format!("[{kind} (anonymous)]"),
+                    }
+                }
+            };
+            self.stylize(&text, Style::Special)
+        } else if let Some(regexp) = object.as_regexp() {
+            let text = format!("/{}/{}", regexp.source()?, regexp.flags()?);
+            self.stylize(&text, Style::RegExp)
+        } else if let Some(date) = object.as_date() {
+            let text: String = if date.is_valid()? {
+                let to_iso_string: Function = object.get("toISOString")?;
+                to_iso_string.call((This(object.clone()),))?
+            } else {
+                "Invalid Date".into()
+            };
+            self.stylize(&text, Style::Date)
+        } else if object.is_error() {
+            let name = object
+                .get::<_, Option<Coerced<String>>>(PredefinedAtom::Name)?
+                .map(|name| name.0)
+                .unwrap_or_else(|| "Error".into());
+            let message = object
+                .get::<_, Option<Coerced<String>>>(PredefinedAtom::Message)?
+                .map(|message| message.0)
+                .unwrap_or_default();
+            let stack = object
+                .get::<_, Option<Coerced<String>>>(PredefinedAtom::Stack)?
+                .map(|stack| stack.0)
+                .unwrap_or_default();
+            let header = if message.is_empty() {
+                name
+            } else {
+                format!("{name}: {message}")
+            };
+            let stack = stack.trim_end();
+            if stack.is_empty() {
+                format!("[{header}]")
+            } else {
+                format!("{header}\n{stack}")
+            }
+        } else {
+            standalone = false;
+            match constructor {
+                Some("Object") => String::new(),
+                Some(name) => name.into(),
+                None => "[Object: null prototype]".into(),
+            }
+        };
+        Ok((base, ("{", "}"), standalone))
+    }
+
+    fn format_property(&mut self, desc: &PropertyDescriptor<'js>, level: usize) -> Result<String> {
+        if let Some(value) = &desc.value {
+            return self.format_value(value, level + 1);
+        }
+        let text = match (&desc.get, &desc.set) {
+            (Some(_), Some(_)) => "[Getter/Setter]",
+            (Some(_), None) => "[Getter]",
+            (None, _) => "[Setter]",
+        };
+        Ok(self.stylize(text, Style::Special))
+    }
+
+    fn format_key(&self, key: &Atom<'js>) -> Result<String> {
+        let key = key.to_property_key()?;
+        if key.is_symbol() {
+            let text = format_symbol(&key)?;
+            return Ok(format!("[{}]", self.stylize(&text, Style::Symbol)));
+        }
+        let key = key.get::<Coerced<String>>()?.0;
+        Ok(if is_identifier(&key) {
+            key
+        } else {
+            self.stylize(&quote(&key), Style::String)
+        })
+    }
+
+    /// Join the entries on a single line if they fit, otherwise on a line each.
+    fn reduce(&self, output: &[String], base: &str, braces: (&str, &str)) -> String {
+        let base = if base.is_empty() {
+            String::new()
+        } else {
+            format!("{base} ")
+        };
+        let start = output.len() + self.indentation + braces.0.len() + visible_len(&base) + 10;
+        let total = output.iter().map(|entry| visible_len(entry)).sum::<usize>();
+        if start + output.len() + total <= BREAK_LENGTH && !base.contains('\n') {
+            let joined = output.join(", ");
+            if !joined.contains('\n') {
+                return format!("{base}{} {joined} {}", braces.0, braces.1);
+            }
+        }
+        let indentation = format!("\n{:1$}", "", self.indentation);
+        let separator = format!(",{indentation}  ");
+        format!(
+            "{base}{}{indentation}  {}{indentation}{}",
+            braces.0,
+            output.join(&separator),
+            braces.1
+        )
+    }
+}
+
+/// Returns the name of the constructor in the prototype chain, `None` for a null prototype.
+fn constructor_name<'js>(object: &Object<'js>) -> Result<Option<String>> {
+    let mut proto = object.get_prototype();
+    let mut found = false;
+    while let Some(object) = proto {
+        found = true;
+        let constructor = object
+            .own_property_descriptor(PredefinedAtom::Constructor)?
+            .and_then(|desc| desc.value)
+            .and_then(|value| value.into_object());
+        if let Some(constructor) = constructor {
+            if let Some(name) = own_string(&constructor, PredefinedAtom::Name)? {
+                if !name.is_empty() {
+                    return Ok(Some(name));
+                }
+            }
+        }
+        proto = object.get_prototype();
+    }
+    Ok(found.then(|| "Object".into()))
+}
+
+/// Returns an own data property if it is a string, without invoking accessors.
+fn own_string<'js>(object: &Object<'js>, key: PredefinedAtom) -> Result<Option<String>> {
+    match object
+        .own_property_descriptor(key)?
+        .and_then(|desc| desc.value)
+    {
+        Some(value) if value.is_string() => Ok(Some(value.get()?)),
+        _ => Ok(None),
+    }
+}
+
+fn format_symbol<'js>(value: &Value<'js>) -> Result<String> {
+    let symbol = value.as_symbol().expect("value is a symbol");
+    let description = symbol.description()?;
+    Ok(if description.is_undefined() {
+        "Symbol()".into()
+    } else {
+        format!("Symbol({})", description.get::<Coerced<String>>()?.0)
+    })
+}
+
+fn is_index<'js>(key: &Atom<'js>, len: usize) -> bool {
+    let Ok(key) = key.to_string() else {
+        return false;
+    };
+    match key.parse::<u32>() {
+        Ok(index) => (index as usize) < len && index.to_string() == key,
+        Err(_) => false,
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// Returns the number of characters without ANSI escape codes.
+fn visible_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            len += 1;
+        }
+    }
+    len
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    fn check(ctx: &Ctx, source: &str, options: InspectOptions, expected: &str) {
+        let value: Value = ctx.eval(source).catch(ctx).unwrap();
+        let res = inspect(ctx.clone(), value, options).catch(ctx).unwrap();
+        assert_eq!(res, expected, "{source}");
+    }
+
+    #[test]
+    fn primitives() {
+        test_with(|ctx| {
+            let options = InspectOptions::default();
+            check(&ctx, "undefined", options, "undefined");
+            check(&ctx, "'text'", options, "'text'");
+            check(
+                &ctx,
+                r#"[null, true, 1, 1.5, -0, 1e21, 10n, "it's", 'a\n"b"']"#,
+                options,
+                r#"[ null, true, 1, 1.5, -0, 1e+21, 10n, "it's", 'a\n"b"' ]"#,
+            );
+            check(
+                &ctx,
+                "[Symbol('s'), Symbol(), Symbol.iterator]",
+                options,
+                "[ Symbol(s), Symbol(), Symbol(Symbol.iterator) ]",
+            );
+        })
+    }
+
+    #[test]
+    fn objects() {
+        test_with(|ctx| {
+            let options = InspectOptions::default();
+            check(&ctx, "({})", options, "{}");
+            check(&ctx, "[]", options, "[]");
+            check(
+                &ctx,
+                "({ 'b-c': [1, , , 4], [Symbol('k')]: {}, u: undefined })",
+                options,
+                "{ 'b-c': [ 1, <2 empty items>, 4 ], u: undefined, [Symbol(k)]: {} }",
+            );
+            check(
+                &ctx,
+                "({ get x() { throw 1 }, set y(v) {}, get z() {}, set z(v) {} })",
+                options,
+                "{ x: [Getter], y: [Setter], z: [Getter/Setter] }",
+            );
+            check(
+                &ctx,
+                "[new (class Point { x = 1 }), Object.create(null), Object.assign([1], { p: 2 })]",
+                options,
+                "[ Point { x: 1 }, [Object: null prototype] {}, [ 1, p: 2 ] ]",
+            );
+            check(
+                &ctx,
+                "({ first: 'a long string value', second: 'another long string value', third: 3 })",
+                options,
+                "{\n  first: 'a long string value',\n  second: 'another long string value',\n  third: 3\n}",
+            );
+        })
+    }
+
+    #[test]
+    fn functions() {
+        test_with(|ctx| {
+            check(
+                &ctx,
+                "[function foo() {}, () => {}, class Bar {}, async function baz() {}, Math.max]",
+                InspectOptions::default(),
+                "[\n  [Function: foo],\n  [Function (anonymous)],\n  [class Bar],\n  [AsyncFunction: baz],\n  [Function: max]\n]",
+            );
+            check(
+                &ctx,
+                "Object.assign(function f() {}, { a: 1 })",
+                InspectOptions::default(),
+                "[Function: f] { a: 1 }",
+            );
+        })
+    }
+
+    #[test]
+    fn builtins() {
+        test_with(|ctx| {
+            let options = InspectOptions::default();
+            check(
+                &ctx,
+                "[new Date(0), new Date(NaN), /a+b/gi]",
+                options,
+                "[ 1970-01-01T00:00:00.000Z, Invalid Date, /a+b/gi ]",
+            );
+            check(
+                &ctx,
+                "[Promise.resolve(1), new Promise(() => {}), Promise.reject(2)]",
+                options,
+                "[ Promise { 1 }, Promise { <pending> }, Promise { <rejected> 2 } ]",
+            );
+            let value: Value = ctx.eval("new TypeError('bad')").unwrap();
+            let res = inspect(ctx.clone(), value, options).unwrap();
+            assert!(res.starts_with("TypeError: bad\n    at "), "{res}");
+        })
+    }
+
+    #[test]
+    fn circular() {
+        test_with(|ctx| {
+            check(
+                &ctx,
+                "const a = { name: 'a', list: [] }; a.list.push(a, { a }); a",
+                InspectOptions::default(),
+                "<ref *1> { name: 'a', list: [ [Circular *1], { a: [Circular *1] } ] }",
+            );
+            check(
+                &ctx,
+                "const b = []; b.push(b); ({ b, again: b })",
+                InspectOptions::default(),
+                "{ b: <ref *1> [ [Circular *1] ], again: <ref *1> [ [Circular *1] ] }",
+            );
+        })
+    }
+
+    #[test]
+    fn options() {
+        test_with(|ctx| {
+            let source = "({ a: { b: { c: { d: 1 } }, e: {} } })";
+            check(
+                &ctx,
+                source,
+                InspectOptions::default(),
+                "{ a: { b: { c: [Object] }, e: {} } }",
+            );
+            let options = InspectOptions {
+                depth: 0,
+                ..Default::default()
+            };
+            check(&ctx, source, options, "{ a: [Object] }");
+            check(&ctx, "[[1]]", options, "[ [Array] ]");
+
+            let options = InspectOptions {
+                max_array_length: 3,
+                ..Default::default()
+            };
+            check(
+                &ctx,
+                "[1, 2, 3, 4, 5]",
+                options,
+                "[ 1, 2, 3, ... 2 more items ]",
+            );
+            check(
+                &ctx,
+                "[1, 2, 3, 4]",
+                options,
+                "[ 1, 2, 3, ... 1 more item ]",
+            );
+
+            let options = InspectOptions {
+                colors: true,
+                ..Default::default()
+            };
+            check(
+                &ctx,
+                "[1, 'a', undefined, null]",
+                options,
+                "[ \x1b[33m1\x1b[39m, \x1b[32m'a'\x1b[39m, \x1b[90mundefined\x1b[39m, \x1b[1mnull\x1b[22m ]",
+            );
+        })
+    }
+}
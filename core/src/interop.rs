@@ -0,0 +1,5 @@
+//! Polyfills for APIs of other JavaScript environments which scripts commonly depend on.
+
+pub mod console;
+pub mod node_buffer;
+pub mod timers;
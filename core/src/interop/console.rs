@@ -0,0 +1,221 @@
+//! A `console` object routing its methods to a single Rust handler, and the formatting used by
+//! `console.log` in other JavaScript environments.
+//!
+//! The handler receives the raw arguments so it can format them however it wants,
+//! [`format_args`] and [`format_value`] format them with [`inspect`] like node's `console.log`.
+
+use alloc::{rc::Rc, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{
+    function::Rest,
+    inspect::{inspect, InspectOptions},
+    Ctx, Function, Object, Result, Value,
+};
+
+/// The level of a console message, following the method it was logged with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// `console.debug`
+    Debug,
+    /// `console.log`
+    Log,
+    /// `console.info`
+    Info,
+    /// `console.warn`
+    Warn,
+    /// `console.error`
+    Error,
+}
+
+impl LogLevel {
+    /// All levels, in increasing severity.
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Debug,
+        LogLevel::Log,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    /// Returns the name of the `console` method logging with this level.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Log => "log",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// Options of [`format_value_with`] and [`format_args_with`], which are the options of
+/// [`inspect`].
+pub type FormatOptions = InspectOptions;
+
+/// Format the value with [`inspect`] and the default options.
+pub fn format_value(value: &Value) -> Result<String> {
+    format_value_with(value, &FormatOptions::default())
+}
+
+/// Format the value with [`inspect`].
+pub fn format_value_with(value: &Value, options: &FormatOptions) -> Result<String> {
+    inspect(value.ctx().clone(), value.clone(), *options)
+}
+
+/// Format the arguments of a console method with the default options.
+///
+/// The arguments are separated by spaces, strings are printed without quotes.
+pub fn format_args(args: &[Value]) -> Result<String> {
+    format_args_with(args, &FormatOptions::default())
+}
+
+/// Format the arguments of a console method.
+pub fn format_args_with(args: &[Value], options: &FormatOptions) -> Result<String> {
+    let mut out = String::new();
+    for (idx, arg) in args.iter().enumerate() {
+        if idx > 0 {
+            out.push(' ');
+        }
+        if let Some(x) = arg.as_string() {
+            out.push_str(&x.to_string()?);
+        } else {
+            out.push_str(&format_value_with(arg, options)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Install a `console` global whose `debug`, `log`, `info`, `warn` and `error` methods call the
+/// handler with the level and the arguments of the call.
+///
+/// Errors returned by the handler are thrown by the called method. See
+/// [`Ctx::set_console`](crate::Ctx::set_console).
+pub fn register_console<'js, F>(ctx: &Ctx<'js>, handler: F) -> Result<()>
+where
+    F: Fn(LogLevel, Vec<Value<'js>>) -> Result<()> + 'js,
+{
+    let handler = Rc::new(handler);
+    let console = Object::new(ctx.clone())?;
+    for level in LogLevel::ALL {
+        let handler = handler.clone();
+        let func = Function::new(ctx.clone(), move |args: Rest<Value<'js>>| {
+            handler(level, args.0)
+        })?
+        .with_name(level.as_str())?;
+        console.set(level.as_str(), func)?;
+    }
+    ctx.globals().set("console", console)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CatchResultExt, Context, Runtime};
+    use core::cell::RefCell;
+
+    fn inspect(ctx: &Ctx, source: &str) -> String {
+        let value: Value = ctx.eval(source).catch(ctx).unwrap();
+        format_value(&value).unwrap()
+    }
+
+    #[test]
+    fn format() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            assert_eq!(inspect(&ctx, "'a\\'b'"), "\"a'b\"");
+            assert_eq!(
+                inspect(&ctx, "({ get a() { throw new Error() } })"),
+                "{ a: [Getter] }"
+            );
+            assert_eq!(inspect(&ctx, "1.5"), "1.5");
+            assert_eq!(inspect(&ctx, "10n"), "10n");
+            assert_eq!(
+                inspect(&ctx, "[1, 'a', null, undefined]"),
+                "[ 1, 'a', null, undefined ]"
+            );
+            assert_eq!(
+                inspect(
+                    &ctx,
+                    "({ a: { b: { c: { d: 1 } } }, 'x-y': [], [Symbol('s')]: {} })"
+                ),
+                "{ a: { b: { c: [Object] } }, 'x-y': [], [Symbol(s)]: {} }"
+            );
+            assert_eq!(
+                inspect(&ctx, "const a = { b: [] }; a.b.push(a); a.self = a; a"),
+                "<ref *1> { b: [ [Circular *1] ], self: [Circular *1] }"
+            );
+            assert_eq!(inspect(&ctx, "(function foo() {})"), "[Function: foo]");
+            assert_eq!(inspect(&ctx, "(() => {})"), "[Function (anonymous)]");
+            assert_eq!(inspect(&ctx, "class Foo {}; Foo"), "[class Foo]");
+            assert_eq!(
+                inspect(
+                    &ctx,
+                    "class Bar { constructor() { this.x = 1 } }; new Bar()"
+                ),
+                "Bar { x: 1 }"
+            );
+            assert_eq!(
+                inspect(&ctx, "Object.create(null)"),
+                "[Object: null prototype] {}"
+            );
+            assert_eq!(inspect(&ctx, "Promise.resolve(1)"), "Promise { 1 }");
+            assert!(inspect(&ctx, "new TypeError('bad')").starts_with("TypeError: bad"));
+
+            let options = FormatOptions {
+                max_array_length: 2,
+                max_string_length: 3,
+                ..Default::default()
+            };
+            let value: Value = ctx.eval("[1, 2, 3, 'abcdef']").unwrap();
+            assert_eq!(
+                format_value_with(&value, &options).unwrap(),
+                "[ 1, 2, ... 2 more items ]"
+            );
+            let value: Value = ctx.eval("({ s: 'abcdef' })").unwrap();
+            assert_eq!(
+                format_value_with(&value, &options).unwrap(),
+                "{ s: 'abc'... 3 more characters }"
+            );
+        })
+    }
+
+    #[test]
+    fn console() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let logs = Rc::new(RefCell::new(Vec::new()));
+        ctx.with(|ctx| {
+            let logs = logs.clone();
+            ctx.set_console(move |level, args| {
+                logs.borrow_mut().push((level, format_args(&args)?));
+                Ok(())
+            })
+            .unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                console.log("hello", { a: 1 }, [2]);
+                console.warn("careful");
+                console.error(new Error("oops").message);
+                "#,
+            )
+            .unwrap();
+        });
+        assert_eq!(
+            *logs.borrow(),
+            [
+                (LogLevel::Log, "hello { a: 1 } [ 2 ]".to_string()),
+                (LogLevel::Warn, "careful".to_string()),
+                (LogLevel::Error, "oops".to_string()),
+            ]
+        );
+    }
+}
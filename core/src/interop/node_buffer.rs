@@ -0,0 +1,549 @@
+//! A Node.js compatible `Buffer` class.
+//!
+//! [`register_node_buffer`] installs a global `Buffer` class which extends `Uint8Array`. The
+//! class itself is a thin JavaScript wrapper, encoding conversions, comparisons, copies and
+//! integer accesses are implemented in Rust on top of [`TypedArray<u8>`].
+//!
+//! Supported encodings are `utf8`, `hex`, `base64`, `base64url`, `latin1`, `ascii` and
+//! `utf16le` together with their Node.js aliases.
+
+use alloc::{format, string::String as StdString, vec::Vec};
+use core::{cmp::Ordering, slice};
+
+use crate::{
+    function::{Func, Opt},
+    value::array_buffer::RawArrayBuffer,
+    Ctx, Exception, Function, Object, Result, TypedArray, Value,
+};
+
+const BUFFER_SOURCE: &str = r#"(function(native) {
+    const { encode, decode, isEncoding, compare, copy, readInt, writeInt } = native;
+    const toBuffer = (bytes) => new Buffer(bytes.buffer, bytes.byteOffset, bytes.length);
+
+    class Buffer extends Uint8Array {
+        static from(value, encodingOrOffset, length) {
+            if (typeof value === "string") {
+                return toBuffer(encode(value, encodingOrOffset));
+            }
+            if (value instanceof ArrayBuffer) {
+                return new Buffer(value, encodingOrOffset, length);
+            }
+            if (value !== null && typeof value === "object" && typeof value.length === "number") {
+                const buf = new Buffer(value.length);
+                buf.set(value);
+                return buf;
+            }
+            throw new TypeError("The first argument must be of type string, Buffer, ArrayBuffer, Array, or Array-like Object");
+        }
+
+        static alloc(size, fill, encoding) {
+            const buf = new Buffer(size);
+            if (fill !== undefined && fill !== 0) {
+                buf.fill(fill, encoding);
+            }
+            return buf;
+        }
+
+        static allocUnsafe(size) {
+            return new Buffer(size);
+        }
+
+        static isBuffer(value) {
+            return value instanceof Buffer;
+        }
+
+        static isEncoding(encoding) {
+            return typeof encoding === "string" && isEncoding(encoding);
+        }
+
+        static byteLength(value, encoding) {
+            return typeof value === "string" ? encode(value, encoding).length : value.byteLength;
+        }
+
+        static compare(a, b) {
+            return compare(a, b);
+        }
+
+        static concat(list, totalLength) {
+            if (totalLength === undefined) {
+                totalLength = list.reduce((len, buf) => len + buf.length, 0);
+            }
+            const res = new Buffer(totalLength);
+            let offset = 0;
+            for (const buf of list) {
+                offset += copy(buf, res, offset, 0, buf.length);
+            }
+            return res;
+        }
+
+        toString(encoding, start = 0, end = this.length) {
+            return decode(this.subarray(start, end), encoding);
+        }
+
+        toJSON() {
+            return { type: "Buffer", data: Array.from(this) };
+        }
+
+        equals(other) {
+            return compare(this, other) === 0;
+        }
+
+        compare(target, targetStart = 0, targetEnd = target.length, sourceStart = 0, sourceEnd = this.length) {
+            return compare(this.subarray(sourceStart, sourceEnd), target.subarray(targetStart, targetEnd));
+        }
+
+        copy(target, targetStart = 0, sourceStart = 0, sourceEnd = this.length) {
+            return copy(this, target, targetStart, sourceStart, sourceEnd);
+        }
+
+        slice(start, end) {
+            return this.subarray(start, end);
+        }
+
+        write(string, offset = 0, length = this.length - offset, encoding) {
+            if (typeof offset === "string") {
+                [encoding, offset, length] = [offset, 0, this.length];
+            } else if (typeof length === "string") {
+                [encoding, length] = [length, this.length - offset];
+            }
+            const bytes = encode(string, encoding);
+            return copy(bytes, this, offset, 0, Math.min(bytes.length, length));
+        }
+
+        fill(value, offset = 0, end = this.length, encoding) {
+            if (typeof offset === "string") {
+                [encoding, offset, end] = [offset, 0, this.length];
+            } else if (typeof end === "string") {
+                [encoding, end] = [end, this.length];
+            }
+            if (typeof value !== "string") {
+                return super.fill(value, offset, end);
+            }
+            const bytes = encode(value, encoding);
+            if (bytes.length === 0) {
+                return super.fill(0, offset, end);
+            }
+            for (let i = offset, j = 0; i < end; i++, j = (j + 1) % bytes.length) {
+                this[i] = bytes[j];
+            }
+            return this;
+        }
+    }
+
+    for (const size of [1, 2, 4]) {
+        for (const signed of [false, true]) {
+            const name = (signed ? "Int" : "UInt") + size * 8;
+            const alias = (signed ? "Int" : "Uint") + size * 8;
+            for (const [suffix, little] of size === 1 ? [["", true]] : [["LE", true], ["BE", false]]) {
+                const read = function(offset = 0) {
+                    return readInt(this, offset, size, signed, little);
+                };
+                const write = function(value, offset = 0) {
+                    return writeInt(this, value, offset, size, signed, little);
+                };
+                for (const type of new Set([name, alias])) {
+                    Buffer.prototype["read" + type + suffix] = read;
+                    Buffer.prototype["write" + type + suffix] = write;
+                }
+            }
+        }
+    }
+
+    return Buffer;
+})"#;
+
+/// The encodings supported by the `Buffer` class.
+#[derive(Clone, Copy)]
+enum Encoding {
+    Utf8,
+    Hex,
+    Base64,
+    Base64Url,
+    Latin1,
+    Ascii,
+    Utf16Le,
+}
+
+impl Encoding {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "utf8" | "utf-8" => Encoding::Utf8,
+            "hex" => Encoding::Hex,
+            "base64" => Encoding::Base64,
+            "base64url" => Encoding::Base64Url,
+            "latin1" | "binary" => Encoding::Latin1,
+            "ascii" => Encoding::Ascii,
+            "utf16le" | "utf-16le" | "ucs2" | "ucs-2" => Encoding::Utf16Le,
+            _ => return None,
+        })
+    }
+
+    fn from_arg(ctx: &Ctx<'_>, name: Opt<Value<'_>>) -> Result<Self> {
+        match name.0 {
+            Some(name) if !name.is_undefined() && !name.is_null() => {
+                let name: StdString = name.get()?;
+                Self::parse(&name)
+                    .ok_or_else(|| Exception::throw_type(ctx, &format!("Unknown encoding: {name}")))
+            }
+            _ => Ok(Encoding::Utf8),
+        }
+    }
+
+    fn encode(self, string: &str) -> Vec<u8> {
+        match self {
+            Encoding::Utf8 => string.as_bytes().to_vec(),
+            Encoding::Hex => decode_hex(string),
+            Encoding::Base64 | Encoding::Base64Url => decode_base64(string),
+            Encoding::Latin1 | Encoding::Ascii => string.chars().map(|c| c as u8).collect(),
+            Encoding::Utf16Le => string.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> StdString {
+        match self {
+            Encoding::Utf8 => StdString::from_utf8_lossy(bytes).into_owned(),
+            Encoding::Hex => bytes.iter().map(|b| format!("{b:02x}")).collect(),
+            Encoding::Base64 => encode_base64(bytes, BASE64, true),
+            Encoding::Base64Url => encode_base64(bytes, BASE64_URL, false),
+            Encoding::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+            Encoding::Ascii => bytes.iter().map(|&b| (b & 0x7f) as char).collect(),
+            Encoding::Utf16Le => {
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|x| u16::from_le_bytes([x[0], x[1]]));
+                char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect()
+            }
+        }
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn encode_base64(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> StdString {
+    let mut res = StdString::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (idx, &b)| n | (b as u32) << (16 - idx * 8));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                res.push(alphabet[(n >> (18 - idx * 6)) as usize & 0x3f] as char);
+            } else if pad {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+/// Decodes both the standard and the url-safe alphabet, like Node.js characters outside of the
+/// alphabets are skipped and decoding stops at the first padding character.
+fn decode_base64(string: &str) -> Vec<u8> {
+    let mut res = Vec::with_capacity(string.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0);
+    for b in string.bytes() {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => continue,
+        };
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            res.push((acc >> bits) as u8);
+        }
+    }
+    res
+}
+
+/// Decodes pairs of hex digits until the first invalid pair, like Node.js.
+fn decode_hex(string: &str) -> Vec<u8> {
+    string
+        .as_bytes()
+        .chunks_exact(2)
+        .map_while(|pair| {
+            let digit = |b: u8| (b as char).to_digit(16);
+            Some((digit(pair[0])? << 4 | digit(pair[1])?) as u8)
+        })
+        .collect()
+}
+
+fn bytes<'a>(ctx: &Ctx<'_>, array: &'a TypedArray<'_, u8>) -> Result<&'a [u8]> {
+    array
+        .as_bytes()
+        .ok_or_else(|| Exception::throw_type(ctx, "Cannot perform operation on a detached buffer"))
+}
+
+fn raw_bytes(ctx: &Ctx<'_>, array: &TypedArray<'_, u8>) -> Result<RawArrayBuffer> {
+    array
+        .as_raw()
+        .ok_or_else(|| Exception::throw_type(ctx, "Cannot perform operation on a detached buffer"))
+}
+
+/// Converts an index argument into an index clamped to `0..=len`.
+fn clamp_index(index: f64, len: usize) -> usize {
+    if index.is_nan() || index <= 0.0 {
+        0
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+fn encode<'js>(
+    ctx: Ctx<'js>,
+    string: StdString,
+    encoding: Opt<Value<'js>>,
+) -> Result<TypedArray<'js, u8>> {
+    let bytes = Encoding::from_arg(&ctx, encoding)?.encode(&string);
+    TypedArray::new(ctx, bytes)
+}
+
+fn decode<'js>(
+    ctx: Ctx<'js>,
+    array: TypedArray<'js, u8>,
+    encoding: Opt<Value<'js>>,
+) -> Result<StdString> {
+    let encoding = Encoding::from_arg(&ctx, encoding)?;
+    Ok(encoding.decode(bytes(&ctx, &array)?))
+}
+
+fn is_encoding(name: StdString) -> bool {
+    Encoding::parse(&name).is_some()
+}
+
+fn compare<'js>(ctx: Ctx<'js>, a: TypedArray<'js, u8>, b: TypedArray<'js, u8>) -> Result<i32> {
+    Ok(match bytes(&ctx, &a)?.cmp(bytes(&ctx, &b)?) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    })
+}
+
+fn copy<'js>(
+    ctx: Ctx<'js>,
+    source: TypedArray<'js, u8>,
+    target: TypedArray<'js, u8>,
+    target_start: f64,
+    source_start: f64,
+    source_end: f64,
+) -> Result<usize> {
+    let source = raw_bytes(&ctx, &source)?;
+    let target = raw_bytes(&ctx, &target)?;
+    let target_start = clamp_index(target_start, target.len);
+    let source_start = clamp_index(source_start, source.len);
+    let source_end = clamp_index(source_end, source.len).max(source_start);
+    let count = (source_end - source_start).min(target.len - target_start);
+    // The source and target might be views into the same memory.
+    unsafe {
+        core::ptr::copy(
+            source.ptr.as_ptr().add(source_start),
+            target.ptr.as_ptr().add(target_start),
+            count,
+        )
+    };
+    Ok(count)
+}
+
+fn check_offset(ctx: &Ctx<'_>, offset: f64, size: usize, len: usize) -> Result<usize> {
+    if offset as usize as f64 != offset || offset + size as f64 > len as f64 {
+        return Err(Exception::throw_range(
+            ctx,
+            &format!(
+                "The value of \"offset\" is out of range. It must be >= 0 and <= {}. Received {offset}",
+                len.saturating_sub(size)
+            ),
+        ));
+    }
+    Ok(offset as usize)
+}
+
+fn read_int<'js>(
+    ctx: Ctx<'js>,
+    array: TypedArray<'js, u8>,
+    offset: f64,
+    size: usize,
+    signed: bool,
+    little: bool,
+) -> Result<f64> {
+    let bytes = bytes(&ctx, &array)?;
+    let offset = check_offset(&ctx, offset, size, bytes.len())?;
+    let mut buf = [0u8; 8];
+    buf[..size].copy_from_slice(&bytes[offset..offset + size]);
+    if !little {
+        buf[..size].reverse();
+    }
+    let value = u64::from_le_bytes(buf);
+    Ok(if signed {
+        let shift = 64 - size * 8;
+        ((value << shift) as i64 >> shift) as f64
+    } else {
+        value as f64
+    })
+}
+
+fn write_int<'js>(
+    ctx: Ctx<'js>,
+    array: TypedArray<'js, u8>,
+    value: f64,
+    offset: f64,
+    size: usize,
+    signed: bool,
+    little: bool,
+) -> Result<usize> {
+    let bits = size * 8;
+    let (min, max) = if signed {
+        (-(1i64 << (bits - 1)), (1i64 << (bits - 1)) - 1)
+    } else {
+        (0, (1i64 << bits) - 1)
+    };
+    if !(min as f64..=max as f64).contains(&value) {
+        return Err(Exception::throw_range(
+            &ctx,
+            &format!(
+                "The value of \"value\" is out of range. It must be >= {min} and <= {max}. Received {value}"
+            ),
+        ));
+    }
+    let raw = raw_bytes(&ctx, &array)?;
+    let offset = check_offset(&ctx, offset, size, raw.len)?;
+    let encoded = (value as i64).to_le_bytes();
+    let target = unsafe { slice::from_raw_parts_mut(raw.ptr.as_ptr().add(offset), size) };
+    target.copy_from_slice(&encoded[..size]);
+    if !little {
+        target.reverse();
+    }
+    Ok(offset + size)
+}
+
+/// Install a Node.js compatible `Buffer` class as a global of the context.
+///
+/// The `Buffer` class extends `Uint8Array` and supports the commonly used parts of the Node.js
+/// API, like `Buffer.from`, `Buffer.alloc`, `Buffer.concat`, `Buffer.isBuffer`, `.toString`,
+/// `.write`, `.copy`, `.compare`, `.equals` and the `.readUInt32LE` like integer accessors.
+///
+/// # Example
+/// ```
+/// # use rquickjs::{Runtime, Context, interop::node_buffer::register_node_buffer};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     register_node_buffer(ctx.clone()).unwrap();
+///     let hex: String = ctx.eval("Buffer.from('hello').toString('hex')").unwrap();
+///     assert_eq!(hex, "68656c6c6f");
+/// });
+/// ```
+pub fn register_node_buffer(ctx: Ctx<'_>) -> Result<()> {
+    let native = Object::new(ctx.clone())?;
+    native.set("encode", Func::from(encode))?;
+    native.set("decode", Func::from(decode))?;
+    native.set("isEncoding", Func::from(is_encoding))?;
+    native.set("compare", Func::from(compare))?;
+    native.set("copy", Func::from(copy))?;
+    native.set("readInt", Func::from(read_int))?;
+    native.set("writeInt", Func::from(write_int))?;
+
+    let init: Function = ctx.eval(BUFFER_SOURCE)?;
+    let buffer: Value = init.call((native,))?;
+    ctx.globals().set("Buffer", buffer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::register_node_buffer;
+    use crate::{test_with, CatchResultExt, TypedArray};
+
+    #[test]
+    fn encodings() {
+        test_with(|ctx| {
+            register_node_buffer(ctx.clone()).unwrap();
+            for (encoding, encoded) in [
+                ("utf8", "héllo wörld"),
+                ("hex", "68c3a96c6c6f2077c3b6726c64"),
+                ("base64", "aMOpbGxvIHfDtnJsZA=="),
+                ("base64url", "aMOpbGxvIHfDtnJsZA"),
+                ("latin1", "hÃ©llo wÃ¶rld"),
+            ] {
+                let res: String = ctx
+                    .eval(format!(
+                        r#"
+                        var buf = Buffer.from("héllo wörld");
+                        var decoded = Buffer.from(buf.toString("{encoding}"), "{encoding}");
+                        if (!decoded.equals(buf)) throw new Error("roundtrip failed");
+                        buf.toString("{encoding}")
+                        "#
+                    ))
+                    .catch(&ctx)
+                    .unwrap();
+                assert_eq!(res, encoded, "{encoding}");
+            }
+            let res: String = ctx
+                .eval("Buffer.from('€a', 'utf16le').toString('ucs2')")
+                .unwrap();
+            assert_eq!(res, "€a");
+            let err = ctx
+                .eval::<(), _>("Buffer.from('a', 'nope')")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("Unknown encoding: nope"));
+        })
+    }
+
+    #[test]
+    fn buffer() {
+        test_with(|ctx| {
+            register_node_buffer(ctx.clone()).unwrap();
+            let res: Vec<bool> = ctx
+                .eval(
+                    r#"
+                    const a = Buffer.alloc(8);
+                    const b = Buffer.from([1, 2, 3]);
+                    b.copy(a, 1);
+                    a.writeUInt32BE(0xdeadbeef, 4);
+                    [
+                        Buffer.isBuffer(a),
+                        !Buffer.isBuffer(new Uint8Array(1)),
+                        a instanceof Uint8Array,
+                        a.toString("hex") === "00010203deadbeef",
+                        a.readUInt32LE(4) === 0xefbeadde,
+                        a.readInt8(7) === -17,
+                        a.slice(1, 4).equals(Buffer.from([1, 2, 3])),
+                        Buffer.isBuffer(a.subarray(1)),
+                        b.compare(Buffer.from([1, 2, 4])) === -1,
+                        Buffer.concat([b, b]).toString("hex") === "010203010203",
+                        Buffer.alloc(5, "ab").toString() === "ababa",
+                        a.write("zz", 1) === 2,
+                        a[2] === 122,
+                    ]
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            for (idx, res) in res.into_iter().enumerate() {
+                assert!(res, "check {idx} failed");
+            }
+
+            let buf: TypedArray<u8> = ctx.eval("Buffer.from('abc')").unwrap();
+            assert_eq!(buf.as_bytes().unwrap(), b"abc");
+
+            let err = ctx
+                .eval::<(), _>("Buffer.alloc(2).readUInt32LE(0)")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("\"offset\" is out of range"));
+            let err = ctx
+                .eval::<(), _>("Buffer.alloc(2).writeUInt8(256)")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("\"value\" is out of range"));
+        })
+    }
+}
@@ -0,0 +1,597 @@
+//! `setTimeout`, `setInterval`, `clearTimeout` and `clearInterval` backed by a [`TimerHost`].
+//!
+//! QuickJS itself has no notion of time so the timers are scheduled on a host provided by the
+//! embedding. [`SimpleTimerHost`] can be polled from the loop of a synchronous embedding and
+//! `TokioTimerHost` runs the timers on a tokio `LocalSet`.
+//!
+//! The timers run their callbacks with [`Context::with`], so the host must not fire timers while
+//! the runtime is in use. Jobs enqueued by the callbacks, like promise reactions, are not run by
+//! the timers, use [`Runtime::execute_pending_job`](crate::Runtime::execute_pending_job) for
+//! that.
+
+use alloc::{boxed::Box, rc::Rc, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    ptr::NonNull,
+};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::{
+    function::{Opt, Rest},
+    qjs,
+    runtime::{UserDataError, UserDataGuard, WeakRuntime},
+    CatchResultExt, CaughtError, Coerced, Context, Ctx, Error, Exception, Function, JsLifetime,
+    Persistent, Result, Value,
+};
+
+/// The identifier of a timer scheduled on a [`TimerHost`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(pub u64);
+
+/// A host which runs callbacks after a delay.
+pub trait TimerHost {
+    /// Schedule the callback to be called after `delay_ms` milliseconds.
+    fn schedule(&self, delay_ms: u64, callback: Box<dyn FnOnce()>) -> TimerId;
+
+    /// Cancel a scheduled timer, the callback must not be called afterwards.
+    ///
+    /// Timers which already fired or were already canceled are ignored.
+    fn cancel(&self, id: TimerId);
+
+    /// Called with the error of a timer callback which threw or failed otherwise.
+    ///
+    /// The default implementation ignores the error.
+    fn report_error<'js>(&self, ctx: &Ctx<'js>, error: CaughtError<'js>) {
+        let _ = (ctx, error);
+    }
+}
+
+/// Returns a report of the error of a timer callback which outlives the context.
+#[cfg(feature = "std")]
+fn error_report<'js>(ctx: &Ctx<'js>, error: CaughtError<'js>) -> crate::ErrorReport {
+    use crate::{ErrorReport, String};
+    use std::string::ToString as _;
+
+    error.report().unwrap_or_else(|| {
+        let message = String::from_str(ctx.clone(), &error.to_string())
+            .map(String::into_value)
+            .unwrap_or_else(|_| Value::new_undefined(ctx.clone()));
+        ErrorReport::from_value(ctx, message)
+    })
+}
+
+/// A JavaScript timer which hasn't been cleared yet.
+struct Timer {
+    callback: Persistent<Function<'static>>,
+    args: Persistent<Vec<Value<'static>>>,
+    interval: Option<u64>,
+    ctx: NonNull<qjs::JSContext>,
+    scheduled: TimerId,
+}
+
+/// The JavaScript timers of a runtime.
+///
+/// Stored as userdata so the callbacks are released before the runtime is freed, even if they
+/// reference the functions which own the [`Timers`].
+#[derive(Default)]
+struct Registry {
+    next_id: Cell<u64>,
+    timers: RefCell<HashMap<u64, Timer>>,
+}
+
+unsafe impl<'js> JsLifetime<'js> for Registry {
+    type Changed<'to> = Registry;
+}
+
+/// The timer host of a single context.
+///
+/// Owned by the installed JavaScript functions, the callbacks given to the host only hold a weak
+/// reference so pending timers don't keep the context alive.
+struct Timers<H> {
+    host: H,
+    runtime: WeakRuntime,
+    ctx: NonNull<qjs::JSContext>,
+}
+
+impl<H: TimerHost + 'static> Timers<H> {
+    fn start<'js>(
+        self: &Rc<Self>,
+        ctx: &Ctx<'js>,
+        callback: Value<'js>,
+        delay: Opt<Coerced<f64>>,
+        args: Rest<Value<'js>>,
+        repeat: bool,
+    ) -> Result<u64> {
+        let Some(callback) = callback.into_function() else {
+            return Err(Exception::throw_type(
+                ctx,
+                "The \"callback\" argument must be of type function",
+            ));
+        };
+        let delay = delay.0.map(|x| x.0).unwrap_or(0.0);
+        // Like browsers, invalid and negative delays are treated as zero.
+        let delay = if delay.is_nan() || delay <= 0.0 {
+            0
+        } else {
+            delay as u64
+        };
+
+        let registry = registry(ctx);
+        let id = registry.next_id.get() + 1;
+        registry.next_id.set(id);
+        let scheduled = self.schedule(id, delay);
+        registry.timers.borrow_mut().insert(
+            id,
+            Timer {
+                callback: Persistent::save(ctx, callback),
+                args: Persistent::save(ctx, args.0),
+                interval: repeat.then_some(delay),
+                ctx: self.ctx,
+                scheduled,
+            },
+        );
+        Ok(id)
+    }
+
+    fn schedule(self: &Rc<Self>, id: u64, delay: u64) -> TimerId {
+        let timers = Rc::downgrade(self);
+        self.host.schedule(
+            delay,
+            Box::new(move || {
+                if let Some(timers) = timers.upgrade() {
+                    timers.fire(id);
+                }
+            }),
+        )
+    }
+
+    fn clear(&self, ctx: &Ctx<'_>, id: Opt<Value<'_>>) {
+        let Some(id) = id.0.and_then(|x| x.as_number()) else {
+            return;
+        };
+        let registry = registry(ctx);
+        let mut timers = registry.timers.borrow_mut();
+        if timers.get(&(id as u64)).is_some_and(|x| x.ctx == self.ctx) {
+            let timer = timers.remove(&(id as u64)).unwrap();
+            drop(timers);
+            self.host.cancel(timer.scheduled);
+        }
+    }
+
+    fn fire(self: &Rc<Self>, id: u64) {
+        let Some(runtime) = self.runtime.try_ref() else {
+            return;
+        };
+        // The context is alive as long as the installed functions, which own the timers, are.
+        let context = {
+            let _lock = runtime.inner.lock();
+            unsafe {
+                qjs::JS_DupContext(self.ctx.as_ptr());
+                Context::from_raw(self.ctx, runtime.clone())
+            }
+        };
+        context.with(|ctx| {
+            let registry = registry(&ctx);
+            let (callback, args) = {
+                let mut timers = registry.timers.borrow_mut();
+                let Some(timer) = timers.get(&id) else {
+                    return;
+                };
+                let res = (timer.callback.clone(), timer.args.clone());
+                match timer.interval {
+                    // Intervals are rescheduled before the callback runs so they can clear
+                    // themselves.
+                    Some(delay) => {
+                        drop(timers);
+                        let scheduled = self.schedule(id, delay);
+                        if let Some(timer) = registry.timers.borrow_mut().get_mut(&id) {
+                            timer.scheduled = scheduled;
+                        }
+                    }
+                    None => {
+                        timers.remove(&id);
+                    }
+                }
+                res
+            };
+            drop(registry);
+
+            let res = (|| {
+                let callback = callback.restore(&ctx)?;
+                let args = args.restore(&ctx)?;
+                callback.call::<_, ()>((Rest(args),))
+            })();
+            if let Err(error) = res.catch(&ctx) {
+                self.host.report_error(&ctx, error);
+            }
+        });
+    }
+}
+
+fn registry<'a, 'js>(ctx: &'a Ctx<'js>) -> UserDataGuard<'a, Registry> {
+    ctx.userdata::<Registry>()
+        .expect("timer registry was removed from the runtime")
+}
+
+fn start_fn<'js, H: TimerHost + 'static>(
+    ctx: &Ctx<'js>,
+    timers: Rc<Timers<H>>,
+    repeat: bool,
+) -> Result<Function<'js>> {
+    Function::new(
+        ctx.clone(),
+        move |ctx: Ctx<'js>,
+              callback: Value<'js>,
+              delay: Opt<Coerced<f64>>,
+              args: Rest<Value<'js>>| { timers.start(&ctx, callback, delay, args, repeat) },
+    )
+}
+
+fn clear_fn<'js, H: TimerHost + 'static>(
+    ctx: &Ctx<'js>,
+    timers: Rc<Timers<H>>,
+) -> Result<Function<'js>> {
+    Function::new(ctx.clone(), move |ctx: Ctx<'js>, id: Opt<Value<'js>>| {
+        timers.clear(&ctx, id)
+    })
+}
+
+/// Install `setTimeout`, `setInterval`, `clearTimeout` and `clearInterval` as globals of the
+/// context, scheduling the timers on the given host.
+///
+/// The timers keep the context alive while the functions are reachable from JavaScript.
+///
+/// # Example
+/// ```
+/// # use rquickjs::{Runtime, Context, interop::timers::{register_timers, SimpleTimerHost}};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// let host = SimpleTimerHost::new();
+/// register_timers(&ctx, host.clone()).unwrap();
+/// ctx.with(|ctx| ctx.eval::<(), _>("setTimeout(() => globalThis.done = true)").unwrap());
+/// host.poll();
+/// assert!(ctx.with(|ctx| ctx.globals().get::<_, bool>("done").unwrap()));
+/// ```
+pub fn register_timers(ctx: &Context, host: impl TimerHost + 'static) -> Result<()> {
+    let timers = Rc::new(Timers {
+        host,
+        runtime: ctx.runtime().weak(),
+        ctx: ctx.as_raw(),
+    });
+
+    ctx.with(|ctx| {
+        if ctx.userdata::<Registry>().is_none() {
+            ctx.store_userdata(Registry::default())
+                .map_err(|_| Error::UserData(UserDataError(())))?;
+        }
+        let globals = ctx.globals();
+        for (name, repeat) in [("setTimeout", false), ("setInterval", true)] {
+            let func = start_fn(&ctx, timers.clone(), repeat)?.with_name(name)?;
+            globals.set(name, func)?;
+        }
+        for name in ["clearTimeout", "clearInterval"] {
+            let func = clear_fn(&ctx, timers.clone())?.with_name(name)?;
+            globals.set(name, func)?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(feature = "std")]
+pub use simple::SimpleTimerHost;
+
+#[cfg(feature = "std")]
+mod simple {
+    use alloc::{boxed::Box, collections::BinaryHeap, rc::Rc};
+    use core::{
+        cell::RefCell,
+        cmp::{Ordering, Reverse},
+        time::Duration,
+    };
+    use std::time::Instant;
+
+    use alloc::vec::Vec;
+
+    use super::{error_report, TimerHost, TimerId};
+    use crate::{CaughtError, Ctx, ErrorReport};
+
+    struct Entry {
+        deadline: Instant,
+        id: TimerId,
+        callback: Box<dyn FnOnce()>,
+    }
+
+    impl PartialEq for Entry {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for Entry {}
+
+    impl PartialOrd for Entry {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Entry {
+        fn cmp(&self, other: &Self) -> Ordering {
+            (self.deadline, self.id).cmp(&(other.deadline, other.id))
+        }
+    }
+
+    #[derive(Default)]
+    struct Inner {
+        next_id: u64,
+        queue: BinaryHeap<Reverse<Entry>>,
+        errors: Vec<ErrorReport>,
+    }
+
+    /// A [`TimerHost`] for synchronous embeddings which runs the timers when polled.
+    ///
+    /// Clones share the same timers, so keep a clone around to poll the host after registering
+    /// it.
+    #[derive(Clone, Default)]
+    pub struct SimpleTimerHost {
+        inner: Rc<RefCell<Inner>>,
+    }
+
+    impl SimpleTimerHost {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Run the callbacks of all timers which are due, returns the number of timers run.
+        ///
+        /// Timers scheduled by the callbacks are run at the earliest on the next poll, even
+        /// without a delay. Must not be called while the runtime the timers belong to is in use.
+        pub fn poll(&self) -> usize {
+            let now = Instant::now();
+            let mut count = 0;
+            loop {
+                let entry = {
+                    let mut inner = self.inner.borrow_mut();
+                    match inner.queue.peek() {
+                        Some(Reverse(entry)) if entry.deadline <= now => inner.queue.pop(),
+                        _ => None,
+                    }
+                };
+                let Some(Reverse(entry)) = entry else {
+                    return count;
+                };
+                (entry.callback)();
+                count += 1;
+            }
+        }
+
+        /// Returns the instant at which the next timer is due.
+        pub fn next_deadline(&self) -> Option<Instant> {
+            self.inner
+                .borrow()
+                .queue
+                .peek()
+                .map(|Reverse(entry)| entry.deadline)
+        }
+
+        /// Returns whether there are no scheduled timers.
+        pub fn is_empty(&self) -> bool {
+            self.inner.borrow().queue.is_empty()
+        }
+
+        /// Returns the errors of the timer callbacks which failed since the last call, in the
+        /// order the timers ran.
+        pub fn take_errors(&self) -> Vec<ErrorReport> {
+            core::mem::take(&mut self.inner.borrow_mut().errors)
+        }
+    }
+
+    impl TimerHost for SimpleTimerHost {
+        fn schedule(&self, delay_ms: u64, callback: Box<dyn FnOnce()>) -> TimerId {
+            let mut inner = self.inner.borrow_mut();
+            inner.next_id += 1;
+            let id = TimerId(inner.next_id);
+            inner.queue.push(Reverse(Entry {
+                deadline: Instant::now() + Duration::from_millis(delay_ms),
+                id,
+                callback,
+            }));
+            id
+        }
+
+        fn cancel(&self, id: TimerId) {
+            self.inner
+                .borrow_mut()
+                .queue
+                .retain(|Reverse(entry)| entry.id != id);
+        }
+
+        fn report_error<'js>(&self, ctx: &Ctx<'js>, error: CaughtError<'js>) {
+            let report = error_report(ctx, error);
+            self.inner.borrow_mut().errors.push(report);
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "tokio")))]
+pub use tokio_host::TokioTimerHost;
+
+#[cfg(feature = "tokio")]
+mod tokio_host {
+    use alloc::{boxed::Box, rc::Rc};
+    use core::{cell::RefCell, time::Duration};
+    use std::collections::HashMap;
+
+    use tokio::task::AbortHandle;
+
+    use super::{error_report, TimerHost, TimerId};
+    use crate::{CaughtError, Ctx, ErrorReport};
+
+    #[derive(Default)]
+    struct Inner {
+        next_id: u64,
+        tasks: HashMap<TimerId, AbortHandle>,
+    }
+
+    /// A [`TimerHost`] which runs the timers as tasks on the current tokio `LocalSet`.
+    ///
+    /// Timers are spawned with `tokio::task::spawn_local` so the host must be used from within a
+    /// `LocalSet`.
+    #[derive(Clone, Default)]
+    pub struct TokioTimerHost {
+        inner: Rc<RefCell<Inner>>,
+        error_handler: Option<Rc<dyn Fn(ErrorReport)>>,
+    }
+
+    impl TokioTimerHost {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Set the function called with the errors of failing timer callbacks, errors are ignored
+        /// without one.
+        pub fn with_error_handler(mut self, handler: impl Fn(ErrorReport) + 'static) -> Self {
+            self.error_handler = Some(Rc::new(handler));
+            self
+        }
+    }
+
+    impl TimerHost for TokioTimerHost {
+        fn schedule(&self, delay_ms: u64, callback: Box<dyn FnOnce()>) -> TimerId {
+            let mut inner = self.inner.borrow_mut();
+            inner.next_id += 1;
+            let id = TimerId(inner.next_id);
+            let tasks = Rc::downgrade(&self.inner);
+            let handle = tokio::task::spawn_local(async move {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                if let Some(tasks) = tasks.upgrade() {
+                    tasks.borrow_mut().tasks.remove(&id);
+                }
+                callback();
+            });
+            inner.tasks.insert(id, handle.abort_handle());
+            id
+        }
+
+        fn cancel(&self, id: TimerId) {
+            if let Some(handle) = self.inner.borrow_mut().tasks.remove(&id) {
+                handle.abort();
+            }
+        }
+
+        fn report_error<'js>(&self, ctx: &Ctx<'js>, error: CaughtError<'js>) {
+            if let Some(handler) = &self.error_handler {
+                handler(error_report(ctx, error));
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use std::{thread, time::Duration};
+
+    use super::{register_timers, SimpleTimerHost};
+    use crate::{CatchResultExt, Context, Runtime};
+
+    #[test]
+    fn simple_host() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let host = SimpleTimerHost::new();
+        register_timers(&ctx, host.clone()).unwrap();
+
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                globalThis.log = [];
+                setTimeout((a, b) => log.push("timeout " + a + b), 0, 1, 2);
+                const cleared = setTimeout(() => log.push("cleared"), 0);
+                clearTimeout(cleared);
+                let count = 0;
+                const interval = setInterval(() => {
+                    log.push("interval");
+                    if (++count == 2) clearInterval(interval);
+                }, 1);
+                setTimeout(() => log.push("later"), 30);
+                "#,
+            )
+            .catch(&ctx)
+            .unwrap();
+        });
+
+        assert_eq!(host.poll(), 1);
+        while !host.is_empty() {
+            thread::sleep(Duration::from_millis(5));
+            host.poll();
+        }
+        ctx.with(|ctx| {
+            let log: Vec<String> = ctx.eval("log").unwrap();
+            assert_eq!(log, ["timeout 12", "interval", "interval", "later"]);
+        });
+    }
+
+    #[test]
+    fn dropped_context() {
+        let host = SimpleTimerHost::new();
+        {
+            let rt = Runtime::new().unwrap();
+            let ctx = Context::full(&rt).unwrap();
+            register_timers(&ctx, host.clone()).unwrap();
+            ctx.with(|ctx| {
+                ctx.eval::<(), _>("setTimeout(() => { throw new Error('unreachable') }, 0)")
+                    .unwrap();
+            });
+        }
+        // Pending timers don't keep the runtime alive and do nothing once it is gone.
+        assert_eq!(host.poll(), 1);
+    }
+
+    #[test]
+    fn callback_errors() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let host = SimpleTimerHost::new();
+        register_timers(&ctx, host.clone()).unwrap();
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                setTimeout(() => { throw new TypeError("first") }, 0);
+                setTimeout(() => globalThis.ran = true, 0);
+                setTimeout(() => { throw 2 }, 0);
+                "#,
+            )
+            .unwrap();
+        });
+        assert_eq!(host.poll(), 3);
+        let errors = host.take_errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].name.as_deref(), Some("TypeError"));
+        assert_eq!(errors[0].message.as_deref(), Some("first"));
+        assert_eq!(errors[1].message.as_deref(), Some("2"));
+        assert!(host.take_errors().is_empty());
+        // Failing timers don't affect the other timers or leave an exception behind.
+        ctx.with(|ctx| {
+            assert!(ctx.globals().get::<_, bool>("ran").unwrap());
+            assert!(!ctx.catch().is_object());
+        });
+    }
+
+    #[test]
+    fn invalid_callback() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        register_timers(&ctx, SimpleTimerHost::new()).unwrap();
+        ctx.with(|ctx| {
+            let err = ctx
+                .eval::<(), _>("setTimeout('code', 0)")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("must be of type function"));
+        });
+    }
+}
@@ -20,11 +20,25 @@ extern crate std;
 pub(crate) use alloc::string::String as StdString;
 pub(crate) use core::result::Result as StdResult;
 
+#[cfg(any(feature = "std", feature = "loader"))]
+mod bytecode_cache;
+#[cfg(feature = "loader")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+pub mod compile;
+pub mod inspect;
 mod js_lifetime;
 pub mod markers;
+#[cfg(all(feature = "std", feature = "serde"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "std", feature = "serde"))))]
+pub mod multithread;
 mod persistent;
 mod result;
 mod safe_ref;
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "serde")))]
+pub mod serde;
+#[cfg(feature = "tracing")]
+mod trace;
 mod util;
 mod value;
 pub(crate) use safe_ref::*;
@@ -34,13 +48,21 @@ pub mod context;
 pub use context::{Context, Ctx};
 pub mod class;
 pub use class::Class;
+pub mod interop;
 pub use js_lifetime::JsLifetime;
 pub use persistent::Persistent;
-pub use result::{CatchResultExt, CaughtError, CaughtResult, Error, Result, ThrowResultExt};
+#[cfg(feature = "std")]
+pub use persistent::SharedPersistent;
+pub use result::{
+    CatchResultExt, CaughtError, CaughtResult, Error, ErrorReport, Result, ThrowJs, ThrowResultExt,
+};
+#[cfg(feature = "futures")]
+pub use value::async_iterator;
 pub use value::{
     array, atom, convert, function, module, object, promise, Array, Atom, BigInt, CString, Coerced,
-    Exception, Filter, FromAtom, FromIteratorJs, FromJs, Function, IntoAtom, IntoJs, IteratorJs,
-    Module, Null, Object, Promise, String, Symbol, Type, Undefined, Value, WriteOptions,
+    Date, Exception, Filter, FinalizationRegistry, FromAtom, FromIteratorJs, FromJs, Function,
+    Generator, GeneratorResult, IntoAtom, IntoJs, IteratorJs, JsDisplay, Module, Null, Object,
+    Promise, Proxy, ProxyHandler, RegExp, String, Symbol, Type, Undefined, Value, WriteOptions,
     WriteOptionsEndianness,
 };
 
@@ -48,6 +70,9 @@ pub mod allocator;
 #[cfg(feature = "loader")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
 pub mod loader;
+#[cfg(feature = "net")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net")))]
+pub mod net;
 
 #[cfg(feature = "futures")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
@@ -57,7 +82,7 @@ pub use context::MultiWith;
 #[cfg(feature = "futures")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
 pub use runtime::AsyncRuntime;
-pub use value::{ArrayBuffer, TypedArray};
+pub use value::{ArrayBuffer, DataView, TypedArray};
 
 //#[doc(hidden)]
 pub mod qjs {
@@ -72,23 +97,44 @@ pub mod phf {
 }
 
 pub mod prelude {
-    //! A group of often used types.
+    //! A group of often used types, traits and macros.
+    //!
+    //! ```
+    //! use rquickjs::prelude::*;
+    //!
+    //! fn main() -> rquickjs::Result<()> {
+    //!     let rt = Runtime::new()?;
+    //!     let ctx = Context::full(&rt)?;
+    //!     ctx.with(|ctx| {
+    //!         let sum: Function = ctx.eval("(a, b) => a + b")?;
+    //!         assert_eq!(sum.call::<_, i32>((1, 2))?, 3);
+    //!         Ok(())
+    //!     })
+    //! }
+    //! ```
     #[cfg(feature = "multi-ctx")]
     pub use crate::context::MultiWith;
+    #[cfg(feature = "futures")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
     pub use crate::{
+        async_iterator::{AsyncIter, AsyncIterable},
+        async_with,
+        function::Async,
+        promise::Promised,
+        AsyncContext, AsyncRuntime,
+    };
+    pub use crate::{
+        class::{Class, JsClass, Trace},
         context::Ctx,
-        convert::{Coerced, FromAtom, FromIteratorJs, FromJs, IntoAtom, IntoJs, IteratorJs, List},
+        convert::{
+            Coerced, FromAtom, FromIteratorJs, FromJs, IntoAtom, IntoJs, IteratorJs, List, Nullable,
+        },
         function::{
             Exhaustive, Flat, Func, FuncArg, IntoArg, IntoArgs, MutFn, OnceFn, Opt, Rest, This,
         },
-        result::{CatchResultExt, ThrowResultExt},
-        JsLifetime,
-    };
-    #[cfg(feature = "futures")]
-    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
-    pub use crate::{
-        function::Async,
-        promise::{Promise, Promised},
+        result::{CatchResultExt, CaughtError, CaughtResult, ThrowJs, ThrowResultExt},
+        Array, Context, Exception, Function, JsLifetime, Module, Object, Persistent, Promise,
+        Runtime, Value,
     };
 }
 
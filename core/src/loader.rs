@@ -2,30 +2,67 @@
 
 use alloc::boxed::Box;
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::{ffi::CStr, ptr};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
 use crate::{module::Declared, qjs, Ctx, Module, Result};
 
+#[cfg(feature = "futures")]
+mod async_loader;
 mod builtin_loader;
 mod builtin_resolver;
 pub mod bundle;
+mod bytecode_cache;
+mod caching_loader;
+mod combinator;
 mod compile;
 #[cfg(feature = "std")]
 mod file_resolver;
+#[cfg(feature = "loader-http")]
+mod http;
 mod module_loader;
+#[cfg(feature = "std")]
+mod node_resolver;
+mod rewriter;
 mod script_loader;
+mod transform_loader;
 mod util;
 
 #[cfg(feature = "dyn-load")]
 mod native_loader;
 
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "tokio")))]
+pub use async_loader::AsyncFileLoader;
+#[cfg(feature = "futures")]
+pub(crate) use async_loader::{dynamic_import_job, load_async, AsyncLoaderData};
+#[cfg(feature = "futures")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub use async_loader::{AsyncLoader, AsyncResolver, LoaderFuture};
 pub use builtin_loader::BuiltinLoader;
 pub use builtin_resolver::BuiltinResolver;
+#[cfg(feature = "std")]
+pub use bytecode_cache::FsBytecodeCache;
+pub use bytecode_cache::{
+    BytecodeCacheLoader, BytecodeCacheStore, CacheKeyStrategy, MemoryBytecodeCache,
+};
+pub use caching_loader::CachingLoader;
+pub use combinator::Or;
 pub use compile::Compile;
 #[cfg(feature = "std")]
 pub use file_resolver::FileResolver;
+#[cfg(feature = "loader-http")]
+pub use http::{HttpLoader, HttpResolver};
 pub use module_loader::ModuleLoader;
+#[cfg(feature = "std")]
+pub use node_resolver::NodeResolver;
+pub use rewriter::Rewriter;
 pub use script_loader::ScriptLoader;
+pub use transform_loader::TransformLoader;
 
 #[cfg(feature = "dyn-load")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "dyn-load")))]
@@ -40,10 +77,22 @@ pub type Bundle = bundle::Bundle<bundle::PhfBundleData<&'static [u8]>>;
 pub type Bundle = bundle::Bundle<bundle::ScaBundleData<&'static [u8]>>;
 
 /// Module resolver interface
+///
+/// The resolver and the [`Loader`] are used for static imports as well as for dynamic `import()`
+/// calls. A dynamic import returns a pending promise and resolves and loads the module in a job,
+/// an error returned by the resolver or the loader rejects the promise with an exception
+/// carrying its message. The same holds for the `AsyncResolver` and `AsyncLoader`, which are
+/// awaited while the promise stays pending.
+///
+/// Resolvers are combined with [`Resolver::or`], which tries them in order. Tuples of resolvers
+/// also try their elements in order.
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
 pub trait Resolver {
     /// Normalize module name
     ///
+    /// The `base` is the name of the importing module, or the name of the script for dynamic
+    /// imports from scripts, which allows resolving relative specifiers.
+    ///
     /// The resolving may looks like:
     ///
     /// ```no_run
@@ -62,18 +111,55 @@ pub trait Resolver {
     /// # }
     /// ```
     fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String>;
+
+    /// Fall back to the other resolver if this one is unable to resolve the module.
+    fn or<R: Resolver>(self, other: R) -> Or<Self, R>
+    where
+        Self: Sized,
+    {
+        Or::new(self, other)
+    }
 }
 
 /// Module loader interface
+///
+/// Loaders are combined with [`Loader::or`], which tries them in order. Tuples of loaders also try
+/// their elements in order.
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
 pub trait Loader {
     /// Load module by name
+    ///
+    /// The module can be declared with another name than the one it was loaded with, like the URL
+    /// a request was redirected to. Its relative imports are then resolved against that name, and
+    /// later imports of the loaded name find the declared module instead of loading it again.
     fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>>;
+
+    /// Fall back to the other loader if this one is unable to load the module.
+    fn or<L: Loader>(self, other: L) -> Or<Self, L>
+    where
+        Self: Sized,
+    {
+        Or::new(self, other)
+    }
+}
+
+/// Module source loader interface
+///
+/// Loaders which read the source of modules, like [`ScriptLoader`] and [`BuiltinLoader`], also
+/// implement this trait to return the source instead of a compiled module, which allows
+/// processing it with a [`TransformLoader`] first.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+pub trait SourceLoader {
+    /// Load the source of a module by name
+    fn load_source<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Vec<u8>>;
 }
 
 struct LoaderOpaque {
     resolver: Box<dyn Resolver>,
     loader: Box<dyn Loader>,
+    /// Resolved names of modules which the loader declared with another name, mapped to that
+    /// name, so later imports find the loaded module instead of loading it again.
+    aliases: HashMap<String, String>,
 }
 
 #[derive(Debug)]
@@ -95,6 +181,7 @@ impl LoaderHolder {
         Self(Box::into_raw(Box::new(LoaderOpaque {
             resolver: Box::new(resolver),
             loader: Box::new(loader),
+            aliases: HashMap::new(),
         })))
     }
 
@@ -120,6 +207,7 @@ impl LoaderHolder {
         let name = name.to_str()?;
 
         let name = opaque.resolver.resolve(ctx, base, name)?;
+        let name = opaque.aliases.get(&name).unwrap_or(&name);
 
         // We should transfer ownership of this string to QuickJS
         Ok(unsafe { qjs::js_strndup(ctx.as_ptr(), name.as_ptr() as _, name.len() as _) })
@@ -150,7 +238,14 @@ impl LoaderHolder {
     ) -> Result<*mut qjs::JSModuleDef> {
         let name = name.to_str()?;
 
-        Ok(opaque.loader.load(ctx, name)?.as_ptr())
+        let module = opaque.loader.load(ctx, name)?;
+        let declared: String = module.name()?;
+        ctx.get_opaque()
+            .run_import_meta_handler(ctx, &declared, &module)?;
+        if declared != name {
+            opaque.aliases.insert(name.into(), declared);
+        }
+        Ok(module.as_ptr())
     }
 
     unsafe extern "C" fn load_raw(
@@ -240,9 +335,13 @@ loader_impls!(A B C D E F G H);
 
 #[cfg(test)]
 mod test {
-    use crate::{CatchResultExt, Context, Ctx, Error, Module, Result, Runtime};
+    use crate::{
+        promise::PromiseState, CatchResultExt, Context, Ctx, Error, Module, Object, Promise,
+        Result, Runtime,
+    };
+    use alloc::{boxed::Box, format, string::String};
 
-    use super::{Loader, Resolver};
+    use super::{BuiltinLoader, BuiltinResolver, Loader, Resolver, ScriptLoader};
 
     struct TestResolver;
 
@@ -271,6 +370,8 @@ mod test {
                     r#"
                       export const n = 123;
                       export const s = "abc";
+                      export const url = import.meta.url;
+                      export const mode = import.meta.env?.MODE;
                     "#,
                 )
             } else {
@@ -320,4 +421,116 @@ mod test {
             .expect("Unable to resolve");
         })
     }
+
+    #[test]
+    fn import_meta_handler() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(TestResolver, TestLoader);
+        rt.set_import_meta_handler(Some(Box::new(|ctx, name, meta| {
+            let env = Object::new(ctx)?;
+            env.set("MODE", "test")?;
+            meta.set("env", env)?;
+            meta.set("url", format!("test://{name}"))
+        })));
+        ctx.with(|ctx| {
+            let (module, promise) = Module::declare(
+                ctx.clone(),
+                "loader",
+                r#"
+                  export { url, mode } from "test";
+                "#,
+            )
+            .unwrap()
+            .eval()
+            .unwrap();
+            promise.finish::<()>().unwrap();
+            assert_eq!(module.get::<_, String>("url").unwrap(), "test://test");
+            assert_eq!(module.get::<_, String>("mode").unwrap(), "test");
+        })
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn script_loader_import_meta_url() {
+        struct DirResolver(std::path::PathBuf);
+
+        impl Resolver for DirResolver {
+            fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, _base: &str, name: &str) -> Result<String> {
+                Ok(self.0.join(name).to_str().unwrap().into())
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("rquickjs-meta-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("dep.js"), "export const url = import.meta.url;").unwrap();
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(
+            DirResolver(dir.clone()),
+            ScriptLoader::default().with_import_meta_url(true),
+        );
+        ctx.with(|ctx| {
+            let (module, promise) =
+                Module::declare(ctx.clone(), "main", "export { url } from 'dep.js'")
+                    .unwrap()
+                    .eval()
+                    .unwrap();
+            promise.finish::<()>().unwrap();
+            let url: String = module.get("url").unwrap();
+            assert_eq!(url, format!("file://{}", dir.join("dep.js").display()));
+        });
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dynamic_import() {
+        struct PluginResolver(BuiltinResolver);
+
+        impl Resolver for PluginResolver {
+            fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+                if name.starts_with("plugin:") && name != "plugin:foo" {
+                    return Err(Error::new_resolving_message(base, name, "unknown plugin"));
+                }
+                self.0.resolve(ctx, base, name)
+            }
+        }
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(
+            PluginResolver(
+                BuiltinResolver::default()
+                    .with_module("plugin:foo")
+                    .with_module("lib/a.js")
+                    .with_module("lib/b.js"),
+            ),
+            BuiltinLoader::default()
+                .with_module("plugin:foo", "export const name = 'foo';")
+                .with_module("lib/a.js", "export const load = () => import('./b.js');")
+                .with_module("lib/b.js", "export const b = 'b';"),
+        );
+        ctx.with(|ctx| {
+            let promise: Promise = ctx.eval("import('plugin:foo')").unwrap();
+            assert_eq!(promise.state(), PromiseState::Pending);
+            let namespace: Object = promise.finish().unwrap();
+            assert_eq!(namespace.get::<_, String>("name").unwrap(), "foo");
+
+            let message: String = ctx
+                .eval::<Promise, _>("import('plugin:bar').catch((error) => error.message)")
+                .unwrap()
+                .finish()
+                .unwrap();
+            assert!(message.contains("unknown plugin"), "{message}");
+
+            let b: String = ctx
+                .eval::<Promise, _>("import('lib/a.js').then((a) => a.load()).then((b) => b.b)")
+                .unwrap()
+                .finish()
+                .unwrap();
+            assert_eq!(b, "b");
+        })
+    }
 }
@@ -0,0 +1,539 @@
+use alloc::{boxed::Box, ffi::CString, string::String, vec::Vec};
+use core::{ffi::c_int, future::Future, mem, pin::Pin, slice};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::{
+    convert::Coerced,
+    loader::{Loader, Resolver},
+    markers::ParallelSend,
+    qjs,
+    value::promise::settle,
+    Ctx, Error, FromJs, JsLifetime, Module, Mut, Promise, Ref, Result, Value,
+};
+
+/// The future returned by [`AsyncResolver::resolve`] and [`AsyncLoader::load`].
+#[cfg(not(feature = "parallel"))]
+pub type LoaderFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + 'a>>;
+
+/// The future returned by [`AsyncResolver::resolve`] and [`AsyncLoader::load`].
+#[cfg(feature = "parallel")]
+pub type LoaderFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Asynchronous module resolver interface
+///
+/// Set with [`AsyncRuntime::set_async_loader`](crate::AsyncRuntime::set_async_loader). It is used
+/// for static imports of modules imported with the asynchronous functions of [`Module`] as well as
+/// for dynamic `import()` calls.
+#[cfg_attr(
+    feature = "doc-cfg",
+    doc(cfg(all(feature = "loader", feature = "futures")))
+)]
+pub trait AsyncResolver: ParallelSend + 'static {
+    /// Normalize module name
+    fn resolve<'a>(&'a self, base: &'a str, name: &'a str) -> LoaderFuture<'a, String>;
+}
+
+/// Asynchronous module loader interface
+///
+/// Set with [`AsyncRuntime::set_async_loader`](crate::AsyncRuntime::set_async_loader). It is used
+/// for static imports of modules imported with the asynchronous functions of [`Module`] as well as
+/// for dynamic `import()` calls.
+#[cfg_attr(
+    feature = "doc-cfg",
+    doc(cfg(all(feature = "loader", feature = "futures")))
+)]
+pub trait AsyncLoader: ParallelSend + 'static {
+    /// Load the source of the module with the resolved name.
+    fn load<'a>(&'a self, name: &'a str) -> LoaderFuture<'a, Vec<u8>>;
+
+    /// Load the source of the module with the resolved name together with the name to declare it
+    /// with.
+    ///
+    /// The declared name can differ from the resolved one, like the URL a request was redirected
+    /// to, see [`Loader::load`]. Returns the resolved name and the result of
+    /// [`AsyncLoader::load`] by default.
+    fn load_named<'a>(&'a self, name: &'a str) -> LoaderFuture<'a, (String, Vec<u8>)> {
+        let source = self.load(name);
+        Box::pin(async move { Ok((name.into(), source.await?)) })
+    }
+}
+
+/// The asynchronous script module loader which reads the modules with `tokio::fs`.
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct AsyncFileLoader {
+    extensions: Vec<String>,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncFileLoader {
+    /// Add script file extension
+    pub fn add_extension<X: Into<String>>(&mut self, extension: X) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Add script file extension
+    #[must_use]
+    pub fn with_extension<X: Into<String>>(mut self, extension: X) -> Self {
+        self.add_extension(extension);
+        self
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Default for AsyncFileLoader {
+    fn default() -> Self {
+        Self {
+            extensions: alloc::vec!["js".into()],
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncLoader for AsyncFileLoader {
+    fn load<'a>(&'a self, name: &'a str) -> LoaderFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            if !super::util::check_extensions(name, &self.extensions) {
+                return Err(Error::new_loading(name));
+            }
+            Ok(tokio::fs::read(name).await?)
+        })
+    }
+}
+
+/// Resolved names and sources fetched by the asynchronous loader.
+#[derive(Default)]
+struct Prefetched {
+    resolved: HashMap<(String, String), String>,
+    /// The declared names and sources by resolved name.
+    sources: HashMap<String, (String, Vec<u8>)>,
+    missing_resolves: Vec<(String, String)>,
+    missing_sources: Vec<String>,
+}
+
+/// The synchronous resolver and loader QuickJS calls, which only use prefetched data.
+///
+/// Resolving or loading a module which wasn't prefetched records it as missing and fails.
+struct PrefetchLoader(Ref<Mut<Prefetched>>);
+
+impl Resolver for PrefetchLoader {
+    fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let mut prefetched = self.0.lock();
+        let key = (base.into(), name.into());
+        if let Some(x) = prefetched.resolved.get(&key) {
+            return Ok(x.clone());
+        }
+        prefetched.missing_resolves.push(key);
+        Err(Error::new_resolving_message(
+            base,
+            name,
+            "modules must be resolved asynchronously with `Module::import_async`",
+        ))
+    }
+}
+
+impl Loader for PrefetchLoader {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js>> {
+        let mut prefetched = self.0.lock();
+        if let Some((declared, source)) = prefetched.sources.get(name) {
+            let (declared, source) = (declared.clone(), source.clone());
+            drop(prefetched);
+            return Module::declare(ctx.clone(), declared, source);
+        }
+        prefetched.missing_sources.push(name.into());
+        Err(Error::new_loading_message(
+            name,
+            "modules must be loaded asynchronously with `Module::import_async`",
+        ))
+    }
+}
+
+/// The asynchronous resolver and loader of a runtime, stored as userdata.
+pub(crate) struct AsyncLoaderData {
+    resolver: Ref<dyn AsyncResolver>,
+    loader: Ref<dyn AsyncLoader>,
+    prefetched: Ref<Mut<Prefetched>>,
+}
+
+unsafe impl<'js> JsLifetime<'js> for AsyncLoaderData {
+    type Changed<'to> = AsyncLoaderData;
+}
+
+impl AsyncLoaderData {
+    /// Returns the data to store as userdata and the loader to set on the runtime.
+    pub fn new<R, L>(resolver: R, loader: L) -> (Self, impl Resolver, impl Loader)
+    where
+        R: AsyncResolver,
+        L: AsyncLoader,
+    {
+        let prefetched = Ref::new(Mut::new(Prefetched::default()));
+        let data = AsyncLoaderData {
+            resolver: Ref::new(resolver),
+            loader: Ref::new(loader),
+            prefetched: prefetched.clone(),
+        };
+        (
+            data,
+            PrefetchLoader(prefetched.clone()),
+            PrefetchLoader(prefetched),
+        )
+    }
+}
+
+/// Run the attempt until it no longer misses modules, fetching the missing ones in between.
+///
+/// QuickJS resolves and loads modules synchronously so an attempt fails at the first module which
+/// wasn't fetched yet. Linking happens before any module is evaluated so retrying is free of side
+/// effects.
+pub(crate) async fn load_async<'js, T, F>(ctx: &Ctx<'js>, mut attempt: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    let Some((resolver, loader, prefetched)) = ctx
+        .userdata::<AsyncLoaderData>()
+        .map(|x| (x.resolver.clone(), x.loader.clone(), x.prefetched.clone()))
+    else {
+        return attempt();
+    };
+
+    let mut fetched = Vec::new();
+    let res = 'retry: loop {
+        let res = attempt();
+        let (resolves, sources) = {
+            let mut prefetched = prefetched.lock();
+            (
+                mem::take(&mut prefetched.missing_resolves),
+                mem::take(&mut prefetched.missing_sources),
+            )
+        };
+        if resolves.is_empty() && sources.is_empty() {
+            break res;
+        }
+        if res.is_err() {
+            ctx.catch();
+        }
+
+        for (base, name) in resolves {
+            match resolver.resolve(&base, &name).await {
+                Ok(x) => prefetched.lock().resolved.insert((base, name), x),
+                Err(e) => break 'retry Err(e),
+            };
+        }
+        for name in sources {
+            match loader.load_named(&name).await {
+                Ok(x) => prefetched.lock().sources.insert(name.clone(), x),
+                Err(e) => break 'retry Err(e),
+            };
+            fetched.push(name);
+        }
+    };
+
+    // Loaded modules are kept by QuickJS so their sources are no longer needed. Resolved names
+    // are kept as they are needed every time a module is imported.
+    let mut prefetched = prefetched.lock();
+    for name in fetched {
+        prefetched.sources.remove(&name);
+    }
+    res
+}
+
+/// The job of a dynamic `import()`, which the runtime runs instead of the job of QuickJS.
+///
+/// Without an asynchronous loader the module is loaded synchronously like QuickJS does. With one
+/// a future is spawned which fetches the imported module and its static imports before importing
+/// it, settling the promise returned by `import()` once the import has finished.
+pub(crate) unsafe extern "C" fn dynamic_import_job(
+    ctx: *mut qjs::JSContext,
+    argc: c_int,
+    argv: *mut qjs::JSValue,
+) -> qjs::JSValue {
+    let ctx_ref = Ctx::from_ptr(ctx);
+    // QuickJS rejects imports from code without a file name.
+    if ctx_ref.userdata::<AsyncLoaderData>().is_none()
+        || argc != 4
+        || !qjs::JS_IsString(*argv.add(2))
+    {
+        return qjs::rquickjs_dynamic_import_job(ctx, argc, argv);
+    }
+    let ctx = ctx_ref;
+    let args = slice::from_raw_parts(argv, argc as usize);
+    let arg = |i: usize| Value::from_js_value_const(ctx.clone(), args[i]);
+    let (Some(resolve), Some(reject)) = (arg(0).into_function(), arg(1).into_function()) else {
+        return qjs::JS_UNDEFINED;
+    };
+
+    let names = Coerced::<String>::from_js(&ctx, arg(2)).and_then(|base| {
+        let specifier = Coerced::<String>::from_js(&ctx, arg(3))?;
+        Ok((CString::new(base.0)?, CString::new(specifier.0)?))
+    });
+    let (base, specifier) = match names {
+        Ok(x) => x,
+        Err(error) => {
+            settle(&ctx, &resolve, &reject, Err(error)).ok();
+            return qjs::JS_UNDEFINED;
+        }
+    };
+
+    let job_ctx = ctx.clone();
+    ctx.spawn(async move {
+        let ctx = job_ctx;
+        let res = load_async(&ctx, || import(&ctx, &base, &specifier)).await;
+        settle(&ctx, &resolve, &reject, res.map(Promise::into_value)).ok();
+    });
+    qjs::JS_UNDEFINED
+}
+
+/// Import a module relative to the given base name.
+fn import<'js>(ctx: &Ctx<'js>, base: &CString, specifier: &CString) -> Result<Promise<'js>> {
+    unsafe {
+        let res = qjs::JS_LoadModule(ctx.as_ptr(), base.as_ptr(), specifier.as_ptr());
+        let res = ctx.handle_exception(res)?;
+        Ok(Promise::from_js_value(ctx.clone(), res))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{async_with, loader::util::resolve_simple, AsyncContext, AsyncRuntime, Object};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    struct TestResolver;
+
+    impl AsyncResolver for TestResolver {
+        fn resolve<'a>(&'a self, base: &'a str, name: &'a str) -> LoaderFuture<'a, String> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                if name.starts_with("plugin:") {
+                    return Err(Error::new_resolving_message(base, name, "unknown plugin"));
+                }
+                Ok(resolve_simple(base, name))
+            })
+        }
+    }
+
+    struct DelayedLoader {
+        loads: Arc<AtomicUsize>,
+    }
+
+    impl AsyncLoader for DelayedLoader {
+        fn load<'a>(&'a self, name: &'a str) -> LoaderFuture<'a, Vec<u8>> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                self.loads.fetch_add(1, Ordering::SeqCst);
+                let source = match name {
+                    "lib/a.js" => "import { b } from './b.js'; export const a = b + 1;",
+                    "lib/b.js" => "import { c } from 'c.js'; export const b = c * 2;",
+                    "c.js" => "export const c = 20;",
+                    "lazy.js" => "export default 'lazy';",
+                    _ => return Err(Error::new_loading(name)),
+                };
+                Ok(source.into())
+            })
+        }
+    }
+
+    async fn setup() -> (AsyncRuntime, AsyncContext, Arc<AtomicUsize>) {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        let loads = Arc::new(AtomicUsize::new(0));
+        let loader = DelayedLoader {
+            loads: loads.clone(),
+        };
+        rt.set_async_loader(TestResolver, loader).await.unwrap();
+        (rt, ctx, loads)
+    }
+
+    #[tokio::test]
+    async fn import_async() {
+        let (_rt, ctx, loads) = setup().await;
+        async_with!(ctx => |ctx| {
+            let namespace: Object = Module::import_async(&ctx, "lib/a.js")
+                .await
+                .unwrap()
+                .into_future()
+                .await
+                .unwrap();
+            assert_eq!(namespace.get::<_, i32>("a").unwrap(), 41);
+
+            // Loaded modules are not fetched again.
+            let namespace: Object = Module::import_async(&ctx, "lib/b.js")
+                .await
+                .unwrap()
+                .into_future()
+                .await
+                .unwrap();
+            assert_eq!(namespace.get::<_, i32>("b").unwrap(), 40);
+        })
+        .await;
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn evaluate_async() {
+        let (_rt, ctx, _loads) = setup().await;
+        async_with!(ctx => |ctx| {
+            Module::evaluate_async(
+                ctx.clone(),
+                "main.js",
+                "import { a } from 'lib/a.js';
+                globalThis.result = a;
+                globalThis.load = (name) => import(name);",
+            )
+            .await
+            .unwrap()
+            .into_future::<()>()
+            .await
+            .unwrap();
+            assert_eq!(ctx.globals().get::<_, i32>("result").unwrap(), 41);
+
+            // Dynamic imports find modules which were fetched.
+            let load: crate::Function = ctx.globals().get("load").unwrap();
+            let namespace: Object = load
+                .call::<_, crate::Promise>(("lib/a.js",))
+                .unwrap()
+                .into_future()
+                .await
+                .unwrap();
+            assert_eq!(namespace.get::<_, i32>("a").unwrap(), 41);
+
+            // And fetch the ones which weren't.
+            let namespace: Object = load
+                .call::<_, crate::Promise>(("lazy.js",))
+                .unwrap()
+                .into_future()
+                .await
+                .unwrap();
+            assert_eq!(namespace.get::<_, String>("default").unwrap(), "lazy");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn dynamic_import() {
+        let (_rt, ctx, loads) = setup().await;
+        async_with!(ctx => |ctx| {
+            Module::declare(
+                ctx.clone(),
+                "lib/main.js",
+                "export const load = () => import('./a.js');",
+            )
+            .unwrap();
+            let promise: crate::Promise = ctx
+                .eval(
+                    r#"
+                    (async () => {
+                        const { load } = await import("lib/main.js");
+                        const { a } = await load();
+                        let error;
+                        try {
+                            await import("missing.js");
+                        } catch (e) {
+                            error = e.message;
+                        }
+                        return { a, error };
+                    })()
+                "#,
+                )
+                .unwrap();
+            let result: Object = promise.into_future().await.unwrap();
+            assert_eq!(result.get::<_, i32>("a").unwrap(), 41);
+            let error: String = result.get("error").unwrap();
+            assert!(error.contains("missing.js"), "{error}");
+        })
+        .await;
+        // The three modules of the graph and the missing one.
+        assert_eq!(loads.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn load_error() {
+        let (_rt, ctx, _loads) = setup().await;
+        async_with!(ctx => |ctx| {
+            let res = Module::evaluate_async(ctx.clone(), "main.js", "import 'missing.js';").await;
+            assert!(matches!(res, Err(Error::Loading { .. })));
+            assert!(Module::import_async(&ctx, "missing.js").await.is_err());
+
+            // Failed imports are retried.
+            let res = Module::evaluate_async(ctx.clone(), "main.js", "import 'c.js';").await;
+            assert!(res.is_ok());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn dynamic_import_pending() {
+        let (_rt, ctx, _loads) = setup().await;
+        async_with!(ctx => |ctx| {
+            let promise: crate::Promise = ctx.eval("import('c.js')").unwrap();
+            // The import waits for the loader instead of failing in the job.
+            while ctx.execute_pending_job() {}
+            assert_eq!(promise.state(), crate::promise::PromiseState::Pending);
+            let namespace: Object = promise.into_future().await.unwrap();
+            assert_eq!(namespace.get::<_, i32>("c").unwrap(), 20);
+
+            let message: String = ctx
+                .eval::<crate::Promise, _>("import('plugin:bar').catch((error) => error.message)")
+                .unwrap()
+                .into_future()
+                .await
+                .unwrap();
+            assert!(message.contains("unknown plugin"), "{message}");
+        })
+        .await;
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn file_loader() {
+        let dir = std::env::temp_dir().join(format!("rquickjs-async-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.js"), "export { b as a } from './b.js';").unwrap();
+        std::fs::write(dir.join("b.js"), "export const b = 'file';").unwrap();
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        struct PathResolver;
+        impl AsyncResolver for PathResolver {
+            fn resolve<'a>(&'a self, base: &'a str, name: &'a str) -> LoaderFuture<'a, String> {
+                Box::pin(async move {
+                    let path = std::path::Path::new(name);
+                    let path = match std::path::Path::new(base).parent() {
+                        Some(dir) if !path.is_absolute() => dir.join(path),
+                        _ => path.into(),
+                    };
+                    Ok(path.to_str().unwrap().replace("/./", "/"))
+                })
+            }
+        }
+        rt.set_async_loader(PathResolver, AsyncFileLoader::default())
+            .await
+            .unwrap();
+
+        let path = dir.join("a.js");
+        let path = path.to_str().unwrap();
+        async_with!(ctx => |ctx| {
+            let namespace: Object = Module::import_async(&ctx, path)
+                .await
+                .unwrap()
+                .into_future()
+                .await
+                .unwrap();
+            assert_eq!(namespace.get::<_, String>("a").unwrap(), "file");
+        })
+        .await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{loader::Loader, module::Declared, Ctx, Error, Module, Result};
+use crate::{
+    loader::{Loader, SourceLoader},
+    module::Declared,
+    Ctx, Error, Module, Result,
+};
 use alloc::{string::String, vec::Vec};
 #[cfg(not(feature = "std"))]
 use hashbrown::HashMap;
@@ -32,11 +36,17 @@ impl BuiltinLoader {
     }
 }
 
+impl SourceLoader for BuiltinLoader {
+    fn load_source<'js>(&mut self, _ctx: &Ctx<'js>, path: &str) -> Result<Vec<u8>> {
+        self.modules
+            .remove(path)
+            .ok_or_else(|| Error::new_loading(path))
+    }
+}
+
 impl Loader for BuiltinLoader {
     fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js, Declared>> {
-        match self.modules.remove(path) {
-            Some(source) => Module::declare(ctx.clone(), path, source),
-            _ => Err(Error::new_loading(path)),
-        }
+        let source = self.load_source(ctx, path)?;
+        Module::declare(ctx.clone(), path, source)
     }
 }
@@ -0,0 +1,305 @@
+use alloc::{string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+pub use crate::bytecode_cache::MemoryBytecodeCache;
+
+#[cfg(feature = "std")]
+use crate::{
+    loader::{util::check_extensions, Loader},
+    Ctx, Error, Module, Result, WriteOptions,
+};
+
+/// A storage for compiled module bytecode.
+pub trait BytecodeCacheStore {
+    /// Returns the bytecode stored under the key, if any.
+    fn load(&mut self, key: &[u8]) -> Option<Vec<u8>>;
+
+    /// Store the bytecode under the key.
+    fn store(&mut self, key: &[u8], bytecode: Vec<u8>);
+}
+
+impl BytecodeCacheStore for MemoryBytecodeCache {
+    fn load(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        self.get(key).map(<[u8]>::to_vec)
+    }
+
+    fn store(&mut self, key: &[u8], bytecode: Vec<u8>) {
+        self.insert(key.to_vec(), bytecode);
+    }
+}
+
+/// A bytecode cache store which keeps the bytecode in a directory, one file per key.
+///
+/// The cache persists across process runs. Bytecode is loaded without verification so the
+/// directory must only be writable by trusted users.
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct FsBytecodeCache {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FsBytecodeCache {
+    /// Create a cache storing its entries in the directory, which is created if it doesn't exist.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, key: &[u8]) -> PathBuf {
+        let mut name = String::with_capacity(key.len() * 2 + 4);
+        for byte in key {
+            name.push(char::from_digit((byte >> 4) as u32, 16).unwrap());
+            name.push(char::from_digit((byte & 0xf) as u32, 16).unwrap());
+        }
+        name.push_str(".qbc");
+        self.dir.join(name)
+    }
+}
+
+#[cfg(feature = "std")]
+impl BytecodeCacheStore for FsBytecodeCache {
+    fn load(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        std::fs::read(self.entry_path(key)).ok()
+    }
+
+    fn store(&mut self, key: &[u8], bytecode: Vec<u8>) {
+        // Write to a temporary file first so other processes never read a partial entry.
+        let path = self.entry_path(key);
+        let tmp = path.with_extension(std::format!("{}.tmp", std::process::id()));
+        if std::fs::write(&tmp, bytecode).is_err() || std::fs::rename(&tmp, &path).is_err() {
+            // Failing to cache only makes the next load slower.
+            let _ = std::fs::remove_file(&tmp);
+        }
+    }
+}
+
+/// How the key of a cached module is derived.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKeyStrategy {
+    /// Hash only the source of the module.
+    ///
+    /// Modules with the same source share an entry, the name of the module which was compiled
+    /// first is then used to resolve the relative imports of all of them.
+    ContentHash,
+    /// Hash both the path and the source of the module.
+    #[default]
+    PathPlusHash,
+}
+
+#[cfg(feature = "std")]
+impl CacheKeyStrategy {
+    fn key(&self, path: &str, source: &[u8]) -> [u8; 16] {
+        let mut hash = Fnv128::new();
+        if let CacheKeyStrategy::PathPlusHash = self {
+            hash.write(path.as_bytes());
+            hash.write(&[0]);
+        }
+        hash.write(source);
+        hash.finish().to_le_bytes()
+    }
+}
+
+/// The 128 bit FNV-1a hash, stable across platforms and versions.
+#[cfg(feature = "std")]
+struct Fnv128(u128);
+
+#[cfg(feature = "std")]
+impl Fnv128 {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013B;
+
+    fn new() -> Self {
+        Fnv128(Self::OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u128 {
+        self.0
+    }
+}
+
+/// The script module loader which caches the compiled bytecode of modules.
+///
+/// When a module is loaded its bytecode is looked up in the store first. On a miss the source is
+/// compiled and the bytecode is stored for later loads. Entries which fail to load, for example
+/// because they were written by another version of QuickJS, are replaced.
+#[derive(Debug)]
+pub struct BytecodeCacheLoader<S> {
+    store: S,
+    strategy: CacheKeyStrategy,
+    extensions: Vec<String>,
+}
+
+impl<S: BytecodeCacheStore> BytecodeCacheLoader<S> {
+    /// Create a loader caching the bytecode in the store.
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            strategy: CacheKeyStrategy::default(),
+            extensions: vec!["js".into()],
+        }
+    }
+
+    /// Set how the key of a cached module is derived.
+    #[must_use]
+    pub fn with_key_strategy(mut self, strategy: CacheKeyStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Add script file extension
+    pub fn add_extension<X: Into<String>>(&mut self, extension: X) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Add script file extension
+    #[must_use]
+    pub fn with_extension<X: Into<String>>(mut self, extension: X) -> Self {
+        self.add_extension(extension);
+        self
+    }
+
+    /// Returns the store of the cache.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: BytecodeCacheStore> Loader for BytecodeCacheLoader<S> {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js>> {
+        if !check_extensions(path, &self.extensions) {
+            return Err(Error::new_loading(path));
+        }
+
+        let source = std::fs::read(path)?;
+        let key = self.strategy.key(path, &source);
+        if let Some(bytecode) = self.store.load(&key) {
+            // SAFETY: The bytecode was written by `Module::write`, the store must be trusted.
+            if let Ok(module) = unsafe { Module::load(ctx.clone(), &bytecode) } {
+                return Ok(module);
+            }
+            ctx.catch();
+        }
+
+        let module = Module::declare(ctx.clone(), path, source)?;
+        self.store
+            .store(&key, module.write(WriteOptions::default())?);
+        Ok(module)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::{loader::Resolver, Context, Runtime};
+    use std::{cell::RefCell, path::Path, rc::Rc};
+
+    struct DirResolver(PathBuf);
+
+    impl Resolver for DirResolver {
+        fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, _base: &str, name: &str) -> Result<String> {
+            Ok(self.0.join(name).to_str().unwrap().into())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedStore {
+        inner: Rc<RefCell<MemoryBytecodeCache>>,
+        hits: Rc<RefCell<usize>>,
+    }
+
+    impl BytecodeCacheStore for SharedStore {
+        fn load(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+            let res = self.inner.borrow_mut().load(key);
+            *self.hits.borrow_mut() += res.is_some() as usize;
+            res
+        }
+
+        fn store(&mut self, key: &[u8], bytecode: Vec<u8>) {
+            self.inner.borrow_mut().store(key, bytecode)
+        }
+    }
+
+    fn import(loader: impl Loader + 'static, dir: &Path) -> i32 {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(DirResolver(dir.into()), loader);
+        ctx.with(|ctx| {
+            let (module, promise) =
+                Module::declare(ctx.clone(), "main", "export { value } from 'dep.js'")
+                    .unwrap()
+                    .eval()
+                    .unwrap();
+            promise.finish::<()>().unwrap();
+            module.get("value").unwrap()
+        })
+    }
+
+    fn key(dir: &Path, source: &str) -> [u8; 16] {
+        let path = dir.join("dep.js");
+        CacheKeyStrategy::PathPlusHash.key(path.to_str().unwrap(), source.as_bytes())
+    }
+
+    #[test]
+    fn fs_cache() {
+        let dir = std::env::temp_dir().join(format!("rquickjs-bc-fs-{}", std::process::id()));
+        let cache_dir = dir.join("cache");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("dep.js"), "export const value = 42;").unwrap();
+
+        let loader = BytecodeCacheLoader::new(FsBytecodeCache::new(&cache_dir).unwrap());
+        assert_eq!(import(loader, &dir), 42);
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+        // Another process finds the entry.
+        let mut store = FsBytecodeCache::new(&cache_dir).unwrap();
+        assert!(store.load(&key(&dir, "export const value = 42;")).is_some());
+        assert_eq!(import(BytecodeCacheLoader::new(store), &dir), 42);
+
+        // Changing the source creates a new entry.
+        std::fs::write(dir.join("dep.js"), "export const value = 1;").unwrap();
+        let loader = BytecodeCacheLoader::new(FsBytecodeCache::new(&cache_dir).unwrap());
+        assert_eq!(import(loader, &dir), 1);
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cache_hits() {
+        let dir = std::env::temp_dir().join(format!("rquickjs-bc-hits-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("dep.js"), "export const value = 7;").unwrap();
+
+        let store = SharedStore::default();
+        assert_eq!(import(BytecodeCacheLoader::new(store.clone()), &dir), 7);
+        assert_eq!(*store.hits.borrow(), 0);
+        assert_eq!(import(BytecodeCacheLoader::new(store.clone()), &dir), 7);
+        assert_eq!(*store.hits.borrow(), 1);
+
+        // Entries which fail to load are replaced.
+        let key = key(&dir, "export const value = 7;");
+        store.inner.borrow_mut().store(&key, vec![0xff; 4]);
+        assert_eq!(import(BytecodeCacheLoader::new(store.clone()), &dir), 7);
+        assert_ne!(store.inner.borrow_mut().load(&key).unwrap(), [0xff; 4]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    loader::{Loader, MemoryBytecodeCache},
+    module::Declared,
+    Ctx, Module, Result, WriteOptions,
+};
+
+/// The loader which memoizes the modules loaded by another loader.
+///
+/// A module belongs to the context it was declared in, so the compiled bytecode is cached per
+/// resolved name instead and modules loaded again, for example by another context of the runtime,
+/// are created from it without calling the inner loader. Modules which can't be written as
+/// bytecode, like native modules, are not cached.
+///
+/// Fields of `import.meta` set by the inner loader are not restored from the cache, use
+/// [`Runtime::set_import_meta_handler`](crate::Runtime::set_import_meta_handler) to set them for
+/// every load.
+#[derive(Debug)]
+pub struct CachingLoader<L> {
+    inner: L,
+    cache: MemoryBytecodeCache,
+}
+
+impl<L: Loader> CachingLoader<L> {
+    /// Create a loader caching the modules loaded by the inner loader.
+    pub fn new(inner: L) -> Self {
+        Self {
+            inner,
+            cache: MemoryBytecodeCache::new(),
+        }
+    }
+
+    /// Create a loader caching at most `capacity` modules loaded by the inner loader, evicting
+    /// the least recently loaded one when full.
+    pub fn with_capacity(inner: L, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: MemoryBytecodeCache::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the inner loader.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Returns the number of cached modules.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Remove the cached module with the resolved name, returning whether it was cached.
+    pub fn invalidate(&mut self, name: &str) -> bool {
+        self.cache.remove(name.as_bytes())
+    }
+
+    /// Remove all cached modules.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+impl<L: Loader> Loader for CachingLoader<L> {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
+        if let Some(bytecode) = self.cache.get(name.as_bytes()) {
+            // SAFETY: The bytecode was written by `Module::write` in this process.
+            if let Ok(module) = unsafe { Module::load(ctx.clone(), bytecode) } {
+                return Ok(module);
+            }
+            ctx.catch();
+            self.cache.remove(name.as_bytes());
+        }
+
+        let module = self.inner.load(ctx, name)?;
+        match module.write(WriteOptions::default()) {
+            Ok(bytecode) => {
+                self.cache.insert(name.as_bytes().to_vec(), bytecode);
+            }
+            Err(_) => {
+                ctx.catch();
+            }
+        }
+        Ok(module)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{loader::BuiltinResolver, Context, Error, Runtime};
+    use alloc::rc::Rc;
+    use core::cell::Cell;
+
+    struct CountingLoader(Rc<Cell<usize>>);
+
+    impl Loader for CountingLoader {
+        fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js>> {
+            if name != "dep" {
+                return Err(Error::new_loading(name));
+            }
+            self.0.set(self.0.get() + 1);
+            Module::declare(ctx.clone(), name, "export const value = 42;")
+        }
+    }
+
+    #[test]
+    fn load_once() {
+        let loads = Rc::new(Cell::new(0));
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            BuiltinResolver::default().with_module("dep"),
+            CachingLoader::new(CountingLoader(loads.clone())),
+        );
+
+        for _ in 0..2 {
+            let ctx = Context::full(&rt).unwrap();
+            ctx.with(|ctx| {
+                let value: i32 = Module::import(&ctx, "dep")
+                    .unwrap()
+                    .finish::<crate::Object>()
+                    .unwrap()
+                    .get("value")
+                    .unwrap();
+                assert_eq!(value, 42);
+            });
+        }
+        assert_eq!(loads.get(), 1);
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{
+    loader::{Loader, Resolver},
+    module::Declared,
+    Ctx, Error, Module, Result,
+};
+
+/// The prefix of the messages of errors returned by [`Or`], used to flatten nested combinators.
+const TRIED: &str = "tried ";
+
+/// Resolver or loader which falls back to a second one, created with [`Resolver::or`] and
+/// [`Loader::or`].
+///
+/// The second one is only tried if the first returns [`Error::Resolving`] or
+/// [`Error::Loading`], other errors are returned directly. If all of them fail the message of the
+/// error lists what was tried in order, for example ``tried `BuiltinLoader`, `ScriptLoader` ``.
+#[derive(Debug, Default, Clone)]
+pub struct Or<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Or<A, B> {
+    /// Create a combinator trying `first` and then `second`.
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Returns the first and the second resolver or loader.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+/// Returns the name of the type without its path and generic parameters.
+fn short_type_name<T>() -> &'static str {
+    let name = core::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Describe a failed attempt of `T` for the message of the aggregated error.
+fn attempt<T>(message: Option<String>) -> String {
+    match message {
+        // Attempts of a nested combinator are listed as is.
+        Some(message) if message.starts_with(TRIED) => message[TRIED.len()..].into(),
+        Some(message) => format!("`{}` ({message})", short_type_name::<T>()),
+        None => format!("`{}`", short_type_name::<T>()),
+    }
+}
+
+fn tried(attempts: Vec<String>) -> String {
+    format!("{TRIED}{}", attempts.join(", "))
+}
+
+impl<A: Resolver, B: Resolver> Resolver for Or<A, B> {
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let first = match self.first.resolve(ctx, base, name) {
+            Err(Error::Resolving { message, .. }) => attempt::<A>(message),
+            result => return result,
+        };
+        let second = match self.second.resolve(ctx, base, name) {
+            Err(Error::Resolving { message, .. }) => attempt::<B>(message),
+            result => return result,
+        };
+        Err(Error::new_resolving_message(
+            base,
+            name,
+            tried(alloc::vec![first, second]),
+        ))
+    }
+}
+
+impl<A: Loader, B: Loader> Loader for Or<A, B> {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
+        let first = match self.first.load(ctx, name) {
+            Err(Error::Loading { message, .. }) => attempt::<A>(message),
+            result => return result,
+        };
+        let second = match self.second.load(ctx, name) {
+            Err(Error::Loading { message, .. }) => attempt::<B>(message),
+            result => return result,
+        };
+        Err(Error::new_loading_message(
+            name,
+            tried(alloc::vec![first, second]),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        loader::{BuiltinLoader, BuiltinResolver, Loader, ModuleLoader, Resolver},
+        CatchResultExt, Context, Error, Module, Runtime,
+    };
+
+    #[test]
+    fn fallback() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(
+            BuiltinResolver::default()
+                .with_module("a")
+                .or(BuiltinResolver::default().with_module("b"))
+                .or(BuiltinResolver::default().with_module("missing")),
+            BuiltinLoader::default()
+                .with_module("a", "export default 'a';")
+                .or(ModuleLoader::default())
+                .or(BuiltinLoader::default().with_module("b", "export default 'b';")),
+        );
+        ctx.with(|ctx| {
+            for name in ["a", "b"] {
+                let value: String = Module::import(&ctx, name)
+                    .unwrap()
+                    .finish::<crate::Object>()
+                    .unwrap()
+                    .get("default")
+                    .unwrap();
+                assert_eq!(value, name);
+            }
+
+            let err = Module::import(&ctx, "missing")
+                .unwrap()
+                .finish::<crate::Object>()
+                .catch(&ctx)
+                .unwrap_err();
+            let message = err.to_string();
+            assert!(
+                message.contains("tried `BuiltinLoader`, `ModuleLoader`, `BuiltinLoader`"),
+                "{message}"
+            );
+
+            let mut resolver = BuiltinResolver::default().or(BuiltinResolver::default());
+            let err = resolver.resolve(&ctx, "main", "unknown").unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Resolving { message: Some(message), .. }
+                    if message == "tried `BuiltinResolver`, `BuiltinResolver`"
+            ));
+        });
+    }
+}
@@ -0,0 +1,525 @@
+use alloc::{
+    string::{String, ToString as _},
+    sync::Arc,
+    vec::Vec,
+};
+use std::{collections::HashMap, io::Read as _, sync::Mutex};
+
+use url::Url;
+
+use crate::{
+    loader::{Loader, Resolver},
+    module::Declared,
+    Ctx, Error, Module, Result,
+};
+
+/// The content types accepted for modules, compared without parameters like the charset.
+const CONTENT_TYPES: [&str; 5] = [
+    "text/javascript",
+    "application/javascript",
+    "application/x-javascript",
+    "text/ecmascript",
+    "application/ecmascript",
+];
+
+/// The maximum number of redirects followed for a single module.
+const MAX_REDIRECTS: usize = 5;
+
+fn is_http(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https")
+}
+
+/// Resolve an absolute http(s) URL or a relative specifier against an http(s) base.
+fn resolve_url(base: &str, name: &str) -> Result<String> {
+    let url = if name.starts_with("./") || name.starts_with("../") || name.starts_with('/') {
+        Url::parse(base)
+            .ok()
+            .filter(is_http)
+            .and_then(|x| x.join(name).ok())
+    } else {
+        Url::parse(name).ok().filter(is_http)
+    };
+    url.map(String::from)
+        .ok_or_else(|| Error::new_resolving(base, name))
+}
+
+/// The module resolver for http(s) URLs
+///
+/// Resolves absolute `http://` and `https://` specifiers as well as relative specifiers like
+/// `./util.js` imported from a module which was itself loaded from a URL. Other specifiers are not
+/// resolved so it can be combined with other resolvers using [`Resolver::or`].
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader-http")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HttpResolver;
+
+impl Resolver for HttpResolver {
+    fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        resolve_url(base, name)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl super::AsyncResolver for HttpResolver {
+    fn resolve<'a>(&'a self, base: &'a str, name: &'a str) -> super::LoaderFuture<'a, String> {
+        alloc::boxed::Box::pin(async move { resolve_url(base, name) })
+    }
+}
+
+/// A module fetched by the [`HttpLoader`].
+#[derive(Clone)]
+struct Fetched {
+    /// The URL after following redirects.
+    url: String,
+    etag: Option<String>,
+    source: Vec<u8>,
+}
+
+impl core::fmt::Debug for Fetched {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Fetched")
+            .field("url", &self.url)
+            .field("etag", &self.etag)
+            .finish()
+    }
+}
+
+/// The module loader fetching modules from http(s) URLs
+///
+/// Only hosts which were explicitly allowed are contacted, including the targets of redirects.
+/// Responses must have a JavaScript content type like `text/javascript` and are limited in size.
+/// A module which was redirected is declared with its final URL so its relative imports resolve
+/// against it, later imports of the requested URL find the declared module.
+///
+/// Responses with an `ETag` header are cached, the cache is shared by clones of the loader. A
+/// cached module is revalidated with `If-None-Match` when it is loaded again, for example by
+/// another runtime, and not downloaded again if it didn't change.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader-http")))]
+#[derive(Debug, Clone)]
+pub struct HttpLoader {
+    agent: ureq::Agent,
+    hosts: Vec<String>,
+    max_size: u64,
+    cache: Arc<Mutex<HashMap<String, Fetched>>>,
+}
+
+impl HttpLoader {
+    /// Allow fetching modules from the host, optionally with a port like `localhost:8080`
+    pub fn add_allowed_host<H: Into<String>>(&mut self, host: H) -> &mut Self {
+        self.hosts.push(host.into());
+        self
+    }
+
+    /// Allow fetching modules from the host, optionally with a port like `localhost:8080`
+    #[must_use]
+    pub fn with_allowed_host<H: Into<String>>(mut self, host: H) -> Self {
+        self.add_allowed_host(host);
+        self
+    }
+
+    /// Set the maximum size of a module in bytes, 8 MiB by default
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Set the agent used for requests, for example to configure timeouts or a proxy
+    ///
+    /// Redirects are followed by the loader, so the agent should be built with `redirects(0)`.
+    #[must_use]
+    pub fn with_agent(mut self, agent: ureq::Agent) -> Self {
+        self.agent = agent;
+        self
+    }
+
+    /// Remove all cached modules.
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    fn is_allowed(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let with_port = url.port().map(|port| std::format!("{host}:{port}"));
+        self.hosts
+            .iter()
+            .any(|allowed| allowed == host || Some(allowed) == with_port.as_ref())
+    }
+
+    /// Fetch the module, following redirects and revalidating a cached version.
+    fn fetch(&self, name: &str) -> Result<Fetched> {
+        let error = |message: String| Error::new_loading_message(name, message);
+
+        let mut url = Url::parse(name)
+            .ok()
+            .filter(is_http)
+            .ok_or_else(|| Error::new_loading(name))?;
+        let cached = self.cache.lock().unwrap().get(name).cloned();
+
+        for _ in 0..=MAX_REDIRECTS {
+            if !self.is_allowed(&url) {
+                return Err(error(std::format!("host of `{url}` is not allowed")));
+            }
+
+            let mut request = self.agent.get(url.as_str());
+            let revalidate = cached
+                .as_ref()
+                .filter(|x| x.url == url.as_str())
+                .and_then(|x| x.etag.as_deref());
+            if let Some(etag) = revalidate {
+                request = request.set("If-None-Match", etag);
+            }
+            let response = match request.call() {
+                Ok(response) => response,
+                Err(ureq::Error::Status(status, _)) => {
+                    return Err(error(std::format!(
+                        "`{url}` responded with status {status}"
+                    )))
+                }
+                Err(err) => return Err(error(err.to_string())),
+            };
+
+            match response.status() {
+                304 if revalidate.is_some() => return Ok(cached.unwrap()),
+                301 | 302 | 303 | 307 | 308 => {
+                    let location = response
+                        .header("Location")
+                        .ok_or_else(|| error("redirect without a location".into()))?;
+                    url =
+                        url.join(location).ok().filter(is_http).ok_or_else(|| {
+                            error(std::format!("invalid redirect to `{location}`"))
+                        })?;
+                }
+                200..=299 => {
+                    let fetched = self.read(&url, response).map_err(error)?;
+                    if fetched.etag.is_some() {
+                        self.cache
+                            .lock()
+                            .unwrap()
+                            .insert(name.into(), fetched.clone());
+                    }
+                    return Ok(fetched);
+                }
+                status => {
+                    return Err(error(std::format!(
+                        "`{url}` responded with status {status}"
+                    )))
+                }
+            }
+        }
+        Err(error("too many redirects".into()))
+    }
+
+    fn read(&self, url: &Url, response: ureq::Response) -> core::result::Result<Fetched, String> {
+        let content_type = response.content_type().to_ascii_lowercase();
+        if !CONTENT_TYPES.contains(&content_type.as_str()) {
+            return Err(std::format!(
+                "`{url}` has the content type `{content_type}` instead of JavaScript"
+            ));
+        }
+        let too_large = || std::format!("`{url}` is larger than {} bytes", self.max_size);
+        let length = response
+            .header("Content-Length")
+            .and_then(|x| x.parse::<u64>().ok());
+        if length.is_some_and(|x| x > self.max_size) {
+            return Err(too_large());
+        }
+
+        let etag = response.header("ETag").map(String::from);
+        let mut source = Vec::new();
+        response
+            .into_reader()
+            .take(self.max_size + 1)
+            .read_to_end(&mut source)
+            .map_err(|x| x.to_string())?;
+        if source.len() as u64 > self.max_size {
+            return Err(too_large());
+        }
+        Ok(Fetched {
+            url: url.to_string(),
+            etag,
+            source,
+        })
+    }
+}
+
+impl Default for HttpLoader {
+    fn default() -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().redirects(0).build(),
+            hosts: Vec::new(),
+            max_size: 8 * 1024 * 1024,
+            cache: Default::default(),
+        }
+    }
+}
+
+impl Loader for HttpLoader {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
+        let fetched = self.fetch(name)?;
+        Module::declare(ctx.clone(), fetched.url, fetched.source)
+    }
+}
+
+/// Fetches on a separate thread so the executor isn't blocked by the request.
+///
+/// Like the synchronous loader, a module which was redirected is declared with its final URL.
+#[cfg(feature = "futures")]
+impl super::AsyncLoader for HttpLoader {
+    fn load<'a>(&'a self, name: &'a str) -> super::LoaderFuture<'a, Vec<u8>> {
+        let fetched = self.load_named(name);
+        alloc::boxed::Box::pin(async move { Ok(fetched.await?.1) })
+    }
+
+    fn load_named<'a>(&'a self, name: &'a str) -> super::LoaderFuture<'a, (String, Vec<u8>)> {
+        let loader = self.clone();
+        let name = String::from(name);
+        alloc::boxed::Box::pin(blocking::Blocking::spawn(move || {
+            loader.fetch(&name).map(|x| (x.url, x.source))
+        }))
+    }
+}
+
+#[cfg(feature = "futures")]
+mod blocking {
+    use alloc::sync::Arc;
+    use core::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+    use std::sync::Mutex;
+
+    struct State<T> {
+        result: Option<T>,
+        waker: Option<Waker>,
+    }
+
+    /// A future resolving to the result of a function running on its own thread.
+    pub struct Blocking<T>(Arc<Mutex<State<T>>>);
+
+    impl<T: Send + 'static> Blocking<T> {
+        pub fn spawn<F>(f: F) -> Self
+        where
+            F: FnOnce() -> T + Send + 'static,
+        {
+            let state = Arc::new(Mutex::new(State {
+                result: None,
+                waker: None,
+            }));
+            let thread_state = state.clone();
+            std::thread::spawn(move || {
+                let result = f();
+                let mut state = thread_state.lock().unwrap();
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+            Blocking(state)
+        }
+    }
+
+    impl<T> Future for Blocking<T> {
+        type Output = T;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+            let mut state = self.0.lock().unwrap();
+            match state.result.take() {
+                Some(result) => Poll::Ready(result),
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{loader::BuiltinResolver, CatchResultExt, Context, Object, Runtime};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tiny_http::{Header, Response, Server};
+
+    struct TestServer {
+        server: Arc<Server>,
+        host: String,
+        revalidated: Arc<AtomicUsize>,
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            self.server.unblock();
+        }
+    }
+
+    fn header(name: &str, value: &str) -> Header {
+        Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+    }
+
+    fn serve() -> TestServer {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let host = std::format!("127.0.0.1:{}", server.server_addr().to_ip().unwrap().port());
+        let revalidated = Arc::new(AtomicUsize::new(0));
+
+        let (thread_server, thread_revalidated) = (server.clone(), revalidated.clone());
+        std::thread::spawn(move || {
+            for request in thread_server.incoming_requests() {
+                let js = header("Content-Type", "text/javascript; charset=utf-8");
+                let response = match request.url() {
+                    "/latest/main.js" => Response::from_string("")
+                        .with_status_code(302)
+                        .with_header(header("Location", "/v2/main.js")),
+                    // Counts its evaluations in `n`.
+                    "/v2/main.js" => Response::from_string(
+                        "import { value } from './dep.js'; globalThis.n = (globalThis.n ?? 0) + 1; \
+                        export default value * 2;",
+                    )
+                    .with_header(js),
+                    "/v2/dep.js" => {
+                        let etag = "\"dep-1\"";
+                        let revalidate = request
+                            .headers()
+                            .iter()
+                            .any(|x| x.field.equiv("If-None-Match") && x.value.as_str() == etag);
+                        if revalidate {
+                            thread_revalidated.fetch_add(1, Ordering::SeqCst);
+                            Response::from_string("").with_status_code(304)
+                        } else {
+                            Response::from_string("export const value = 21;")
+                                .with_header(js)
+                                .with_header(header("ETag", etag))
+                        }
+                    }
+                    "/text.js" => Response::from_string("export default 1;")
+                        .with_header(header("Content-Type", "text/plain")),
+                    "/large.js" => {
+                        Response::from_string(std::format!("export default '{}';", "x".repeat(100)))
+                            .with_header(js)
+                    }
+                    "/external.js" => Response::from_string("")
+                        .with_status_code(302)
+                        .with_header(header("Location", "http://localhost/main.js")),
+                    _ => Response::from_string("").with_status_code(404),
+                };
+                request.respond(response).unwrap();
+            }
+        });
+
+        TestServer {
+            server,
+            host,
+            revalidated,
+        }
+    }
+
+    #[test]
+    fn resolve() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let mut resolver = HttpResolver;
+            let base = "https://example.com/lib/main.js";
+            assert_eq!(
+                resolver.resolve(&ctx, base, "./dep.js").unwrap(),
+                "https://example.com/lib/dep.js"
+            );
+            assert_eq!(
+                resolver.resolve(&ctx, base, "../dep.js").unwrap(),
+                "https://example.com/dep.js"
+            );
+            assert_eq!(
+                resolver
+                    .resolve(&ctx, "main", "http://example.com/a.js")
+                    .unwrap(),
+                "http://example.com/a.js"
+            );
+            assert!(resolver.resolve(&ctx, base, "dep").is_err());
+            assert!(resolver.resolve(&ctx, "main.js", "./dep.js").is_err());
+            assert!(resolver.resolve(&ctx, base, "file:///etc/passwd").is_err());
+        })
+    }
+
+    #[test]
+    fn load() {
+        let server = serve();
+        let loader = HttpLoader::default()
+            .with_allowed_host(server.host.clone())
+            .with_max_size(100);
+
+        for _ in 0..2 {
+            let rt = Runtime::new().unwrap();
+            let ctx = Context::full(&rt).unwrap();
+            rt.set_loader(HttpResolver.or(BuiltinResolver::default()), loader.clone());
+            ctx.with(|ctx| {
+                let url = std::format!("http://{}/latest/main.js", server.host);
+                // The redirected module is found again instead of being evaluated twice.
+                for _ in 0..2 {
+                    let value: i32 = Module::import(&ctx, url.clone())
+                        .unwrap()
+                        .finish::<Object>()
+                        .catch(&ctx)
+                        .unwrap()
+                        .get("default")
+                        .unwrap();
+                    assert_eq!(value, 42);
+                }
+                assert_eq!(ctx.globals().get::<_, i32>("n").unwrap(), 1);
+
+                for (path, message) in [
+                    ("text.js", "content type `text/plain`"),
+                    ("large.js", "larger than 100 bytes"),
+                    ("missing.js", "status 404"),
+                    (
+                        "external.js",
+                        "host of `http://localhost/main.js` is not allowed",
+                    ),
+                ] {
+                    let url = std::format!("http://{}/{path}", server.host);
+                    let err = Module::import(&ctx, url)
+                        .unwrap()
+                        .finish::<Object>()
+                        .catch(&ctx)
+                        .unwrap_err();
+                    assert!(err.to_string().contains(message), "{err}");
+                }
+            });
+        }
+        // The dependency was cached with its etag and revalidated by the second runtime.
+        assert_eq!(server.revalidated.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "futures")]
+    #[tokio::test]
+    async fn load_async() {
+        use crate::{async_with, AsyncContext, AsyncRuntime};
+
+        let server = serve();
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        rt.set_async_loader(
+            HttpResolver,
+            HttpLoader::default().with_allowed_host(server.host.clone()),
+        )
+        .await
+        .unwrap();
+
+        let url = std::format!("http://{}/latest/main.js", server.host);
+        async_with!(ctx => |ctx| {
+            for _ in 0..2 {
+                let namespace: Object = Module::import_async(&ctx, url.clone())
+                    .await
+                    .unwrap()
+                    .into_future()
+                    .await
+                    .unwrap();
+                assert_eq!(namespace.get::<_, i32>("default").unwrap(), 42);
+            }
+            assert_eq!(ctx.globals().get::<_, i32>("n").unwrap(), 1);
+        })
+        .await;
+    }
+}
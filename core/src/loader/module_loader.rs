@@ -1,4 +1,7 @@
-use crate::{module::ModuleDef, Ctx, Error, Module, Result};
+use crate::{
+    module::{ModuleDef, NativeModule},
+    Ctx, Error, Module, Result,
+};
 use alloc::{string::String, vec::Vec};
 use core::fmt::Debug;
 #[cfg(not(feature = "std"))]
@@ -10,12 +13,18 @@ use super::Loader;
 
 type LoadFn = for<'js> fn(Ctx<'js>, Vec<u8>) -> Result<Module<'js>>;
 
+#[derive(Debug)]
+enum NativeSource {
+    Def(LoadFn),
+    Native(NativeModule),
+}
+
 /// The builtin native module loader
 ///
 /// This loader can be used as the nested backing loader in user-defined loaders.
 #[derive(Debug, Default)]
 pub struct ModuleLoader {
-    modules: HashMap<String, LoadFn>,
+    modules: HashMap<String, NativeSource>,
 }
 
 impl ModuleLoader {
@@ -25,7 +34,8 @@ impl ModuleLoader {
 
     /// Add module
     pub fn add_module<N: Into<String>, M: ModuleDef>(&mut self, name: N, _module: M) -> &mut Self {
-        self.modules.insert(name.into(), Self::load_func::<M>);
+        self.modules
+            .insert(name.into(), NativeSource::Def(Self::load_func::<M>));
         self
     }
 
@@ -35,15 +45,32 @@ impl ModuleLoader {
         self.add_module(name, module);
         self
     }
+
+    /// Add a module built with [`NativeModule::builder`] under its name
+    pub fn add_native(&mut self, module: NativeModule) -> &mut Self {
+        self.modules
+            .insert(module.name().into(), NativeSource::Native(module));
+        self
+    }
+
+    /// Add a module built with [`NativeModule::builder`] under its name
+    #[must_use]
+    pub fn with_native(mut self, module: NativeModule) -> Self {
+        self.add_native(module);
+        self
+    }
 }
 
 impl Loader for ModuleLoader {
     fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js>> {
-        let load = self
-            .modules
-            .remove(path)
-            .ok_or_else(|| Error::new_loading(path))?;
-
-        (load)(ctx.clone(), Vec::from(path))
+        // Native modules create their exports in every context, so they are kept to be loaded
+        // again by other contexts.
+        if let Some(NativeSource::Native(module)) = self.modules.get(path) {
+            return module.declare(ctx.clone());
+        }
+        match self.modules.remove(path) {
+            Some(NativeSource::Def(load)) => (load)(ctx.clone(), Vec::from(path)),
+            _ => Err(Error::new_loading(path)),
+        }
     }
 }
@@ -0,0 +1,308 @@
+use crate::{loader::Resolver, Ctx, Error, Object, Result, Value};
+use alloc::{
+    string::{String, ToString as _},
+    vec,
+    vec::Vec,
+};
+use std::path::{Path, PathBuf};
+
+/// The conditions of the `exports` field of `package.json` which are matched, in order.
+const CONDITIONS: [&str; 3] = ["import", "module", "default"];
+
+/// The module resolver following the resolution algorithm of Node.js
+///
+/// Relative specifiers like `./util` are resolved against the directory of the importing module
+/// and bare specifiers like `lodash-es` or `@scope/pkg/sub` are looked up in the `node_modules`
+/// directories of the importing module and all its ancestors. A path resolves to the file itself,
+/// the file with one of the extensions appended or, for directories, the entry point of its
+/// `package.json` or its `index` file.
+///
+/// The entry point of a package is taken from the `exports` field of its `package.json`, which
+/// can be a string or map subpaths to strings or to objects with `import`, `module` or `default`
+/// conditions, followed by the `module` and `main` fields. Resolved names are canonical absolute
+/// paths so a module imported with different specifiers is only loaded once.
+#[derive(Debug)]
+pub struct NodeResolver {
+    extensions: Vec<String>,
+}
+
+impl NodeResolver {
+    /// Add a file extension which is probed for specifiers without one
+    pub fn add_extension<X: Into<String>>(&mut self, extension: X) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Add a file extension which is probed for specifiers without one
+    #[must_use]
+    pub fn with_extension<X: Into<String>>(mut self, extension: X) -> Self {
+        self.add_extension(extension);
+        self
+    }
+
+    /// Set the file extensions which are probed for specifiers without one, in order
+    #[must_use]
+    pub fn with_extensions<I: IntoIterator<Item = X>, X: Into<String>>(
+        mut self,
+        extensions: I,
+    ) -> Self {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Resolve the path to a file, probing the extensions and directory entry points.
+    fn resolve_path<'js>(&self, ctx: &Ctx<'js>, path: &Path) -> Result<Option<PathBuf>> {
+        if path.is_file() {
+            return Ok(Some(path.into()));
+        }
+        if let Some(file) = self.probe_extensions(path) {
+            return Ok(Some(file));
+        }
+        if path.is_dir() {
+            return self.resolve_package(ctx, path, ".");
+        }
+        Ok(None)
+    }
+
+    fn probe_extensions(&self, path: &Path) -> Option<PathBuf> {
+        let name = path.file_name()?.to_str()?;
+        self.extensions.iter().find_map(|extension| {
+            let file = path.with_file_name(std::format!("{name}.{extension}"));
+            file.is_file().then_some(file)
+        })
+    }
+
+    /// Resolve a subpath of a package directory, like `.` for its entry point.
+    fn resolve_package<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        dir: &Path,
+        subpath: &str,
+    ) -> Result<Option<PathBuf>> {
+        let manifest = dir.join("package.json");
+        if manifest.is_file() {
+            let source = std::fs::read(&manifest)?;
+            let package = ctx.json_parse(source)?.into_object();
+            if let Some(package) = package {
+                if let Some(target) = exports_target(&package, subpath)? {
+                    return self.resolve_path(ctx, &dir.join(target));
+                }
+                if subpath == "." {
+                    for field in ["module", "main"] {
+                        if let Some(main) = package.get::<_, Option<String>>(field)? {
+                            if let Some(path) = self.resolve_path(ctx, &dir.join(main))? {
+                                return Ok(Some(path));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if subpath == "." {
+            Ok(self.probe_extensions(&dir.join("index")))
+        } else {
+            self.resolve_path(ctx, &dir.join(subpath))
+        }
+    }
+
+    /// Look up a bare specifier in the `node_modules` directories from `dir` upwards.
+    fn resolve_bare<'js>(&self, ctx: &Ctx<'js>, dir: &Path, name: &str) -> Result<Option<PathBuf>> {
+        let mut parts = name.splitn(if name.starts_with('@') { 3 } else { 2 }, '/');
+        let package: Vec<_> = parts
+            .by_ref()
+            .take(if name.starts_with('@') { 2 } else { 1 })
+            .collect();
+        let package = package.join("/");
+        let subpath = parts
+            .next()
+            .map(|x| std::format!("./{x}"))
+            .unwrap_or_else(|| ".".into());
+
+        for dir in dir.ancestors() {
+            let package_dir = dir.join("node_modules").join(&package);
+            if package_dir.is_dir() {
+                return self.resolve_package(ctx, &package_dir, &subpath);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the target of the subpath in the `exports` field of the package, if any.
+fn exports_target<'js>(package: &Object<'js>, subpath: &str) -> Result<Option<String>> {
+    let exports: Value = package.get("exports")?;
+    let Some(exports) = exports.into_object() else {
+        return Ok(if subpath == "." {
+            package.get("exports")?
+        } else {
+            None
+        });
+    };
+
+    // An object either maps subpaths, which start with a dot, or conditions of the entry point.
+    let is_subpath_map = exports
+        .keys::<String>()
+        .next()
+        .transpose()?
+        .is_some_and(|x| x.starts_with('.'));
+    let target: Value = if is_subpath_map {
+        exports.get(subpath)?
+    } else if subpath == "." {
+        exports.into_value()
+    } else {
+        return Ok(None);
+    };
+    condition_target(target)
+}
+
+/// Returns the target of a string or of the first matching condition.
+fn condition_target(target: Value<'_>) -> Result<Option<String>> {
+    if let Some(target) = target.as_string() {
+        return target.to_string().map(Some);
+    }
+    let Some(conditions) = target.into_object() else {
+        return Ok(None);
+    };
+    for condition in CONDITIONS {
+        if let Some(target) = condition_target(conditions.get(condition)?)? {
+            return Ok(Some(target));
+        }
+    }
+    Ok(None)
+}
+
+impl Default for NodeResolver {
+    fn default() -> Self {
+        Self {
+            extensions: vec!["js".into(), "mjs".into()],
+        }
+    }
+}
+
+impl Resolver for NodeResolver {
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let dir = match Path::new(base).parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let path = if name.starts_with("./") || name.starts_with("../") || name.starts_with('/') {
+            self.resolve_path(ctx, &dir.join(name))?
+        } else {
+            let dir = dir.canonicalize().unwrap_or_else(|_| dir.into());
+            self.resolve_bare(ctx, &dir, name)?
+        }
+        .ok_or_else(|| Error::new_resolving_message(base, name, "module not found"))?;
+
+        let path = path.canonicalize()?;
+        path.to_str()
+            .map(|x| x.to_string())
+            .ok_or_else(|| Error::new_resolving_message(base, name, "path is not valid UTF-8"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Context, Runtime};
+    use std::fs;
+
+    fn write(path: &Path, source: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+
+    #[test]
+    fn resolve() {
+        let dir = std::env::temp_dir().join(std::format!("rquickjs-node-{}", std::process::id()));
+        let root = dir.join("app");
+        write(&root.join("main.js"), "");
+        write(&root.join("util.mjs"), "");
+        write(&root.join("lib/index.js"), "");
+        write(&root.join("src/nested/entry.js"), "");
+
+        let modules = root.join("node_modules");
+        write(&modules.join("plain/index.js"), "");
+        write(
+            &modules.join("main-pkg/package.json"),
+            r#"{ "main": "dist/main" }"#,
+        );
+        write(&modules.join("main-pkg/dist/main.js"), "");
+        write(
+            &modules.join("@scope/pkg/package.json"),
+            r#"{
+                "main": "./main.js",
+                "exports": {
+                    ".": { "require": "./cjs.js", "import": "./esm.js" },
+                    "./sub": "./lib/sub.js"
+                }
+            }"#,
+        );
+        write(&modules.join("@scope/pkg/esm.js"), "");
+        write(&modules.join("@scope/pkg/lib/sub.js"), "");
+        write(
+            &modules.join("string-exports/package.json"),
+            r#"{ "exports": "./entry.mjs" }"#,
+        );
+        write(&modules.join("string-exports/entry.mjs"), "");
+        // A nested node_modules directory overrides the outer one.
+        write(&root.join("src/node_modules/plain/index.mjs"), "");
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let mut resolver = NodeResolver::default();
+            let main = root.join("main.js");
+            let main = main.to_str().unwrap();
+            let mut resolve = |base: &str, name: &str| {
+                let path = resolver.resolve(&ctx, base, name).unwrap();
+                PathBuf::from(path)
+                    .strip_prefix(root.canonicalize().unwrap())
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .replace('\\', "/")
+            };
+
+            assert_eq!(resolve(main, "./util"), "util.mjs");
+            assert_eq!(resolve(main, "./lib"), "lib/index.js");
+            assert_eq!(resolve(main, "./lib/../util.mjs"), "util.mjs");
+            assert_eq!(resolve(main, "plain"), "node_modules/plain/index.js");
+            assert_eq!(
+                resolve(main, "main-pkg"),
+                "node_modules/main-pkg/dist/main.js"
+            );
+            assert_eq!(
+                resolve(main, "@scope/pkg"),
+                "node_modules/@scope/pkg/esm.js"
+            );
+            assert_eq!(
+                resolve(main, "@scope/pkg/sub"),
+                "node_modules/@scope/pkg/lib/sub.js"
+            );
+            assert_eq!(
+                resolve(main, "string-exports"),
+                "node_modules/string-exports/entry.mjs"
+            );
+
+            let nested = root.join("src/nested/entry.js");
+            let nested = nested.to_str().unwrap();
+            assert_eq!(resolve(nested, "plain"), "src/node_modules/plain/index.mjs");
+            assert_eq!(
+                resolve(nested, "main-pkg"),
+                "node_modules/main-pkg/dist/main.js"
+            );
+            assert_eq!(resolve(nested, "../../util"), "util.mjs");
+
+            let err = resolver.resolve(&ctx, main, "missing").unwrap_err();
+            assert!(matches!(err, Error::Resolving { .. }));
+            assert!(resolver.resolve(&ctx, main, "./missing").is_err());
+
+            let mut resolver = NodeResolver::default().with_extensions(["js"]);
+            assert!(resolver.resolve(&ctx, main, "./util").is_err());
+        });
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
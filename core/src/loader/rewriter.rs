@@ -0,0 +1,105 @@
+use crate::{loader::Resolver, Ctx, Result};
+use alloc::string::String;
+
+/// A resolver which can rewrite module specifiers before they are resolved.
+///
+/// This is useful to implement import maps or aliases without reimplementing a whole resolver.
+/// The rewrite function is called with the base and the specifier of every static and dynamic
+/// import. If it returns a name it is used as the resolved module name, otherwise the specifier
+/// is resolved by the wrapped resolver.
+///
+/// ```
+/// # use rquickjs::{loader::{BuiltinLoader, BuiltinResolver, Rewriter}, Runtime};
+/// let rt = Runtime::new().unwrap();
+/// rt.set_loader(
+///     Rewriter::new(
+///         |_base: &str, name: &str| match name {
+///             "react" => Some("https://esm.sh/react".into()),
+///             _ => None,
+///         },
+///         BuiltinResolver::default(),
+///     ),
+///     BuiltinLoader::default(),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Rewriter<F, R> {
+    rewrite: F,
+    resolver: R,
+}
+
+impl<F, R> Rewriter<F, R> {
+    /// Create a new rewriter which falls through to the given resolver.
+    pub fn new(rewrite: F, resolver: R) -> Self {
+        Self { rewrite, resolver }
+    }
+}
+
+impl<F, R> Resolver for Rewriter<F, R>
+where
+    F: FnMut(&str, &str) -> Option<String>,
+    R: Resolver,
+{
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        match (self.rewrite)(base, name) {
+            Some(name) => Ok(name),
+            None => self.resolver.resolve(ctx, base, name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        loader::{BuiltinLoader, BuiltinResolver},
+        CatchResultExt, Context, Module, Runtime,
+    };
+
+    use super::Rewriter;
+
+    #[test]
+    fn rewrite_specifiers() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(
+            Rewriter::new(
+                |_: &str, name: &str| match name {
+                    "react" => Some("https://esm.sh/react".into()),
+                    "lazy" => Some("https://esm.sh/lazy".into()),
+                    _ => None,
+                },
+                BuiltinResolver::default().with_module("local"),
+            ),
+            BuiltinLoader::default()
+                .with_module("https://esm.sh/react", "export default 'react';")
+                .with_module("https://esm.sh/lazy", "export default 'lazy';")
+                .with_module("local", "export default 'local';"),
+        );
+        ctx.with(|ctx| {
+            let module = Module::declare(
+                ctx.clone(),
+                "main",
+                r#"
+                    import react from "react";
+                    import local from "local";
+                    export const names = [react, local];
+                    export const lazy = import("lazy").then((m) => m.default);
+                "#,
+            )
+            .catch(&ctx)
+            .unwrap();
+            let (module, promise) = module.eval().catch(&ctx).unwrap();
+            promise.finish::<()>().catch(&ctx).unwrap();
+
+            let names: Vec<String> = module.get("names").unwrap();
+            assert_eq!(names, ["react", "local"]);
+            let lazy: String = module
+                .get::<_, crate::Promise>("lazy")
+                .unwrap()
+                .finish()
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(lazy, "lazy");
+        })
+    }
+}
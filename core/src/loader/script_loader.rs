@@ -2,7 +2,7 @@ use alloc::{string::String, vec, vec::Vec};
 
 #[cfg(feature = "std")]
 use crate::{
-    loader::{util::check_extensions, Loader},
+    loader::{util::check_extensions, Loader, SourceLoader},
     Ctx, Error, Module, Result,
 };
 
@@ -12,6 +12,7 @@ use crate::{
 #[derive(Debug)]
 pub struct ScriptLoader {
     extensions: Vec<String>,
+    import_meta_url: bool,
 }
 
 impl ScriptLoader {
@@ -27,24 +28,62 @@ impl ScriptLoader {
         self.add_extension(extension);
         self
     }
+
+    /// Set `import.meta.url` of loaded modules to the `file://` URL of their absolute path
+    pub fn set_import_meta_url(&mut self, enabled: bool) -> &mut Self {
+        self.import_meta_url = enabled;
+        self
+    }
+
+    /// Set `import.meta.url` of loaded modules to the `file://` URL of their absolute path
+    #[must_use]
+    pub fn with_import_meta_url(mut self, enabled: bool) -> Self {
+        self.set_import_meta_url(enabled);
+        self
+    }
 }
 
 impl Default for ScriptLoader {
     fn default() -> Self {
         Self {
             extensions: vec!["js".into()],
+            import_meta_url: false,
         }
     }
 }
 
 #[cfg(feature = "std")]
-impl Loader for ScriptLoader {
-    fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js>> {
+impl SourceLoader for ScriptLoader {
+    fn load_source<'js>(&mut self, _ctx: &Ctx<'js>, path: &str) -> Result<Vec<u8>> {
         if !check_extensions(path, &self.extensions) {
             return Err(Error::new_loading(path));
         }
 
-        let source: Vec<_> = std::fs::read(path)?;
-        Module::declare(ctx.clone(), path, source)
+        Ok(std::fs::read(path)?)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Loader for ScriptLoader {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js>> {
+        let source = self.load_source(ctx, path)?;
+        let module = Module::declare(ctx.clone(), path, source)?;
+        if self.import_meta_url {
+            module.meta()?.set("url", file_url(path))?;
+        }
+        Ok(module)
+    }
+}
+
+/// Returns the `file://` URL of the given path, made absolute against the working directory.
+#[cfg(feature = "std")]
+fn file_url(path: &str) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.into());
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.starts_with('/') {
+        std::format!("file://{path}")
+    } else {
+        // Windows paths start with the drive letter.
+        std::format!("file:///{path}")
     }
 }
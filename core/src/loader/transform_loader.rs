@@ -0,0 +1,154 @@
+use crate::{
+    loader::{Loader, SourceLoader},
+    module::Declared,
+    Ctx, Error, Module, Result,
+};
+use alloc::{
+    string::{String, ToString as _},
+    vec::Vec,
+};
+
+/// A loader which transforms the source of modules before they are compiled.
+///
+/// The transform function is called with the name and the source returned by the inner
+/// [`SourceLoader`] and returns the source to compile, which allows transpiling languages like
+/// TypeScript or JSX in user code. An error returned by the transform is reported as an error
+/// loading the module.
+///
+/// The module is declared by this loader, so options of the inner loader which apply to the
+/// declared module, like [`ScriptLoader::with_import_meta_url`](super::ScriptLoader::with_import_meta_url),
+/// have no effect.
+///
+/// ```
+/// # use rquickjs::{loader::{FileResolver, ScriptLoader, TransformLoader}, Runtime};
+/// let rt = Runtime::new().unwrap();
+/// rt.set_loader(
+///     FileResolver::default().with_pattern("{}.ts"),
+///     TransformLoader::new(
+///         ScriptLoader::default().with_extension("ts"),
+///         |_name: &str, source: String| Ok(source.replace(": number", "")),
+///     ),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct TransformLoader<L, F> {
+    inner: L,
+    transform: F,
+}
+
+impl<L, F> TransformLoader<L, F> {
+    /// Create a loader transforming the sources loaded by the inner loader.
+    pub fn new(inner: L, transform: F) -> Self {
+        Self { inner, transform }
+    }
+
+    /// Returns the inner loader.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+}
+
+impl<L, F> SourceLoader for TransformLoader<L, F>
+where
+    L: SourceLoader,
+    F: FnMut(&str, String) -> Result<String>,
+{
+    fn load_source<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Vec<u8>> {
+        let source = self.inner.load_source(ctx, name)?;
+        let source = String::from_utf8(source)
+            .map_err(|_| Error::new_loading_message(name, "The source is not valid UTF-8"))?;
+        match (self.transform)(name, source) {
+            Ok(source) => Ok(source.into_bytes()),
+            Err(error @ Error::Loading { .. }) => Err(error),
+            Err(error) => Err(Error::new_loading_message(name, error.to_string())),
+        }
+    }
+}
+
+impl<L, F> Loader for TransformLoader<L, F>
+where
+    L: SourceLoader,
+    F: FnMut(&str, String) -> Result<String>,
+{
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
+        let source = self.load_source(ctx, name)?;
+        Module::declare(ctx.clone(), name, source)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::TransformLoader;
+    use crate::{
+        loader::{BuiltinResolver, Rewriter, ScriptLoader},
+        CatchResultExt, Context, Error, Module, Runtime,
+    };
+    use std::{fs, io, string::String};
+
+    #[test]
+    fn transform_sources() {
+        let dir =
+            std::env::temp_dir().join(std::format!("rquickjs-transform-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("math.ts"),
+            "export function add(a: number, b: number): number { return a + b; }",
+        )
+        .unwrap();
+        fs::write(dir.join("plain.js"), "export const plain: number = 1;").unwrap();
+        fs::write(dir.join("bad.ts"), "export const value: any = 1;").unwrap();
+
+        let modules = dir.clone();
+        let annotation = regex::Regex::new(r":\s*number\b").unwrap();
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(
+            Rewriter::new(
+                move |_: &str, name: &str| {
+                    let file = match name {
+                        "plain" => "plain.js".into(),
+                        name => std::format!("{name}.ts"),
+                    };
+                    Some(modules.join(file).to_string_lossy().into_owned())
+                },
+                BuiltinResolver::default(),
+            ),
+            TransformLoader::new(
+                ScriptLoader::default().with_extension("ts"),
+                move |name: &str, source: String| {
+                    if source.contains(": any") {
+                        return Err(Error::Io(io::Error::other("type 'any' is not allowed")));
+                    }
+                    assert!(name.ends_with(".ts") || name.ends_with(".js"));
+                    Ok(annotation.replace_all(&source, "").into_owned())
+                },
+            ),
+        );
+
+        ctx.with(|ctx| {
+            let module = Module::declare(
+                ctx.clone(),
+                "main",
+                r#"
+                    import { add } from "math";
+                    import { plain } from "plain";
+                    export const sum = add(plain, 2);
+                "#,
+            )
+            .catch(&ctx)
+            .unwrap();
+            let (module, promise) = module.eval().catch(&ctx).unwrap();
+            promise.finish::<()>().catch(&ctx).unwrap();
+            assert_eq!(module.get::<_, i32>("sum").unwrap(), 3);
+
+            let err = Module::declare(ctx.clone(), "main2", r#"import "bad";"#)
+                .catch(&ctx)
+                .unwrap_err();
+            let message = err.to_string();
+            assert!(message.contains("bad.ts"), "{message}");
+            assert!(message.contains("type 'any' is not allowed"), "{message}");
+        });
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
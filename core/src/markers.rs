@@ -20,6 +20,17 @@ impl<'inv> Invariant<'inv> {
     }
 }
 
+/// A marker which makes the types containing it `!Send` and `!Sync` unless the `"parallel"`
+/// feature is enabled.
+///
+/// Without the `"parallel"` feature the runtime is not synchronized so none of the types which
+/// access it may be moved to another thread.
+#[cfg(not(feature = "parallel"))]
+pub(crate) type ThreadMarker = PhantomData<*mut ()>;
+
+#[cfg(feature = "parallel")]
+pub(crate) type ThreadMarker = PhantomData<()>;
+
 /// The marker trait which requires [`Send`] when `"parallel"` feature is used
 #[cfg(not(feature = "parallel"))]
 pub trait ParallelSend {}
@@ -32,3 +43,16 @@ impl<T> ParallelSend for T {}
 
 #[cfg(feature = "parallel")]
 impl<T: Send> ParallelSend for T {}
+
+/// The marker trait which requires [`Sync`] when `"parallel"` feature is used
+#[cfg(not(feature = "parallel"))]
+pub trait ParallelSync {}
+
+#[cfg(feature = "parallel")]
+pub trait ParallelSync: Sync {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> ParallelSync for T {}
+
+#[cfg(feature = "parallel")]
+impl<T: Sync> ParallelSync for T {}
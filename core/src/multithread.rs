@@ -0,0 +1,246 @@
+//! Passing values between runtimes running on different threads.
+//!
+//! JavaScript values belong to the runtime they were created in and can't be sent to another
+//! thread. A [`channel`] instead carries Rust data: [`JsSender::send`] deserializes a value into
+//! `T` with [`serde`](crate::serde) and [`JsReceiver::recv_into_js`] serializes it into a new
+//! value of the receiving context. Both ends can also be turned into JavaScript functions, for
+//! example to implement `postMessage` for a worker running in its own runtime.
+//!
+//! ```
+//! # use rquickjs::{multithread, Context, Runtime};
+//! let (sender, receiver) = multithread::channel::<Vec<i32>>();
+//!
+//! std::thread::spawn(move || {
+//!     let rt = Runtime::new().unwrap();
+//!     let ctx = Context::full(&rt).unwrap();
+//!     ctx.with(|ctx| {
+//!         ctx.globals()
+//!             .set("postMessage", sender.into_function(ctx.clone()).unwrap())
+//!             .unwrap();
+//!         ctx.eval::<(), _>("postMessage([1, 2, 3])").unwrap();
+//!     });
+//! })
+//! .join()
+//! .unwrap();
+//!
+//! let rt = Runtime::new().unwrap();
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     let value = receiver.recv_into_js(ctx.clone()).unwrap();
+//!     assert_eq!(value.get::<Vec<i32>>().unwrap(), [1, 2, 3]);
+//! });
+//! ```
+
+use std::{
+    io,
+    sync::mpsc::{self, RecvError, SendError, TryRecvError},
+};
+
+use ::serde::{de::DeserializeOwned, Serialize};
+
+use crate::{markers::ParallelSend, Ctx, Error, Function, Result, Value};
+
+/// Create a channel sending data of type `T` between threads.
+pub fn channel<T>() -> (JsSender<T>, JsReceiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    (JsSender(sender), JsReceiver(receiver))
+}
+
+/// Create a channel and return JavaScript functions sending and receiving its messages.
+///
+/// The first function takes a value and sends it, the second returns the next message or
+/// `undefined` if there is none. Unlike the ends of a [`channel`] the functions are bound to the
+/// context and only useful within the same runtime, like a message queue between scripts.
+pub fn register_channel<'js, T>(ctx: Ctx<'js>) -> Result<(Function<'js>, Function<'js>)>
+where
+    T: Serialize + DeserializeOwned + ParallelSend + 'static,
+{
+    let (sender, receiver) = channel::<T>();
+    Ok((
+        sender.into_function(ctx.clone())?,
+        receiver.into_function(ctx)?,
+    ))
+}
+
+fn disconnected() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::BrokenPipe,
+        "the other end of the channel was dropped",
+    ))
+}
+
+/// The sending end of a [`channel`], which can be cloned to send from multiple threads.
+#[derive(Debug)]
+pub struct JsSender<T>(mpsc::Sender<T>);
+
+impl<T> Clone for JsSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> JsSender<T> {
+    /// Send data which doesn't need to be converted from a JavaScript value.
+    pub fn send_data(&self, data: T) -> Result<()> {
+        self.0.send(data).map_err(|SendError(_)| disconnected())
+    }
+
+    /// Returns the underlying channel sender.
+    pub fn into_inner(self) -> mpsc::Sender<T> {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned> JsSender<T> {
+    /// Convert the value into `T` and send it.
+    ///
+    /// Returns an [`Error::FromJs`] if the value doesn't match `T` and an [`Error::Io`] if the
+    /// receiver was dropped.
+    pub fn send<'js>(&self, value: Value<'js>) -> Result<()> {
+        self.send_data(crate::serde::from_value(value)?)
+    }
+}
+
+impl<T: DeserializeOwned + ParallelSend + 'static> JsSender<T> {
+    /// Create a JavaScript function which sends its argument.
+    pub fn into_function<'js>(self, ctx: Ctx<'js>) -> Result<Function<'js>> {
+        Function::new(ctx, move |value: Value<'js>| self.send(value))
+    }
+}
+
+/// The receiving end of a [`channel`].
+#[derive(Debug)]
+pub struct JsReceiver<T>(mpsc::Receiver<T>);
+
+impl<T> JsReceiver<T> {
+    /// Wait for the next data without converting it into a JavaScript value.
+    pub fn recv_data(&self) -> Result<T> {
+        self.0.recv().map_err(|RecvError| disconnected())
+    }
+
+    /// Returns the next data if there is one, without waiting.
+    pub fn try_recv_data(&self) -> Result<Option<T>> {
+        match self.0.try_recv() {
+            Ok(data) => Ok(Some(data)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(disconnected()),
+        }
+    }
+
+    /// Returns the underlying channel receiver.
+    pub fn into_inner(self) -> mpsc::Receiver<T> {
+        self.0
+    }
+}
+
+impl<T: Serialize> JsReceiver<T> {
+    /// Wait for the next message and convert it into a value of the context.
+    ///
+    /// Returns an [`Error::Io`] once all senders were dropped and no messages are left.
+    pub fn recv_into_js<'js>(&self, ctx: Ctx<'js>) -> Result<Value<'js>> {
+        let data = self.recv_data()?;
+        crate::serde::to_value(ctx, &data)
+    }
+
+    /// Returns the next message as a value of the context if there is one, without waiting.
+    pub fn try_recv_into_js<'js>(&self, ctx: Ctx<'js>) -> Result<Option<Value<'js>>> {
+        self.try_recv_data()?
+            .map(|data| crate::serde::to_value(ctx, &data))
+            .transpose()
+    }
+}
+
+impl<T: Serialize + ParallelSend + 'static> JsReceiver<T> {
+    /// Create a JavaScript function which returns the next message or `undefined` if there is
+    /// none, without waiting.
+    pub fn into_function<'js>(self, ctx: Ctx<'js>) -> Result<Function<'js>> {
+        Function::new(ctx, move |ctx: Ctx<'js>| self.try_recv_into_js(ctx))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CatchResultExt, Context, Runtime};
+    use ::serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Message {
+        id: u32,
+        text: std::string::String,
+    }
+
+    #[test]
+    fn across_threads() {
+        let (sender, receiver) = channel::<Message>();
+
+        let worker = std::thread::spawn(move || {
+            let rt = Runtime::new().unwrap();
+            let ctx = Context::full(&rt).unwrap();
+            ctx.with(|ctx| {
+                let post = sender.clone().into_function(ctx.clone()).unwrap();
+                ctx.globals().set("postMessage", post).unwrap();
+                ctx.eval::<(), _>(
+                    r#"
+                    postMessage({ id: 1, text: "hello" });
+                    postMessage({ id: 2, text: "world" });
+                    "#,
+                )
+                .unwrap();
+
+                let value = ctx.eval("({ id: 'wrong' })").unwrap();
+                assert!(matches!(sender.send(value), Err(Error::FromJs { .. })));
+            });
+        });
+        worker.join().unwrap();
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let value = receiver.recv_into_js(ctx.clone()).unwrap();
+            let message: Message = crate::serde::from_value(value).unwrap();
+            assert_eq!(
+                message,
+                Message {
+                    id: 1,
+                    text: "hello".into()
+                }
+            );
+
+            let value = receiver.try_recv_into_js(ctx.clone()).unwrap().unwrap();
+            let text: std::string::String = value.into_object().unwrap().get("text").unwrap();
+            assert_eq!(text, "world");
+
+            // All senders were dropped with the worker.
+            assert!(matches!(
+                receiver.try_recv_into_js(ctx.clone()),
+                Err(Error::Io(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn register() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let (send, recv) = register_channel::<Vec<i32>>(ctx.clone()).unwrap();
+            ctx.globals().set("send", send).unwrap();
+            ctx.globals().set("recv", recv).unwrap();
+            let res: Vec<i32> = ctx
+                .eval(
+                    r#"
+                    send([1, 2]);
+                    send([3]);
+                    [...recv(), ...recv(), recv() === undefined ? 0 : -1]
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, [1, 2, 3, 0]);
+
+            let err = ctx.eval::<(), _>("send('nope')").catch(&ctx).unwrap_err();
+            assert!(err.to_string().contains("deserialized type"), "{err}");
+        });
+    }
+}
@@ -0,0 +1,522 @@
+//! The `fetch` global backed by a host provided [`FetchHandler`].
+//!
+//! QuickJS has no networking of its own, [`register_fetch`] installs `fetch` together with
+//! minimal `Headers` and `Response` classes which hand the requests to a [`FetchHandler`]
+//! implemented by the embedding. This keeps the policy of which requests are allowed and how they
+//! are performed with the host. With the `"reqwest"` feature [`ReqwestFetchHandler`] performs the
+//! requests with [`reqwest`].
+//!
+//! The promise returned by `fetch` resolves once the handler returned the complete response, so
+//! the runtime must drive spawned futures, for example with an
+//! [`AsyncRuntime`](crate::AsyncRuntime).
+
+use alloc::{boxed::Box, format, string::String, sync::Arc, vec::Vec};
+use core::{future::Future, pin::Pin};
+
+use crate::{
+    convert::List,
+    function::{Async, Func},
+    ArrayBuffer, Ctx, Exception, FromJs, Function, IntoJs, Object, Result, TypedArray, Value,
+};
+
+const FETCH_SOURCE: &str = r#"(function(native) {
+    const { fetch: send, encode, decode } = native;
+
+    const toBytes = (body) => {
+        if (body === undefined || body === null) {
+            return null;
+        }
+        if (body instanceof ArrayBuffer) {
+            return new Uint8Array(body);
+        }
+        if (ArrayBuffer.isView(body)) {
+            return new Uint8Array(body.buffer, body.byteOffset, body.byteLength);
+        }
+        return encode(String(body));
+    };
+
+    class Headers {
+        #list = [];
+
+        constructor(init) {
+            if (init === undefined || init === null) {
+                return;
+            }
+            const pairs = typeof init[Symbol.iterator] === "function" ? init : Object.entries(init);
+            for (const [name, value] of pairs) {
+                this.append(name, value);
+            }
+        }
+
+        append(name, value) {
+            this.#list.push([String(name).toLowerCase(), String(value)]);
+        }
+
+        set(name, value) {
+            this.delete(name);
+            this.append(name, value);
+        }
+
+        delete(name) {
+            name = String(name).toLowerCase();
+            this.#list = this.#list.filter(([key]) => key !== name);
+        }
+
+        get(name) {
+            name = String(name).toLowerCase();
+            const values = this.#list.filter(([key]) => key === name).map(([, value]) => value);
+            return values.length ? values.join(", ") : null;
+        }
+
+        has(name) {
+            name = String(name).toLowerCase();
+            return this.#list.some(([key]) => key === name);
+        }
+
+        forEach(callback, thisArg) {
+            for (const [name, value] of this) {
+                callback.call(thisArg, value, name, this);
+            }
+        }
+
+        *entries() {
+            for (const [name, value] of this.#list) {
+                yield [name, value];
+            }
+        }
+
+        *keys() {
+            for (const [name] of this.#list) {
+                yield name;
+            }
+        }
+
+        *values() {
+            for (const [, value] of this.#list) {
+                yield value;
+            }
+        }
+
+        [Symbol.iterator]() {
+            return this.entries();
+        }
+    }
+
+    class Response {
+        #bytes;
+        #used = false;
+
+        constructor(body, init = {}) {
+            this.#bytes = toBytes(body);
+            this.status = init.status ?? 200;
+            this.statusText = init.statusText ?? "";
+            this.headers = new Headers(init.headers);
+            this.url = "";
+        }
+
+        get ok() {
+            return this.status >= 200 && this.status < 300;
+        }
+
+        get bodyUsed() {
+            return this.#used;
+        }
+
+        #consume() {
+            if (this.#used) {
+                return Promise.reject(new TypeError("Body has already been consumed"));
+            }
+            this.#used = true;
+            return Promise.resolve(this.#bytes ?? new Uint8Array(0));
+        }
+
+        arrayBuffer() {
+            return this.#consume().then((bytes) => bytes.slice().buffer);
+        }
+
+        text() {
+            return this.#consume().then(decode);
+        }
+
+        json() {
+            return this.text().then(JSON.parse);
+        }
+    }
+
+    async function fetch(input, init = {}) {
+        const url = input !== null && typeof input === "object" && "url" in input
+            ? String(input.url)
+            : String(input);
+        const method = String(init.method ?? "GET").toUpperCase();
+        const headers = new Headers(init.headers);
+        const body = toBytes(init.body);
+        if (body !== null && (method === "GET" || method === "HEAD")) {
+            throw new TypeError("Request with GET/HEAD method cannot have body");
+        }
+        if (typeof init.body === "string" && !headers.has("content-type")) {
+            headers.set("content-type", "text/plain;charset=UTF-8");
+        }
+        const res = await send({ url, method, headers: [...headers], body });
+        const response = new Response(res.body, { status: res.status, headers: res.headers });
+        response.url = url;
+        return response;
+    }
+
+    return { fetch, Headers, Response };
+})"#;
+
+/// A request made by the `fetch` global.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchRequest {
+    /// The requested URL, as given to `fetch`.
+    pub url: String,
+    /// The upper-case request method, `GET` if none was given.
+    pub method: String,
+    /// The request headers with lower-case names.
+    pub headers: Vec<(String, String)>,
+    /// The request body, strings are encoded as UTF-8.
+    pub body: Option<Vec<u8>>,
+}
+
+/// The response to a [`FetchRequest`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The response headers.
+    pub headers: Vec<(String, String)>,
+    /// The complete response body.
+    pub body: Vec<u8>,
+}
+
+/// The future returned by [`FetchHandler::fetch`].
+#[cfg(not(feature = "parallel"))]
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<FetchResponse>> + 'a>>;
+
+/// The future returned by [`FetchHandler::fetch`].
+#[cfg(feature = "parallel")]
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<FetchResponse>> + Send + 'a>>;
+
+/// The host performing the requests of the `fetch` global.
+///
+/// Set with [`register_fetch`].
+pub trait FetchHandler {
+    /// Perform the request and return the complete response.
+    ///
+    /// A returned error rejects the promise returned by `fetch`, errors other than JavaScript
+    /// exceptions are converted to a `TypeError` like the network errors of browsers.
+    fn fetch(&self, request: FetchRequest) -> FetchFuture<'_>;
+}
+
+impl<'js> FromJs<'js> for FetchRequest {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let object = Object::from_js(ctx, value)?;
+        let body: Option<TypedArray<u8>> = object.get("body")?;
+        let body = body
+            .map(|body| {
+                body.as_bytes()
+                    .map(Vec::from)
+                    .ok_or_else(|| Exception::throw_type(ctx, "Request body is detached"))
+            })
+            .transpose()?;
+        Ok(FetchRequest {
+            url: object.get("url")?,
+            method: object.get("method")?,
+            headers: object
+                .get::<_, Vec<List<(String, String)>>>("headers")?
+                .into_iter()
+                .map(|List(header)| header)
+                .collect(),
+            body,
+        })
+    }
+}
+
+impl<'js> IntoJs<'js> for FetchResponse {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let object = Object::new(ctx.clone())?;
+        object.set("status", self.status)?;
+        let headers: Vec<_> = self.headers.into_iter().map(List).collect();
+        object.set("headers", headers)?;
+        object.set("body", ArrayBuffer::new(ctx.clone(), self.body)?)?;
+        Ok(object.into_value())
+    }
+}
+
+fn encode<'js>(ctx: Ctx<'js>, string: String) -> Result<TypedArray<'js, u8>> {
+    TypedArray::new(ctx, string.into_bytes())
+}
+
+fn decode(ctx: Ctx<'_>, bytes: TypedArray<'_, u8>) -> Result<String> {
+    let bytes = bytes
+        .as_bytes()
+        .ok_or_else(|| Exception::throw_type(&ctx, "Response body is detached"))?;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Install the global `fetch` function together with the `Headers` and `Response` classes, the
+/// requests are performed by the handler.
+///
+/// # Example
+/// ```
+/// # use rquickjs::{AsyncRuntime, AsyncContext, async_with, Promise, Result};
+/// use rquickjs::net::{register_fetch, FetchFuture, FetchHandler, FetchRequest, FetchResponse};
+///
+/// struct Hello;
+///
+/// impl FetchHandler for Hello {
+///     fn fetch(&self, request: FetchRequest) -> FetchFuture<'_> {
+///         Box::pin(async move {
+///             Ok(FetchResponse {
+///                 status: 200,
+///                 headers: vec![("content-type".into(), "text/plain".into())],
+///                 body: format!("Hello from {}", request.url).into_bytes(),
+///             })
+///         })
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let rt = AsyncRuntime::new().unwrap();
+/// let ctx = AsyncContext::full(&rt).await.unwrap();
+/// async_with!(ctx => |ctx| {
+///     register_fetch(ctx.clone(), Hello).unwrap();
+///     let text: Promise = ctx
+///         .eval("fetch('https://example.com').then((res) => res.text())")
+///         .unwrap();
+///     let text: String = text.into_future().await.unwrap();
+///     assert_eq!(text, "Hello from https://example.com");
+/// })
+/// .await;
+/// # }
+/// ```
+pub fn register_fetch<'js>(
+    ctx: Ctx<'js>,
+    handler: impl FetchHandler + Send + Sync + 'static,
+) -> Result<()> {
+    let handler = Arc::new(handler);
+    let fetch = Function::new(
+        ctx.clone(),
+        Async(move |ctx: Ctx<'js>, request: FetchRequest| {
+            let handler = handler.clone();
+            async move {
+                handler.fetch(request).await.map_err(|error| {
+                    if error.is_exception() {
+                        error
+                    } else {
+                        Exception::throw_type(&ctx, &format!("fetch failed: {error}"))
+                    }
+                })
+            }
+        }),
+    )?;
+
+    let native = Object::new(ctx.clone())?;
+    native.set("fetch", fetch)?;
+    native.set("encode", Func::from(encode))?;
+    native.set("decode", Func::from(decode))?;
+
+    let init: Function = ctx.eval(FETCH_SOURCE)?;
+    let exports: Object = init.call((native,))?;
+    let globals = ctx.globals();
+    for name in ["fetch", "Headers", "Response"] {
+        globals.set(name, exports.get::<_, Value>(name)?)?;
+    }
+    Ok(())
+}
+
+/// A [`FetchHandler`] performing the requests with a [`reqwest::Client`].
+///
+/// The requests must be run within a tokio runtime. Support for `https` URLs requires enabling
+/// one of the TLS features of `reqwest`.
+#[cfg(feature = "reqwest")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "reqwest")))]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestFetchHandler {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestFetchHandler {
+    /// Create a handler with a default client.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a handler performing the requests with the given client.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl FetchHandler for ReqwestFetchHandler {
+    fn fetch(&self, request: FetchRequest) -> FetchFuture<'_> {
+        fn io_error(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> crate::Error {
+            crate::Error::Io(std::io::Error::other(error))
+        }
+
+        Box::pin(async move {
+            let method =
+                reqwest::Method::from_bytes(request.method.as_bytes()).map_err(io_error)?;
+            let mut builder = self.client.request(method, request.url.as_str());
+            for (name, value) in request.headers {
+                builder = builder.header(name, value);
+            }
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+            let response = builder.send().await.map_err(io_error)?;
+            let status = response.status().as_u16();
+            let headers = response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        String::from(name.as_str()),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect();
+            let body = response.bytes().await.map_err(io_error)?.to_vec();
+            Ok(FetchResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{async_with, AsyncContext, AsyncRuntime, CatchResultExt, Error, Promise};
+
+    /// Echoes the request back in the body.
+    struct Echo;
+
+    impl FetchHandler for Echo {
+        fn fetch(&self, request: FetchRequest) -> FetchFuture<'_> {
+            Box::pin(async move {
+                if request.url == "http://offline/" {
+                    return Err(Error::Io(std::io::Error::other("connection refused")));
+                }
+                let mut body = format!("{} {}", request.method, request.url).into_bytes();
+                if let Some(request_body) = request.body {
+                    body.push(b' ');
+                    body.extend(request_body);
+                }
+                Ok(FetchResponse {
+                    status: if request.method == "DELETE" { 404 } else { 200 },
+                    headers: request.headers,
+                    body,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            register_fetch(ctx.clone(), Echo).unwrap();
+            let promise: Promise = ctx
+                .eval(
+                    r#"
+                    (async () => {
+                        const res = await fetch("http://test/a", {
+                            method: "post",
+                            headers: { "X-Token": "secret" },
+                            body: "héllo",
+                        });
+                        const text = await res.text();
+                        const consumed = await res.text().catch((e) => e instanceof TypeError);
+                        const buffer = await (await fetch({ url: "http://test/b" })).arrayBuffer();
+                        const missing = await fetch("http://test/c", { method: "DELETE" });
+                        const json = await new Response('{"a":1}').json();
+                        return [
+                            text, res.status, res.ok, res.url, res.headers.get("x-token"),
+                            res.headers.get("Content-Type"), consumed, res.bodyUsed,
+                            buffer.byteLength, missing.status, missing.ok, json.a,
+                        ].join("|");
+                    })()
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            let res: String = promise.into_future().await.unwrap();
+            assert_eq!(
+                res,
+                "POST http://test/a héllo|200|true|http://test/a|secret|\
+                 text/plain;charset=UTF-8|true|true|17|404|false|1"
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            register_fetch(ctx.clone(), Echo).unwrap();
+            for (source, expected) in [
+                ("fetch('http://offline/')", "TypeError: fetch failed: IO Error: connection refused"),
+                ("fetch('http://test/', { body: 'a' })", "TypeError: Request with GET/HEAD method cannot have body"),
+            ] {
+                let promise: Promise = ctx
+                    .eval(format!("{source}.catch((e) => `${{e.name}}: ${{e.message}}`)"))
+                    .unwrap();
+                let message: String = promise.into_future().await.unwrap();
+                assert_eq!(message, expected, "{source}");
+            }
+        })
+        .await;
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn reqwest_handler() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let reply = format!("{} {} {}", request.method(), request.url(), body);
+            let header = tiny_http::Header::from_bytes("x-reply", "yes").unwrap();
+            request
+                .respond(tiny_http::Response::from_string(reply).with_header(header))
+                .unwrap();
+        });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let rt = AsyncRuntime::new().unwrap();
+            let ctx = AsyncContext::full(&rt).await.unwrap();
+            let url = format!("http://{addr}/echo");
+            async_with!(ctx => |ctx| {
+                register_fetch(ctx.clone(), ReqwestFetchHandler::new()).unwrap();
+                let fetch: Function = ctx
+                    .eval(
+                        r#"(async (url) => {
+                            const res = await fetch(url, { method: "PUT", body: "data" });
+                            return `${res.status} ${res.headers.get("x-reply")} ${await res.text()}`;
+                        })"#,
+                    )
+                    .unwrap();
+                let promise: Promise = fetch.call((url.clone(),)).unwrap();
+                let res: String = promise.into_future().await.unwrap();
+                assert_eq!(res, "200 yes PUT /echo data");
+            })
+            .await;
+        });
+        server.join().unwrap();
+    }
+}
@@ -1,7 +1,19 @@
-use crate::{qjs, Ctx, Error, FromJs, IntoJs, JsLifetime, Result, Value};
+use crate::{
+    markers::ThreadMarker, qjs, Ctx, Error, FromJs, IntoJs, JsLifetime, Result, StdResult, Value,
+};
+
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+pub(crate) use shared::DropQueue;
+#[cfg(feature = "std")]
+pub use shared::SharedPersistent;
 
+use alloc::sync::Weak;
 use core::{
-    fmt,
+    any, fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
     mem::{self, ManuallyDrop},
 };
 
@@ -28,34 +40,52 @@ use core::{
 /// ```
 ///
 /// It is an error (`Error::UnrelatedRuntime`) to restore the `Persistent` in a
-/// context who isn't part of the original `Runtime`.
+/// context who isn't part of the original `Runtime`, this includes restoring it after the
+/// original runtime was dropped. Use [`Persistent::is_alive`] to check if the original runtime
+/// still exists.
 ///
 /// NOTE: Be careful and ensure that no persistent links outlives the runtime,
 /// otherwise Runtime will abort the process when dropped.
 ///
-#[derive(Eq, PartialEq, Hash)]
 pub struct Persistent<T> {
     pub(crate) rt: *mut qjs::JSRuntime,
+    pub(crate) runtime: Weak<()>,
     pub(crate) value: T,
+    _thread: ThreadMarker,
 }
 
 impl<T: Clone> Clone for Persistent<T> {
     fn clone(&self) -> Self {
         Persistent {
             rt: self.rt,
+            runtime: self.runtime.clone(),
             value: self.value.clone(),
+            _thread: PhantomData,
         }
     }
 }
 
-impl<T> fmt::Debug for Persistent<T>
-where
-    T: fmt::Debug,
-{
+impl<T: PartialEq> PartialEq for Persistent<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.runtime.ptr_eq(&other.runtime) && self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Persistent<T> {}
+
+impl<T: Hash> Hash for Persistent<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rt.hash(state);
+        self.value.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Persistent<T> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Persistent")
+            .field("type", &any::type_name::<T>())
             .field("rt", &self.rt)
-            .field("value", &self.value)
+            .field("alive", &self.is_alive())
             .finish()
     }
 }
@@ -92,20 +122,40 @@ impl<T> Persistent<T> {
         let outlived: T::Changed<'static> =
             unsafe { Self::outlive_transmute::<'js, 'static, T>(val) };
         let ptr = unsafe { qjs::JS_GetRuntime(ctx.as_ptr()) };
+        let runtime = unsafe { ctx.get_opaque().alive_token() };
         Persistent {
             rt: ptr,
+            runtime,
             value: outlived,
+            _thread: PhantomData,
         }
     }
 
+    /// Returns whether the runtime the value was saved in still exists.
+    pub fn is_alive(&self) -> bool {
+        self.runtime.strong_count() != 0
+    }
+
     /// Restore the value of an arbitrary type
     pub fn restore<'js>(self, ctx: &Ctx<'js>) -> Result<T::Changed<'js>>
     where
         T: JsLifetime<'static>,
     {
-        let ctx_runtime_ptr = unsafe { qjs::JS_GetRuntime(ctx.as_ptr()) };
-        if self.rt != ctx_runtime_ptr {
-            return Err(Error::UnrelatedRuntime);
+        self.try_restore(ctx).map_err(|_| Error::UnrelatedRuntime)
+    }
+
+    /// Restore the value of an arbitrary type, returning the persistent value back if the
+    /// context is not part of the original runtime.
+    ///
+    /// Unlike [`Persistent::restore`] the value is not dropped on failure, which allows
+    /// forgetting values whose runtime was already dropped instead of freeing them.
+    pub fn try_restore<'js>(self, ctx: &Ctx<'js>) -> StdResult<T::Changed<'js>, Self>
+    where
+        T: JsLifetime<'static>,
+    {
+        let related = unsafe { ctx.get_opaque().is_alive_token(&self.runtime) };
+        if !related {
+            return Err(self);
         }
         Ok(unsafe { Self::outlive_transmute::<'static, 'js, T>(self.value) })
     }
@@ -154,6 +204,44 @@ mod test {
         });
     }
 
+    #[test]
+    fn restore_errors() {
+        let rt1 = Runtime::new().unwrap();
+        let ctx1 = Context::full(&rt1).unwrap();
+        let (persistent_v, persistent_i) = ctx1.with(|ctx| {
+            let v: Value = ctx.eval("({ a: 1 })").unwrap();
+            (Persistent::save(&ctx, v), Persistent::save(&ctx, 1i32))
+        });
+        assert!(persistent_v.is_alive());
+        assert!(persistent_i.is_alive());
+
+        let rt2 = Runtime::new().unwrap();
+        let ctx2 = Context::full(&rt2).unwrap();
+        let persistent_v = ctx2.with(|ctx| {
+            let persistent_v = persistent_v.try_restore(&ctx).unwrap_err();
+            assert!(persistent_v.is_alive());
+            persistent_v
+        });
+        ctx1.with(|ctx| {
+            let v = persistent_v.restore(&ctx).unwrap();
+            assert_eq!(v.as_object().unwrap().get::<_, i32>("a").unwrap(), 1);
+        });
+
+        std::mem::drop(ctx1);
+        std::mem::drop(rt1);
+        assert!(!persistent_i.is_alive());
+        assert!(format!("{persistent_i:?}").contains("alive: false"));
+
+        // A new runtime may reuse the address of the dropped one.
+        let rt3 = Runtime::new().unwrap();
+        let ctx3 = Context::full(&rt3).unwrap();
+        ctx3.with(|ctx| {
+            assert!(persistent_i.clone().try_restore(&ctx).is_err());
+            let err = persistent_i.restore(&ctx).unwrap_err();
+            assert!(matches!(err, Error::UnrelatedRuntime));
+        });
+    }
+
     #[test]
     fn different_context() {
         let rt1 = Runtime::new().unwrap();
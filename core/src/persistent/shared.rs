@@ -0,0 +1,256 @@
+use alloc::{
+    boxed::Box,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::{any::Any, fmt, mem::ManuallyDrop, ops::Deref};
+use std::sync::{Mutex, PoisonError};
+
+use super::Persistent;
+use crate::{Ctx, Error, JsLifetime, Result};
+
+struct Deferred(#[allow(dead_code)] Box<dyn Any>);
+
+// Deferred values are only dropped by `DropQueue::drain` while the runtime lock is held.
+unsafe impl Send for Deferred {}
+
+/// Queue of values which were dropped without holding the runtime lock.
+///
+/// The queue is `None` once the runtime started freeing itself, values dropped after that point
+/// are leaked instead.
+pub(crate) struct DropQueue(Mutex<Option<Vec<Deferred>>>);
+
+impl DropQueue {
+    pub fn new() -> Self {
+        DropQueue(Mutex::new(Some(Vec::new())))
+    }
+
+    fn push<T: 'static>(&self, value: T) {
+        let mut queue = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        match queue.as_mut() {
+            Some(queue) => queue.push(Deferred(Box::new(value))),
+            None => core::mem::forget(value),
+        }
+    }
+
+    /// Drop all queued values, must only be called while holding the runtime lock.
+    pub fn drain(&self) {
+        let values = self
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+            .map(core::mem::take);
+        // Drop outside of the queue lock as freeing values can run finalizers.
+        drop(values);
+    }
+
+    /// Drop all queued values and stop accepting new ones.
+    pub fn close(&self) {
+        let values = self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        drop(values);
+    }
+}
+
+struct Shared<T: 'static> {
+    persistent: ManuallyDrop<Persistent<T>>,
+    queue: Weak<DropQueue>,
+}
+
+impl<T: 'static> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let persistent = unsafe { ManuallyDrop::take(&mut self.persistent) };
+        match self.queue.upgrade() {
+            Some(queue) => queue.push(persistent),
+            // The runtime is gone so the value can no longer be freed.
+            None => core::mem::forget(persistent),
+        }
+    }
+}
+
+/// A reference counted handle to a [`Persistent`] value.
+///
+/// Cloning the handle is cheap and doesn't require access to a context. When the last handle is
+/// dropped the JavaScript value isn't freed immediately, instead it is queued on its runtime and
+/// freed the next time the runtime is locked, for example by [`Context::with`](crate::Context::with)
+/// or [`Runtime::execute_pending_job`](crate::Runtime::execute_pending_job). This makes it
+/// possible to drop the handle without holding the runtime lock.
+///
+/// Values still queued when the runtime is dropped are freed with the runtime. Values whose
+/// last handle is dropped after the runtime was dropped are leaked.
+pub struct SharedPersistent<T: 'static>(Arc<Shared<T>>);
+
+impl<T: 'static> Clone for SharedPersistent<T> {
+    fn clone(&self) -> Self {
+        SharedPersistent(self.0.clone())
+    }
+}
+
+#[cfg(feature = "parallel")]
+unsafe impl<T: 'static> Send for SharedPersistent<T> {}
+
+// The value can only be accessed through a `Ctx` which requires holding the runtime lock.
+#[cfg(feature = "parallel")]
+unsafe impl<T: 'static> Sync for SharedPersistent<T> {}
+
+impl<T: 'static> fmt::Debug for SharedPersistent<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("SharedPersistent")
+            .field(self.0.persistent.deref())
+            .finish()
+    }
+}
+
+impl<T: 'static> SharedPersistent<T> {
+    /// Save the value of an arbitrary type into a shared handle.
+    pub fn save<'js, U>(ctx: &Ctx<'js>, val: U) -> Self
+    where
+        U: JsLifetime<'js, Changed<'static> = T>,
+    {
+        Self::new(ctx, Persistent::save(ctx, val))
+    }
+
+    /// Wrap a persistent value into a shared handle.
+    pub fn new(ctx: &Ctx<'_>, persistent: Persistent<T>) -> Self {
+        let queue = unsafe { ctx.get_opaque().drop_queue() };
+        SharedPersistent(Arc::new(Shared {
+            persistent: ManuallyDrop::new(persistent),
+            queue,
+        }))
+    }
+
+    /// Returns whether the runtime the value was saved in still exists.
+    pub fn is_alive(&self) -> bool {
+        self.0.persistent.is_alive()
+    }
+
+    /// Returns the number of handles to the value.
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.0)
+    }
+
+    /// Restore a clone of the value.
+    ///
+    /// Returns `Error::UnrelatedRuntime` if the context isn't part of the original runtime.
+    pub fn restore<'js>(&self, ctx: &Ctx<'js>) -> Result<T::Changed<'js>>
+    where
+        T: JsLifetime<'static> + Clone,
+    {
+        let persistent = &*self.0.persistent;
+        if !unsafe { ctx.get_opaque().is_alive_token(&persistent.runtime) } {
+            return Err(Error::UnrelatedRuntime);
+        }
+        (*persistent).clone().restore(ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn save_function(
+        ctx: &Context,
+        dropped: &Arc<AtomicBool>,
+    ) -> SharedPersistent<Function<'static>> {
+        let flag = DropFlag(dropped.clone());
+        ctx.with(|ctx| {
+            let func = Function::new(ctx.clone(), move || {
+                let _ = &flag;
+                1
+            })
+            .unwrap();
+            SharedPersistent::save(&ctx, func)
+        })
+    }
+
+    #[test]
+    fn shared_restore() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let shared = save_function(&ctx, &dropped);
+        let clone = shared.clone();
+        assert_eq!(shared.handle_count(), 2);
+        ctx.with(|ctx| {
+            let res: i32 = clone.restore(&ctx).unwrap().call(()).unwrap();
+            assert_eq!(res, 1);
+        });
+
+        let rt2 = Runtime::new().unwrap();
+        let ctx2 = Context::full(&rt2).unwrap();
+        ctx2.with(|ctx| {
+            let err = shared.restore(&ctx).unwrap_err();
+            assert!(matches!(err, Error::UnrelatedRuntime));
+        });
+    }
+
+    #[test]
+    fn shared_deferred_drop() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let shared = save_function(&ctx, &dropped);
+        let clone = shared.clone();
+
+        drop(shared);
+        drop(clone);
+        // The value is only freed the next time the runtime is locked.
+        assert!(!dropped.load(Ordering::SeqCst));
+        ctx.with(|_| {});
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn shared_drop_before_runtime() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let shared = save_function(&ctx, &dropped);
+
+        drop(shared);
+        assert!(!dropped.load(Ordering::SeqCst));
+        // Values still in the queue are freed together with the runtime.
+        drop(ctx);
+        drop(rt);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn shared_drop_after_runtime() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let shared = ctx.with(|ctx| SharedPersistent::save(&ctx, 1i32));
+        assert!(shared.is_alive());
+
+        drop(ctx);
+        drop(rt);
+        assert!(!shared.is_alive());
+        drop(shared);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn shared_drop_off_thread() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let shared = save_function(&ctx, &dropped);
+
+        std::thread::spawn(move || drop(shared)).join().unwrap();
+        assert!(!dropped.load(Ordering::SeqCst));
+        ctx.with(|_| {});
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
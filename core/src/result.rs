@@ -22,6 +22,9 @@ use crate::{
     Ctx, Exception, Object, StdResult, StdString, Type, Value,
 };
 
+mod report;
+pub use report::ErrorReport;
+
 /// Result type used throughout the library.
 pub type Result<T> = StdResult<T, Error>;
 
@@ -64,10 +67,29 @@ pub enum Error {
     /// Could not allocate memory
     /// This is generally only triggered when out of memory.
     Allocation,
-    /// A module defined two exported values with the same name.
-    DuplicateExports,
-    /// Tried to export a entry which was not previously declared.
-    InvalidExport,
+    /// QuickJS ran out of memory while executing JavaScript.
+    ///
+    /// Unlike [`Error::Exception`] there is no pending exception to catch. Thrown back into
+    /// JavaScript this raises a new out of memory error.
+    ///
+    /// If QuickJS can't even allocate the error object it throws `null` instead, which is
+    /// returned as an [`Error::Exception`].
+    OutOfMemory,
+    /// A module declared two exports with the same name.
+    #[non_exhaustive]
+    DuplicateExports {
+        name: StdString,
+    },
+    /// Tried to set the value of an export which was not previously declared.
+    #[non_exhaustive]
+    InvalidExport {
+        name: StdString,
+    },
+    /// A native module declared an export but didn't set its value while it was evaluated.
+    #[non_exhaustive]
+    MissingExport {
+        name: StdString,
+    },
     /// Found a string with a internal null byte while converting
     /// to C string.
     InvalidString(NulError),
@@ -84,10 +106,17 @@ pub enum Error {
     /// An error happened while trying to borrow a Rust function.
     FunctionBorrow(BorrowError),
     /// An exception raised by QuickJS itself.
-    /// The actual JavaScript value can be retrieved by calling [`Ctx::catch`].
+    /// The actual JavaScript value can be retrieved by calling [`Ctx::catch`] or by turning the
+    /// result into a [`CaughtResult`] with [`CatchResultExt::catch`].
     ///
     /// When returned from a callback the JavaScript will continue to unwind with the current
     /// error.
+    ///
+    /// The thrown value isn't carried by the variant: `Error` is `'static` and `Send` so it can't
+    /// hold a value bound to the context, and the exception has to stay pending in the context
+    /// for it to keep propagating when the error is returned to JavaScript. Use
+    /// [`CatchResultExt::catch`] to take it out as a [`CaughtError`], which gives access to the
+    /// value, its message and stack, and a [`CaughtError::report`] which can outlive the context.
     Exception,
     /// Error converting from JavaScript to a Rust type.
     FromJs {
@@ -123,12 +152,22 @@ pub enum Error {
         name: StdString,
         message: Option<StdString>,
     },
+    #[cfg(feature = "loader")]
+    /// Bytecode loaded with [`Module::load_bytecode`] was compiled by another version of QuickJS
+    /// or rquickjs, or doesn't start with the header written by
+    /// [`compile_module`](crate::compile::compile_module), in which case `found` is `None`.
+    BytecodeVersion {
+        expected: StdString,
+        found: Option<StdString>,
+    },
     AsSlice(AsSliceError),
     /// Error when restoring a Persistent in a runtime other than the original runtime.
     UnrelatedRuntime,
-    /// An error returned by a blocked on promise if block on the promise would result in a dead
-    /// lock.
+    /// An error returned by a blocked on promise or by trying to lock the runtime if blocking
+    /// would result in a dead lock.
     WouldBlock,
+    /// The evaluation was interrupted because the execution timeout of the runtime elapsed.
+    Timeout,
     /// An error related to userdata
     UserData(UserDataError<()>),
     /// An error from QuickJS from which the specifics are unknown.
@@ -204,6 +243,11 @@ impl Error {
         matches!(self, Error::Exception)
     }
 
+    /// Returns whether the error is caused by running out of memory.
+    pub fn is_out_of_memory(&self) -> bool {
+        matches!(self, Error::OutOfMemory | Error::Allocation)
+    }
+
     /// Create from JS conversion error
     pub fn new_from_js(from: &'static str, to: &'static str) -> Self {
         Error::FromJs {
@@ -225,6 +269,29 @@ impl Error {
         }
     }
 
+    /// Create from JS conversion error for a string which doesn't name any of the expected
+    /// enum variants.
+    pub fn new_unknown_variant(to: &'static str, variant: &str, expected: &[&str]) -> Self {
+        use core::fmt::Write as _;
+
+        let mut msg = StdString::new();
+        _ = write!(msg, "unknown variant `{variant}`, expected ");
+        match expected {
+            [] => msg.push_str("no variants"),
+            [x] => _ = write!(msg, "`{x}`"),
+            expected => {
+                msg.push_str("one of ");
+                for (idx, x) in expected.iter().enumerate() {
+                    if idx != 0 {
+                        msg.push_str(", ");
+                    }
+                    _ = write!(msg, "`{x}`");
+                }
+            }
+        }
+        Error::new_from_js_message("string", to, msg)
+    }
+
     /// Create into JS conversion error
     pub fn new_into_js(from: &'static str, to: &'static str) -> Self {
         Error::IntoJs {
@@ -282,7 +349,7 @@ impl Error {
         use Error::*;
         match self {
             Exception => qjs::JS_EXCEPTION,
-            Allocation => unsafe { qjs::JS_ThrowOutOfMemory(ctx.as_ptr()) },
+            Allocation | OutOfMemory => unsafe { qjs::JS_ThrowOutOfMemory(ctx.as_ptr()) },
             InvalidString(_)
             | Utf8(_)
             | FromJs { .. }
@@ -359,11 +426,21 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
             Error::Allocation => "Allocation failed while creating object".fmt(f)?,
-            Error::DuplicateExports => {
-                "Tried to export two values with the same name from one module".fmt(f)?
+            Error::OutOfMemory => "Out of memory".fmt(f)?,
+            Error::DuplicateExports { name } => {
+                "Tried to declare the export '".fmt(f)?;
+                name.fmt(f)?;
+                "' twice in one module".fmt(f)?;
             }
-            Error::InvalidExport => {
-                "Tried to export a value which was not previously declared".fmt(f)?
+            Error::InvalidExport { name } => {
+                "Tried to set the export '".fmt(f)?;
+                name.fmt(f)?;
+                "' which was not previously declared".fmt(f)?;
+            }
+            Error::MissingExport { name } => {
+                "The declared export '".fmt(f)?;
+                name.fmt(f)?;
+                "' was not set when the module was evaluated".fmt(f)?;
             }
             Error::InvalidString(error) => {
                 "String contained internal null bytes: ".fmt(f)?;
@@ -463,13 +540,25 @@ impl Display for Error {
                 "Error borrowing function: ".fmt(f)?;
                 x.fmt(f)?;
             }
-            Error::WouldBlock => "Error blocking on a promise resulted in a dead lock".fmt(f)?,
+            Error::WouldBlock => "Error blocking would result in a dead lock".fmt(f)?,
+            Error::Timeout => "Execution timed out".fmt(f)?,
             Error::UserData(x) => x.fmt(f)?,
             Error::AsSlice(x) => {
                 "Could not convert array buffer to slice: ".fmt(f)?;
                 x.fmt(f)?;
             }
             Error::UnrelatedRuntime => "Restoring Persistent in an unrelated runtime".fmt(f)?,
+            #[cfg(feature = "loader")]
+            Error::BytecodeVersion { expected, found } => {
+                "Bytecode is incompatible with ".fmt(f)?;
+                expected.fmt(f)?;
+                if let Some(found) = found {
+                    ", it was compiled by ".fmt(f)?;
+                    found.fmt(f)?;
+                } else {
+                    ", it has no version header".fmt(f)?;
+                }
+            }
         }
         Ok(())
     }
@@ -584,6 +673,48 @@ impl<'js> CaughtError<'js> {
     pub fn is_js_error(&self) -> bool {
         matches!(self, CaughtError::Exception(_) | CaughtError::Value(_))
     }
+
+    /// Returns the thrown JavaScript value, if the error was an exception.
+    pub fn value(&self) -> Option<Value<'js>> {
+        match self {
+            CaughtError::Error(_) => None,
+            CaughtError::Exception(ex) => Some(ex.clone().into_value()),
+            CaughtError::Value(value) => Some(value.clone()),
+        }
+    }
+
+    /// Returns the message describing the error.
+    ///
+    /// This is the `message` property of thrown `Error` instances, the thrown value itself if a
+    /// string was thrown, and the display text of errors which weren't exceptions.
+    pub fn message(&self) -> Option<StdString> {
+        match self {
+            CaughtError::Error(e) => Some(e.to_string()),
+            CaughtError::Exception(ex) => ex.message(),
+            CaughtError::Value(value) => value.as_string().and_then(|x| x.to_string().ok()),
+        }
+    }
+
+    /// Returns the JavaScript stack trace, if a thrown `Error` instance has one.
+    pub fn stack(&self) -> Option<StdString> {
+        match self {
+            CaughtError::Exception(ex) => ex.stack(),
+            CaughtError::Error(_) | CaughtError::Value(_) => None,
+        }
+    }
+
+    /// Returns a report of the thrown value which outlives the context, with its name, message,
+    /// stack, location and `cause` chain.
+    ///
+    /// Returns `None` if the error wasn't an exception.
+    ///
+    /// The report isn't captured when the error is caught as reading the properties of the
+    /// thrown value can run getters, which would happen on every catch even if the report is
+    /// never used, and a report field on the variants would break matching on them.
+    pub fn report(&self) -> Option<ErrorReport> {
+        let value = self.value()?;
+        Some(ErrorReport::from_value(&value.ctx().clone(), value))
+    }
 }
 
 /// Extension trait to easily turn results with [`Error`] into results with [`CaughtError`]
@@ -612,17 +743,80 @@ impl<'js, T> CatchResultExt<'js, T> for Result<T> {
     }
 }
 
-/// Extension trait to easily turn results with [`CaughtError`] into results with [`Error`]
+/// A trait for errors which can be thrown as JavaScript values.
+///
+/// Implement it for a Rust error type to define the JavaScript error class and properties the
+/// error is thrown with, then use [`ThrowResultExt::throw`] to throw the error of a result.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Ctx, Exception, Function, Result, ThrowJs, ThrowResultExt, Value};
+/// enum FsError {
+///     NotFound(String),
+///     TooLarge(u64),
+/// }
+///
+/// impl<'js> ThrowJs<'js> for FsError {
+///     fn into_thrown(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+///         let error = match self {
+///             FsError::NotFound(path) => {
+///                 let error = Exception::from_message(ctx.clone(), &format!("{path} not found"))?;
+///                 error.set("code", "ENOENT")?;
+///                 error
+///             }
+///             FsError::TooLarge(size) => {
+///                 Exception::range_error(ctx, &format!("file of {size} bytes is too large"))?
+///             }
+///         };
+///         Ok(error.into_value())
+///     }
+/// }
+///
+/// fn read(path: String) -> std::result::Result<String, FsError> {
+///     Err(FsError::NotFound(path))
+/// }
+///
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let read = Function::new(ctx.clone(), |ctx: Ctx, path: String| read(path).throw(&ctx)).unwrap();
+/// ctx.globals().set("read", read).unwrap();
+/// let code: String = ctx.eval("try { read('a.txt') } catch (e) { e.code }").unwrap();
+/// assert_eq!(code, "ENOENT");
+/// # });
+/// ```
+pub trait ThrowJs<'js> {
+    /// Returns the value which is thrown for this error, usually an [`Exception`].
+    ///
+    /// Returning an error instead throws that error.
+    fn into_thrown(self, ctx: &Ctx<'js>) -> Result<Value<'js>>;
+}
+
+impl<'js> ThrowJs<'js> for CaughtError<'js> {
+    fn into_thrown(self, _ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        match self {
+            CaughtError::Error(e) => Err(e),
+            CaughtError::Exception(ex) => Ok(ex.into_value()),
+            CaughtError::Value(ex) => Ok(ex),
+        }
+    }
+}
+
+/// Extension trait to easily turn results with errors which can be thrown, like
+/// [`CaughtError`], into results with [`Error`]
 ///
 /// Calling throw on a `CaughtError` will set the current error to the one contained in
-/// `CaughtError` if such a value exists and then turn `CaughtError` into `Error`.
+/// `CaughtError` if such a value exists and then turn `CaughtError` into `Error`. Other errors
+/// are thrown as the value returned by [`ThrowJs::into_thrown`].
 pub trait ThrowResultExt<'js, T> {
     fn throw(self, ctx: &Ctx<'js>) -> Result<T>;
 }
 
-impl<'js, T> ThrowResultExt<'js, T> for CaughtResult<'js, T> {
+impl<'js, T, E: ThrowJs<'js>> ThrowResultExt<'js, T> for StdResult<T, E> {
     fn throw(self, ctx: &Ctx<'js>) -> Result<T> {
-        self.map_err(|e| e.throw(ctx))
+        self.map_err(|e| match e.into_thrown(ctx) {
+            Ok(value) => ctx.throw(value),
+            Err(e) => e,
+        })
     }
 }
 
@@ -698,22 +892,290 @@ impl<'js> Ctx<'js> {
         if qjs::JS_VALUE_GET_NORM_TAG(js_val) != qjs::JS_TAG_EXCEPTION {
             Ok(js_val)
         } else {
-            if let Some(x) = self.get_opaque().take_panic() {
-                crate::util::resume_unwind(x);
-            }
-            Err(Error::Exception)
+            Err(self.raise_exception())
         }
     }
 
     /// Returns [`Error::Exception`] if there is no existing panic,
     /// otherwise continues panicking.
+    ///
+    /// Returns [`Error::OutOfMemory`] instead, clearing the exception, if the pending exception
+    /// is the error QuickJS throws when it runs out of memory.
     pub(crate) fn raise_exception(&self) -> Error {
         // Safety
         unsafe {
             if let Some(x) = self.get_opaque().take_panic() {
                 crate::util::resume_unwind(x);
             }
+            if self.take_out_of_memory() {
+                return Error::OutOfMemory;
+            }
             Error::Exception
         }
     }
+
+    /// Clears the pending exception and returns true if it is an out of memory error.
+    ///
+    /// The runtime recognizes the error QuickJS throws when it runs out of memory by the memory
+    /// allocated while it is created, so a script throwing the same error isn't mistaken for it.
+    unsafe fn take_out_of_memory(&self) -> bool {
+        let ctx = self.as_ptr();
+        if !qjs::JS_HasException(ctx) {
+            return false;
+        }
+        let exception = qjs::JS_GetException(ctx);
+        let oom = qjs::rquickjs_is_out_of_memory_error(qjs::JS_GetRuntime(ctx), exception);
+        if oom {
+            qjs::JS_FreeValue(ctx, exception);
+        } else {
+            qjs::JS_Throw(ctx, exception);
+        }
+        oom
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::EvalOptions, test_with, CatchResultExt, Context, Error, Object, Runtime,
+        StdString, Value,
+    };
+
+    #[test]
+    fn caught_error() {
+        test_with(|ctx| {
+            let err = ctx
+                .eval::<(), _>("\n\nthrow new TypeError('bad value')")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.is_exception());
+            assert_eq!(err.message().as_deref(), Some("bad value"));
+            assert!(err.stack().unwrap().contains(":3"));
+            assert!(err.value().unwrap().is_object());
+
+            let err = ctx.eval::<(), _>("throw 'text'").catch(&ctx).unwrap_err();
+            assert!(err.is_js_error());
+            assert_eq!(err.message().as_deref(), Some("text"));
+            assert_eq!(err.stack(), None);
+
+            let res = ctx.eval::<Value, _>("1").unwrap().get::<bool>();
+            assert!(!res.as_ref().unwrap_err().is_exception());
+            let err = res.catch(&ctx).unwrap_err();
+            assert!(!err.is_js_error());
+            assert!(err.message().is_some());
+            assert!(err.value().is_none());
+        })
+    }
+
+    #[test]
+    fn error_report() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let report = ctx.with(|ctx| {
+            let options = EvalOptions {
+                source_name: Some("main.js".into()),
+                ..Default::default()
+            };
+            let err = ctx
+                .eval_with_options::<(), _>(
+                    r#"function g() { throw new Error("inner"); }
+function f() {
+    try {
+        g();
+    } catch (e) {
+        throw new TypeError("outer", { cause: e });
+    }
+}
+f();"#,
+                    options,
+                )
+                .catch(&ctx)
+                .unwrap_err();
+            let report = err.report().unwrap();
+            assert!(ctx.eval::<(), _>("1").catch(&ctx).is_ok());
+            report
+        });
+        // The report outlives the context.
+        assert_eq!(report.name.as_deref(), Some("TypeError"));
+        assert_eq!(report.message.as_deref(), Some("outer"));
+        assert_eq!(report.file.as_deref(), Some("main.js"));
+        assert_eq!((report.line, report.column), (Some(6), Some(19)));
+        let cause = report.cause.as_deref().unwrap();
+        assert_eq!(cause.name.as_deref(), Some("Error"));
+        assert_eq!(cause.message.as_deref(), Some("inner"));
+        assert_eq!((cause.line, cause.column), (Some(1), Some(25)));
+        assert!(cause.cause.is_none());
+
+        let text = report.to_string();
+        assert!(
+            text.starts_with("TypeError: outer\n    at f (main.js:6:19)"),
+            "{text}"
+        );
+        assert!(
+            text.contains("\n  [cause]: Error: inner\n      at g (main.js:1:25)"),
+            "{text}"
+        );
+
+        let source = core::error::Error::source(&report).unwrap();
+        assert!(source.to_string().starts_with("Error: inner"));
+        let boxed: Box<dyn core::error::Error + Send + Sync + 'static> = Box::new(report);
+        assert!(boxed.to_string().starts_with("TypeError: outer"));
+
+        ctx.with(|ctx| {
+            let err = ctx
+                .eval::<(), _>(
+                    r#"
+                    const e = new Error("getter");
+                    Object.defineProperty(e, "name", { get() { throw new Error("nope") } });
+                    throw e;
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap_err();
+            let report = err.report().unwrap();
+            assert!(report.name.is_none());
+            assert_eq!(report.message.as_deref(), Some("getter"));
+            assert!(ctx.catch().type_of().is_void());
+
+            let err = ctx.eval::<(), _>("throw 42").catch(&ctx).unwrap_err();
+            let report = err.report().unwrap();
+            assert_eq!(report.to_string(), "Uncaught 42");
+            assert!(report.line.is_none());
+            assert!(ctx
+                .eval::<Value, _>("1")
+                .unwrap()
+                .get::<bool>()
+                .catch(&ctx)
+                .unwrap_err()
+                .report()
+                .is_none());
+        });
+    }
+
+    #[test]
+    fn out_of_memory() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_memory_limit(16 * 1024 * 1024);
+        ctx.with(|ctx| {
+            let res = ctx.eval::<(), _>("const a = []; for(;;) { a.push(new Uint8Array(1 << 20)) }");
+            assert!(matches!(res, Err(Error::OutOfMemory)), "{res:?}");
+            assert!(res.unwrap_err().is_out_of_memory());
+            // There is no pending exception left behind.
+            assert!(ctx.catch().type_of().is_void());
+
+            // Other internal errors are still exceptions.
+            let res = ctx.eval::<(), _>("function f() { f() } f()");
+            assert!(matches!(res, Err(Error::Exception)), "{res:?}");
+            let err = ctx.catch();
+            assert!(err.is_error());
+
+            let res = ctx.eval::<(), _>("throw new Error('out of memory')");
+            assert!(matches!(res, Err(Error::Exception)), "{res:?}");
+            ctx.catch();
+
+            // Thrown back into JavaScript it can be caught as an out of memory error again.
+            let res = ctx.eval::<bool, _>(
+                "try { const b = []; for(;;) { b.push(new Uint8Array(1 << 20)) } } catch(e) { e instanceof InternalError }",
+            );
+            assert!(res.unwrap());
+
+            // Errors with the same message thrown by scripts are not out of memory errors.
+            let res = ctx.eval::<(), _>("throw new InternalError('out of memory')");
+            assert!(matches!(res, Err(Error::Exception)), "{res:?}");
+            ctx.catch();
+        });
+    }
+
+    #[test]
+    fn throw_rich_errors() {
+        use crate::{Ctx, Exception, Function, ThrowJs, ThrowResultExt};
+
+        enum AppError {
+            NotFound(&'static str),
+            Invalid(&'static str),
+            Range,
+            Syntax,
+            Reference,
+            Internal,
+            Custom,
+        }
+
+        impl<'js> ThrowJs<'js> for AppError {
+            fn into_thrown(self, ctx: &Ctx<'js>) -> crate::Result<Value<'js>> {
+                let error = match self {
+                    AppError::NotFound(path) => {
+                        let error = Exception::from_message(ctx.clone(), path)?;
+                        error.set("code", "ENOENT")?;
+                        error
+                    }
+                    AppError::Invalid(message) => Exception::type_error(ctx, message)?,
+                    AppError::Range => Exception::range_error(ctx, "range")?,
+                    AppError::Syntax => Exception::syntax_error(ctx, "syntax")?,
+                    AppError::Reference => Exception::reference_error(ctx, "reference")?,
+                    AppError::Internal => Exception::internal_error(ctx, "internal")?,
+                    AppError::Custom => {
+                        let error: Object = ctx.eval(
+                            "class AppError extends Error { name = 'AppError' }; new AppError('custom')",
+                        )?;
+                        error.set("code", 42)?;
+                        return Ok(error.into_value());
+                    }
+                };
+                Ok(error.into_value())
+            }
+        }
+
+        test_with(|ctx| {
+            let fail = Function::new(
+                ctx.clone(),
+                |ctx: Ctx, kind: StdString| -> crate::Result<()> {
+                    let error = match kind.as_str() {
+                        "notFound" => AppError::NotFound("a.txt"),
+                        "invalid" => AppError::Invalid("bad"),
+                        "range" => AppError::Range,
+                        "syntax" => AppError::Syntax,
+                        "reference" => AppError::Reference,
+                        "internal" => AppError::Internal,
+                        "custom" => AppError::Custom,
+                        "value" => return Err(ctx.throw(vec!["not", "an", "error"])),
+                        _ => return Ok(()),
+                    };
+                    Err(error).throw(&ctx)
+                },
+            )
+            .unwrap();
+            ctx.globals().set("fail", fail).unwrap();
+            let res: bool = ctx
+                .eval(
+                    r#"
+                    function check(kind, test) {
+                        try {
+                            fail(kind);
+                        } catch (e) {
+                            if (!test(e)) throw new Error(`unexpected ${kind} error: ${e}`);
+                            return true;
+                        }
+                        throw new Error(`${kind} did not throw`);
+                    }
+                    check("notFound", (e) => e instanceof Error && e.message === "a.txt" && e.code === "ENOENT")
+                        && check("invalid", (e) => e instanceof TypeError && e.message === "bad" && "stack" in e)
+                        && check("range", (e) => e instanceof RangeError && e.message === "range")
+                        && check("syntax", (e) => e instanceof SyntaxError && e.message === "syntax")
+                        && check("reference", (e) => e instanceof ReferenceError && e.message === "reference")
+                        && check("internal", (e) => e instanceof InternalError && e.message === "internal")
+                        && check("custom", (e) => e instanceof Error && e.name === "AppError" && e.code === 42)
+                        && check("value", (e) => Array.isArray(e) && e.join(" ") === "not an error")
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert!(res);
+
+            // Creating errors doesn't leave a pending exception behind.
+            let error = Exception::type_error(&ctx, "pending").unwrap();
+            assert!(!ctx.catch().is_object());
+            assert_eq!(error.message().as_deref(), Some("pending"));
+        });
+    }
 }
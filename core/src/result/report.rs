@@ -0,0 +1,183 @@
+use alloc::{boxed::Box, string::ToString as _};
+use core::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    atom::PredefinedAtom, convert::Coerced, CatchResultExt, Ctx, FromJs, StdString, Value,
+};
+
+/// A report of a thrown JavaScript value which can outlive the context, created with
+/// [`CaughtError::report`](crate::CaughtError::report).
+///
+/// Captures the information which is otherwise only retrievable while the context is entered,
+/// including the chain of errors given as `cause`. The report only holds Rust data, so it is
+/// `Send` and `Sync` and can be kept after the runtime is dropped, for example in a
+/// `Box<dyn Error + Send + Sync>`. The thrown value itself isn't part of the report, it can be
+/// kept with [`Persistent::save`](crate::Persistent::save) of
+/// [`CaughtError::value`](crate::CaughtError::value) instead.
+///
+/// The display text is a multi-line report like the one Node.js prints for uncaught errors:
+/// ```text
+/// TypeError: outer
+///     at f (main.js:3:19)
+///     at main.js:6:9
+///   [cause]: Error: inner
+///       at g (main.js:2:19)
+/// ```
+#[derive(Clone, Debug)]
+pub struct ErrorReport {
+    /// The `name` of a thrown object, like `TypeError`.
+    pub name: Option<StdString>,
+    /// The `message` of a thrown object, or the string conversion of other thrown values.
+    pub message: Option<StdString>,
+    /// The `stack` of a thrown object.
+    pub stack: Option<StdString>,
+    /// The file name of the location the error was created at, taken from the stack.
+    pub file: Option<StdString>,
+    /// The 1-based line number of the location the error was created at.
+    pub line: Option<u32>,
+    /// The 1-based column number of the location the error was created at.
+    pub column: Option<u32>,
+    /// The report of the `cause` of the error, if it has one.
+    pub cause: Option<Box<ErrorReport>>,
+}
+
+impl ErrorReport {
+    /// The maximum depth of the reported `cause` chain, which guards against cycles.
+    const MAX_CAUSES: usize = 16;
+
+    /// Create a report for a thrown value.
+    ///
+    /// Reading the properties of the value can run getters, exceptions they throw are caught and
+    /// the property is left out of the report.
+    pub fn from_value<'js>(ctx: &Ctx<'js>, value: Value<'js>) -> Self {
+        Self::with_depth(ctx, value, 0)
+    }
+
+    fn with_depth<'js>(ctx: &Ctx<'js>, value: Value<'js>, depth: usize) -> Self {
+        let (name, message, stack, cause) = if let Some(object) = value.as_object() {
+            let get = |key| {
+                object
+                    .get::<_, Option<Coerced<StdString>>>(key)
+                    .catch(ctx)
+                    .ok()
+                    .flatten()
+                    .map(|x| x.0)
+            };
+            let cause = object
+                .get::<_, Option<Value>>("cause")
+                .catch(ctx)
+                .ok()
+                .flatten()
+                .filter(|_| depth < Self::MAX_CAUSES)
+                .map(|cause| Box::new(Self::with_depth(ctx, cause, depth + 1)));
+            (
+                get(PredefinedAtom::Name),
+                get(PredefinedAtom::Message),
+                get(PredefinedAtom::Stack),
+                cause,
+            )
+        } else {
+            let message = Coerced::<StdString>::from_js(ctx, value)
+                .catch(ctx)
+                .ok()
+                .map(|x| x.0);
+            (None, message, None, None)
+        };
+        let (file, line, column) = stack
+            .as_deref()
+            .and_then(location)
+            .map_or((None, None, None), |(file, line, column)| {
+                (Some(file.to_string()), Some(line), column)
+            });
+        ErrorReport {
+            name,
+            message,
+            stack,
+            file,
+            line,
+            column,
+            cause,
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+        match (&self.name, &self.message) {
+            (Some(name), Some(message)) if !message.is_empty() => write!(f, "{name}: {message}")?,
+            (Some(name), _) => name.fmt(f)?,
+            (None, Some(message)) => write!(f, "Uncaught {message}")?,
+            (None, None) => "Uncaught exception".fmt(f)?,
+        }
+        if let Some(stack) = &self.stack {
+            for line in stack.lines().filter(|line| !line.trim().is_empty()) {
+                write!(f, "\n{:indent$}{line}", "")?;
+            }
+        }
+        if let Some(cause) = &self.cause {
+            write!(f, "\n{:indent$}  [cause]: ", "")?;
+            cause.fmt_indented(f, indent + 2)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ErrorReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl StdError for ErrorReport {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.cause.as_deref().map(|x| x as _)
+    }
+}
+
+/// Returns the file, line and column of the first frame of a stack with a location.
+///
+/// Frames are formatted as `at name (file:line:column)` or `at file:line:column`, native frames
+/// as `at name (native)`.
+fn location(stack: &str) -> Option<(&str, u32, Option<u32>)> {
+    stack.lines().find_map(|frame| {
+        let frame = frame.trim().strip_prefix("at ")?;
+        let frame = match frame.strip_suffix(')') {
+            Some(frame) => &frame[frame.rfind('(')? + 1..],
+            None => frame,
+        };
+        let (rest, last) = frame.rsplit_once(':')?;
+        let last = last.parse().ok()?;
+        match rest.rsplit_once(':') {
+            Some((file, line)) => match line.parse() {
+                Ok(line) => Some((file, line, Some(last))),
+                Err(_) => Some((rest, last, None)),
+            },
+            None => Some((rest, last, None)),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::location;
+
+    #[test]
+    fn frame_location() {
+        assert_eq!(
+            location("    at f (main.js:3:19)\n    at main.js:6:9\n"),
+            Some(("main.js", 3, Some(19)))
+        );
+        assert_eq!(
+            location("    at C:\\js\\main.js:6:9\n"),
+            Some(("C:\\js\\main.js", 6, Some(9)))
+        );
+        assert_eq!(location("    at main.js:6"), Some(("main.js", 6, None)));
+        assert_eq!(
+            location("    at parse (native)\n    at <eval> (main.js:1:6)\n"),
+            Some(("main.js", 1, Some(6)))
+        );
+        assert_eq!(location("    at parse (native)\n"), None);
+        assert_eq!(location(""), None);
+    }
+}
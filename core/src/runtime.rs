@@ -1,6 +1,7 @@
 //! QuickJS runtime related types.
 
 mod base;
+mod dump;
 pub(crate) mod opaque;
 pub(crate) mod raw;
 mod userdata;
@@ -8,14 +9,27 @@ mod userdata;
 #[cfg(feature = "futures")]
 mod r#async;
 #[cfg(feature = "futures")]
+mod cancel;
+#[cfg(feature = "futures")]
+mod executor;
+#[cfg(feature = "futures")]
 pub(crate) mod schedular;
 #[cfg(feature = "futures")]
 mod spawner;
 
 use alloc::boxed::Box;
-pub use base::{Runtime, WeakRuntime};
+pub use base::{JobStatus, Runtime, RuntimeLock, WeakRuntime};
+pub use dump::DumpFlags;
 pub use userdata::{UserDataError, UserDataGuard};
 
+#[cfg(feature = "futures")]
+pub use cancel::{CancellationToken, JobHandle};
+#[cfg(feature = "async-std")]
+pub use executor::AsyncStdSpawner;
+#[cfg(feature = "tokio")]
+pub use executor::TokioSpawner;
+#[cfg(feature = "futures")]
+pub use executor::{ExecutorSpawner, LocalBoxFuture, SendBoxFuture};
 #[cfg(feature = "futures")]
 pub(crate) use r#async::InnerRuntime;
 #[cfg(feature = "futures")]
@@ -23,6 +37,8 @@ pub use r#async::{AsyncRuntime, AsyncWeakRuntime};
 
 use crate::value::promise::PromiseHookType;
 use crate::{Ctx, Value};
+#[cfg(feature = "loader")]
+use crate::{Object, Result};
 
 /// The type of the promise hook.
 #[cfg(not(feature = "parallel"))]
@@ -48,5 +64,16 @@ pub type InterruptHandler = Box<dyn FnMut() -> bool + 'static>;
 #[cfg(feature = "parallel")]
 pub type InterruptHandler = Box<dyn FnMut() -> bool + Send + 'static>;
 
+/// The type of the `import.meta` handler, called with the name and the `import.meta` object of
+/// every module loaded by the module loader.
+#[cfg(all(feature = "loader", not(feature = "parallel")))]
+pub type ImportMetaHandler =
+    Box<dyn for<'a> FnMut(Ctx<'a>, &str, Object<'a>) -> Result<()> + 'static>;
+/// The type of the `import.meta` handler, called with the name and the `import.meta` object of
+/// every module loaded by the module loader.
+#[cfg(all(feature = "loader", feature = "parallel"))]
+pub type ImportMetaHandler =
+    Box<dyn for<'a> FnMut(Ctx<'a>, &str, Object<'a>) -> Result<()> + Send + 'static>;
+
 /// A struct with information about the runtimes memory usage.
 pub type MemoryUsage = crate::qjs::JSMemoryUsage;
@@ -1,11 +1,15 @@
 use alloc::{
+    boxed::Box,
     ffi::CString,
     sync::{Arc, Weak},
     vec::Vec,
 };
-use core::{ptr::NonNull, result::Result as StdResult, task::Poll};
+use core::{any::Any, ptr::NonNull, result::Result as StdResult, task::Poll};
 #[cfg(feature = "std")]
-use std::println;
+use std::{println, time::Duration};
+
+#[cfg(feature = "std")]
+use crate::context::EvalCache;
 
 #[cfg(feature = "parallel")]
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -13,19 +17,21 @@ use std::sync::mpsc::{self, Receiver, Sender};
 use async_lock::Mutex;
 
 use super::{
-    opaque::Opaque, raw::RawRuntime, schedular::SchedularPoll, spawner::DriveFuture,
-    InterruptHandler, MemoryUsage, PromiseHook,
+    opaque::Opaque, raw::RawRuntime, schedular::SchedularPoll, spawner::DriveFuture, DumpFlags,
+    ExecutorSpawner, InterruptHandler, MemoryUsage, PromiseHook, UserDataError,
 };
 use crate::allocator::Allocator;
-#[cfg(feature = "loader")]
-use crate::loader::{Loader, Resolver};
+#[cfg(feature = "parallel")]
+use crate::util::{AssertSendFuture, AssertSyncFuture};
 use crate::{
-    context::AsyncContext, result::AsyncJobException, util::ManualPoll, Ctx, Exception, Result,
+    context::AsyncContext, markers::ParallelSend, qjs, result::AsyncJobException, util::ManualPoll,
+    Ctx, Exception, JsLifetime, Result,
 };
-#[cfg(feature = "parallel")]
+#[cfg(feature = "loader")]
 use crate::{
-    qjs,
-    util::{AssertSendFuture, AssertSyncFuture},
+    loader::{AsyncLoader, AsyncLoaderData, AsyncResolver, Loader, Resolver},
+    runtime::ImportMetaHandler,
+    Error,
 };
 
 #[derive(Debug)]
@@ -37,6 +43,7 @@ pub(crate) struct InnerRuntime {
 
 impl InnerRuntime {
     pub fn drop_pending(&self) {
+        self.runtime.drop_pending();
         #[cfg(feature = "parallel")]
         while let Ok(x) = self.drop_recv.try_recv() {
             unsafe { qjs::JS_FreeContext(x.as_ptr()) }
@@ -106,42 +113,58 @@ impl AsyncRuntime {
     ///
     /// # Features
     /// *If the `"rust-alloc"` feature is enabled the Rust's global allocator will be used in favor of libc's one.*
-    // Annoying false positive clippy lint
-    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new() -> Result<Self> {
         let opaque = Opaque::with_spawner();
         let runtime = unsafe { RawRuntime::new(opaque) }?;
-
-        #[cfg(feature = "parallel")]
-        let (drop_send, drop_recv) = mpsc::channel();
-
-        Ok(Self {
-            inner: Arc::new(Mutex::new(InnerRuntime {
-                runtime,
-                #[cfg(feature = "parallel")]
-                drop_recv,
-            })),
-            #[cfg(feature = "parallel")]
-            drop_send,
-        })
+        Ok(Self::from_raw(runtime))
     }
 
     /// Create a new runtime using specified allocator
     ///
     /// Will generally only fail if not enough memory was available.
-    // Annoying false positive clippy lint
-    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new_with_alloc<A>(allocator: A) -> Result<Self>
     where
         A: Allocator + 'static,
     {
         let opaque = Opaque::with_spawner();
         let runtime = unsafe { RawRuntime::new_with_allocator(opaque, allocator) }?;
+        Ok(Self::from_raw(runtime))
+    }
 
+    /// Create a new runtime which runs on the given executor.
+    ///
+    /// The future returned by [`AsyncRuntime::drive`] is spawned on the executor right away, so
+    /// futures spawned inside the runtime, like the ones of [`Async`](crate::prelude::Async)
+    /// functions, make progress without the runtime being awaited. These futures can access the
+    /// executor with [`Ctx::executor`].
+    ///
+    /// Without the `"parallel"` feature the driving future is spawned with
+    /// [`ExecutorSpawner::spawn_local`] so this needs to be called from a context where the
+    /// executor can spawn local futures, like a tokio `LocalSet`.
+    ///
+    /// Will generally only fail if not enough memory was available.
+    pub fn new_with_spawner<S: ExecutorSpawner>(spawner: S) -> Result<Self> {
+        #[allow(clippy::arc_with_non_send_sync)]
+        let spawner: Arc<dyn ExecutorSpawner> = Arc::new(spawner);
+        let opaque = Opaque::with_executor(spawner.clone());
+        let runtime = unsafe { RawRuntime::new(opaque) }?;
+        let rt = Self::from_raw(runtime);
+
+        #[cfg(feature = "parallel")]
+        spawner.spawn(Box::pin(rt.drive()));
+        #[cfg(not(feature = "parallel"))]
+        spawner.spawn_local(Box::pin(rt.drive()));
+
+        Ok(rt)
+    }
+
+    // Annoying false positive clippy lint
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn from_raw(runtime: RawRuntime) -> Self {
         #[cfg(feature = "parallel")]
         let (drop_send, drop_recv) = mpsc::channel();
 
-        Ok(Self {
+        Self {
             inner: Arc::new(Mutex::new(InnerRuntime {
                 runtime,
                 #[cfg(feature = "parallel")]
@@ -149,7 +172,7 @@ impl AsyncRuntime {
             })),
             #[cfg(feature = "parallel")]
             drop_send,
-        })
+        }
     }
 
     /// Get weak ref to runtime
@@ -183,6 +206,54 @@ impl AsyncRuntime {
         }
     }
 
+    /// Set the time after which evaluations with [`Ctx::eval_catching_interrupt`] are
+    /// interrupted, `None` disables the timeout.
+    ///
+    /// See [`Runtime::set_execution_timeout`](crate::Runtime::set_execution_timeout).
+    ///
+    /// [`Ctx::eval_catching_interrupt`]: crate::Ctx::eval_catching_interrupt
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    #[inline]
+    pub async fn set_execution_timeout(&self, timeout: Option<Duration>) {
+        unsafe {
+            self.inner
+                .lock()
+                .await
+                .runtime
+                .set_execution_timeout(timeout);
+        }
+    }
+
+    /// Enable a cache of compiled bytecode shared by all contexts of the runtime.
+    ///
+    /// See [`Runtime::enable_bytecode_cache`](crate::Runtime::enable_bytecode_cache).
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub async fn enable_bytecode_cache(&self, capacity: usize) -> Arc<EvalCache> {
+        let cache = Arc::new(EvalCache::with_capacity(capacity));
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .set_bytecode_cache(Some(cache.clone()));
+        cache
+    }
+
+    /// Disable the cache of compiled bytecode enabled with
+    /// [`AsyncRuntime::enable_bytecode_cache`], returning it if there was one.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub async fn disable_bytecode_cache(&self) -> Option<Arc<EvalCache>> {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .set_bytecode_cache(None)
+    }
+
     /// Set the module loader
     #[cfg(feature = "loader")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
@@ -196,6 +267,83 @@ impl AsyncRuntime {
         }
     }
 
+    /// Set an asynchronous module resolver and loader.
+    ///
+    /// This is the asynchronous counterpart of [`AsyncRuntime::set_loader`] and replaces the
+    /// loader set with it. QuickJS resolves and loads modules synchronously, so modules are
+    /// fetched ahead of time by [`Module::import_async`](crate::Module::import_async) and
+    /// [`Module::evaluate_async`](crate::Module::evaluate_async) which complete once the whole
+    /// module graph was loaded. The promise returned by `import()` in JavaScript stays pending
+    /// while the imported module and its static imports are fetched by a spawned future, an error
+    /// of the resolver or the loader rejects it.
+    #[cfg(feature = "loader")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+    pub async fn set_async_loader<R, L>(&self, resolver: R, loader: L) -> Result<()>
+    where
+        R: AsyncResolver,
+        L: AsyncLoader,
+    {
+        let (data, resolver, loader) = AsyncLoaderData::new(resolver, loader);
+        let mut lock = self.inner.lock().await;
+        lock.runtime
+            .get_opaque()
+            .insert_userdata(data)
+            .map_err(|_| Error::UserData(UserDataError(())))?;
+        unsafe { lock.runtime.set_loader(resolver, loader) };
+        Ok(())
+    }
+
+    /// Set a closure which is called with the name and the `import.meta` object of every module
+    /// loaded by the module loader, before the module is evaluated.
+    ///
+    /// Can be used to set `import.meta.url`, `import.meta.main` or any other field. An error
+    /// returned from the handler fails the import of the module.
+    #[cfg(feature = "loader")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+    pub async fn set_import_meta_handler(&self, handler: Option<ImportMetaHandler>) {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .set_import_meta_handler(handler);
+    }
+
+    /// Store host state in the runtime which can be retrieved with
+    /// [`Ctx::userdata`](crate::Ctx::userdata) by any callback with access to a context.
+    ///
+    /// The data is stored per type and can't contain JavaScript values. Returns the previous
+    /// value of the type if there was one, or the argument if the userdata is currently being
+    /// accessed and insertion is not possible.
+    pub async fn store_userdata<U>(&self, data: U) -> StdResult<Option<Box<U>>, UserDataError<U>>
+    where
+        U: JsLifetime<'static> + ParallelSend,
+        U::Changed<'static>: Any,
+    {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .insert_userdata(data)
+    }
+
+    /// Remove the host state of the given type stored with [`AsyncRuntime::store_userdata`].
+    ///
+    /// Returns an error if the userdata is currently being accessed and removing isn't possible.
+    pub async fn remove_userdata<U>(&self) -> StdResult<Option<Box<U>>, UserDataError<()>>
+    where
+        U: JsLifetime<'static>,
+        U::Changed<'static>: Any,
+    {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .remove_userdata()
+    }
+
     /// Set the info of the runtime
     pub async fn set_info<S: Into<Vec<u8>>>(&self, info: S) -> Result<()> {
         let string = CString::new(info)?;
@@ -205,6 +353,21 @@ impl AsyncRuntime {
         Ok(())
     }
 
+    /// Set the flags selecting which QuickJS debug dumps are written to stdout.
+    ///
+    /// Runtimes start with the flags of the enabled `dump-*` features, see
+    /// [`DumpFlags::from_features`].
+    pub async fn set_dump_flags(&self, flags: DumpFlags) {
+        unsafe {
+            self.inner.lock().await.runtime.set_dump_flags(flags);
+        }
+    }
+
+    /// Get the flags selecting which QuickJS debug dumps are written to stdout.
+    pub async fn dump_flags(&self) -> DumpFlags {
+        unsafe { self.inner.lock().await.runtime.dump_flags() }
+    }
+
     /// Set a limit on the max amount of memory the runtime will use.
     ///
     /// Setting the limit to 0 is equivalent to unlimited memory.
@@ -275,6 +438,8 @@ impl AsyncRuntime {
             let job_res = lock.runtime.execute_pending_job().map_err(|e| {
                 let ptr = NonNull::new(e)
                     .expect("executing pending job returned a null context on error");
+                // The context is borrowed from the job, the exception needs its own reference.
+                unsafe { qjs::JS_DupContext(ptr.as_ptr()) };
                 AsyncJobException(unsafe { AsyncContext::from_raw(ptr, self.clone()) })
             })?;
 
@@ -296,7 +461,49 @@ impl AsyncRuntime {
         f.await
     }
 
+    /// Execute at most `max` pending jobs.
+    ///
+    /// Returns the number of jobs which were executed. Futures spawned inside the runtime are not
+    /// polled, use [`AsyncRuntime::execute_pending_job`] or [`AsyncRuntime::idle`] to drive them.
+    /// Like when jobs are driven automatically, exceptions thrown by jobs are ignored.
+    ///
+    /// Together with [`AsyncRuntime::set_auto_drive`] this allows bounding the amount of work
+    /// done at a time.
+    pub async fn execute_pending_jobs(&self, max: usize) -> usize {
+        let mut lock = self.inner.lock().await;
+        lock.runtime.update_stack_top();
+        lock.drop_pending();
+
+        let mut executed = 0;
+        while executed < max {
+            match lock.runtime.execute_pending_job() {
+                Ok(false) => break,
+                // TODO figure out what to do with a job error.
+                Ok(true) | Err(_) => executed += 1,
+            }
+        }
+        executed
+    }
+
+    /// Set whether the runtime drives jobs and spawned futures automatically, enabled by default.
+    ///
+    /// When disabled, [`AsyncContext::with`] only polls the given future and the future returned
+    /// by [`AsyncRuntime::drive`] does nothing until driving is enabled again. Jobs then only run
+    /// when executed explicitly with [`AsyncRuntime::execute_pending_job`],
+    /// [`AsyncRuntime::execute_pending_jobs`] or [`AsyncRuntime::idle`].
+    pub async fn set_auto_drive(&self, enabled: bool) {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .set_auto_drive(enabled);
+    }
+
     /// Run all futures and jobs in the runtime until all are finished.
+    ///
+    /// Completes once both the job queue and the futures spawned inside the runtime are empty.
+    /// This also runs them when automatic driving is disabled.
     #[inline]
     pub async fn idle(&self) {
         let mut lock = self.inner.lock().await;
@@ -308,6 +515,7 @@ impl AsyncRuntime {
                 let pending = lock.runtime.execute_pending_job().map_err(|e| {
                     let ptr = NonNull::new(e)
                         .expect("executing pending job returned a null context on error");
+                    unsafe { qjs::JS_DupContext(ptr.as_ptr()) };
                     AsyncJobException(unsafe { AsyncContext::from_raw(ptr, self.clone()) })
                 });
                 match pending {
@@ -352,41 +560,80 @@ impl AsyncRuntime {
     }
 }
 
+/// Defines a test which is run both on tokio and on a minimal executor build with the futures
+/// crate, through [`ExecutorSpawner`].
+///
+/// The runtime is driven by the executor, unless the test is marked as `undriven`.
 #[cfg(test)]
 macro_rules! async_test_case {
+    ($name:ident => ($rt:ident,$ctx:ident) undriven { $($t:tt)* }) => {
+        async_test_case!(@case $name, |_spawner| crate::AsyncRuntime::new(), ($rt,$ctx) { $($t)* });
+    };
     ($name:ident => ($rt:ident,$ctx:ident) { $($t:tt)* }) => {
-    #[test]
-    fn $name() {
-        #[cfg(feature = "parallel")]
-        let mut new_thread = tokio::runtime::Builder::new_multi_thread();
+        async_test_case!(@case $name, crate::AsyncRuntime::new_with_spawner, ($rt,$ctx) { $($t)* });
+    };
+    (@case $name:ident, $new:expr, ($rt:ident,$ctx:ident) { $($t:tt)* }) => {
+    mod $name {
+        use super::*;
 
-        #[cfg(not(feature = "parallel"))]
-        let mut new_thread = tokio::runtime::Builder::new_current_thread();
+        #[test]
+        fn with_tokio() {
+            #[cfg(feature = "parallel")]
+            let mut new_thread = tokio::runtime::Builder::new_multi_thread();
 
-        let rt = new_thread
-            .enable_all()
-            .build()
-            .unwrap();
+            #[cfg(not(feature = "parallel"))]
+            let mut new_thread = tokio::runtime::Builder::new_current_thread();
 
-        #[cfg(feature = "parallel")]
-        {
-            rt.block_on(async {
-                let $rt = crate::AsyncRuntime::new().unwrap();
-                let $ctx = crate::AsyncContext::full(&$rt).await.unwrap();
+            let rt = new_thread
+                .enable_all()
+                .build()
+                .unwrap();
+
+            #[cfg(feature = "parallel")]
+            {
+                rt.block_on(async {
+                    let $rt = ($new)(TokioExecutor).unwrap();
+                    let $ctx = crate::AsyncContext::full(&$rt).await.unwrap();
 
-                $($t)*
+                    $($t)*
 
-            })
+                })
+            }
+            #[cfg(not(feature = "parallel"))]
+            {
+                let set = tokio::task::LocalSet::new();
+                set.block_on(&rt, async {
+                    let $rt = ($new)(TokioExecutor).unwrap();
+                    let $ctx = crate::AsyncContext::full(&$rt).await.unwrap();
+
+                    $($t)*
+                })
+            }
         }
-        #[cfg(not(feature = "parallel"))]
-        {
-            let set = tokio::task::LocalSet::new();
-            set.block_on(&rt, async {
-                let $rt = crate::AsyncRuntime::new().unwrap();
-                let $ctx = crate::AsyncContext::full(&$rt).await.unwrap();
-
-                $($t)*
-            })
+
+        #[test]
+        fn with_futures() {
+            #[cfg(feature = "parallel")]
+            {
+                let pool = futures_rs::executor::ThreadPool::new().unwrap();
+                futures_rs::executor::block_on(async {
+                    let $rt = ($new)(FuturesExecutor(pool)).unwrap();
+                    let $ctx = crate::AsyncContext::full(&$rt).await.unwrap();
+
+                    $($t)*
+                })
+            }
+            #[cfg(not(feature = "parallel"))]
+            {
+                let mut pool = futures_rs::executor::LocalPool::new();
+                let spawner = FuturesExecutor(pool.spawner());
+                pool.run_until(async {
+                    let $rt = ($new)(spawner).unwrap();
+                    let $ctx = crate::AsyncContext::full(&$rt).await.unwrap();
+
+                    $($t)*
+                })
+            }
         }
     }
     };
@@ -394,11 +641,100 @@ macro_rules! async_test_case {
 
 #[cfg(test)]
 mod test {
-    use std::time::Duration;
+    use std::{
+        future::{poll_fn, Future},
+        pin::pin,
+        task::Poll,
+        thread,
+        time::Duration,
+    };
+
+    use futures_rs::{
+        channel::oneshot,
+        future::{select, Either},
+        task::SpawnExt,
+    };
 
     use crate::*;
 
     use self::context::EvalOptions;
+    use self::runtime::{ExecutorSpawner, LocalBoxFuture, SendBoxFuture};
+
+    struct TokioExecutor;
+
+    impl ExecutorSpawner for TokioExecutor {
+        fn spawn(&self, future: SendBoxFuture) {
+            tokio::spawn(future);
+        }
+
+        fn spawn_local(&self, future: LocalBoxFuture) {
+            tokio::task::spawn_local(future);
+        }
+
+        fn sleep(&self, duration: Duration) -> SendBoxFuture {
+            Box::pin(tokio::time::sleep(duration))
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    struct FuturesExecutor(futures_rs::executor::ThreadPool);
+
+    #[cfg(not(feature = "parallel"))]
+    struct FuturesExecutor(futures_rs::executor::LocalSpawner);
+
+    impl ExecutorSpawner for FuturesExecutor {
+        fn spawn(&self, future: SendBoxFuture) {
+            self.0.spawn(future).unwrap();
+        }
+
+        #[cfg(feature = "parallel")]
+        fn spawn_local(&self, _future: LocalBoxFuture) {
+            unreachable!("the runtime spawns only send futures with the parallel feature")
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        fn spawn_local(&self, future: LocalBoxFuture) {
+            use futures_rs::task::LocalSpawnExt;
+            self.0.spawn_local(future).unwrap();
+        }
+
+        fn sleep(&self, duration: Duration) -> SendBoxFuture {
+            Box::pin(sleep(duration))
+        }
+    }
+
+    /// Sleep which doesn't depend on the executor.
+    async fn sleep(duration: Duration) {
+        let (tx, rx) = oneshot::channel();
+        thread::spawn(move || {
+            thread::sleep(duration);
+            let _ = tx.send(());
+        });
+        let _ = rx.await;
+    }
+
+    /// Timeout which doesn't depend on the executor, returns `None` if the future didn't finish
+    /// in time.
+    async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+        match select(pin!(future), pin!(sleep(duration))).await {
+            Either::Left((res, _)) => Some(res),
+            Either::Right(_) => None,
+        }
+    }
+
+    /// Yield which doesn't depend on the executor.
+    async fn yield_now() {
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                return Poll::Ready(());
+            }
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
 
     async_test_case!(basic => (_rt,ctx){
         async_with!(&ctx => |ctx|{
@@ -414,41 +750,38 @@ mod test {
 
 
         async_with!(&ctx => |ctx|{
-            tokio::time::sleep(Duration::from_secs_f64(0.01)).await;
+            sleep(Duration::from_secs_f64(0.01)).await;
             ctx.globals().set("foo","bar").unwrap();
             *a_ref += 1;
         }).await;
         assert_eq!(a,2);
     });
 
-    async_test_case!(drive => (rt,ctx){
+    async_test_case!(drive => (_rt,ctx){
         use std::sync::{Arc, atomic::{Ordering,AtomicUsize}};
 
-        #[cfg(feature = "parallel")]
-        tokio::spawn(rt.drive());
-        #[cfg(not(feature = "parallel"))]
-        tokio::task::spawn_local(rt.drive());
-
-        // Give drive time to start.
-        tokio::time::sleep(Duration::from_secs_f64(0.01)).await;
+        // The runtime is driven by the task spawned on the executor, give it time to start.
+        sleep(Duration::from_secs_f64(0.01)).await;
 
         let number = Arc::new(AtomicUsize::new(0));
         let number_clone = number.clone();
+        let (tx, rx) = oneshot::channel::<()>();
 
         async_with!(&ctx => |ctx|{
             ctx.spawn(async move {
-                tokio::task::yield_now().await;
+                rx.await.unwrap();
                 number_clone.store(1,Ordering::SeqCst);
             });
         }).await;
         assert_eq!(number.load(Ordering::SeqCst),0);
+        tx.send(()).unwrap();
         // Give drive time to finish the task.
-        tokio::time::sleep(Duration::from_secs_f64(0.01)).await;
+        sleep(Duration::from_secs_f64(0.01)).await;
         assert_eq!(number.load(Ordering::SeqCst),1);
 
     });
 
-    async_test_case!(no_drive => (rt,ctx){
+    async_test_case!(no_drive => (_rt,ctx) undriven {
         use std::sync::{Arc, atomic::{Ordering,AtomicUsize}};
 
         let number = Arc::new(AtomicUsize::new(0));
@@ -456,66 +789,143 @@ mod test {
 
         async_with!(&ctx => |ctx|{
             ctx.spawn(async move {
-                tokio::task::yield_now().await;
+                yield_now().await;
                 number_clone.store(1,Ordering::SeqCst);
             });
         }).await;
         assert_eq!(number.load(Ordering::SeqCst),0);
-        tokio::time::sleep(Duration::from_secs_f64(0.01)).await;
+        sleep(Duration::from_secs_f64(0.01)).await;
         assert_eq!(number.load(Ordering::SeqCst),0);
 
     });
 
+    async_test_case!(execute_pending_jobs => (rt,ctx){
+        rt.set_auto_drive(false).await;
+        async_with!(&ctx => |ctx|{
+            ctx.eval::<(),_>(r#"
+                globalThis.count = 0;
+                for (let i = 0; i < 10; i++) {
+                    Promise.resolve().then(() => count++);
+                }
+            "#).unwrap();
+        }).await;
+        let count = || async_with!(&ctx => |ctx|{
+            ctx.globals().get::<_,usize>("count").unwrap()
+        });
+        assert_eq!(count().await,0);
+        assert_eq!(rt.execute_pending_jobs(3).await,3);
+        assert_eq!(count().await,3);
+        assert_eq!(rt.execute_pending_jobs(100).await,7);
+        assert_eq!(count().await,10);
+        assert!(!rt.is_job_pending().await);
+    });
+
+    async_test_case!(no_auto_drive => (rt,ctx){
+        use std::sync::{Arc, atomic::{Ordering,AtomicUsize}};
+
+        rt.set_auto_drive(false).await;
+        let number = Arc::new(AtomicUsize::new(0));
+        let number_clone = number.clone();
+
+        async_with!(&ctx => |ctx|{
+            ctx.spawn(async move {
+                yield_now().await;
+                number_clone.store(1,Ordering::SeqCst);
+            });
+        }).await;
+        sleep(Duration::from_secs_f64(0.01)).await;
+        assert_eq!(number.load(Ordering::SeqCst),0);
+        rt.idle().await;
+        assert_eq!(number.load(Ordering::SeqCst),1);
+    });
+
     async_test_case!(idle => (rt,ctx){
         use std::sync::{Arc, atomic::{Ordering,AtomicUsize}};
 
         let number = Arc::new(AtomicUsize::new(0));
         let number_clone = number.clone();
+        let (tx, rx) = oneshot::channel::<()>();
 
         async_with!(&ctx => |ctx|{
             ctx.spawn(async move {
-                tokio::task::yield_now().await;
+                rx.await.unwrap();
                 number_clone.store(1,Ordering::SeqCst);
             });
         }).await;
         assert_eq!(number.load(Ordering::SeqCst),0);
+        tx.send(()).unwrap();
         rt.idle().await;
         assert_eq!(number.load(Ordering::SeqCst),1);
 
     });
 
-    async_test_case!(recursive_spawn => (rt,ctx){
-        use tokio::sync::oneshot;
+    // Without the parallel feature futures are run on the current thread, so they don't need to
+    // be send.
+    #[cfg(not(feature = "parallel"))]
+    async_test_case!(local_state => (rt,ctx){
+        use std::{cell::RefCell, rc::Rc};
+        use crate::function::Async;
 
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let log_ref = &log;
+        async_with!(ctx => |ctx|{
+            let log = log_ref.clone();
+            let record = Function::new(ctx.clone(), Async(move |value: i32| {
+                let log = log.clone();
+                async move {
+                    yield_now().await;
+                    log.borrow_mut().push(value);
+                    Result::Ok(log.borrow().len())
+                }
+            }))
+            .unwrap();
+            ctx.globals().set("record", record).unwrap();
+
+            let log = log_ref.clone();
+            ctx.spawn(async move {
+                yield_now().await;
+                log.borrow_mut().push(0);
+            });
+
+            let promise: Promise = ctx.eval("record(1).then(() => record(2))").unwrap();
+            assert_eq!(promise.into_future::<usize>().await.unwrap(), 3);
+        }).await;
+        rt.idle().await;
+        let mut log = log.take();
+        log.sort();
+        assert_eq!(log, [0, 1, 2]);
+    });
+
+    async_test_case!(recursive_spawn => (rt,ctx){
         async_with!(&ctx => |ctx|{
             let ctx_clone = ctx.clone();
             let (tx,rx) = oneshot::channel::<()>();
             let (tx2,rx2) = oneshot::channel::<()>();
             ctx.spawn(async move {
-                tokio::task::yield_now().await;
+                yield_now().await;
 
                 let ctx = ctx_clone.clone();
 
                 ctx_clone.spawn(async move {
-                    tokio::task::yield_now().await;
+                    yield_now().await;
                     ctx.spawn(async move {
-                        tokio::task::yield_now().await;
+                        yield_now().await;
                         tx2.send(()).unwrap();
-                        tokio::task::yield_now().await;
+                        yield_now().await;
                     });
-                    tokio::task::yield_now().await;
+                    yield_now().await;
                     tx.send(()).unwrap();
                 });
 
                 // Add a bunch of futures just to make sure possible segfaults are more likely to
                 // happen
                 for _ in 0..32{
-                    ctx_clone.spawn(async move {})
+                    ctx_clone.spawn(async move {});
                 }
 
             });
-            tokio::time::timeout(Duration::from_millis(500), rx).await.unwrap().unwrap();
-            tokio::time::timeout(Duration::from_millis(500), rx2).await.unwrap().unwrap();
+            timeout(Duration::from_millis(500), rx).await.unwrap().unwrap();
+            timeout(Duration::from_millis(500), rx2).await.unwrap().unwrap();
         }).await;
 
     });
@@ -547,8 +957,9 @@ mod test {
         }
 
         fn set_timeout_spawn<'js>(ctx: Ctx<'js>, callback: Function<'js>, millis: usize) -> Result<()> {
+            let executor = ctx.executor().unwrap();
             ctx.spawn(async move {
-                tokio::time::sleep(Duration::from_millis(millis as u64)).await;
+                executor.sleep(Duration::from_millis(millis as u64)).await;
                 callback.call::<_, ()>(()).unwrap();
             });
 
@@ -575,7 +986,7 @@ mod test {
 
             match res.catch(&ctx){
                 Ok(promise) => {
-                    if let Err(err) = promise.into_future::<Value>().await.catch(&ctx){
+                    if let Err(err) = promise.into_future::<Value>().await{
                         eprintln!("{}", err)
                     }
                 },
@@ -1,14 +1,28 @@
 //! QuickJS runtime related types.
 
+#[cfg(feature = "loader")]
+use super::ImportMetaHandler;
 use super::{
-    opaque::Opaque, raw::RawRuntime, InterruptHandler, MemoryUsage, PromiseHook, RejectionTracker,
+    opaque::Opaque, raw::RawRuntime, DumpFlags, InterruptHandler, MemoryUsage, PromiseHook,
+    RejectionTracker, UserDataError,
 };
 use crate::allocator::Allocator;
+#[cfg(feature = "std")]
+use crate::context::EvalCache;
 #[cfg(feature = "loader")]
 use crate::loader::{Loader, Resolver};
-use crate::{result::JobException, Context, Mut, Ref, Result, Weak};
-use alloc::{ffi::CString, vec::Vec};
-use core::{ptr::NonNull, result::Result as StdResult};
+use crate::{
+    markers::{ParallelSend, ThreadMarker},
+    qjs,
+    result::JobException,
+    Context, Error, JsLifetime, Lock, Mut, Persistent, Ref, Result, Value, Weak,
+};
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+use alloc::{boxed::Box, ffi::CString, vec::Vec};
+use core::{any::Any, fmt, marker::PhantomData, ptr::NonNull, result::Result as StdResult};
+#[cfg(feature = "std")]
+use std::time::Duration;
 
 /// A weak handle to the runtime.
 ///
@@ -19,15 +33,29 @@ pub struct WeakRuntime(Weak<Mut<RawRuntime>>);
 
 impl WeakRuntime {
     pub fn try_ref(&self) -> Option<Runtime> {
-        self.0.upgrade().map(|inner| Runtime { inner })
+        self.0.upgrade().map(|inner| Runtime {
+            inner,
+            _thread: PhantomData,
+        })
     }
 }
 
+/// A lock on a [`Runtime`] acquired with [`Runtime::try_lock`].
+///
+/// Other threads trying to use the runtime block until the lock is dropped.
+pub struct RuntimeLock<'a> {
+    _lock: Lock<'a, RawRuntime>,
+}
+
 /// QuickJS runtime, entry point of the library.
+///
+/// Without the `"parallel"` feature the runtime and all types using it are `!Send` and can't be
+/// moved to another thread.
 #[derive(Clone)]
 #[repr(transparent)]
 pub struct Runtime {
     pub(crate) inner: Ref<Mut<RawRuntime>>,
+    _thread: ThreadMarker,
 }
 
 impl Runtime {
@@ -42,6 +70,7 @@ impl Runtime {
         let rt = unsafe { RawRuntime::new(opaque)? };
         Ok(Self {
             inner: Ref::new(Mut::new(rt)),
+            _thread: PhantomData,
         })
     }
 
@@ -56,6 +85,7 @@ impl Runtime {
         let rt = unsafe { RawRuntime::new_with_allocator(opaque, allocator)? };
         Ok(Self {
             inner: Ref::new(Mut::new(rt)),
+            _thread: PhantomData,
         })
     }
 
@@ -82,6 +112,16 @@ impl Runtime {
         }
     }
 
+    /// Try to lock the runtime without blocking.
+    ///
+    /// Returns [`Error::WouldBlock`] if the runtime is currently locked, including when it is
+    /// locked by the calling thread, for example from within [`Context::with`]. Locking the
+    /// runtime again from the thread which holds the lock would otherwise dead lock.
+    pub fn try_lock(&self) -> Result<RuntimeLock<'_>> {
+        let lock = self.inner.try_lock_now().ok_or(Error::WouldBlock)?;
+        Ok(RuntimeLock { _lock: lock })
+    }
+
     /// Set a closure which is regularly called by the engine when it is executing code.
     /// If the provided closure returns `true` the interpreter will raise and uncatchable
     /// exception and return control flow to the caller.
@@ -92,6 +132,47 @@ impl Runtime {
         }
     }
 
+    /// Set the time after which evaluations with [`Ctx::eval_catching_interrupt`] are
+    /// interrupted, `None` disables the timeout.
+    ///
+    /// The timeout applies to each evaluation separately and works alongside an interrupt
+    /// handler set with [`Runtime::set_interrupt_handler`].
+    ///
+    /// [`Ctx::eval_catching_interrupt`]: crate::Ctx::eval_catching_interrupt
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub fn set_execution_timeout(&self, timeout: Option<Duration>) {
+        unsafe {
+            self.inner.lock().set_execution_timeout(timeout);
+        }
+    }
+
+    /// Enable a cache of compiled bytecode shared by all contexts of the runtime.
+    ///
+    /// The cache holds at most `capacity` compiled scripts, evicting the least recently used one
+    /// when full, and is used by [`Ctx::eval_cached`](crate::Ctx::eval_cached) in contexts which
+    /// don't have their own cache attached with
+    /// [`Context::set_eval_cache`](crate::Context::set_eval_cache). Replaces a previously enabled
+    /// cache and returns the new one, for example to retrieve its statistics.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub fn enable_bytecode_cache(&self, capacity: usize) -> Arc<EvalCache> {
+        let cache = Arc::new(EvalCache::with_capacity(capacity));
+        self.inner
+            .lock()
+            .get_opaque()
+            .set_bytecode_cache(Some(cache.clone()));
+        cache
+    }
+
+    /// Disable the cache of compiled bytecode enabled with [`Runtime::enable_bytecode_cache`],
+    /// returning it if there was one.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub fn disable_bytecode_cache(&self) -> Option<Arc<EvalCache>> {
+        self.inner.lock().get_opaque().set_bytecode_cache(None)
+    }
+
     /// Set the module loader
     #[cfg(feature = "loader")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
@@ -105,6 +186,45 @@ impl Runtime {
         }
     }
 
+    /// Set a closure which is called with the name and the `import.meta` object of every module
+    /// loaded by the module loader, before the module is evaluated.
+    ///
+    /// Can be used to set `import.meta.url`, `import.meta.main` or any other field. An error
+    /// returned from the handler fails the import of the module.
+    #[cfg(feature = "loader")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+    pub fn set_import_meta_handler(&self, handler: Option<ImportMetaHandler>) {
+        self.inner
+            .lock()
+            .get_opaque()
+            .set_import_meta_handler(handler);
+    }
+
+    /// Store host state in the runtime which can be retrieved with
+    /// [`Ctx::userdata`](crate::Ctx::userdata) by any callback with access to a context.
+    ///
+    /// The data is stored per type and can't contain JavaScript values. Returns the previous
+    /// value of the type if there was one, or the argument if the userdata is currently being
+    /// accessed and insertion is not possible.
+    pub fn store_userdata<U>(&self, data: U) -> StdResult<Option<Box<U>>, UserDataError<U>>
+    where
+        U: JsLifetime<'static> + ParallelSend,
+        U::Changed<'static>: Any,
+    {
+        self.inner.lock().get_opaque().insert_userdata(data)
+    }
+
+    /// Remove the host state of the given type stored with [`Runtime::store_userdata`].
+    ///
+    /// Returns an error if the userdata is currently being accessed and removing isn't possible.
+    pub fn remove_userdata<U>(&self) -> StdResult<Option<Box<U>>, UserDataError<()>>
+    where
+        U: JsLifetime<'static>,
+        U::Changed<'static>: Any,
+    {
+        self.inner.lock().get_opaque().remove_userdata()
+    }
+
     /// Set the info of the runtime
     pub fn set_info<S: Into<Vec<u8>>>(&self, info: S) -> Result<()> {
         let string = CString::new(info)?;
@@ -142,13 +262,21 @@ impl Runtime {
         }
     }
 
-    /// Set debug flags for dumping memory
-    pub fn set_dump_flags(&self, flags: u64) {
+    /// Set the flags selecting which QuickJS debug dumps are written to stdout.
+    ///
+    /// Runtimes start with the flags of the enabled `dump-*` features, see
+    /// [`DumpFlags::from_features`].
+    pub fn set_dump_flags(&self, flags: DumpFlags) {
         unsafe {
             self.inner.lock().set_dump_flags(flags);
         }
     }
 
+    /// Get the flags selecting which QuickJS debug dumps are written to stdout.
+    pub fn dump_flags(&self) -> DumpFlags {
+        unsafe { self.inner.lock().dump_flags() }
+    }
+
     /// Manually run the garbage collection.
     ///
     /// Most of QuickJS values are reference counted and
@@ -169,6 +297,9 @@ impl Runtime {
     /// Test for pending jobs
     ///
     /// Returns true when at least one job is pending.
+    ///
+    /// QuickJS doesn't expose the number of pending jobs.
+    #[doc(alias = "has_pending_jobs")]
     #[inline]
     pub fn is_job_pending(&self) -> bool {
         self.inner.lock().is_job_pending()
@@ -181,15 +312,54 @@ impl Runtime {
     pub fn execute_pending_job(&self) -> StdResult<bool, JobException> {
         let mut lock = self.inner.lock();
         lock.update_stack_top();
+        lock.drop_pending();
         lock.execute_pending_job().map_err(|e| {
-            JobException(unsafe {
-                Context::from_raw(
-                    NonNull::new(e).expect("QuickJS returned null ptr for job error"),
-                    self.clone(),
-                )
-            })
+            let ptr = NonNull::new(e).expect("QuickJS returned null ptr for job error");
+            // The context is borrowed from the job, the exception needs its own reference.
+            unsafe { qjs::JS_DupContext(ptr.as_ptr()) };
+            JobException(unsafe { Context::from_raw(ptr, self.clone()) })
         })
     }
+
+    /// Execute first pending job, catching the exception if it threw.
+    ///
+    /// Unlike [`Runtime::execute_pending_job`] the exception is removed from the context, so it
+    /// isn't returned by a later call to [`Ctx::catch`](crate::Ctx::catch).
+    pub fn run_pending_job(&self) -> JobStatus {
+        match self.execute_pending_job() {
+            Ok(true) => JobStatus::Executed,
+            Ok(false) => JobStatus::Empty,
+            Err(JobException(context)) => {
+                let exception = context.with(|ctx| Persistent::save(&ctx, ctx.catch()));
+                JobStatus::Error { context, exception }
+            }
+        }
+    }
+}
+
+/// The result of [`Runtime::run_pending_job`].
+pub enum JobStatus {
+    /// A job was executed.
+    Executed,
+    /// There was no pending job.
+    Empty,
+    /// The job threw an exception.
+    Error {
+        /// The context the job was executed in.
+        context: Context,
+        /// The thrown value, restore it with the context.
+        exception: Persistent<Value<'static>>,
+    },
+}
+
+impl fmt::Debug for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Executed => f.write_str("Executed"),
+            JobStatus::Empty => f.write_str("Empty"),
+            JobStatus::Error { .. } => f.write_str("Error"),
+        }
+    }
 }
 
 // Since all functions which use runtime are behind a mutex
@@ -218,4 +388,66 @@ mod test {
         rt.set_gc_threshold(0xFF);
         rt.run_gc();
     }
+
+    #[test]
+    fn userdata() {
+        use crate::{Ctx, Function};
+
+        struct AppState(usize);
+
+        unsafe impl<'js> JsLifetime<'js> for AppState {
+            type Changed<'to> = AppState;
+        }
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        assert!(rt.store_userdata(AppState(1)).unwrap().is_none());
+        assert_eq!(rt.store_userdata(AppState(2)).unwrap().unwrap().0, 1);
+        ctx.with(|ctx| {
+            let count = Function::new(ctx.clone(), |ctx: Ctx| {
+                ctx.userdata::<AppState>().map(|x| x.0)
+            })
+            .unwrap();
+            assert_eq!(count.call::<_, Option<usize>>(()).unwrap(), Some(2));
+        });
+        assert_eq!(rt.remove_userdata::<AppState>().unwrap().unwrap().0, 2);
+        ctx.with(|ctx| assert!(ctx.userdata::<AppState>().is_none()));
+    }
+
+    #[test]
+    fn dump_flags() {
+        let rt = Runtime::new().unwrap();
+        assert_eq!(rt.dump_flags(), DumpFlags::from_features());
+
+        let flags = DumpFlags::GC | DumpFlags::PROMISE;
+        rt.set_dump_flags(flags);
+        assert_eq!(rt.dump_flags(), flags);
+        assert!(flags.contains(DumpFlags::GC) && !flags.contains(DumpFlags::LEAKS));
+
+        rt.set_dump_flags(DumpFlags::NONE);
+        assert!(rt.dump_flags().is_empty());
+    }
+
+    #[test]
+    fn run_pending_job() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        assert!(matches!(rt.run_pending_job(), JobStatus::Empty));
+
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>("queueMicrotask(() => { throw 42 }); queueMicrotask(() => {})")
+                .unwrap()
+        });
+        assert!(rt.is_job_pending());
+        let JobStatus::Error { context, exception } = rt.run_pending_job() else {
+            panic!("job didn't throw");
+        };
+        context.with(|ctx| {
+            assert_eq!(exception.restore(&ctx).unwrap().as_int(), Some(42));
+            assert_eq!(ctx.catch().type_of(), crate::Type::Uninitialized);
+        });
+        assert!(matches!(rt.run_pending_job(), JobStatus::Executed));
+        assert!(matches!(rt.run_pending_job(), JobStatus::Empty));
+        assert!(!rt.is_job_pending());
+    }
 }
@@ -0,0 +1,176 @@
+use alloc::vec::Vec;
+use core::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{Mut, Ref, Weak};
+
+struct JobState {
+    aborted: bool,
+    finished: bool,
+    waker: Option<Waker>,
+}
+
+/// A handle to a future spawned inside an async runtime with [`Ctx::spawn`](crate::Ctx::spawn).
+///
+/// Dropping the handle does not abort the future.
+#[derive(Clone)]
+pub struct JobHandle(Ref<Mut<JobState>>);
+
+impl JobHandle {
+    fn new() -> Self {
+        JobHandle(Ref::new(Mut::new(JobState {
+            aborted: false,
+            finished: false,
+            waker: None,
+        })))
+    }
+
+    /// Abort the future.
+    ///
+    /// The future is not polled again and is dropped the next time the runtime is driven.
+    pub fn abort(&self) {
+        let waker = {
+            let mut state = self.0.lock();
+            if state.finished {
+                return;
+            }
+            state.aborted = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Returns whether the future has completed or was dropped.
+    pub fn is_finished(&self) -> bool {
+        self.0.lock().finished
+    }
+}
+
+/// A future which can be aborted through a [`JobHandle`].
+pub(crate) struct Abortable<F> {
+    future: F,
+    handle: JobHandle,
+}
+
+impl<F> Abortable<F> {
+    pub fn new(future: F) -> (Self, JobHandle) {
+        let handle = JobHandle::new();
+        let this = Abortable {
+            future,
+            handle: handle.clone(),
+        };
+        (this, handle)
+    }
+}
+
+impl<F: Future<Output = ()>> Future for Abortable<F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // SAFETY: The future is never moved out of the struct.
+        let this = unsafe { self.get_unchecked_mut() };
+        {
+            let mut state = this.handle.0.lock();
+            if state.aborted {
+                return Poll::Ready(());
+            }
+            if !state
+                .waker
+                .as_ref()
+                .is_some_and(|x| x.will_wake(cx.waker()))
+            {
+                state.waker = Some(cx.waker().clone());
+            }
+        }
+        // The lock must not be held while polling as the future might abort itself.
+        unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+    }
+}
+
+impl<F> Drop for Abortable<F> {
+    fn drop(&mut self) {
+        let mut state = self.handle.0.lock();
+        state.finished = true;
+        state.waker = None;
+    }
+}
+
+struct TokenState {
+    cancelled: bool,
+    owners: usize,
+    jobs: Vec<Weak<Mut<JobState>>>,
+}
+
+/// A token which aborts all futures spawned inside a context once cancelled.
+///
+/// Every [`AsyncContext`](crate::AsyncContext) has a token which is cancelled when the last
+/// handle to the context is dropped or when [`AsyncContext::abort`](crate::AsyncContext::abort)
+/// is called.
+#[derive(Clone)]
+pub struct CancellationToken(Ref<Mut<TokenState>>);
+
+impl CancellationToken {
+    pub(crate) fn new() -> Self {
+        CancellationToken(Ref::new(Mut::new(TokenState {
+            cancelled: false,
+            owners: 0,
+            jobs: Vec::new(),
+        })))
+    }
+
+    /// Cancel the token, aborting all futures spawned inside the context.
+    ///
+    /// Futures spawned inside the context afterwards are aborted right away.
+    pub fn cancel(&self) {
+        let jobs = {
+            let mut state = self.0.lock();
+            state.cancelled = true;
+            mem::take(&mut state.jobs)
+        };
+        jobs.iter()
+            .filter_map(Weak::upgrade)
+            .for_each(|x| JobHandle(x).abort());
+    }
+
+    /// Returns whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.lock().cancelled
+    }
+
+    /// Abort the job when the token is cancelled.
+    pub(crate) fn register(&self, job: &JobHandle) {
+        let mut state = self.0.lock();
+        if state.cancelled {
+            mem::drop(state);
+            job.abort();
+            return;
+        }
+        // Only remove the finished jobs when growing to keep registering amortized constant.
+        if state.jobs.len() == state.jobs.capacity() {
+            state.jobs.retain(|x| x.strong_count() > 0);
+        }
+        state.jobs.push(Ref::downgrade(&job.0));
+    }
+
+    pub(crate) fn acquire(&self) {
+        self.0.lock().owners += 1;
+    }
+
+    /// Release an owner of the context, cancelling the token if it was the last one.
+    pub(crate) fn release(&self) {
+        let last = {
+            let mut state = self.0.lock();
+            state.owners -= 1;
+            state.owners == 0
+        };
+        if last {
+            self.cancel();
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use core::ops::{BitAnd, BitOr, BitOrAssign};
+
+/// Flags selecting which QuickJS debug dumps are written to stdout.
+///
+/// Dumps are only available if QuickJS was compiled with dump support, which is the case for
+/// debug builds and when any of the `dump-*` features is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct DumpFlags(u64);
+
+impl DumpFlags {
+    /// No dumps.
+    pub const NONE: Self = Self(0);
+    /// Dump the final bytecode of compiled functions.
+    pub const BYTECODE_FINAL: Self = Self(0x01);
+    /// Dump the bytecode after the second compilation pass.
+    pub const BYTECODE_PASS2: Self = Self(0x02);
+    /// Dump the bytecode after the first compilation pass.
+    pub const BYTECODE_PASS1: Self = Self(0x04);
+    /// Dump the bytecode in hex.
+    pub const BYTECODE_HEX: Self = Self(0x10);
+    /// Dump the line number table.
+    pub const BYTECODE_PC2LINE: Self = Self(0x20);
+    /// Dump the stack size computation.
+    pub const BYTECODE_STACK: Self = Self(0x40);
+    /// Dump every executed bytecode instruction.
+    pub const BYTECODE_STEP: Self = Self(0x80);
+    /// Dump objects read from serialized bytecode.
+    pub const READ_OBJECT: Self = Self(0x100);
+    /// Dump every freed object.
+    pub const FREE: Self = Self(0x200);
+    /// Dump each run of the garbage collector.
+    pub const GC: Self = Self(0x400);
+    /// Dump objects freed by the garbage collector.
+    pub const GC_FREE: Self = Self(0x800);
+    /// Dump module resolution steps.
+    pub const MODULE_RESOLVE: Self = Self(0x1000);
+    /// Dump promise steps.
+    pub const PROMISE: Self = Self(0x2000);
+    /// Dump leaked objects and strings when the runtime is freed.
+    pub const LEAKS: Self = Self(0x4000);
+    /// Dump leaked atoms when the runtime is freed.
+    pub const ATOM_LEAKS: Self = Self(0x8000);
+    /// Dump memory usage when the runtime is freed.
+    pub const MEM: Self = Self(0x10000);
+    /// Dump remaining objects when the runtime is freed.
+    pub const OBJECTS: Self = Self(0x20000);
+    /// Dump remaining atoms when the runtime is freed.
+    pub const ATOMS: Self = Self(0x40000);
+    /// Dump remaining shapes when the runtime is freed.
+    pub const SHAPES: Self = Self(0x80000);
+
+    /// The bytecode of all compilation passes, as enabled by the `dump-bytecode` feature.
+    pub const BYTECODE: Self =
+        Self(Self::BYTECODE_FINAL.0 | Self::BYTECODE_PASS2.0 | Self::BYTECODE_PASS1.0);
+
+    /// Create flags from their raw bits.
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits of the flags.
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns if no flags are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns if all the given flags are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// The flags enabled by the `dump-*` features the crate was compiled with.
+    ///
+    /// New runtimes start with these flags set.
+    pub const fn from_features() -> Self {
+        #[allow(unused_mut)]
+        let mut flags = Self::NONE.0;
+
+        #[cfg(feature = "dump-bytecode")]
+        {
+            flags |= Self::BYTECODE.0;
+        }
+
+        #[cfg(feature = "dump-gc")]
+        {
+            flags |= Self::GC.0;
+        }
+
+        #[cfg(feature = "dump-gc-free")]
+        {
+            flags |= Self::GC_FREE.0;
+        }
+
+        #[cfg(feature = "dump-free")]
+        {
+            flags |= Self::FREE.0;
+        }
+
+        #[cfg(feature = "dump-leaks")]
+        {
+            flags |= Self::LEAKS.0;
+        }
+
+        #[cfg(feature = "dump-mem")]
+        {
+            flags |= Self::MEM.0;
+        }
+
+        #[cfg(feature = "dump-objects")]
+        {
+            flags |= Self::OBJECTS.0;
+        }
+
+        #[cfg(feature = "dump-atoms")]
+        {
+            flags |= Self::ATOMS.0;
+        }
+
+        #[cfg(feature = "dump-shapes")]
+        {
+            flags |= Self::SHAPES.0;
+        }
+
+        #[cfg(feature = "dump-module-resolve")]
+        {
+            flags |= Self::MODULE_RESOLVE.0;
+        }
+
+        #[cfg(feature = "dump-promise")]
+        {
+            flags |= Self::PROMISE.0;
+        }
+
+        #[cfg(feature = "dump-read-object")]
+        {
+            flags |= Self::READ_OBJECT.0;
+        }
+
+        Self(flags)
+    }
+}
+
+impl BitOr for DumpFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for DumpFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for DumpFlags {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
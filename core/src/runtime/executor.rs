@@ -0,0 +1,124 @@
+use alloc::boxed::Box;
+use core::{future::Future, pin::Pin, time::Duration};
+
+use crate::markers::{ParallelSend, ParallelSync};
+
+/// A boxed future which can be moved between threads.
+pub type SendBoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// A boxed future which has to stay on the thread it was spawned on.
+pub type LocalBoxFuture = Pin<Box<dyn Future<Output = ()> + 'static>>;
+
+/// An interface to the async executor an [`AsyncRuntime`](crate::AsyncRuntime) runs on.
+///
+/// Implement this trait to run the runtime on any executor, see
+/// [`AsyncRuntime::new_with_spawner`](crate::AsyncRuntime::new_with_spawner).
+/// Implementations for tokio and async-std are available behind the `"tokio"` and
+/// `"async-std"` features.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub trait ExecutorSpawner: ParallelSend + ParallelSync + 'static {
+    /// Spawn a future which may be run on any thread of the executor.
+    fn spawn(&self, future: SendBoxFuture);
+
+    /// Spawn a future which has to be run on the current thread.
+    fn spawn_local(&self, future: LocalBoxFuture);
+
+    /// Returns a future which completes after the given duration.
+    fn sleep(&self, duration: Duration) -> SendBoxFuture;
+}
+
+/// An [`ExecutorSpawner`] for the tokio runtime.
+///
+/// Must be used from within a tokio runtime. Local futures are spawned with
+/// `tokio::task::spawn_local` which requires a running `LocalSet`.
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "tokio")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioSpawner;
+
+#[cfg(feature = "tokio")]
+impl ExecutorSpawner for TokioSpawner {
+    fn spawn(&self, future: SendBoxFuture) {
+        tokio::spawn(future);
+    }
+
+    fn spawn_local(&self, future: LocalBoxFuture) {
+        tokio::task::spawn_local(future);
+    }
+
+    fn sleep(&self, duration: Duration) -> SendBoxFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// An [`ExecutorSpawner`] for the async-std runtime.
+///
+/// Local futures are run by the thread local executor which is driven by
+/// `async_std::task::block_on`.
+#[cfg(feature = "async-std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "async-std")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncStdSpawner;
+
+#[cfg(feature = "async-std")]
+impl ExecutorSpawner for AsyncStdSpawner {
+    fn spawn(&self, future: SendBoxFuture) {
+        async_std::task::spawn(future);
+    }
+
+    fn spawn_local(&self, future: LocalBoxFuture) {
+        // async-std only exposes `spawn_local` behind its unstable feature.
+        async_global_executor::spawn_local(future).detach();
+    }
+
+    fn sleep(&self, duration: Duration) -> SendBoxFuture {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+#[cfg(all(test, any(feature = "tokio", feature = "async-std")))]
+mod test {
+    use core::time::Duration;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::ExecutorSpawner;
+    use crate::{async_with, AsyncContext, AsyncRuntime};
+
+    /// Checks that futures spawned inside the runtime complete without awaiting the runtime.
+    async fn drives_runtime<S: ExecutorSpawner + Clone>(spawner: S) {
+        let rt = AsyncRuntime::new_with_spawner(spawner.clone()).unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        let done = Arc::new(AtomicBool::new(false));
+        let done_clone = done.clone();
+        async_with!(ctx => |ctx| {
+            let executor = ctx.executor().unwrap();
+            ctx.spawn(async move {
+                executor.sleep(Duration::from_millis(1)).await;
+                done_clone.store(true, Ordering::SeqCst);
+            });
+        })
+        .await;
+        spawner.sleep(Duration::from_millis(50)).await;
+        assert!(done.load(Ordering::SeqCst));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_spawner() {
+        #[cfg(feature = "parallel")]
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        #[cfg(not(feature = "parallel"))]
+        let mut builder = tokio::runtime::Builder::new_current_thread();
+        let rt = builder.enable_all().build().unwrap();
+        tokio::task::LocalSet::new().block_on(&rt, drives_runtime(super::TokioSpawner));
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn async_std_spawner() {
+        async_std::task::block_on(drives_runtime(super::AsyncStdSpawner));
+    }
+}
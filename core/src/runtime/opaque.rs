@@ -1,13 +1,19 @@
 use crate::{
     class::{self, ffi::VTable, JsClass},
-    qjs, Ctx, Error, JsLifetime, Object, Value,
+    module::NativeExport,
+    qjs, Ctx, Error, JsLifetime, Object, Ref, Symbol, Value,
 };
 
 use super::{
     userdata::{UserDataGuard, UserDataMap},
     InterruptHandler, PromiseHook, PromiseHookType, RejectionTracker, UserDataError,
 };
-use alloc::boxed::Box;
+use alloc::{
+    boxed::Box,
+    ffi::CString,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
 use core::{
     any::{Any, TypeId},
     cell::{Cell, UnsafeCell},
@@ -15,14 +21,28 @@ use core::{
     ptr,
 };
 
+#[cfg(feature = "loader")]
+use super::ImportMetaHandler;
+#[cfg(feature = "std")]
+use crate::{context::EvalCache, persistent::DropQueue};
 #[cfg(feature = "std")]
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::{Duration, Instant},
+};
 
 #[cfg(not(feature = "std"))]
 use hashbrown::{hash_map::Entry, HashMap};
 
 #[cfg(feature = "futures")]
-use super::{schedular::SchedularPoll, spawner::Spawner};
+use super::{
+    cancel::{Abortable, CancellationToken, JobHandle},
+    schedular::SchedularPoll,
+    spawner::Spawner,
+    ExecutorSpawner,
+};
+#[cfg(feature = "futures")]
+use crate::Mut;
 
 #[cfg(feature = "futures")]
 use core::{
@@ -44,18 +64,81 @@ pub(crate) struct Opaque<'js> {
     /// The user provided interrupt handler, if any.
     interrupt_handler: UnsafeCell<Option<InterruptHandler>>,
 
+    /// The user provided `import.meta` handler, if any.
+    #[cfg(feature = "loader")]
+    import_meta_handler: UnsafeCell<Option<ImportMetaHandler>>,
+
+    /// The execution timeout of evaluations which can be interrupted, if any.
+    #[cfg(feature = "std")]
+    execution_timeout: Cell<Option<Duration>>,
+    /// The deadline of the current evaluation which can be interrupted, if any.
+    #[cfg(feature = "std")]
+    deadline: Cell<Option<Instant>>,
+    /// Whether the current evaluation was interrupted because the deadline passed.
+    #[cfg(feature = "std")]
+    timed_out: Cell<bool>,
+
     /// The class id for rust classes.
     class_id: qjs::JSClassID,
     /// The class id for rust classes which can be called.
     callable_class_id: qjs::JSClassID,
+    /// The class id for rust classes with exotic property behavior.
+    exotic_class_id: qjs::JSClassID,
+    /// The class id for rust classes with exotic property behavior which also intercept reads and
+    /// assignments.
+    exotic_hooks_class_id: qjs::JSClassID,
 
     prototypes: UnsafeCell<HashMap<TypeId, Option<Object<'js>>>>,
 
+    /// The key of the Rust data attached to objects with `Object::set_opaque`.
+    object_data_key: UnsafeCell<Option<Symbol<'js>>>,
+
     userdata: UserDataMap,
 
+    /// The exports of native modules built with closures which were declared but not evaluated.
+    native_modules: UnsafeCell<HashMap<*mut qjs::JSModuleDef, Ref<[NativeExport]>>>,
+
+    /// The data of array buffers created from byte vectors, which can be moved out of the buffer
+    /// when it is detached, mapped to whether the buffer is currently being detached.
+    byte_buffers: UnsafeCell<HashMap<*mut u8, bool>>,
+    /// The data moved out of the array buffer which was detached last.
+    detached_bytes: Cell<Option<Vec<u8>>>,
+
+    /// Source names of the scripts currently being evaluated from Rust, innermost last.
+    source_names: UnsafeCell<Vec<CString>>,
+
+    /// Token whose weak handles are used to check if the runtime is still alive.
+    alive: Arc<()>,
+
+    /// Values of dropped shared persistent handles waiting to be freed.
+    #[cfg(feature = "std")]
+    drop_queue: Arc<DropQueue>,
+
+    /// The eval caches attached to contexts of this runtime.
+    #[cfg(feature = "std")]
+    eval_caches: UnsafeCell<HashMap<*mut qjs::JSContext, Arc<EvalCache>>>,
+
+    /// The bytecode cache shared by all contexts of this runtime.
+    #[cfg(feature = "std")]
+    bytecode_cache: UnsafeCell<Option<Arc<EvalCache>>>,
+
     #[cfg(feature = "futures")]
     spawner: Option<UnsafeCell<Spawner>>,
 
+    /// The executor the runtime runs on, if any.
+    #[cfg(feature = "futures")]
+    executor: Option<Arc<dyn ExecutorSpawner>>,
+
+    /// Whether jobs and spawned futures are driven automatically.
+    #[cfg(feature = "futures")]
+    auto_drive: Cell<bool>,
+
+    /// The tokens aborting the futures spawned inside contexts of this runtime.
+    ///
+    /// Behind a lock as async contexts are created and dropped without locking the runtime.
+    #[cfg(feature = "futures")]
+    cancellation_tokens: Mut<HashMap<*mut qjs::JSContext, CancellationToken>>,
+
     _marker: PhantomData<&'js ()>,
 }
 
@@ -70,17 +153,57 @@ impl<'js> Opaque<'js> {
 
             interrupt_handler: UnsafeCell::new(None),
 
+            #[cfg(feature = "loader")]
+            import_meta_handler: UnsafeCell::new(None),
+
+            #[cfg(feature = "std")]
+            execution_timeout: Cell::new(None),
+            #[cfg(feature = "std")]
+            deadline: Cell::new(None),
+            #[cfg(feature = "std")]
+            timed_out: Cell::new(false),
+
             class_id: qjs::JS_INVALID_CLASS_ID,
             callable_class_id: qjs::JS_INVALID_CLASS_ID,
+            exotic_class_id: qjs::JS_INVALID_CLASS_ID,
+            exotic_hooks_class_id: qjs::JS_INVALID_CLASS_ID,
 
             prototypes: UnsafeCell::new(HashMap::new()),
 
+            object_data_key: UnsafeCell::new(None),
+
             userdata: UserDataMap::default(),
 
+            native_modules: UnsafeCell::new(HashMap::new()),
+            byte_buffers: UnsafeCell::new(HashMap::new()),
+            detached_bytes: Cell::new(None),
+
+            source_names: UnsafeCell::new(Vec::new()),
+
+            alive: Arc::new(()),
+
+            #[cfg(feature = "std")]
+            drop_queue: Arc::new(DropQueue::new()),
+
+            #[cfg(feature = "std")]
+            eval_caches: UnsafeCell::new(HashMap::new()),
+
+            #[cfg(feature = "std")]
+            bytecode_cache: UnsafeCell::new(None),
+
             _marker: PhantomData,
 
             #[cfg(feature = "futures")]
             spawner: None,
+
+            #[cfg(feature = "futures")]
+            executor: None,
+
+            #[cfg(feature = "futures")]
+            auto_drive: Cell::new(true),
+
+            #[cfg(feature = "futures")]
+            cancellation_tokens: Mut::new(HashMap::new()),
         }
     }
 
@@ -91,9 +214,23 @@ impl<'js> Opaque<'js> {
         this
     }
 
+    #[cfg(feature = "futures")]
+    pub fn with_executor(executor: Arc<dyn ExecutorSpawner>) -> Self {
+        let mut this = Self::with_spawner();
+        this.executor = Some(executor);
+        this
+    }
+
+    #[cfg(feature = "futures")]
+    pub fn executor(&self) -> Option<Arc<dyn ExecutorSpawner>> {
+        self.executor.clone()
+    }
+
     pub unsafe fn initialize(&mut self, rt: *mut qjs::JSRuntime) -> Result<(), Error> {
         qjs::JS_NewClassID(rt, (&mut self.class_id) as *mut qjs::JSClassID);
         qjs::JS_NewClassID(rt, (&mut self.callable_class_id) as *mut qjs::JSClassID);
+        qjs::JS_NewClassID(rt, (&mut self.exotic_class_id) as *mut qjs::JSClassID);
+        qjs::JS_NewClassID(rt, (&mut self.exotic_hooks_class_id) as *mut qjs::JSClassID);
 
         let class_def = qjs::JSClassDef {
             class_name: c"RustClass".as_ptr().cast(),
@@ -119,6 +256,28 @@ impl<'js> Opaque<'js> {
             return Err(Error::Unknown);
         }
 
+        let class_def = qjs::JSClassDef {
+            class_name: c"RustClass".as_ptr().cast(),
+            finalizer: Some(class::ffi::exotic_finalizer),
+            gc_mark: Some(class::ffi::exotic_trace),
+            call: None,
+            // QuickJS never writes through this pointer.
+            exotic: ptr::addr_of!(class::ffi::EXOTIC_METHODS).cast_mut(),
+        };
+
+        if 0 != qjs::JS_NewClass(rt, self.exotic_class_id, &class_def) {
+            return Err(Error::Unknown);
+        }
+
+        let class_def = qjs::JSClassDef {
+            exotic: ptr::addr_of!(class::ffi::EXOTIC_HOOK_METHODS).cast_mut(),
+            ..class_def
+        };
+
+        if 0 != qjs::JS_NewClass(rt, self.exotic_hooks_class_id, &class_def) {
+            return Err(Error::Unknown);
+        }
+
         Ok(())
     }
 
@@ -134,11 +293,32 @@ impl<'js> Opaque<'js> {
     }
 
     #[cfg(feature = "futures")]
-    pub unsafe fn push<F>(&self, f: F)
+    pub unsafe fn push<F>(&self, ctx: *mut qjs::JSContext, f: F) -> JobHandle
     where
         F: Future<Output = ()>,
     {
-        (*self.spawner().get()).push(f)
+        let (f, handle) = Abortable::new(f);
+        if let Some(token) = self.cancellation_tokens.lock().get(&ctx) {
+            token.register(&handle);
+        }
+        (*self.spawner().get()).push(f);
+        handle
+    }
+
+    /// Set the cancellation token of a newly created context.
+    #[cfg(feature = "futures")]
+    pub fn set_cancellation_token(&self, ctx: *mut qjs::JSContext, token: CancellationToken) {
+        self.cancellation_tokens.lock().insert(ctx, token);
+    }
+
+    /// Returns the cancellation token of a context, creating one if it doesn't have one yet.
+    #[cfg(feature = "futures")]
+    pub fn cancellation_token(&self, ctx: *mut qjs::JSContext) -> CancellationToken {
+        self.cancellation_tokens
+            .lock()
+            .entry(ctx)
+            .or_insert_with(CancellationToken::new)
+            .clone()
     }
 
     #[cfg(feature = "futures")]
@@ -156,6 +336,20 @@ impl<'js> Opaque<'js> {
         unsafe { (*self.spawner().get()).poll(cx) }
     }
 
+    #[cfg(feature = "futures")]
+    pub fn auto_drive(&self) -> bool {
+        self.auto_drive.get()
+    }
+
+    #[cfg(feature = "futures")]
+    pub fn set_auto_drive(&self, enabled: bool) {
+        self.auto_drive.set(enabled);
+        if enabled {
+            // Wake up the drive futures which are waiting for the runtime to be driven again.
+            unsafe { (*self.spawner().get()).wake() };
+        }
+    }
+
     pub fn insert_userdata<U>(&self, data: U) -> Result<Option<Box<U>>, UserDataError<U>>
     where
         U: JsLifetime<'js>,
@@ -214,8 +408,59 @@ impl<'js> Opaque<'js> {
         unsafe { (*self.interrupt_handler.get()) = interupt }
     }
 
+    /// Returns whether the interpreter needs to call [`Opaque::run_interrupt_handler`].
+    pub fn has_interrupt_handler(&self) -> bool {
+        #[cfg(feature = "std")]
+        if self.execution_timeout.get().is_some() {
+            return true;
+        }
+        unsafe { (*self.interrupt_handler.get()).is_some() }
+    }
+
     pub fn run_interrupt_handler(&self) -> bool {
-        unsafe { (*self.interrupt_handler.get()).as_mut().unwrap()() }
+        #[cfg(feature = "std")]
+        if self
+            .deadline
+            .get()
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out.set(true);
+            return true;
+        }
+        unsafe {
+            (*self.interrupt_handler.get())
+                .as_mut()
+                .is_some_and(|handler| handler())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn set_execution_timeout(&self, timeout: Option<Duration>) {
+        self.execution_timeout.set(timeout)
+    }
+
+    /// Start the deadline of an evaluation which can be interrupted.
+    ///
+    /// Returns `false` if a deadline was already running, which then also applies to the nested
+    /// evaluation.
+    #[cfg(feature = "std")]
+    pub fn start_deadline(&self) -> bool {
+        if self.deadline.get().is_some() {
+            return false;
+        }
+        let Some(timeout) = self.execution_timeout.get() else {
+            return false;
+        };
+        self.timed_out.set(false);
+        self.deadline.set(Instant::now().checked_add(timeout));
+        true
+    }
+
+    /// Stop the running deadline, returns whether the evaluation timed out.
+    #[cfg(feature = "std")]
+    pub fn stop_deadline(&self) -> bool {
+        self.deadline.set(None);
+        self.timed_out.replace(false)
     }
 
     #[allow(dead_code)] // not used in no_std
@@ -235,6 +480,114 @@ impl<'js> Opaque<'js> {
         self.callable_class_id
     }
 
+    /// Returns a handle which identifies this runtime and is dead once the runtime is dropped.
+    pub fn alive_token(&self) -> Weak<()> {
+        Arc::downgrade(&self.alive)
+    }
+
+    /// Returns whether the given handle was created by [`Opaque::alive_token`] of this runtime.
+    pub fn is_alive_token(&self, token: &Weak<()>) -> bool {
+        ptr::eq(Arc::as_ptr(&self.alive), token.as_ptr())
+    }
+
+    pub fn push_source_name(&self, name: CString) {
+        unsafe { (*self.source_names.get()).push(name) }
+    }
+
+    pub fn pop_source_name(&self) {
+        unsafe { (*self.source_names.get()).pop() };
+    }
+
+    /// Returns the source name of the innermost script currently being evaluated.
+    pub fn current_source_name(&self) -> Option<CString> {
+        unsafe { (*self.source_names.get()).last().cloned() }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn drop_queue(&self) -> Weak<DropQueue> {
+        Arc::downgrade(&self.drop_queue)
+    }
+
+    /// Free the values of shared persistent handles which were dropped since the last call.
+    pub fn drop_pending(&self) {
+        #[cfg(feature = "std")]
+        self.drop_queue.drain();
+    }
+
+    /// Attach or remove the eval cache of the given context, returning the previous one.
+    #[cfg(feature = "std")]
+    pub fn set_eval_cache(
+        &self,
+        ctx: *mut qjs::JSContext,
+        cache: Option<Arc<EvalCache>>,
+    ) -> Option<Arc<EvalCache>> {
+        let caches = unsafe { &mut *self.eval_caches.get() };
+        match cache {
+            Some(cache) => caches.insert(ctx, cache),
+            None => caches.remove(&ctx),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn eval_cache(&self, ctx: *mut qjs::JSContext) -> Option<Arc<EvalCache>> {
+        unsafe { (*self.eval_caches.get()).get(&ctx).cloned() }
+    }
+
+    /// Set or remove the bytecode cache of the runtime, returning the previous one.
+    #[cfg(feature = "loader")]
+    pub fn set_import_meta_handler(&self, handler: Option<ImportMetaHandler>) {
+        unsafe { *self.import_meta_handler.get() = handler }
+    }
+
+    /// Run the `import.meta` handler, if any, for a module loaded by the module loader.
+    #[cfg(feature = "loader")]
+    pub fn run_import_meta_handler<'a>(
+        &self,
+        ctx: &Ctx<'a>,
+        name: &str,
+        module: &crate::Module<'a, crate::module::Declared>,
+    ) -> crate::Result<()> {
+        // Take the handler while it runs so that modules loaded from within the handler can't
+        // alias it.
+        let Some(mut handler) = (unsafe { (*self.import_meta_handler.get()).take() }) else {
+            return Ok(());
+        };
+        let res = module
+            .meta()
+            .and_then(|meta| handler(ctx.clone(), name, meta));
+        unsafe {
+            let slot = &mut *self.import_meta_handler.get();
+            // Don't restore the handler if it was replaced while running.
+            if slot.is_none() {
+                *slot = Some(handler);
+            }
+        }
+        res
+    }
+
+    #[cfg(feature = "std")]
+    pub fn set_bytecode_cache(&self, cache: Option<Arc<EvalCache>>) -> Option<Arc<EvalCache>> {
+        unsafe { core::mem::replace(&mut *self.bytecode_cache.get(), cache) }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn bytecode_cache(&self) -> Option<Arc<EvalCache>> {
+        unsafe { (*self.bytecode_cache.get()).clone() }
+    }
+
+    /// Returns the class id used for instances of the given class.
+    pub fn get_class_id_of<C: JsClass<'js>>(&self) -> qjs::JSClassID {
+        if C::CALLABLE {
+            self.callable_class_id
+        } else if C::EXOTIC && C::HAS_PROPERTY_HOOKS {
+            self.exotic_hooks_class_id
+        } else if C::EXOTIC {
+            self.exotic_class_id
+        } else {
+            self.class_id
+        }
+    }
+
     pub fn get_or_insert_prototype<C: JsClass<'js>>(
         &self,
         ctx: &Ctx<'js>,
@@ -252,17 +605,83 @@ impl<'js> Opaque<'js> {
         }
     }
 
+    pub fn set_prototype<C: JsClass<'js>>(&self, proto: Option<Object<'js>>) {
+        let id = VTable::get::<C>().id();
+        unsafe { (*self.prototypes.get()).insert(id, proto) };
+    }
+
+    pub fn has_prototype<C: JsClass<'js>>(&self) -> bool {
+        let id = VTable::get::<C>().id();
+        unsafe { (*self.prototypes.get()).contains_key(&id) }
+    }
+
+    pub fn object_data_key(&self) -> Option<Symbol<'js>> {
+        unsafe { (*self.object_data_key.get()).clone() }
+    }
+
+    pub fn set_object_data_key(&self, key: Symbol<'js>) {
+        unsafe { *self.object_data_key.get() = Some(key) };
+    }
+
+    pub fn insert_native_module(
+        &self,
+        module: *mut qjs::JSModuleDef,
+        exports: Ref<[NativeExport]>,
+    ) {
+        unsafe { (*self.native_modules.get()).insert(module, exports) };
+    }
+
+    pub fn take_native_module(&self, module: *mut qjs::JSModuleDef) -> Option<Ref<[NativeExport]>> {
+        unsafe { (*self.native_modules.get()).remove(&module) }
+    }
+
+    pub fn insert_byte_buffer(&self, data: *mut u8) {
+        unsafe { (*self.byte_buffers.get()).insert(data, false) };
+    }
+
+    /// Calls `detach` and returns the data of the buffer if it was created from a byte vector,
+    /// otherwise returns `None` without calling it.
+    pub fn detach_byte_buffer(&self, data: *mut u8, detach: impl FnOnce()) -> Option<Vec<u8>> {
+        *unsafe { (*self.byte_buffers.get()).get_mut(&data) }? = true;
+        detach();
+        self.detached_bytes.take()
+    }
+
+    /// Called when the data of a buffer created from a byte vector is freed.
+    pub fn release_byte_buffer(&self, data: *mut u8, bytes: Vec<u8>) {
+        if unsafe { (*self.byte_buffers.get()).remove(&data) } == Some(true) {
+            self.detached_bytes.set(Some(bytes));
+        }
+    }
+
     /// Cleans up all the internal state.
     ///
     /// Called before dropping the runtime to ensure that we drop everything before freeing the
     /// runtime.
     pub fn clear(&mut self) {
+        #[cfg(feature = "std")]
+        self.drop_queue.close();
         self.rejection_tracker.get_mut().take();
         self.interrupt_handler.get_mut().take();
+        #[cfg(feature = "loader")]
+        self.import_meta_handler.get_mut().take();
         self.panic.take();
         self.prototypes.get_mut().clear();
+        self.object_data_key.get_mut().take();
+        self.native_modules.get_mut().clear();
+        self.byte_buffers.get_mut().clear();
+        self.detached_bytes.take();
+        self.source_names.get_mut().clear();
+        #[cfg(feature = "std")]
+        self.eval_caches.get_mut().clear();
+        #[cfg(feature = "std")]
+        self.bytecode_cache.get_mut().take();
         #[cfg(feature = "futures")]
         self.spawner.take();
+        #[cfg(feature = "futures")]
+        self.executor.take();
+        #[cfg(feature = "futures")]
+        self.cancellation_tokens.lock().clear();
         self.userdata.clear()
     }
 }
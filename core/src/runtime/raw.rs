@@ -1,6 +1,11 @@
 #![allow(dead_code, unused_imports)]
 use alloc::{boxed::Box, ffi::CString};
-use core::{mem, panic::AssertUnwindSafe, ptr::NonNull, result::Result as StdResult};
+use core::{
+    mem,
+    panic::AssertUnwindSafe,
+    ptr::{self, NonNull},
+    result::Result as StdResult,
+};
 
 use rquickjs_sys::JSPromiseHookType;
 
@@ -12,95 +17,9 @@ use crate::{
     Ctx, Error, Result, Value,
 };
 
-use super::{opaque::Opaque, InterruptHandler, PromiseHook, PromiseHookType, RejectionTracker};
-
-const DUMP_BYTECODE_FINAL: u64 = 0x01;
-const DUMP_BYTECODE_PASS2: u64 = 0x02;
-const DUMP_BYTECODE_PASS1: u64 = 0x04;
-const DUMP_BYTECODE_HEX: u64 = 0x10;
-const DUMP_BYTECODE_PC2LINE: u64 = 0x20;
-const DUMP_BYTECODE_STACK: u64 = 0x40;
-const DUMP_BYTECODE_STEP: u64 = 0x80;
-const DUMP_READ_OBJECT: u64 = 0x100;
-const DUMP_FREE: u64 = 0x200;
-const DUMP_GC: u64 = 0x400;
-const DUMP_GC_FREE: u64 = 0x800;
-const DUMP_MODULE_RESOLVE: u64 = 0x1000;
-const DUMP_PROMISE: u64 = 0x2000;
-const DUMP_LEAKS: u64 = 0x4000;
-const DUMP_ATOM_LEAKS: u64 = 0x8000;
-const DUMP_MEM: u64 = 0x10000;
-const DUMP_OBJECTS: u64 = 0x20000;
-const DUMP_ATOMS: u64 = 0x40000;
-const DUMP_SHAPES: u64 = 0x80000;
-
-// Build the flags using `#[cfg]` at compile time
-const fn build_dump_flags() -> u64 {
-    #[allow(unused_mut)]
-    let mut flags: u64 = 0;
-
-    #[cfg(feature = "dump-bytecode")]
-    {
-        flags |= DUMP_BYTECODE_FINAL | DUMP_BYTECODE_PASS2 | DUMP_BYTECODE_PASS1;
-    }
-
-    #[cfg(feature = "dump-gc")]
-    {
-        flags |= DUMP_GC;
-    }
-
-    #[cfg(feature = "dump-gc-free")]
-    {
-        flags |= DUMP_GC_FREE;
-    }
-
-    #[cfg(feature = "dump-free")]
-    {
-        flags |= DUMP_FREE;
-    }
-
-    #[cfg(feature = "dump-leaks")]
-    {
-        flags |= DUMP_LEAKS;
-    }
-
-    #[cfg(feature = "dump-mem")]
-    {
-        flags |= DUMP_MEM;
-    }
-
-    #[cfg(feature = "dump-objects")]
-    {
-        flags |= DUMP_OBJECTS;
-    }
-
-    #[cfg(feature = "dump-atoms")]
-    {
-        flags |= DUMP_ATOMS;
-    }
-
-    #[cfg(feature = "dump-shapes")]
-    {
-        flags |= DUMP_SHAPES;
-    }
-
-    #[cfg(feature = "dump-module-resolve")]
-    {
-        flags |= DUMP_MODULE_RESOLVE;
-    }
-
-    #[cfg(feature = "dump-promise")]
-    {
-        flags |= DUMP_PROMISE;
-    }
-
-    #[cfg(feature = "dump-read-object")]
-    {
-        flags |= DUMP_READ_OBJECT;
-    }
-
-    flags
-}
+use super::{
+    opaque::Opaque, DumpFlags, InterruptHandler, PromiseHook, PromiseHookType, RejectionTracker,
+};
 
 #[derive(Debug)]
 pub(crate) struct RawRuntime {
@@ -126,7 +45,7 @@ impl Drop for RawRuntime {
             let ptr = qjs::JS_GetRuntimeOpaque(self.rt.as_ptr());
             let mut opaque: Box<Opaque> = Box::from_raw(ptr as *mut _);
             opaque.clear();
-            qjs::JS_FreeRuntime(self.rt.as_ptr());
+            qjs::rquickjs_free_runtime(self.rt.as_ptr());
             mem::drop(opaque);
         }
     }
@@ -143,7 +62,7 @@ impl RawRuntime {
 
     #[allow(dead_code)]
     pub unsafe fn new_base(mut opaque: Opaque<'static>) -> Result<Self> {
-        let rt = qjs::JS_NewRuntime();
+        let rt = qjs::rquickjs_new_runtime(ptr::null(), ptr::null_mut());
 
         Self::add_dump_flags(rt);
 
@@ -171,7 +90,7 @@ impl RawRuntime {
         let functions = AllocatorHolder::functions::<A>();
         let opaque_ptr = allocator.opaque_ptr();
 
-        let rt = qjs::JS_NewRuntime2(&functions, opaque_ptr as _);
+        let rt = qjs::rquickjs_new_runtime(&functions, opaque_ptr as _);
 
         Self::add_dump_flags(rt);
 
@@ -202,13 +121,28 @@ impl RawRuntime {
         unsafe { &*(qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()) as *mut _) }
     }
 
+    pub fn drop_pending(&self) {
+        self.get_opaque().drop_pending();
+    }
+
+    /// Execute the next pending job of the runtime, like `JS_ExecutePendingJob`.
+    ///
+    /// Dynamic imports are run by the asynchronous module loader, if one is set.
+    pub unsafe fn execute_job(rt: *mut qjs::JSRuntime, pctx: *mut *mut qjs::JSContext) -> i32 {
+        #[cfg(all(feature = "loader", feature = "futures"))]
+        let dynamic_import: qjs::JSJobFunc = Some(crate::loader::dynamic_import_job);
+        #[cfg(not(all(feature = "loader", feature = "futures")))]
+        let dynamic_import: qjs::JSJobFunc = None;
+        qjs::rquickjs_execute_pending_job(rt, pctx, dynamic_import)
+    }
+
     pub fn is_job_pending(&self) -> bool {
         (unsafe { qjs::JS_IsJobPending(self.rt.as_ptr()) } as i32) != 0
     }
 
     pub fn execute_pending_job(&mut self) -> StdResult<bool, *mut qjs::JSContext> {
         let mut ctx_ptr = mem::MaybeUninit::<*mut qjs::JSContext>::uninit();
-        let result = unsafe { qjs::JS_ExecutePendingJob(self.rt.as_ptr(), ctx_ptr.as_mut_ptr()) };
+        let result = unsafe { Self::execute_job(self.rt.as_ptr(), ctx_ptr.as_mut_ptr()) };
         if result == 0 {
             // no jobs executed
             return Ok(false);
@@ -262,8 +196,13 @@ impl RawRuntime {
     }
 
     /// Set dump flags.
-    pub unsafe fn set_dump_flags(&self, flags: u64) {
-        qjs::JS_SetDumpFlags(self.rt.as_ptr(), flags);
+    pub unsafe fn set_dump_flags(&self, flags: DumpFlags) {
+        qjs::JS_SetDumpFlags(self.rt.as_ptr(), flags.bits());
+    }
+
+    /// Get dump flags.
+    pub unsafe fn dump_flags(&self) -> DumpFlags {
+        DumpFlags::from_bits(qjs::JS_GetDumpFlags(self.rt.as_ptr()))
     }
 
     /// Manually run the garbage collection.
@@ -273,6 +212,8 @@ impl RawRuntime {
     /// references. The garbage collector is only for collecting
     /// cyclic references.
     pub unsafe fn run_gc(&mut self) {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::GcSpan::enter(self.rt.as_ptr());
         qjs::JS_RunGC(self.rt.as_ptr());
     }
 
@@ -381,6 +322,20 @@ impl RawRuntime {
     /// If the provided closure returns `true` the interpreter will raise and uncatchable
     /// exception and return control flow to the caller.
     pub unsafe fn set_interrupt_handler(&mut self, handler: Option<InterruptHandler>) {
+        self.get_opaque().set_interrupt_handler(handler);
+        self.update_interrupt_handler();
+    }
+
+    /// Set the time after which evaluations with [`Ctx::eval_catching_interrupt`] are
+    /// interrupted.
+    #[cfg(feature = "std")]
+    pub unsafe fn set_execution_timeout(&mut self, timeout: Option<core::time::Duration>) {
+        self.get_opaque().set_execution_timeout(timeout);
+        self.update_interrupt_handler();
+    }
+
+    /// Install the interrupt handler of the engine if the opaque needs one.
+    unsafe fn update_interrupt_handler(&mut self) {
         unsafe extern "C" fn interrupt_handler_trampoline(
             _rt: *mut qjs::JSRuntime,
             opaque: *mut ::core::ffi::c_void,
@@ -409,15 +364,16 @@ impl RawRuntime {
 
         qjs::JS_SetInterruptHandler(
             self.rt.as_ptr(),
-            handler.as_ref().map(|_| interrupt_handler_trampoline as _),
+            self.get_opaque()
+                .has_interrupt_handler()
+                .then_some(interrupt_handler_trampoline as _),
             qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()),
         );
-        self.get_opaque().set_interrupt_handler(handler);
     }
 
     fn add_dump_flags(rt: *mut rquickjs_sys::JSRuntime) {
         unsafe {
-            qjs::JS_SetDumpFlags(rt, build_dump_flags());
+            qjs::JS_SetDumpFlags(rt, DumpFlags::from_features().bits());
         }
     }
 }
@@ -31,13 +31,17 @@ impl Spawner {
         F: Future<Output = ()>,
     {
         unsafe { self.schedular.push(f) };
-        self.wakeup.drain(..).for_each(Waker::wake);
+        self.wake();
     }
 
     pub fn listen(&mut self, wake: Waker) {
         self.wakeup.push(wake);
     }
 
+    pub fn wake(&mut self) {
+        self.wakeup.drain(..).for_each(Waker::wake);
+    }
+
     pub fn is_empty(&mut self) -> bool {
         self.schedular.is_empty()
     }
@@ -113,7 +117,8 @@ impl Future for DriveFuture {
 
             lock.runtime.get_opaque().listen(cx.waker().clone());
 
-            loop {
+            // If automatic driving is disabled just wait until it is enabled again.
+            while lock.runtime.get_opaque().auto_drive() {
                 // TODO: Handle error.
                 if let Ok(true) = lock.runtime.execute_pending_job() {
                     continue;
@@ -41,6 +41,24 @@ impl<T: ?Sized> Mut<T> {
         }
     }
 
+    /// Lock without blocking, returns `None` if the lock is currently held, including when it is
+    /// held by the current thread.
+    pub fn try_lock_now(&self) -> Option<Lock<T>> {
+        #[cfg(not(feature = "parallel"))]
+        {
+            self.0.try_borrow_mut().ok()
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            match self.0.try_lock() {
+                Ok(x) => Some(x),
+                Err(std::sync::TryLockError::WouldBlock) => None,
+                Err(std::sync::TryLockError::Poisoned(e)) => panic!("{e}"),
+            }
+        }
+    }
+
     pub fn try_lock(&self) -> Option<Lock<T>> {
         #[cfg(not(feature = "parallel"))]
         {
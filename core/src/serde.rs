@@ -0,0 +1,767 @@
+//! Conversion between JavaScript values and Rust types using [`serde`](::serde).
+
+use alloc::{string::ToString as _, vec::IntoIter as VecIntoIter, vec::Vec};
+use core::fmt::Display;
+
+use ::serde::{
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, VariantAccess, Visitor,
+    },
+    ser::{self, Serialize},
+};
+
+use crate::{Array, Ctx, Error, Object, Result, StdString, Type, Value};
+
+/// Deserialize a Rust type from a JavaScript value.
+///
+/// Arrays are deserialized as sequences and other objects as maps of their own string enumerable
+/// properties. Functions, symbols and other values which have no data representation result in an
+/// error.
+pub fn from_value<'js, T>(value: Value<'js>) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(Deserializer::new(value))
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::new_from_js_message("value", "deserialized type", msg.to_string())
+    }
+}
+
+/// A [`serde::Deserializer`](::serde::Deserializer) which reads from a JavaScript value.
+pub struct Deserializer<'js> {
+    value: Value<'js>,
+}
+
+impl<'js> Deserializer<'js> {
+    /// Create a deserializer reading from the given value.
+    pub fn new(value: Value<'js>) -> Self {
+        Deserializer { value }
+    }
+
+    fn unsupported(&self) -> Error {
+        Error::new_from_js(self.value.type_name(), "deserialized type")
+    }
+}
+
+impl<'de, 'js> de::Deserializer<'de> for Deserializer<'js> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value.type_of() {
+            Type::Uninitialized | Type::Undefined | Type::Null => visitor.visit_unit(),
+            Type::Bool => visitor.visit_bool(self.value.as_bool().unwrap()),
+            Type::Int => visitor.visit_i32(self.value.as_int().unwrap()),
+            Type::Float => {
+                let float = self.value.as_float().unwrap();
+                // Integers which don't fit into an `i32` are stored as floats.
+                if float >= i64::MIN as f64
+                    && float < i64::MAX as f64
+                    && float as i64 as f64 == float
+                {
+                    visitor.visit_i64(float as i64)
+                } else {
+                    visitor.visit_f64(float)
+                }
+            }
+            Type::BigInt => {
+                let bigint = self.value.into_big_int().unwrap();
+                visitor.visit_i64(bigint.to_i64()?)
+            }
+            Type::String => {
+                let string = self.value.into_string().unwrap();
+                visitor.visit_string(string.to_string()?)
+            }
+            Type::Array => {
+                let array = self.value.into_array().unwrap();
+                let items = array.iter::<Value>().collect::<Result<Vec<_>>>()?;
+                visitor.visit_seq(SeqDeserializer {
+                    iter: items.into_iter(),
+                })
+            }
+            Type::Object | Type::Exception => {
+                let object = self.value.into_object().unwrap();
+                let entries = object
+                    .props::<StdString, Value>()
+                    .collect::<Result<Vec<_>>>()?;
+                visitor.visit_map(MapDeserializer {
+                    iter: entries.into_iter(),
+                    value: None,
+                })
+            }
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.value.type_of().is_void() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value.type_of() {
+            Type::String => {
+                let variant = self.value.into_string().unwrap().to_string()?;
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            Type::Object => {
+                let object = self.value.into_object().unwrap();
+                let mut props = object.props::<StdString, Value>();
+                match (props.next(), props.next()) {
+                    (Some(entry), None) => {
+                        let (variant, value) = entry?;
+                        visitor.visit_enum(EnumDeserializer { variant, value })
+                    }
+                    _ => Err(Error::new_from_js_message(
+                        "object",
+                        "enum",
+                        "expected an object with a single property",
+                    )),
+                }
+            }
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    ::serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de, 'js> IntoDeserializer<'de, Error> for Deserializer<'js> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct SeqDeserializer<'js> {
+    iter: VecIntoIter<Value<'js>>,
+}
+
+impl<'de, 'js> SeqAccess<'de> for SeqDeserializer<'js> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.iter
+            .next()
+            .map(|value| seed.deserialize(Deserializer::new(value)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer<'js> {
+    iter: VecIntoIter<(StdString, Value<'js>)>,
+    value: Option<Value<'js>>,
+}
+
+impl<'de, 'js> MapAccess<'de> for MapDeserializer<'js> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(IntoDeserializer::<Error>::into_deserializer(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer::new(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumDeserializer<'js> {
+    variant: StdString,
+    value: Value<'js>,
+}
+
+impl<'de, 'js> EnumAccess<'de> for EnumDeserializer<'js> {
+    type Error = Error;
+    type Variant = Deserializer<'js>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant =
+            seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.variant))?;
+        Ok((variant, Deserializer::new(self.value)))
+    }
+}
+
+impl<'de, 'js> VariantAccess<'de> for Deserializer<'js> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+/// Serialize a Rust type into a JavaScript value.
+///
+/// Sequences and tuples become arrays, maps and structs become objects and enums are externally
+/// tagged like `{ "Variant": value }`, matching what [`from_value`] reads back. Unit values and
+/// `None` become `null`.
+pub fn to_value<'js, T>(ctx: Ctx<'js>, value: &T) -> Result<Value<'js>>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(Serializer::new(ctx))
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::new_into_js_message("serialized type", "value", msg.to_string())
+    }
+}
+
+/// A [`serde::Serializer`](::serde::Serializer) which creates JavaScript values.
+pub struct Serializer<'js> {
+    ctx: Ctx<'js>,
+}
+
+impl<'js> Serializer<'js> {
+    /// Create a serializer creating values in the given context.
+    pub fn new(ctx: Ctx<'js>) -> Self {
+        Serializer { ctx }
+    }
+
+    fn tagged(&self, variant: &'static str, value: Value<'js>) -> Result<Value<'js>> {
+        let object = Object::new(self.ctx.clone())?;
+        object.set(variant, value)?;
+        Ok(object.into_value())
+    }
+}
+
+impl<'js> ser::Serializer for Serializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'js>;
+    type SerializeTuple = SeqSerializer<'js>;
+    type SerializeTupleStruct = SeqSerializer<'js>;
+    type SerializeTupleVariant = SeqSerializer<'js>;
+    type SerializeMap = MapSerializer<'js>;
+    type SerializeStruct = MapSerializer<'js>;
+    type SerializeStructVariant = MapSerializer<'js>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value<'js>> {
+        Ok(Value::new_bool(self.ctx, v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value<'js>> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value<'js>> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value<'js>> {
+        Ok(Value::new_int(self.ctx, v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value<'js>> {
+        Ok(Value::new_number(self.ctx, v as f64))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value<'js>> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value<'js>> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value<'js>> {
+        Ok(Value::new_number(self.ctx, v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value<'js>> {
+        Ok(Value::new_number(self.ctx, v as f64))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value<'js>> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value<'js>> {
+        Ok(Value::new_float(self.ctx, v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value<'js>> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value<'js>> {
+        crate::String::from_str(self.ctx, v).map(|x| x.into_value())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value<'js>> {
+        let mut seq = ser::Serializer::serialize_seq(self, Some(v.len()))?;
+        for byte in v {
+            ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    fn serialize_none(self) -> Result<Value<'js>> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value<'js>>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value<'js>> {
+        Ok(Value::new_null(self.ctx))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value<'js>> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value<'js>> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Value<'js>>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value<'js>>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = value.serialize(Serializer::new(self.ctx.clone()))?;
+        self.tagged(variant, value)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'js>> {
+        Ok(SeqSerializer {
+            array: Array::new(self.ctx)?,
+            len: 0,
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'js>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer<'js>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'js>> {
+        let mut seq = self.serialize_seq(Some(len))?;
+        seq.variant = Some(variant);
+        Ok(seq)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'js>> {
+        Ok(MapSerializer {
+            object: Object::new(self.ctx)?,
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer<'js>> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer<'js>> {
+        let mut map = self.serialize_map(Some(len))?;
+        map.variant = Some(variant);
+        Ok(map)
+    }
+}
+
+#[doc(hidden)]
+pub struct SeqSerializer<'js> {
+    array: Array<'js>,
+    len: usize,
+    variant: Option<&'static str>,
+}
+
+impl<'js> SeqSerializer<'js> {
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = value.serialize(Serializer::new(self.array.ctx().clone()))?;
+        self.array.set(self.len, value)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value<'js>> {
+        let ctx = self.array.ctx().clone();
+        let array = self.array.into_value();
+        match self.variant {
+            Some(variant) => Serializer::new(ctx).tagged(variant, array),
+            None => Ok(array),
+        }
+    }
+}
+
+impl<'js> ser::SerializeSeq for SeqSerializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value<'js>> {
+        self.finish()
+    }
+}
+
+impl<'js> ser::SerializeTuple for SeqSerializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value<'js>> {
+        self.finish()
+    }
+}
+
+impl<'js> ser::SerializeTupleStruct for SeqSerializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value<'js>> {
+        self.finish()
+    }
+}
+
+impl<'js> ser::SerializeTupleVariant for SeqSerializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value<'js>> {
+        self.finish()
+    }
+}
+
+#[doc(hidden)]
+pub struct MapSerializer<'js> {
+    object: Object<'js>,
+    key: Option<Value<'js>>,
+    variant: Option<&'static str>,
+}
+
+impl<'js> MapSerializer<'js> {
+    fn serialize<T>(&self, value: &T) -> Result<Value<'js>>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(Serializer::new(self.object.ctx().clone()))
+    }
+
+    fn finish(self) -> Result<Value<'js>> {
+        let ctx = self.object.ctx().clone();
+        let object = self.object.into_value();
+        match self.variant {
+            Some(variant) => Serializer::new(ctx).tagged(variant, object),
+            None => Ok(object),
+        }
+    }
+}
+
+impl<'js> ser::SerializeMap for MapSerializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self.serialize(key)?;
+        if !(key.is_string() || key.is_number()) {
+            return Err(Error::new_into_js(key.type_name(), "property key"));
+        }
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        let value = self.serialize(value)?;
+        self.object.set(key, value)
+    }
+
+    fn end(self) -> Result<Value<'js>> {
+        self.finish()
+    }
+}
+
+impl<'js> ser::SerializeStruct for MapSerializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = self.serialize(value)?;
+        self.object.set(key, value)
+    }
+
+    fn end(self) -> Result<Value<'js>> {
+        self.finish()
+    }
+}
+
+impl<'js> ser::SerializeStructVariant for MapSerializer<'js> {
+    type Ok = Value<'js>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = self.serialize(value)?;
+        self.object.set(key, value)
+    }
+
+    fn end(self) -> Result<Value<'js>> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use ::serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect { w: u32, h: u32 },
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Data {
+        name: std::string::String,
+        count: u64,
+        ratio: f32,
+        tags: Vec<std::string::String>,
+        extra: Option<bool>,
+        shapes: Vec<Shape>,
+        map: BTreeMap<std::string::String, i8>,
+    }
+
+    #[test]
+    fn from_value() {
+        test_with(|ctx| {
+            let value: Value = ctx
+                .eval(
+                    r#"({
+                        name: "foo",
+                        count: 2 ** 40,
+                        ratio: 0.5,
+                        tags: ["a", "b"],
+                        extra: undefined,
+                        shapes: ["Empty", { Circle: 1.5 }, { Rect: { w: 2, h: 3 } }],
+                        map: { x: -1, y: 1 },
+                    })"#,
+                )
+                .unwrap();
+            let data: Data = serde::from_value(value).unwrap();
+            assert_eq!(
+                data,
+                Data {
+                    name: "foo".into(),
+                    count: 1 << 40,
+                    ratio: 0.5,
+                    tags: vec!["a".into(), "b".into()],
+                    extra: None,
+                    shapes: vec![Shape::Empty, Shape::Circle(1.5), Shape::Rect { w: 2, h: 3 }],
+                    map: [("x".into(), -1), ("y".into(), 1)].into_iter().collect(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn from_value_errors() {
+        test_with(|ctx| {
+            let value: Value = ctx.eval("() => 1").unwrap();
+            let err = serde::from_value::<i32>(value).unwrap_err();
+            assert!(matches!(
+                err,
+                Error::FromJs {
+                    from: "function",
+                    ..
+                }
+            ));
+
+            let value: Value = ctx.eval("({ count: 'many' })").unwrap();
+            assert!(serde::from_value::<BTreeMap<std::string::String, u32>>(value).is_err());
+        })
+    }
+
+    #[test]
+    fn to_value() {
+        test_with(|ctx| {
+            let data = Data {
+                name: "foo".into(),
+                count: 1 << 40,
+                ratio: 0.5,
+                tags: vec!["a".into(), "b".into()],
+                extra: None,
+                shapes: vec![Shape::Empty, Shape::Circle(1.5), Shape::Rect { w: 2, h: 3 }],
+                map: [("x".into(), -1), ("y".into(), 1)].into_iter().collect(),
+            };
+            let value = serde::to_value(ctx.clone(), &data).unwrap();
+            let json = ctx.json_stringify(value.clone()).unwrap().unwrap();
+            assert_eq!(
+                json.to_string().unwrap(),
+                r#"{"name":"foo","count":1099511627776,"ratio":0.5,"tags":["a","b"],"extra":null,"shapes":["Empty",{"Circle":1.5},{"Rect":{"w":2,"h":3}}],"map":{"x":-1,"y":1}}"#
+            );
+            assert_eq!(serde::from_value::<Data>(value).unwrap(), data);
+
+            let map: BTreeMap<(i32, i32), i32> = [((1, 2), 3)].into_iter().collect();
+            assert!(serde::to_value(ctx.clone(), &map).is_err());
+        })
+    }
+}
@@ -0,0 +1,233 @@
+//! Spans emitted with the `tracing` feature.
+//!
+//! Spans are emitted at the `DEBUG` level under the `rquickjs` target so they can be filtered
+//! independently of the spans of the embedding application. Duration is tracked by the
+//! subscriber from when a span is entered until it is exited.
+
+use crate::{atom::PredefinedAtom, qjs, StdString, Value};
+use core::{ffi::CStr, mem::MaybeUninit};
+use tracing::{field, span::EnteredSpan};
+
+/// Enter a span for evaluating a script or module with the given file name.
+pub(crate) fn eval(file_name: &CStr) -> EnteredSpan {
+    tracing::debug_span!(target: "rquickjs", "eval", file = %file_name.to_string_lossy()).entered()
+}
+
+/// Enter a span for a call crossing the Rust/JavaScript boundary.
+///
+/// `direction` is either `"js"` for calls from Rust into JavaScript or `"rust"` for calls from
+/// JavaScript into Rust.
+pub(crate) fn call<'js>(function: &Value<'js>, direction: &'static str) -> EnteredSpan {
+    let span = tracing::debug_span!(
+        target: "rquickjs",
+        "call",
+        direction,
+        function = field::Empty
+    );
+    if !span.is_disabled() {
+        if let Some(name) = function_name(function) {
+            span.record("function", name.as_str());
+        }
+    }
+    span.entered()
+}
+
+/// Returns the name of a function from its own `name` data property, without invoking
+/// accessors.
+fn function_name<'js>(function: &Value<'js>) -> Option<StdString> {
+    let object = function.as_object()?;
+    let name = object
+        .own_property_descriptor(PredefinedAtom::Name)
+        .and_then(|desc| match desc.and_then(|desc| desc.value) {
+            Some(value) if value.is_string() => value.get().map(Some),
+            _ => Ok(None),
+        });
+    match name {
+        Ok(name) => name.filter(|name: &StdString| !name.is_empty()),
+        Err(_) => {
+            // A failing proxy trap must not leave its exception pending.
+            object.ctx().catch();
+            None
+        }
+    }
+}
+
+/// A span for a garbage collection cycle which records the amount of bytes freed on drop.
+pub(crate) struct GcSpan {
+    span: EnteredSpan,
+    rt: *mut qjs::JSRuntime,
+    before: i64,
+}
+
+impl GcSpan {
+    /// Enter the span for a garbage collection run in the given runtime.
+    ///
+    /// # Safety
+    /// The runtime pointer must be valid for the lifetime of the span.
+    pub(crate) unsafe fn enter(rt: *mut qjs::JSRuntime) -> Self {
+        let span = tracing::debug_span!(target: "rquickjs", "gc", bytes_freed = field::Empty);
+        let before = if span.is_disabled() {
+            0
+        } else {
+            malloc_size(rt)
+        };
+        GcSpan {
+            span: span.entered(),
+            rt,
+            before,
+        }
+    }
+}
+
+impl Drop for GcSpan {
+    fn drop(&mut self) {
+        if !self.span.is_disabled() {
+            let after = unsafe { malloc_size(self.rt) };
+            self.span
+                .record("bytes_freed", self.before.saturating_sub(after).max(0));
+        }
+    }
+}
+
+unsafe fn malloc_size(rt: *mut qjs::JSRuntime) -> i64 {
+    let mut stats = MaybeUninit::uninit();
+    qjs::JS_ComputeMemoryUsage(rt, stats.as_mut_ptr());
+    stats.assume_init().malloc_size
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::{
+        fmt::Debug,
+        string::{String, ToString},
+        sync::{Arc, Mutex},
+        vec::Vec,
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    type Spans = Vec<(&'static str, Vec<(String, String)>)>;
+
+    #[derive(Default, Clone)]
+    struct Recorder {
+        spans: Arc<Mutex<Spans>>,
+    }
+
+    struct Fields<'a>(&'a mut Vec<(String, String)>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let mut fields = Vec::new();
+            span.record(&mut Fields(&mut fields));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata().name(), fields));
+            span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut Fields(&mut spans[id.into_u64() as usize - 1].1));
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    impl Recorder {
+        fn field(&self, name: &str, field: &str) -> Option<String> {
+            self.spans
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(n, _)| *n == name)
+                .flat_map(|(_, fields)| fields.iter())
+                .find(|(f, _)| f == field)
+                .map(|(_, v)| v.clone())
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            test_with(|ctx| {
+                let f = Function::new(ctx.clone(), |a: i32| a + 1)
+                    .unwrap()
+                    .with_name("increment")
+                    .unwrap();
+                ctx.globals().set("increment", f).unwrap();
+                let add: Function = ctx
+                    .eval("function add(a, b) { return increment(a) + b }; add")
+                    .unwrap();
+                let res: i32 = add.call((1, 2)).unwrap();
+                assert_eq!(res, 4);
+                ctx.run_gc();
+            });
+        });
+
+        assert_eq!(recorder.field("eval", "file").unwrap(), "eval_script");
+        let functions: Vec<_> = recorder
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(n, _)| *n == "call")
+            .flat_map(|(_, f)| {
+                f.iter()
+                    .filter(|(f, _)| f == "function")
+                    .map(|(_, v)| v.clone())
+            })
+            .collect();
+        assert_eq!(functions, ["add", "increment"]);
+        assert!(recorder.field("gc", "bytes_freed").is_some());
+    }
+
+    #[test]
+    fn function_name_without_side_effects() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            test_with(|ctx| {
+                let getter: Function = ctx
+                    .eval(
+                        r#"
+                        globalThis.reads = 0;
+                        const f = () => 1;
+                        Object.defineProperty(f, "name", { get() { reads += 1; return "getter" } });
+                        f
+                        "#,
+                    )
+                    .unwrap();
+                assert_eq!(getter.call::<_, i32>(()).unwrap(), 1);
+                assert_eq!(ctx.globals().get::<_, i32>("reads").unwrap(), 0);
+
+                let proxy: Function = ctx
+                    .eval("new Proxy(() => 2, { getOwnPropertyDescriptor() { throw new Error('trap') } })")
+                    .unwrap();
+                assert_eq!(proxy.call::<_, i32>(()).unwrap(), 2);
+                assert!(!unsafe { qjs::JS_HasException(ctx.as_ptr()) });
+            });
+        });
+
+        assert!(recorder.field("call", "function").is_none());
+    }
+}
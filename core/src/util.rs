@@ -1,5 +1,6 @@
 //! Module with some util types.
 
+use alloc::{format, string::String};
 use core::panic::UnwindSafe;
 
 /// A trait for preventing implementing traits which should not be implemented outside of rquickjs.
@@ -175,3 +176,48 @@ pub fn resume_unwind(_payload: alloc::boxed::Box<dyn core::any::Any + Send>) ->
     // with no-std we can't unwind, just panic
     panic!()
 }
+
+/// Returns whether the text is a JavaScript identifier name, which can be written without quotes
+/// as a property key.
+pub(crate) fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Quote a string as a JavaScript string literal with single quotes, or other quotes if it
+/// contains single quotes.
+pub(crate) fn quote(text: &str) -> String {
+    let quote = if !text.contains('\'') {
+        '\''
+    } else if !text.contains('"') {
+        '"'
+    } else if !text.contains('`') && !text.contains("${") {
+        '`'
+    } else {
+        '\''
+    };
+    let mut res = String::with_capacity(text.len() + 2);
+    res.push(quote);
+    for c in text.chars() {
+        match c {
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            '\r' => res.push_str("\\r"),
+            '\x08' => res.push_str("\\b"),
+            '\x0c' => res.push_str("\\f"),
+            '\x0b' => res.push_str("\\v"),
+            '\\' => res.push_str("\\\\"),
+            c if c == quote => {
+                res.push('\\');
+                res.push(c);
+            }
+            c if c.is_control() => res.push_str(&format!("\\x{:02X}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push(quote);
+    res
+}
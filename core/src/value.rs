@@ -1,15 +1,26 @@
 use crate::{qjs, Ctx, Error, Result};
-use core::{fmt, hash::Hash, mem, ops::Deref, result::Result as StdResult, str};
+use alloc::string::String as StdString;
+use core::{fmt, hash::Hash, mem, ops::Deref, result::Result as StdResult, slice, str};
 
 pub mod array;
+#[cfg(feature = "futures")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub mod async_iterator;
 pub mod atom;
 mod bigint;
 pub mod convert;
+mod date;
+mod deep_clone;
+mod deep_eq;
 pub(crate) mod exception;
+mod finalization_registry;
 pub mod function;
+mod generator;
 pub mod module;
 pub mod object;
 pub mod promise;
+mod proxy;
+mod regexp;
 mod string;
 mod symbol;
 
@@ -17,18 +28,25 @@ pub use array::Array;
 pub use atom::Atom;
 pub use bigint::BigInt;
 pub use convert::{Coerced, FromAtom, FromIteratorJs, FromJs, IntoAtom, IntoJs, IteratorJs};
+pub use date::Date;
 pub use exception::Exception;
+pub use finalization_registry::FinalizationRegistry;
 pub use function::{Constructor, Function};
+pub use generator::{Generator, GeneratorResult};
 pub use module::{Module, WriteOptions, WriteOptionsEndianness};
 pub use object::{Filter, Object};
 pub use promise::Promise;
+pub use proxy::{Proxy, ProxyHandler};
+pub use regexp::RegExp;
 pub use string::{CString, String};
 pub use symbol::Symbol;
 
 pub mod array_buffer;
+pub mod data_view;
 pub mod typed_array;
 
 pub use array_buffer::ArrayBuffer;
+pub use data_view::DataView;
 pub use typed_array::TypedArray;
 
 /// Any JavaScript value
@@ -388,6 +406,48 @@ impl<'js> Value<'js> {
         unsafe { qjs::JS_IsBigInt(self.value) }
     }
 
+    /// Compare two values with the loose equality operator `==`, which converts the operands.
+    ///
+    /// Returns an error if a conversion throws, for example calling `valueOf` on an object.
+    pub fn loose_eq(&self, other: &Value<'js>) -> Result<bool> {
+        let res = unsafe { qjs::JS_IsEqual(self.ctx.as_ptr(), self.value, other.value) };
+        if res < 0 {
+            return Err(self.ctx.raise_exception());
+        }
+        Ok(res != 0)
+    }
+
+    /// Compare two values with the strict equality operator `===`.
+    ///
+    /// The equality predicates differ in how they treat `NaN` and zeros, otherwise they all
+    /// compare primitives by value and objects by identity:
+    ///
+    /// | Comparison      | [`strict_eq`](Value::strict_eq) | [`same_value`](Value::same_value) | [`same_value_zero`](Value::same_value_zero) |
+    /// |-----------------|---------------------------------|-----------------------------------|---------------------------------------------|
+    /// | JavaScript      | `a === b`                       | `Object.is(a, b)`                 | `Map` keys, `Array.prototype.includes`      |
+    /// | `NaN` vs `NaN`  | `false`                         | `true`                            | `true`                                      |
+    /// | `+0` vs `-0`    | `true`                          | `false`                           | `true`                                      |
+    ///
+    /// Unlike these predicates the [`PartialEq`] implementation of `Value` compares the
+    /// representation of the values, so for example a number stored as an integer isn't equal to
+    /// the same number stored as a float.
+    pub fn strict_eq(&self, other: &Value<'js>) -> bool {
+        unsafe { qjs::JS_IsStrictEqual(self.ctx.as_ptr(), self.value, other.value) }
+    }
+
+    /// Check whether two values are the same according to the `SameValue` algorithm, like
+    /// `Object.is`, see [`Value::strict_eq`] for the differences between the predicates.
+    pub fn same_value(&self, other: &Value<'js>) -> bool {
+        unsafe { qjs::JS_IsSameValue(self.ctx.as_ptr(), self.value, other.value) }
+    }
+
+    /// Check whether two values are the same according to the `SameValueZero` algorithm, used
+    /// by `Map`, `Set` and `Array.prototype.includes`, see [`Value::strict_eq`] for the
+    /// differences between the predicates.
+    pub fn same_value_zero(&self, other: &Value<'js>) -> bool {
+        unsafe { qjs::JS_IsSameValueZero(self.ctx.as_ptr(), self.value, other.value) }
+    }
+
     /// Reference as value
     #[inline]
     pub fn as_value(&self) -> &Self {
@@ -404,6 +464,51 @@ impl<'js> Value<'js> {
         T::from_js(self.ctx(), self.clone())
     }
 
+    /// Convert the value to a string following the rules of JavaScript `String(value)`.
+    ///
+    /// Objects are converted by calling their `toString` method, which can throw.
+    pub fn to_display_string(&self) -> Result<StdString> {
+        if let Some(symbol) = self.as_symbol() {
+            // `String(symbol)` is allowed while converting a symbol to a string throws.
+            let description = symbol.description()?;
+            return Ok(match description.as_string() {
+                Some(x) => alloc::format!("Symbol({})", x.to_string()?),
+                None => "Symbol()".into(),
+            });
+        }
+
+        let mut len = mem::MaybeUninit::uninit();
+        let ptr = unsafe { qjs::JS_ToCStringLen(self.ctx.as_ptr(), len.as_mut_ptr(), self.value) };
+        if ptr.is_null() {
+            return Err(Error::Exception);
+        }
+        let len = unsafe { len.assume_init() };
+        let bytes: &[u8] = unsafe { slice::from_raw_parts(ptr as _, len as _) };
+        let result = str::from_utf8(bytes).map(StdString::from);
+        unsafe { qjs::JS_FreeCString(self.ctx.as_ptr(), ptr) };
+        Ok(result?)
+    }
+
+    /// Convert the value to a number following the JavaScript `ToNumber` rules.
+    ///
+    /// Unlike [`Value::as_number`] any value is coerced, for example `""` becomes `0`. Converting a
+    /// symbol or a big int throws a `TypeError`.
+    pub fn to_number(&self) -> Result<f64> {
+        self.get::<convert::Coerced<f64>>().map(|x| x.0)
+    }
+
+    /// Convert the value to a string following the JavaScript `ToString` rules.
+    ///
+    /// Unlike [`Value::to_display_string`] converting a symbol throws a `TypeError`.
+    pub fn to_js_string(&self) -> Result<String<'js>> {
+        self.get::<convert::Coerced<String<'js>>>().map(|x| x.0)
+    }
+
+    /// Convert the value to a boolean following the JavaScript `ToBoolean` rules.
+    pub fn to_bool(&self) -> bool {
+        unsafe { qjs::JS_ToBool(self.ctx.as_ptr(), self.value) == 1 }
+    }
+
     /// Returns the raw C library JavaScript value.
     pub fn as_raw(&self) -> qjs::JSValue {
         self.value
@@ -427,6 +532,34 @@ impl<'js> AsRef<Value<'js>> for Value<'js> {
     }
 }
 
+/// An adapter which formats a value with [`Value::to_display_string`].
+///
+/// If the conversion throws, the exception is cleared and the [`Debug`](fmt::Debug) output of the
+/// value is written instead.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Value, JsDisplay};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let value: Value = ctx.eval("[1, 'a', { b: 2 }]").unwrap();
+/// assert_eq!(JsDisplay(value).to_string(), "1,a,[object Object]");
+/// # });
+/// ```
+pub struct JsDisplay<'js>(pub Value<'js>);
+
+impl fmt::Display for JsDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.to_display_string() {
+            Ok(x) => fmt::Display::fmt(&x, f),
+            Err(_) => {
+                self.0.ctx.catch();
+                fmt::Debug::fmt(&self.0, f)
+            }
+        }
+    }
+}
+
 macro_rules! type_impls {
     // type: name => tag
     ($($type:ident: $name:ident => $($tag:ident)|+,)*) => {
@@ -567,6 +700,9 @@ macro_rules! sub_types {
                 }
 
                 /// Convert from value
+                ///
+                /// Returns [`Error::FromJs`] if the value has a different type, which makes it the
+                /// way to cast a value with `?`.
                 pub fn from_value(value: Value<'js>) -> Result<Self> {
                     let type_ = value.type_of();
                     if type_.interpretable_as(Type::$head) {
@@ -631,6 +767,8 @@ macro_rules! sub_types {
                 }
 
                 #[doc = concat!("Try convert into [`",stringify!($head),"`]")]
+                ///
+                #[doc = concat!("See [`",stringify!($head),"::from_value`] for a conversion returning an error.")]
                 pub fn $into(self) -> Option<$head<'js>> {
                     if self.type_of().interpretable_as(Type::$head) {
                         Some(sub_types!(@wrap $head$(->$sub_type)* self))
@@ -798,6 +936,38 @@ mod test {
         assert!(!Type::Bool.interpretable_as(Type::Int));
     }
 
+    #[test]
+    fn equality() {
+        test_with(|ctx| {
+            let values: Vec<Value> = ctx.eval("[NaN, NaN, 0, -0, 1, 1.0, '1', {}, {}]").unwrap();
+            let [nan, nan2, zero, neg_zero, one, one_float, one_str, obj, obj2] =
+                <[Value; 9]>::try_from(values).unwrap();
+
+            assert!(!nan.strict_eq(&nan2));
+            assert!(nan.same_value(&nan2));
+            assert!(nan.same_value_zero(&nan2));
+
+            assert!(zero.strict_eq(&neg_zero));
+            assert!(!zero.same_value(&neg_zero));
+            assert!(zero.same_value_zero(&neg_zero));
+
+            assert!(one.strict_eq(&one_float));
+            assert!(one.same_value(&one_float));
+            assert!(!one.strict_eq(&one_str));
+            assert!(one.loose_eq(&one_str).unwrap());
+
+            assert!(obj.strict_eq(&obj.clone()));
+            assert!(!obj.same_value(&obj2));
+            assert!(!obj.loose_eq(&obj2).unwrap());
+
+            let throws: Value = ctx
+                .eval("({ valueOf() { throw new Error('no value') } })")
+                .unwrap();
+            let err = throws.loose_eq(&one).catch(&ctx).unwrap_err();
+            assert!(err.to_string().contains("no value"), "{err}");
+        })
+    }
+
     #[test]
     fn big_int() {
         test_with(|ctx| {
@@ -811,4 +981,113 @@ mod test {
             assert_eq!(val.type_of(), Type::BigInt);
         });
     }
+
+    #[test]
+    fn display_string() {
+        test_with(|ctx| {
+            let display = |source: &str| {
+                let value: Value = ctx.eval(source).unwrap();
+                value.to_display_string().unwrap()
+            };
+            assert_eq!(display("undefined"), "undefined");
+            assert_eq!(display("null"), "null");
+            assert_eq!(display("1.5"), "1.5");
+            assert_eq!(display("10n"), "10");
+            assert_eq!(display("'abc'"), "abc");
+            assert_eq!(display("[1, [2, 3]]"), "1,2,3");
+            assert_eq!(display("({})"), "[object Object]");
+            assert_eq!(display("Symbol('a')"), "Symbol(a)");
+            assert_eq!(display("Symbol()"), "Symbol()");
+            assert_eq!(display("({ toString() { return 'custom' } })"), "custom");
+
+            let throwing: Value = ctx
+                .eval("({ toString() { throw new Error('no') } })")
+                .unwrap();
+            assert!(matches!(
+                throwing.to_display_string(),
+                Err(Error::Exception)
+            ));
+            ctx.catch();
+            let formatted = format!("{}", JsDisplay(throwing));
+            assert!(formatted.starts_with("Object("));
+            assert!(!unsafe { qjs::JS_HasException(ctx.as_raw().as_ptr()) });
+
+            let value: Value = ctx.eval("[true, 'x']").unwrap();
+            assert_eq!(format!("{:>8}", JsDisplay(value)), "  true,x");
+        });
+    }
+
+    #[test]
+    fn coercion() {
+        test_with(|ctx| {
+            let eval = |source: &str| -> Value { ctx.eval(source).unwrap() };
+            assert_eq!(eval("''").to_number().unwrap(), 0.0);
+            assert_eq!(eval("' 12 '").to_number().unwrap(), 12.0);
+            assert_eq!(eval("null").to_number().unwrap(), 0.0);
+            assert_eq!(eval("true").to_number().unwrap(), 1.0);
+            assert_eq!(eval("[5]").to_number().unwrap(), 5.0);
+            assert!(eval("undefined").to_number().unwrap().is_nan());
+            assert!(eval("({})").to_number().unwrap().is_nan());
+            assert!(matches!(
+                eval("Symbol()").to_number(),
+                Err(Error::Exception)
+            ));
+            let error: Object = ctx.catch().into_object().unwrap();
+            assert!(error
+                .get::<_, StdString>("message")
+                .unwrap()
+                .contains("symbol"));
+            assert_eq!(error.get::<_, StdString>("name").unwrap(), "TypeError");
+
+            let string = |source: &str| eval(source).to_js_string().unwrap().to_string().unwrap();
+            assert_eq!(string("[]"), "");
+            assert_eq!(string("[1, 2]"), "1,2");
+            assert_eq!(string("null"), "null");
+            assert_eq!(string("-0"), "0");
+            assert!(eval("Symbol()").to_js_string().is_err());
+            ctx.catch();
+
+            assert!(!eval("''").to_bool());
+            assert!(!eval("0").to_bool());
+            assert!(!eval("NaN").to_bool());
+            assert!(!eval("null").to_bool());
+            assert!(!eval("0n").to_bool());
+            assert!(eval("'0'").to_bool());
+            assert!(eval("[]").to_bool());
+            assert!(eval("Symbol()").to_bool());
+        });
+    }
+
+    #[test]
+    fn from_value() {
+        test_with(|ctx| {
+            let eval = |source: &str| -> Value { ctx.eval(source).unwrap() };
+            assert!(Object::from_value(eval("[]")).is_ok());
+            assert!(Array::from_value(eval("[]")).is_ok());
+            assert!(Function::from_value(eval("(class {})")).is_ok());
+            assert!(String::from_value(eval("'a'")).is_ok());
+            assert!(BigInt::from_value(eval("1n")).is_ok());
+
+            let err = Object::from_value(eval("1")).unwrap_err();
+            assert!(matches!(
+                err,
+                Error::FromJs {
+                    from: "int",
+                    to: "object",
+                    ..
+                }
+            ));
+            assert!(matches!(
+                Array::from_value(eval("({})")),
+                Err(Error::FromJs {
+                    from: "object",
+                    to: "array",
+                    ..
+                })
+            ));
+            assert!(Function::from_value(eval("({})")).is_err());
+            assert!(String::from_value(eval("Symbol()")).is_err());
+            assert!(BigInt::from_value(eval("1")).is_err());
+        });
+    }
 }
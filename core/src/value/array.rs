@@ -1,6 +1,11 @@
 //! JavaScript array types.
 
-use crate::{atom::PredefinedAtom, qjs, Ctx, FromJs, IntoJs, Object, Result, Value};
+use crate::{
+    atom::PredefinedAtom,
+    function::{Rest, This},
+    qjs, Ctx, FromJs, Function, IntoJs, Object, Result, Value,
+};
+use alloc::vec::Vec;
 use core::{iter::FusedIterator, marker::PhantomData};
 
 use super::convert::FromIteratorJs;
@@ -67,6 +72,63 @@ impl<'js> Array<'js> {
         Ok(())
     }
 
+    /// Append a value to the end of the JavaScript array.
+    pub fn push<V: IntoJs<'js>>(&self, val: V) -> Result<()> {
+        self.set(self.len(), val)
+    }
+
+    /// Remove the last value of the JavaScript array and return it.
+    ///
+    /// Returns `undefined` if the array is empty.
+    pub fn pop(&self) -> Result<Value<'js>> {
+        let len = self.len();
+        if len == 0 {
+            return Ok(Value::new_undefined(self.ctx().clone()));
+        }
+        let val = self.get(len - 1)?;
+        self.0.set(PredefinedAtom::Length, len - 1)?;
+        Ok(val)
+    }
+
+    /// Remove `delete_count` values starting at `start` from the JavaScript array and insert the
+    /// items in their place, returns an array with the removed values.
+    ///
+    /// Calls the `splice` method of the array, so a negative `start` counts from the end of the
+    /// array and `start` and `delete_count` are clamped to the length of the array.
+    pub fn splice<I, V>(&self, start: i32, delete_count: u32, items: I) -> Result<Array<'js>>
+    where
+        I: IntoIterator<Item = V>,
+        V: IntoJs<'js>,
+    {
+        let ctx = self.ctx();
+        let items = items
+            .into_iter()
+            .map(|x| x.into_js(ctx))
+            .collect::<Result<Vec<_>>>()?;
+        let splice: Function = self.0.get("splice")?;
+        splice.call((This(self.clone()), start, delete_count, Rest(items)))
+    }
+
+    /// Set the values from `start` up to but not including `end` to the value, `None` fills up
+    /// to the end of the array.
+    ///
+    /// Calls the `fill` method of the array, so negative indices count from the end of the array.
+    pub fn fill<V: IntoJs<'js>>(&self, value: V, start: i32, end: Option<i32>) -> Result<&Self> {
+        let fill: Function = self.0.get("fill")?;
+        fill.call::<_, ()>((This(self.clone()), value, start, end))?;
+        Ok(self)
+    }
+
+    /// Returns a new array with the values from `start` up to but not including `end`, `None`
+    /// copies up to the end of the array.
+    ///
+    /// Calls the `slice` method of the array, so negative indices count from the end of the
+    /// array.
+    pub fn slice_js(&self, start: i32, end: Option<i32>) -> Result<Array<'js>> {
+        let slice: Function = self.0.get("slice")?;
+        slice.call((This(self.clone()), start, end))
+    }
+
     /// Get an iterator over elements of an array
     pub fn iter<T: FromJs<'js>>(&self) -> ArrayIter<'js, T> {
         let count = self.len() as _;
@@ -181,6 +243,78 @@ where
 mod test {
 
     use crate::*;
+
+    #[test]
+    fn push_pop_splice() {
+        test_with(|ctx| {
+            let array = Array::new(ctx.clone()).unwrap();
+            assert!(array.pop().unwrap().is_undefined());
+            array.push(1).unwrap();
+            array.push("b").unwrap();
+            array.push(3).unwrap();
+            assert_eq!(array.len(), 3);
+            assert_eq!(array.pop().unwrap().as_int(), Some(3));
+            assert_eq!(array.len(), 2);
+
+            for i in 2..6 {
+                array.push(i).unwrap();
+            }
+            let removed = array.splice(1, 2, [10, 11, 12]).unwrap();
+            assert_eq!(removed.len(), 2);
+            assert_eq!(removed.get::<StdString>(0).unwrap(), "b");
+            assert_eq!(removed.get::<i32>(1).unwrap(), 2);
+            ctx.globals().set("array", array.clone()).unwrap();
+            let joined: StdString = ctx.eval("array.join()").unwrap();
+            assert_eq!(joined, "1,10,11,12,3,4,5");
+
+            // Out of range arguments are clamped like in JavaScript.
+            let removed = array.splice(5, 100, Vec::<i32>::new()).unwrap();
+            assert_eq!(removed.len(), 2);
+            array.splice(100, 0, [6]).unwrap();
+            let joined: StdString = ctx.eval("array.join()").unwrap();
+            assert_eq!(joined, "1,10,11,12,3,6");
+
+            let frozen: Array = ctx.eval("Object.freeze([1])").unwrap();
+            assert!(frozen.push(2).is_err());
+            ctx.catch();
+            assert!(frozen.pop().is_err());
+            ctx.catch();
+        });
+    }
+    #[test]
+    fn fill_slice() {
+        test_with(|ctx| {
+            let array: Array = ctx.eval("[1, 2, 3, 4, 5]").unwrap();
+            ctx.globals().set("array", array.clone()).unwrap();
+            let slice = array.slice_js(1, Some(-1)).unwrap();
+            assert_eq!(
+                slice.iter().collect::<Result<Vec<i32>>>().unwrap(),
+                [2, 3, 4]
+            );
+            let tail = array.slice_js(-2, None).unwrap();
+            assert_eq!(tail.iter().collect::<Result<Vec<i32>>>().unwrap(), [4, 5]);
+
+            array
+                .fill(0, 1, Some(3))
+                .unwrap()
+                .fill("x", -1, None)
+                .unwrap();
+            let joined: StdString = ctx.eval("array.join()").unwrap();
+            assert_eq!(joined, "1,0,0,4,x");
+
+            let removed = array.splice(-2, 1, Vec::<i32>::new()).unwrap();
+            assert_eq!(removed.get::<i32>(0).unwrap(), 4);
+            let joined: StdString = ctx.eval("array.join()").unwrap();
+            assert_eq!(joined, "1,0,0,x");
+
+            // Overridden methods are respected.
+            ctx.eval::<(), _>("array.slice = () => ['overridden']")
+                .unwrap();
+            let slice = array.slice_js(0, None).unwrap();
+            assert_eq!(slice.get::<StdString>(0).unwrap(), "overridden");
+        });
+    }
+
     #[test]
     fn from_javascript() {
         test_with(|ctx| {
@@ -1,4 +1,6 @@
-use crate::{qjs, Ctx, Error, FromJs, IntoJs, JsLifetime, Object, Result, Value};
+use crate::{
+    qjs, runtime::opaque::Opaque, Ctx, Error, FromJs, IntoJs, JsLifetime, Object, Result, Value,
+};
 use alloc::vec::Vec;
 use core::{
     ffi::c_void,
@@ -52,9 +54,19 @@ impl<'js> ArrayBuffer<'js> {
         let capacity = src.capacity();
         let size = src.len() * size_of::<T>();
 
-        extern "C" fn drop_raw<T>(_rt: *mut qjs::JSRuntime, opaque: *mut c_void, ptr: *mut c_void) {
-            let ptr = ptr as *mut T;
+        extern "C" fn drop_raw<T>(rt: *mut qjs::JSRuntime, opaque: *mut c_void, ptr: *mut c_void) {
+            // the data of detached buffers was already freed
+            if ptr.is_null() {
+                return;
+            }
             let capacity = opaque as usize;
+            if is_byte::<T>() {
+                // the data can be moved out if the buffer is being detached
+                let bytes = unsafe { Vec::from_raw_parts(ptr as *mut u8, 0, capacity) };
+                unsafe { Opaque::from_runtime_ptr(rt).release_byte_buffer(ptr.cast(), bytes) };
+                return;
+            }
+            let ptr = ptr as *mut T;
             // reconstruct vector in order to free data
             // the length of actual data does not matter for copyable types
             unsafe { Vec::from_raw_parts(ptr, capacity, capacity) };
@@ -73,10 +85,21 @@ impl<'js> ArrayBuffer<'js> {
                 // don't forget to free data when error occurred
                 Vec::from_raw_parts(ptr, capacity, capacity);
             })?;
+            if is_byte::<T>() && capacity != 0 {
+                ctx.get_opaque().insert_byte_buffer(ptr.cast());
+            }
             Value::from_js_value(ctx, val)
         })))
     }
 
+    /// Create array buffer from the bytes, for example the bytes returned by
+    /// [`ArrayBuffer::detach`] in another context.
+    ///
+    /// The bytes are not copied, the buffer takes ownership of the vector.
+    pub fn from_vec(ctx: Ctx<'js>, bytes: Vec<u8>) -> Result<Self> {
+        Self::new(ctx, bytes)
+    }
+
     /// Create array buffer from slice
     pub fn new_copy<T: Copy>(ctx: Ctx<'js>, src: impl AsRef<[T]>) -> Result<Self> {
         let src = src.as_ref();
@@ -119,9 +142,35 @@ impl<'js> ArrayBuffer<'js> {
         Ok(unsafe { slice::from_raw_parts(raw.ptr.as_ptr().cast(), len) })
     }
 
-    /// Detach array buffer
-    pub fn detach(&mut self) {
-        unsafe { qjs::JS_DetachArrayBuffer(self.0.ctx.as_ptr(), self.0.as_js_value()) }
+    /// Detach the array buffer and return its bytes, transferring the ownership of the data.
+    ///
+    /// Afterwards the buffer and all typed arrays viewing it have a length of zero and data
+    /// views throw when accessed. The bytes are moved out without copying if the buffer was
+    /// created from a vector of bytes with [`ArrayBuffer::new`] or [`ArrayBuffer::from_vec`],
+    /// otherwise they are copied before the buffer is detached.
+    ///
+    /// Returns an error if the buffer is already detached or is a `SharedArrayBuffer`, which
+    /// can't be detached.
+    pub fn detach(&self) -> Result<Vec<u8>> {
+        let ctx = self.0.ctx();
+        if !unsafe { qjs::JS_IsArrayBuffer(self.0.as_js_value()) } {
+            return Err(Error::new_from_js_message(
+                "SharedArrayBuffer",
+                "ArrayBuffer",
+                "A shared buffer can't be detached",
+            ));
+        }
+        let raw = self.as_raw().ok_or_else(|| ctx.raise_exception())?;
+        let detach = || unsafe { qjs::JS_DetachArrayBuffer(ctx.as_ptr(), self.0.as_js_value()) };
+        let opaque = unsafe { ctx.get_opaque() };
+        if let Some(mut bytes) = opaque.detach_byte_buffer(raw.ptr.as_ptr(), detach) {
+            // the buffer was created from these bytes so they are initialized
+            unsafe { bytes.set_len(raw.len) };
+            return Ok(bytes);
+        }
+        let bytes = unsafe { slice::from_raw_parts(raw.ptr.as_ptr(), raw.len) }.to_vec();
+        detach();
+        Ok(bytes)
     }
 
     /// Reference to value
@@ -186,6 +235,11 @@ impl<'js> ArrayBuffer<'js> {
     }
 }
 
+/// Returns whether a vector of `T` has the memory layout of a vector of bytes.
+const fn is_byte<T>() -> bool {
+    size_of::<T>() == 1 && mem::align_of::<T>() == 1
+}
+
 impl<'js, T: TypedArrayItem> AsRef<[T]> for ArrayBuffer<'js> {
     fn as_ref(&self) -> &[T] {
         self.as_slice().expect("ArrayBuffer was detached")
@@ -347,4 +401,58 @@ mod test {
             assert_eq!(val.as_bytes().unwrap(), &res)
         });
     }
+
+    #[test]
+    fn detach_transfer() {
+        let rt = Runtime::new().unwrap();
+        let src = Context::full(&rt).unwrap();
+        let dst = Context::full(&rt).unwrap();
+        let bytes = src.with(|ctx| {
+            let buffer: ArrayBuffer = ctx
+                .eval(
+                    r#"
+                        globalThis.view = new Uint8Array([1, 2, 3, 4]);
+                        globalThis.data = new DataView(view.buffer);
+                        view.buffer
+                    "#,
+                )
+                .unwrap();
+            let bytes = buffer.detach().unwrap();
+            let res: StdString = ctx
+                .eval("[view.length, view.buffer.byteLength, view[0]].join()")
+                .unwrap();
+            assert_eq!(res, "0,0,");
+            assert!(ctx.eval::<(), _>("data.getUint8(0)").is_err());
+            assert!(buffer.detach().is_err());
+            bytes
+        });
+        assert_eq!(bytes, [1, 2, 3, 4]);
+        dst.with(|ctx| {
+            let buffer = ArrayBuffer::from_vec(ctx.clone(), bytes).unwrap();
+            ctx.globals().set("buffer", buffer).unwrap();
+            let sum: i32 = ctx
+                .eval("new Uint8Array(buffer).reduce((a, b) => a + b)")
+                .unwrap();
+            assert_eq!(sum, 10);
+        });
+    }
+
+    #[test]
+    fn detach_without_copy() {
+        test_with(|ctx| {
+            let bytes = alloc::vec![7u8; 16];
+            let ptr = bytes.as_ptr();
+            let buffer = ArrayBuffer::from_vec(ctx.clone(), bytes).unwrap();
+            let bytes = buffer.detach().unwrap();
+            assert_eq!(bytes.as_ptr(), ptr);
+            assert_eq!(bytes, [7u8; 16]);
+
+            let buffer = ArrayBuffer::new(ctx.clone(), [1u32, 2]).unwrap();
+            assert_eq!(buffer.detach().unwrap().len(), 8);
+
+            let buffer: ArrayBuffer = ctx.eval("new SharedArrayBuffer(4)").unwrap();
+            assert!(buffer.detach().is_err());
+            assert_eq!(buffer.len(), 4);
+        })
+    }
 }
@@ -0,0 +1,446 @@
+//! Async iterator integration for Rust streams.
+use crate::{
+    atom::PredefinedAtom,
+    function::This,
+    promise::{MaybePromise, MaybePromiseFuture},
+    CatchResultExt, CaughtResult, Ctx, FromJs, Function, IntoJs, Object, Promise, Result, Value,
+};
+use alloc::{boxed::Box, rc::Rc};
+use async_lock::Mutex;
+use core::{
+    future::{poll_fn, Future},
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_core::Stream;
+
+/// Wrapper for streams to convert to JS async iterators.
+///
+/// The converted value implements the async iterator protocol and can be used with
+/// `for await (const x of value)`. Every call to `next()` returns a promise which resolves once
+/// the next item has been pulled from the stream. When the stream has ended, the promise
+/// resolves to `{ value: undefined, done: true }`.
+///
+/// The stream is only polled from futures spawned on the context, so an
+/// [`AsyncRuntime`](crate::AsyncRuntime) is required to drive it.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+#[repr(transparent)]
+pub struct AsyncIter<S>(pub S);
+
+impl<S> From<S> for AsyncIter<S> {
+    fn from(stream: S) -> Self {
+        Self(stream)
+    }
+}
+
+type SharedStream<S> = Rc<Mutex<Option<Pin<Box<S>>>>>;
+
+/// Create an iterator result object, i.e. `{ value, done }`.
+fn iter_result<'js>(ctx: &Ctx<'js>, value: Option<Value<'js>>) -> Result<Object<'js>> {
+    let res = Object::new(ctx.clone())?;
+    let done = value.is_none();
+    res.set(
+        PredefinedAtom::Value,
+        value.unwrap_or_else(|| Value::new_undefined(ctx.clone())),
+    )?;
+    res.set(PredefinedAtom::Done, done)?;
+    Ok(res)
+}
+
+impl<'js, S, R> IntoJs<'js> for AsyncIter<S>
+where
+    S: Stream<Item = R> + 'js,
+    R: IntoJs<'js> + 'js,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let stream: SharedStream<S> = Rc::new(Mutex::new(Some(Box::pin(self.0))));
+        let iter = Object::new(ctx.clone())?;
+
+        let next_stream = stream.clone();
+        let next = Function::new(ctx.clone(), move |ctx: Ctx<'js>| {
+            let stream = next_stream.clone();
+            let ctx_clone = ctx.clone();
+            // The lock ensures concurrent calls to `next` resolve in the order they were made.
+            Promise::wrap_future(&ctx, async move {
+                let mut guard = stream.lock().await;
+                let item = match guard.as_mut() {
+                    Some(x) => poll_fn(|cx| x.as_mut().poll_next(cx)).await,
+                    None => None,
+                };
+                let value = match item {
+                    Some(x) => Some(x.into_js(&ctx_clone)?),
+                    None => {
+                        // Drop the stream as soon as it is finished.
+                        *guard = None;
+                        None
+                    }
+                };
+                iter_result(&ctx_clone, value)
+            })
+        })?
+        .with_name("next")?;
+
+        let return_stream = stream;
+        let return_ = Function::new(ctx.clone(), move |ctx: Ctx<'js>| {
+            let stream = return_stream.clone();
+            let ctx_clone = ctx.clone();
+            Promise::wrap_future(&ctx, async move {
+                *stream.lock().await = None;
+                iter_result(&ctx_clone, None)
+            })
+        })?
+        .with_name("return")?;
+
+        let async_iterator = Function::new(ctx.clone(), |this: This<Value<'js>>| this.0)?
+            .with_name("[Symbol.asyncIterator]")?;
+
+        iter.set(PredefinedAtom::Next, next)?;
+        iter.set(PredefinedAtom::Return, return_)?;
+        iter.set(PredefinedAtom::SymbolAsyncIterator, async_iterator)?;
+        Ok(iter.into_value())
+    }
+}
+
+/// Convert the stream into a JS async iterable, see [`AsyncIter`].
+pub fn into_async_iterable<'js, S, R>(ctx: &Ctx<'js>, stream: S) -> Result<Value<'js>>
+where
+    S: Stream<Item = R> + 'js,
+    R: IntoJs<'js> + 'js,
+{
+    AsyncIter(stream).into_js(ctx)
+}
+
+/// A JS async iterable consumed as a Rust [`Stream`].
+///
+/// Every item awaits the promise returned by a call to the `next` method of the iterator, the
+/// next call is only made once the previous promise settled. The stream ends when the iterator is
+/// done or after the first error. Dropping the stream before it ended calls the `return` method of
+/// the iterator, which runs the `finally` blocks of async generators.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub struct AsyncIterable<'js, T> {
+    iterator: Object<'js>,
+    next: Function<'js>,
+    pending: Option<MaybePromiseFuture<'js, Object<'js>>>,
+    done: bool,
+    marker: PhantomData<T>,
+}
+
+impl<'js, T> AsyncIterable<'js, T> {
+    /// Returns the iterator the items are pulled from.
+    pub fn iterator(&self) -> &Object<'js> {
+        &self.iterator
+    }
+}
+
+impl<'js, T> FromJs<'js> for AsyncIterable<'js, T> {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let object = Object::from_js(ctx, value.clone())?;
+        let method: Function = object.get(PredefinedAtom::SymbolAsyncIterator)?;
+        let iterator: Object = method.call((This(value),))?;
+        let next = iterator.get(PredefinedAtom::Next)?;
+        Ok(AsyncIterable {
+            iterator,
+            next,
+            pending: None,
+            done: false,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<'js, T: FromJs<'js>> AsyncIterable<'js, T> {
+    fn poll_result(&mut self, cx: &mut Context<'_>) -> Poll<CaughtResult<'js, Option<T>>> {
+        let ctx = self.iterator.ctx().clone();
+        if self.pending.is_none() {
+            let promise: MaybePromise =
+                self.next.call((This(self.iterator.clone()),)).catch(&ctx)?;
+            self.pending = Some(promise.into_future());
+        }
+
+        let Poll::Ready(result) = Pin::new(self.pending.as_mut().unwrap()).poll(cx) else {
+            return Poll::Pending;
+        };
+        self.pending = None;
+        let result = result?;
+        if result.get(PredefinedAtom::Done).catch(&ctx)? {
+            return Poll::Ready(Ok(None));
+        }
+        Poll::Ready(result.get(PredefinedAtom::Value).catch(&ctx).map(Some))
+    }
+}
+
+impl<'js, T: FromJs<'js>> Stream for AsyncIterable<'js, T> {
+    type Item = CaughtResult<'js, T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let res = match this.poll_result(cx) {
+            Poll::Ready(x) => x,
+            Poll::Pending => return Poll::Pending,
+        };
+        if !matches!(res, Ok(Some(_))) {
+            // Iterators which threw must not be closed.
+            this.done = true;
+        }
+        Poll::Ready(res.transpose())
+    }
+}
+
+impl<T> Unpin for AsyncIterable<'_, T> {}
+
+impl<T> Drop for AsyncIterable<'_, T> {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        let ctx = self.iterator.ctx();
+        let res = self
+            .iterator
+            .get::<_, Option<Function>>(PredefinedAtom::Return)
+            .and_then(|x| match x {
+                Some(x) => x.call::<_, ()>((This(self.iterator.clone()),)),
+                None => Ok(()),
+            });
+        if res.is_err() {
+            ctx.catch();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{into_async_iterable, AsyncIter, AsyncIterable};
+    use crate::{async_with, function::Func, AsyncContext, AsyncRuntime, CatchResultExt, Promise};
+    use core::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use futures_rs::StreamExt;
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    struct Ticks {
+        count: u32,
+        max: u32,
+        sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+        dropped: Arc<AtomicBool>,
+    }
+
+    impl Drop for Ticks {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl futures_core::Stream for Ticks {
+        type Item = u32;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u32>> {
+            if self.count == self.max {
+                return Poll::Ready(None);
+            }
+            let sleep = self
+                .sleep
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep(Duration::from_millis(5))));
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            self.sleep = None;
+            self.count += 1;
+            Poll::Ready(Some(self.count))
+        }
+    }
+
+    #[tokio::test]
+    async fn for_await_stream() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        let dropped = Arc::new(AtomicBool::new(false));
+        let dropped_clone = dropped.clone();
+        async_with!(ctx => |ctx| {
+            let dropped = dropped_clone;
+            ctx.globals()
+                .set(
+                    "ticks",
+                    Func::from(move |max: u32| {
+                        AsyncIter(Ticks {
+                            count: 0,
+                            max,
+                            sleep: None,
+                            dropped: dropped.clone(),
+                        })
+                    }),
+                )
+                .unwrap();
+
+            let promise: Promise = ctx
+                .eval(
+                    r#"
+                    (async () => {
+                        let res = [];
+                        for await (const x of ticks(4)) {
+                            res.push(x);
+                        }
+                        return res.join(",");
+                    })()
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            let res = promise.into_future::<String>().await.unwrap();
+            assert_eq!(res, "1,2,3,4");
+        })
+        .await;
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn break_drops_stream() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        let dropped = Arc::new(AtomicBool::new(false));
+        let dropped_clone = dropped.clone();
+        async_with!(ctx => |ctx| {
+            let dropped = dropped_clone;
+            ctx.globals()
+                .set(
+                    "ticks",
+                    Func::from(move || {
+                        AsyncIter(Ticks {
+                            count: 0,
+                            max: u32::MAX,
+                            sleep: None,
+                            dropped: dropped.clone(),
+                        })
+                    }),
+                )
+                .unwrap();
+
+            let promise: Promise = ctx
+                .eval(
+                    r#"
+                    (async () => {
+                        for await (const x of ticks()) {
+                            if (x === 2) {
+                                break;
+                            }
+                        }
+                        const iter = ticks();
+                        await iter.return();
+                        return (await iter.next()).done;
+                    })()
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert!(promise.into_future::<bool>().await.unwrap());
+        })
+        .await;
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn consume_async_generator() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            let generator: crate::Function = ctx
+                .eval(
+                    r#"
+                    (async function* () {
+                        try {
+                            for (let i = 1; ; i++) {
+                                await null;
+                                if (i === 4) throw new Error("four");
+                                yield i;
+                            }
+                        } finally {
+                            globalThis.closed = (globalThis.closed ?? 0) + 1;
+                        }
+                    })
+                    "#,
+                )
+                .unwrap();
+
+            // Errors end the stream without closing the iterator.
+            let stream: AsyncIterable<i32> = generator.call(()).unwrap();
+            let items = stream.collect::<Vec<_>>().await;
+            assert_eq!(items.len(), 4);
+            assert!(items[..3].iter().map(|x| x.as_ref().unwrap()).eq(&[1, 2, 3]));
+            assert!(items[3].is_err());
+
+            // Early termination closes the iterator.
+            let mut stream: AsyncIterable<i32> = generator.call(()).unwrap();
+            assert_eq!(stream.next().await.unwrap().unwrap(), 1);
+            drop(stream);
+        })
+        .await;
+        rt.idle().await;
+
+        async_with!(ctx => |ctx| {
+            assert_eq!(ctx.globals().get::<_, i32>("closed").unwrap(), 2);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        let dropped = Arc::new(AtomicBool::new(false));
+        let dropped_clone = dropped.clone();
+        async_with!(ctx => |ctx| {
+            let ticks = Ticks {
+                count: 0,
+                max: u32::MAX,
+                sleep: None,
+                dropped: dropped_clone,
+            };
+            let iterable = into_async_iterable(&ctx, ticks).unwrap();
+
+            // Wrap the iterator to check only one call to `next` is in flight.
+            let wrapped: crate::Value = ctx
+                .eval::<crate::Function, _>(
+                    r#"
+                    (iterable) => {
+                        const iter = iterable[Symbol.asyncIterator]();
+                        let inFlight = 0;
+                        globalThis.maxInFlight = 0;
+                        return {
+                            [Symbol.asyncIterator]() { return this; },
+                            async next() {
+                                maxInFlight = Math.max(maxInFlight, ++inFlight);
+                                try { return await iter.next(); } finally { inFlight--; }
+                            },
+                            return() { return iter.return(); },
+                        };
+                    }
+                    "#,
+                )
+                .unwrap()
+                .call((iterable,))
+                .unwrap();
+            let stream: AsyncIterable<u32> = wrapped.get().unwrap();
+            let items = stream.take(3).map(|x| x.unwrap()).collect::<Vec<_>>().await;
+            assert_eq!(items, [1, 2, 3]);
+            assert_eq!(ctx.globals().get::<_, u32>("maxInFlight").unwrap(), 1);
+        })
+        .await;
+        rt.idle().await;
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
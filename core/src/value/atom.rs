@@ -2,7 +2,7 @@
 
 use crate::{qjs, Ctx, Error, Result, String, Value};
 use alloc::string::{String as StdString, ToString as _};
-use core::{ffi::CStr, hash::Hash, ptr::null_mut};
+use core::{ffi::CStr, hash::Hash, mem, ptr::null_mut, slice};
 
 mod predefined;
 pub use predefined::PredefinedAtom;
@@ -128,6 +128,23 @@ impl<'js> Atom<'js> {
         }
     }
 
+    /// Call `f` with the atom as a string slice without allocating an owned string.
+    ///
+    /// Lone surrogates are replaced with the replacement character.
+    pub(crate) fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> Result<R> {
+        unsafe {
+            let mut len = mem::MaybeUninit::uninit();
+            let c_str = qjs::JS_AtomToCStringLen(self.ctx.as_ptr(), len.as_mut_ptr(), self.atom);
+            if c_str.is_null() {
+                return Err(Error::Unknown);
+            }
+            let bytes = slice::from_raw_parts(c_str.cast::<u8>(), len.assume_init() as _);
+            let res = f(&StdString::from_utf8_lossy(bytes));
+            qjs::JS_FreeCString(self.ctx.as_ptr(), c_str);
+            Ok(res)
+        }
+    }
+
     /// Convert the atom to a JavaScript string.
     pub fn to_js_string(&self) -> Result<String<'js>> {
         unsafe {
@@ -142,6 +159,15 @@ impl<'js> Atom<'js> {
         self.to_js_string().map(|String(value)| value)
     }
 
+    /// Convert the atom to the property key it represents, either a string or a symbol.
+    pub(crate) fn to_property_key(&self) -> Result<Value<'js>> {
+        unsafe {
+            let val = qjs::JS_AtomToValue(self.ctx.as_ptr(), self.atom);
+            let val = self.ctx.handle_exception(val)?;
+            Ok(Value::from_js_value(self.ctx.clone(), val))
+        }
+    }
+
     pub(crate) unsafe fn from_atom_val(ctx: Ctx<'js>, val: qjs::JSAtom) -> Self {
         Atom { atom: val, ctx }
     }
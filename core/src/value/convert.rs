@@ -39,6 +39,55 @@ mod into;
 #[repr(transparent)]
 pub struct Coerced<T>(pub T);
 
+/// A value which distinguishes JavaScript `null` from `undefined`
+///
+/// Unlike [`Option`], which converts both `null` and `undefined` to [`None`], this type keeps
+/// track of which of the two was given.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Result, convert::Nullable};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| -> Result<()> {
+/// #
+/// assert_eq!(ctx.eval::<Nullable<i32>, _>("null")?, Nullable::Null);
+/// assert_eq!(ctx.eval::<Nullable<i32>, _>("undefined")?, Nullable::Undefined);
+/// assert_eq!(ctx.eval::<Nullable<i32>, _>("42")?, Nullable::Value(42));
+/// assert!(ctx.eval::<Nullable<i32>, _>("'42'").is_err());
+///
+/// ctx.globals().set("x", Nullable::<i32>::Null)?;
+/// assert!(ctx.eval::<bool, _>("x === null")?);
+/// ctx.globals().set("x", Nullable::<i32>::Undefined)?;
+/// assert!(ctx.eval::<bool, _>("x === undefined")?);
+/// #
+/// # Ok(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Nullable<T> {
+    /// The JavaScript `null` value.
+    Null,
+    /// The JavaScript `undefined` value.
+    Undefined,
+    /// Any other value.
+    Value(T),
+}
+
+impl<T> Nullable<T> {
+    /// Returns the contained value, collapsing `null` and `undefined` to [`None`].
+    pub fn value(self) -> Option<T> {
+        match self {
+            Nullable::Value(x) => Some(x),
+            Nullable::Null | Nullable::Undefined => None,
+        }
+    }
+
+    /// Returns true if the value is either `null` or `undefined`.
+    pub fn is_nullish(&self) -> bool {
+        !matches!(self, Nullable::Value(_))
+    }
+}
+
 /// For converting JavaScript values to Rust values
 ///
 /// This trait automatically converts any value which can be
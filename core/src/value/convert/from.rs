@@ -1,6 +1,6 @@
 use crate::{
-    convert::List, Array, CString, Ctx, Error, FromAtom, FromJs, Object, Result, StdString, String,
-    Type, Value,
+    convert::{List, Nullable},
+    Array, CString, Ctx, Error, FromAtom, FromJs, Object, Result, StdString, String, Type, Value,
 };
 use alloc::{
     boxed::Box,
@@ -45,7 +45,17 @@ impl<'js> FromJs<'js> for StdString {
 impl<'js> FromJs<'js> for char {
     fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
         let type_name = value.type_name();
-        let s = String::from_value(value)?.to_string()?;
+        // JavaScript strings can contain unpaired surrogates which aren't valid chars.
+        let s = String::from_value(value)?
+            .to_string()
+            .map_err(|e| match e {
+                Error::Utf8(_) => Error::FromJs {
+                    from: type_name,
+                    to: "char",
+                    message: Some("The string converted to char contains a lone surrogate".into()),
+                },
+                e => e,
+            })?;
 
         let mut chars = s.chars();
         let (c, more) = (chars.next(), chars.next());
@@ -88,6 +98,22 @@ where
     }
 }
 
+/// Convert from JS as optional, keeping whether the value was `null` or `undefined`
+impl<'js, T> FromJs<'js> for Nullable<T>
+where
+    T: FromJs<'js>,
+{
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        if value.is_null() {
+            Ok(Nullable::Null)
+        } else if value.is_undefined() {
+            Ok(Nullable::Undefined)
+        } else {
+            T::from_js(ctx, value).map(Nullable::Value)
+        }
+    }
+}
+
 /// Convert from JS as result
 impl<'js, T> FromJs<'js> for Result<T>
 where
@@ -432,6 +458,19 @@ chrono_from_js_impls! {
     Local;
 }
 
+/// Convert from a JS string in any of the formats accepted by [`uuid::Uuid::parse_str`]
+#[cfg(feature = "uuid")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "uuid")))]
+impl<'js> FromJs<'js> for uuid::Uuid {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        use alloc::string::ToString;
+
+        let string = StdString::from_js(ctx, value)?;
+        uuid::Uuid::parse_str(&string)
+            .map_err(|error| Error::new_from_js_message("string", "uuid::Uuid", error.to_string()))
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(target_arch = "wasm32")]
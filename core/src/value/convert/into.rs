@@ -1,5 +1,5 @@
 use crate::{
-    convert::{IteratorJs, List},
+    convert::{IteratorJs, List, Nullable},
     value::Constructor,
     Array, CString, Ctx, Error, IntoAtom, IntoJs, Object, Result, StdResult, StdString, String,
     Value,
@@ -97,6 +97,19 @@ impl<'js> IntoJs<'js> for &() {
     }
 }
 
+impl<'js, T> IntoJs<'js> for Nullable<T>
+where
+    T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(match self {
+            Nullable::Null => Value::new_null(ctx.clone()),
+            Nullable::Undefined => Value::new_undefined(ctx.clone()),
+            Nullable::Value(value) => value.into_js(ctx)?,
+        })
+    }
+}
+
 impl<'js, T> IntoJs<'js> for Option<T>
 where
     T: IntoJs<'js>,
@@ -537,6 +550,16 @@ impl<'js, Tz: chrono::TimeZone> IntoJs<'js> for chrono::DateTime<Tz> {
     }
 }
 
+/// Convert into a hyphenated lowercase JS string
+#[cfg(feature = "uuid")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "uuid")))]
+impl<'js> IntoJs<'js> for uuid::Uuid {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let mut buffer = uuid::Uuid::encode_buffer();
+        self.hyphenated().encode_lower(&mut buffer).into_js(ctx)
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -560,6 +583,16 @@ mod test {
             assert!(rt.is_ok());
             let rt = ctx.eval::<char, _>("'ab'");
             assert!(rt.is_err());
+            let rt = ctx.eval::<char, _>(r"'\u{1F600}'");
+            assert_eq!(rt.unwrap(), '\u{1F600}');
+            let rt = ctx.eval::<char, _>(r"'\uD83D'");
+            assert!(matches!(rt, Err(crate::Error::FromJs { to: "char", .. })));
+            let rt = ctx.eval::<char, _>(r"'a\uDE00'");
+            assert!(rt.is_err());
+
+            globs.set("char", '\u{1F600}').unwrap();
+            let res: bool = ctx.eval(r"globalThis.char === '\u{1F600}'").unwrap();
+            assert!(res);
         });
     }
 
@@ -624,4 +657,38 @@ mod test {
             assert_eq!(millis, res);
         });
     }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_round_trip() {
+        use crate::{Context, Error, Runtime};
+        use uuid::Uuid;
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        let id = Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+
+        ctx.with(|ctx| {
+            ctx.globals().set("id", id).unwrap();
+            let res: bool = ctx
+                .eval("id === '67e55044-10b1-426f-9247-bb680e5fe0c8'")
+                .unwrap();
+            assert!(res);
+
+            let res: Uuid = ctx.eval("id.toUpperCase()").unwrap();
+            assert_eq!(res, id);
+
+            let res = ctx.eval::<Uuid, _>("'67e55044-10b1-426f-9247'");
+            assert!(matches!(
+                res,
+                Err(Error::FromJs {
+                    to: "uuid::Uuid",
+                    ..
+                })
+            ));
+            let res = ctx.eval::<Uuid, _>("42");
+            assert!(matches!(res, Err(Error::FromJs { from: "int", .. })));
+        });
+    }
 }
@@ -0,0 +1,254 @@
+use crate::{
+    atom::PredefinedAtom, qjs, value::Constructor, ArrayBuffer, Ctx, Error, FromJs, IntoJs,
+    JsLifetime, Object, Result, Value,
+};
+use core::{fmt, mem, ops::Deref, ptr::NonNull, slice};
+
+/// The trait which implements types which can be read from and written to a [`DataView`].
+pub trait DataViewItem: Copy {
+    /// Read the value from the start of the given bytes.
+    fn read(bytes: &[u8], little_endian: bool) -> Self;
+
+    /// Write the value to the start of the given bytes.
+    fn write(self, bytes: &mut [u8], little_endian: bool);
+}
+
+macro_rules! data_view_items {
+    ($($type:ty,)*) => {
+        $(impl DataViewItem for $type {
+            fn read(bytes: &[u8], little_endian: bool) -> Self {
+                let bytes = bytes[..mem::size_of::<Self>()].try_into().unwrap();
+                if little_endian {
+                    Self::from_le_bytes(bytes)
+                } else {
+                    Self::from_be_bytes(bytes)
+                }
+            }
+
+            fn write(self, bytes: &mut [u8], little_endian: bool) {
+                let src = if little_endian {
+                    self.to_le_bytes()
+                } else {
+                    self.to_be_bytes()
+                };
+                bytes[..mem::size_of::<Self>()].copy_from_slice(&src);
+            }
+        })*
+    };
+}
+
+data_view_items! {
+    i8, u8, i16, u16, i32, u32, i64, u64, f32, f64,
+}
+
+/// Rust representation of a JavaScript object of class DataView.
+///
+/// Unlike [`TypedArray`](crate::TypedArray), values in a data view are read and written with an
+/// explicit byte order.
+#[derive(PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct DataView<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for DataView<'js> {
+    type Changed<'to> = DataView<'to>;
+}
+
+impl<'js> fmt::Debug for DataView<'js> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("DataView").field(&self.0).finish()
+    }
+}
+
+impl<'js> DataView<'js> {
+    /// Create a data view of `len` bytes starting at `offset` into the given buffer.
+    pub fn new(buffer: ArrayBuffer<'js>, offset: usize, len: usize) -> Result<Self> {
+        let ctx = buffer.ctx().clone();
+        let constructor: Constructor = ctx.globals().get(PredefinedAtom::DataView)?;
+        constructor.construct((buffer, offset, len))
+    }
+
+    /// Get the underlying ArrayBuffer together with the byte offset and byte length of the view
+    /// into it.
+    ///
+    /// Returns an error if the buffer is detached.
+    pub fn buffer(&self) -> Result<(ArrayBuffer<'js>, usize, usize)> {
+        let buffer = self.0.get("buffer")?;
+        let offset = self.0.get("byteOffset")?;
+        let len = self.0.get("byteLength")?;
+        Ok((buffer, offset, len))
+    }
+
+    /// Returns the bytes of the view.
+    ///
+    /// Returns `None` if the buffer is detached or the view is out of bounds.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        let (ptr, len) = self.get_raw()?;
+        Some(unsafe { slice::from_raw_parts(ptr.as_ptr(), len) })
+    }
+
+    /// Read a value at the given byte offset into the view.
+    pub fn get<T: DataViewItem>(&self, offset: usize, little_endian: bool) -> Result<T> {
+        let ptr = self.item_ptr::<T>(offset)?;
+        let bytes = unsafe { slice::from_raw_parts(ptr.as_ptr(), mem::size_of::<T>()) };
+        Ok(T::read(bytes, little_endian))
+    }
+
+    /// Write a value at the given byte offset into the view.
+    pub fn set<T: DataViewItem>(&self, offset: usize, value: T, little_endian: bool) -> Result<()> {
+        let ptr = self.item_ptr::<T>(offset)?;
+        // SAFETY: The range was checked to be within the buffer, which can also be mutated from
+        // JavaScript at any time.
+        let bytes = unsafe { slice::from_raw_parts_mut(ptr.as_ptr(), mem::size_of::<T>()) };
+        value.write(bytes, little_endian);
+        Ok(())
+    }
+
+    /// Returns a pointer to the bytes of the view and their length.
+    fn get_raw(&self) -> Option<(NonNull<u8>, usize)> {
+        let (buffer, offset, len) = self.buffer().ok()?;
+        let raw = buffer.as_raw()?;
+        if offset.checked_add(len)? > raw.len {
+            return None;
+        }
+        // SAFETY: The offset was checked to be within the buffer.
+        Some((unsafe { raw.ptr.add(offset) }, len))
+    }
+
+    /// Returns a pointer to an item at the given offset, checking the bounds of the view.
+    fn item_ptr<T: DataViewItem>(&self, offset: usize) -> Result<NonNull<u8>> {
+        let (ptr, len) = self.get_raw().ok_or_else(Self::detached)?;
+        if len < offset || len - offset < mem::size_of::<T>() {
+            return Err(Self::out_of_bounds(offset));
+        }
+        Ok(unsafe { ptr.add(offset) })
+    }
+
+    fn detached() -> Error {
+        Error::new_from_js_message("DataView", "bytes", "the buffer is detached")
+    }
+
+    fn out_of_bounds(offset: usize) -> Error {
+        Error::new_from_js_message(
+            "DataView",
+            "bytes",
+            alloc::format!("offset {offset} is out of bounds"),
+        )
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_data_view().then_some(Self(object))
+    }
+}
+
+impl<'js> Deref for DataView<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for DataView<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for DataView<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for DataView<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        Self::from_value(value).ok_or_else(|| Error::new_from_js(ty_name, "DataView"))
+    }
+}
+
+impl<'js> IntoJs<'js> for DataView<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of [`DataView`].
+    pub fn is_data_view(&self) -> bool {
+        unsafe { qjs::JS_IsDataView(self.0.as_js_value()) }
+    }
+
+    /// Turn the object into a data view if the object is an instance of [`DataView`].
+    pub fn as_data_view(&self) -> Option<&DataView<'js>> {
+        // SAFETY: DataView is a transparent wrapper around an object.
+        self.is_data_view()
+            .then(|| unsafe { mem::transmute::<&Object<'js>, &DataView<'js>>(self) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn read_write_endianness() {
+        test_with(|ctx| {
+            let view: DataView = ctx
+                .eval(
+                    r#"
+                        globalThis.view = new DataView(new ArrayBuffer(8), 2, 4);
+                        view.setUint16(0, 0x1234, true);
+                        view
+                    "#,
+                )
+                .unwrap();
+            assert_eq!(view.get::<u16>(0, true).unwrap(), 0x1234);
+            assert_eq!(view.get::<u16>(0, false).unwrap(), 0x3412);
+
+            view.set::<i16>(2, -2, false).unwrap();
+            let res: i32 = ctx.eval("view.getInt16(2, false)").unwrap();
+            assert_eq!(res, -2);
+
+            assert!(view.get::<u32>(1, true).is_err());
+            assert!(view.set::<u8>(4, 1, true).is_err());
+
+            let (buffer, offset, len) = view.buffer().unwrap();
+            assert_eq!((buffer.len(), offset, len), (8, 2, 4));
+
+            let view = DataView::new(buffer, 4, 2).unwrap();
+            assert_eq!(view.get::<i16>(0, false).unwrap(), -2);
+            assert!(!Object::new(ctx).unwrap().is_data_view());
+        });
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{
+    function::This, qjs, Ctx, Error, FromJs, Function, IntoJs, JsLifetime, Object, Result, Value,
+};
+use core::{fmt, mem, ops::Deref};
+
+/// Rust representation of a JavaScript `Date` object.
+///
+/// The time of a date is the number of milliseconds since the unix epoch, which JavaScript
+/// truncates to whole milliseconds and limits to ±100,000,000 days. Times outside this range and
+/// dates parsed from invalid strings are invalid dates, whose time is `NaN`.
+///
+/// Requires the `Date` intrinsic to be available in the context.
+#[derive(PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct Date<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for Date<'js> {
+    type Changed<'to> = Date<'to>;
+}
+
+impl<'js> fmt::Debug for Date<'js> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Date").field(&self.0).finish()
+    }
+}
+
+impl<'js> Date<'js> {
+    /// Create a new date from the milliseconds since the unix epoch.
+    ///
+    /// Like `new Date(millis)` the fractional part is truncated and a time out of range results
+    /// in an invalid date.
+    pub fn new(ctx: Ctx<'js>, millis: f64) -> Result<Self> {
+        let value = unsafe {
+            let value = qjs::JS_NewDate(ctx.as_ptr(), millis);
+            ctx.handle_exception(value)?;
+            Value::from_js_value(ctx, value)
+        };
+        Ok(Self(Object(value)))
+    }
+
+    /// Returns the milliseconds since the unix epoch, `NaN` for an invalid date.
+    pub fn get_time(&self) -> Result<f64> {
+        let get_time: Function = self.0.get("getTime")?;
+        get_time.call((This(self.0.clone()),))
+    }
+
+    /// Returns whether the date is valid, i.e. its time is not `NaN`.
+    pub fn is_valid(&self) -> Result<bool> {
+        Ok(!self.get_time()?.is_nan())
+    }
+
+    /// Create a new date from a `chrono` date time.
+    ///
+    /// The time is truncated to whole milliseconds, the precision of a JavaScript date.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "chrono")))]
+    pub fn from_chrono<Tz: chrono::TimeZone>(
+        ctx: Ctx<'js>,
+        date_time: &chrono::DateTime<Tz>,
+    ) -> Result<Self> {
+        Self::new(ctx, date_time.timestamp_millis() as f64)
+    }
+
+    /// Convert the date into a `chrono` date time in UTC.
+    ///
+    /// Returns an error for an invalid date and for dates more than about 262,000 years away from
+    /// the unix epoch, which JavaScript supports but `chrono` doesn't.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "chrono")))]
+    pub fn to_chrono(&self) -> Result<chrono::DateTime<chrono::Utc>> {
+        let time = self.get_time()?;
+        if time.is_nan() {
+            return Err(Error::new_from_js_message(
+                "Date",
+                "chrono::DateTime",
+                "Invalid date",
+            ));
+        }
+        chrono::DateTime::from_timestamp_millis(time as i64).ok_or_else(|| {
+            Error::new_from_js_message("Date", "chrono::DateTime", "Timestamp out of range")
+        })
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_date().then_some(Self(object))
+    }
+}
+
+impl<'js> Deref for Date<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for Date<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for Date<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for Date<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        Self::from_value(value).ok_or_else(|| Error::new_from_js(ty_name, "Date"))
+    }
+}
+
+impl<'js> IntoJs<'js> for Date<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is a date.
+    pub fn is_date(&self) -> bool {
+        unsafe { qjs::JS_IsDate(self.0.as_js_value()) }
+    }
+
+    /// Turn the object into a date if it is one.
+    pub fn as_date(&self) -> Option<&Date<'js>> {
+        // SAFETY: Date is a transparent wrapper around an object.
+        self.is_date()
+            .then(|| unsafe { mem::transmute::<&Object<'js>, &Date<'js>>(self) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn time() {
+        test_with(|ctx| {
+            let date = Date::new(ctx.clone(), 1_700_000_000_123.75).unwrap();
+            assert_eq!(date.get_time().unwrap(), 1_700_000_000_123.0);
+            assert!(date.is_valid().unwrap());
+            ctx.globals().set("date", date).unwrap();
+            let iso: StdString = ctx.eval("date.toISOString()").unwrap();
+            assert_eq!(iso, "2023-11-14T22:13:20.123Z");
+
+            let date: Date = ctx.eval("new Date('not a date')").unwrap();
+            assert!(date.get_time().unwrap().is_nan());
+            let date = Date::new(ctx.clone(), 1e20).unwrap();
+            assert!(!date.is_valid().unwrap());
+
+            assert!(ctx
+                .eval::<Date, _>("({ getTime() { return 0; } })")
+                .is_err());
+            assert!(Object::new(ctx).unwrap().as_date().is_none());
+        })
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        use chrono::{DateTime, TimeZone, Utc};
+
+        test_with(|ctx| {
+            let time = Utc.timestamp_opt(1_700_000_000, 123_456_789).unwrap();
+            let date = Date::from_chrono(ctx.clone(), &time).unwrap();
+            // The sub-millisecond part is lost.
+            assert_eq!(
+                date.to_chrono().unwrap(),
+                DateTime::from_timestamp_millis(1_700_000_000_123).unwrap()
+            );
+
+            let date: Date = ctx.eval("new Date(-62198755200000)").unwrap();
+            assert_eq!(
+                date.to_chrono().unwrap().to_rfc3339(),
+                "-0001-01-01T00:00:00+00:00"
+            );
+            let date: Date = ctx.eval("new Date(8.64e15)").unwrap();
+            assert!(date.is_valid().unwrap());
+            assert!(date.to_chrono().is_err());
+
+            let date: Date = ctx.eval("new Date(NaN)").unwrap();
+            assert!(matches!(
+                date.to_chrono(),
+                Err(Error::FromJs {
+                    to: "chrono::DateTime",
+                    ..
+                })
+            ));
+        })
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    atom::PredefinedAtom, function::This, qjs, value::Constructor, Array, Atom, Ctx, Exception,
+    Filter, Function, Object, RegExp, Result, Type, Value,
+};
+use alloc::{format, vec::Vec};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use super::deep_eq::collection_entries;
+
+/// The kinds of objects which can be cloned.
+enum Kind {
+    Array,
+    Plain,
+    Date,
+    RegExp,
+    Map,
+    Set,
+}
+
+/// Returns the kind of a cloneable object.
+fn kind_of<'js>(obj: &Object<'js>, plain_class: qjs::JSClassID) -> Option<Kind> {
+    let raw = obj.as_js_value();
+    unsafe {
+        Some(if obj.is_array() {
+            Kind::Array
+        } else if qjs::JS_IsDate(raw) {
+            Kind::Date
+        } else if qjs::JS_IsRegExp(raw) {
+            Kind::RegExp
+        } else if qjs::JS_IsMap(raw) {
+            Kind::Map
+        } else if qjs::JS_IsSet(raw) {
+            Kind::Set
+        } else if qjs::JS_GetClassID(raw) == plain_class {
+            Kind::Plain
+        } else {
+            return None;
+        })
+    }
+}
+
+/// State of a single structured clone.
+struct Cloner<'js> {
+    ctx: Ctx<'js>,
+    /// The class id of ordinary objects.
+    plain_class: qjs::JSClassID,
+    /// Objects already cloned, used to preserve shared references and cycles.
+    cloned: HashMap<Value<'js>, Value<'js>>,
+    /// Values left to clone together with the object and key the clone is assigned to.
+    pending: Vec<(Value<'js>, Object<'js>, Atom<'js>)>,
+    /// Cloned `Map` and `Set` objects with their cloned entries which are inserted after all
+    /// values are cloned.
+    collections: Vec<(Object<'js>, Array<'js>)>,
+}
+
+impl<'js> Cloner<'js> {
+    fn new(ctx: Ctx<'js>) -> Result<Self> {
+        let plain = Object::new(ctx.clone())?;
+        Ok(Cloner {
+            plain_class: unsafe { qjs::JS_GetClassID(plain.as_js_value()) },
+            ctx,
+            cloned: HashMap::new(),
+            pending: Vec::new(),
+            collections: Vec::new(),
+        })
+    }
+
+    fn clone_root(mut self, value: &Value<'js>) -> Result<Value<'js>> {
+        let root = self.clone_value(value)?;
+        while let Some((value, target, key)) = self.pending.pop() {
+            let value = self.clone_value(&value)?;
+            target.set(key, value)?;
+        }
+        for (target, entries) in self.collections {
+            let (method, is_map) = if unsafe { qjs::JS_IsMap(target.as_js_value()) } {
+                ("set", true)
+            } else {
+                ("add", false)
+            };
+            let method: Function = target.get(method)?;
+            for pair in entries.iter::<Array>() {
+                let pair = pair?;
+                if is_map {
+                    let (key, value): (Value, Value) = (pair.get(0)?, pair.get(1)?);
+                    method.call::<_, ()>((This(target.clone()), key, value))?;
+                } else {
+                    method.call::<_, ()>((This(target.clone()), pair.get::<Value>(0)?))?;
+                }
+            }
+        }
+        Ok(root)
+    }
+
+    /// Clone a single value, the properties of objects are added to the pending values.
+    fn clone_value(&mut self, value: &Value<'js>) -> Result<Value<'js>> {
+        let obj = match value.type_of() {
+            Type::Uninitialized
+            | Type::Undefined
+            | Type::Null
+            | Type::Bool
+            | Type::Int
+            | Type::Float
+            | Type::String
+            | Type::BigInt => return Ok(value.clone()),
+            Type::Symbol => return Err(self.throw("symbol")),
+            Type::Function | Type::Constructor => return Err(self.throw("function")),
+            _ => value.as_object().unwrap(),
+        };
+        if let Some(cloned) = self.cloned.get(value) {
+            return Ok(cloned.clone());
+        }
+
+        let Some(kind) = kind_of(obj, self.plain_class) else {
+            return Err(self.throw(value.type_name()));
+        };
+        let global =
+            |name: PredefinedAtom| -> Result<Constructor<'js>> { self.ctx.globals().get(name) };
+        let cloned: Object = match kind {
+            Kind::Array => Array::new(self.ctx.clone())?.into_object(),
+            Kind::Plain => Object::new(self.ctx.clone())?,
+            Kind::Date => {
+                let time: Value = obj
+                    .get::<_, Function>("getTime")?
+                    .call((This(obj.clone()),))?;
+                global(PredefinedAtom::Date)?.construct((time,))?
+            }
+            Kind::RegExp => {
+                let regexp = RegExp(obj.clone());
+                RegExp::new(self.ctx.clone(), &regexp.source()?, &regexp.flags()?)?.0
+            }
+            Kind::Map => global(PredefinedAtom::Map)?.construct(())?,
+            Kind::Set => global(PredefinedAtom::Set)?.construct(())?,
+        };
+        self.cloned
+            .insert(value.clone(), cloned.clone().into_value());
+
+        if matches!(kind, Kind::Map | Kind::Set) {
+            let entries = Array::new(self.ctx.clone())?;
+            for (idx, (key, value)) in collection_entries(obj)?.into_iter().enumerate() {
+                let pair = Array::new(self.ctx.clone())?;
+                self.pending.push((
+                    key,
+                    pair.as_object().clone(),
+                    Atom::from_u32(self.ctx.clone(), 0)?,
+                ));
+                self.pending.push((
+                    value,
+                    pair.as_object().clone(),
+                    Atom::from_u32(self.ctx.clone(), 1)?,
+                ));
+                entries.set(idx, pair)?;
+            }
+            self.collections.push((cloned.clone(), entries));
+        }
+
+        if obj
+            .own_keys::<Atom>(Filter::new().symbol().enum_only())
+            .len()
+            != 0
+        {
+            return Err(self.throw("object with symbol keys"));
+        }
+        if let Kind::Array = kind {
+            cloned.set(
+                PredefinedAtom::Length,
+                obj.get::<_, Value>(PredefinedAtom::Length)?,
+            )?;
+        }
+        // Push in reverse so properties are assigned in their original order.
+        let mut props = obj.props::<Atom, Value>().collect::<Result<Vec<_>>>()?;
+        while let Some((key, value)) = props.pop() {
+            self.pending.push((value, cloned.clone(), key));
+        }
+        Ok(cloned.into_value())
+    }
+
+    fn throw(&self, type_name: &str) -> crate::Error {
+        Exception::throw_type(&self.ctx, &format!("{type_name} could not be cloned"))
+    }
+}
+
+impl<'js> Value<'js> {
+    /// Create a deep copy of the value using structured clone semantics.
+    ///
+    /// Primitive values are immutable and thus shared with the copy. Arrays and ordinary objects
+    /// are copied with their own enumerable string keyed properties, `Date`, `RegExp`, `Map` and
+    /// `Set` objects are copied together with their contents. The copy of an ordinary object
+    /// always has `Object.prototype` as its prototype. Shared references and cycles are
+    /// preserved in the copy.
+    ///
+    /// Throws a `TypeError` for values which can't be cloned: symbols, functions, objects with
+    /// enumerable symbol keyed properties and objects of other classes, including Rust classes.
+    ///
+    /// The value is copied iteratively so deeply nested values don't overflow the stack.
+    pub fn deep_clone(&self) -> Result<Value<'js>> {
+        Cloner::new(self.ctx.clone())?.clone_root(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test_with, Array, CatchResultExt, Error, Object, Value};
+
+    #[test]
+    fn deep_clone() {
+        test_with(|ctx| {
+            let value: Value = ctx
+                .eval(
+                    r#"
+                    const shared = { n: 1 };
+                    const value = {
+                        num: 1.5,
+                        str: "text",
+                        big: 10n,
+                        list: [1, , shared, shared],
+                        date: new Date(1000),
+                        re: /a+b/gi,
+                        map: new Map([[shared, "shared"], ["key", [1, 2]]]),
+                        set: new Set([1, shared]),
+                    };
+                    value.self = value;
+                    value
+                    "#,
+                )
+                .unwrap();
+            let cloned = value.deep_clone().catch(&ctx).unwrap();
+            assert_ne!(value, cloned);
+
+            ctx.globals().set("cloned", cloned).unwrap();
+            let res: bool = ctx
+                .eval(
+                    r#"
+                    cloned.self === cloned
+                        && cloned.list !== value.list
+                        && cloned.list.length === 4
+                        && !(1 in cloned.list)
+                        && cloned.list[2] === cloned.list[3]
+                        && cloned.list[2] !== shared
+                        && cloned.date.getTime() === 1000
+                        && cloned.re.source === "a+b"
+                        && cloned.re.flags === "gi"
+                        && cloned.map.get(cloned.list[2]) === "shared"
+                        && cloned.set.has(cloned.list[2])
+                        && cloned.big === 10n
+                    "#,
+                )
+                .unwrap();
+            assert!(res);
+        })
+    }
+
+    #[test]
+    fn deep_clone_errors() {
+        test_with(|ctx| {
+            for source in [
+                "({ f() {} })",
+                "[Symbol()]",
+                "({ [Symbol()]: 1 })",
+                "({ p: Promise.resolve() })",
+            ] {
+                let value: Value = ctx.eval(source).unwrap();
+                let err = value.deep_clone().catch(&ctx).unwrap_err();
+                assert!(err.to_string().contains("could not be cloned"), "{source}");
+            }
+            let value: Value = ctx.eval("({ f() {} })").unwrap();
+            assert!(matches!(value.deep_clone(), Err(Error::Exception)));
+            ctx.catch();
+        })
+    }
+
+    #[test]
+    fn deeply_nested() {
+        test_with(|ctx| {
+            let root = Object::new(ctx.clone()).unwrap();
+            let mut obj = root.clone();
+            for _ in 0..100_000 {
+                let next = Array::new(ctx.clone()).unwrap();
+                obj.set("next", next.clone()).unwrap();
+                obj = next.into_object();
+            }
+            let root = root.into_value();
+            let cloned = root.deep_clone().unwrap();
+            assert!(root.deep_equal(&cloned));
+        })
+    }
+}
@@ -0,0 +1,408 @@
+use crate::{
+    function::{Rest, This},
+    qjs, Atom, Filter, Function, Object, RegExp, Result, Value,
+};
+use alloc::{rc::Rc, vec, vec::Vec};
+use core::cell::RefCell;
+use hashbrown::HashSet;
+
+/// State for a single structural comparison.
+///
+/// Values are compared iteratively with an explicit stack, so deeply nested values don't
+/// overflow the native stack.
+struct DeepEq<'js> {
+    /// The comparison of primitive values and of objects which are the same object.
+    same: fn(&Value<'js>, &Value<'js>) -> bool,
+    /// Pairs of objects which are being compared or were found to be equal.
+    assumed: HashSet<(usize, usize)>,
+    /// The pairs added to `assumed` in insertion order, so they can be removed again when a
+    /// comparison which is allowed to fail differs.
+    assumed_order: Vec<(usize, usize)>,
+    /// Pairs of values left to compare.
+    stack: Vec<(Value<'js>, Value<'js>)>,
+}
+
+impl<'js> DeepEq<'js> {
+    fn new(same: fn(&Value<'js>, &Value<'js>) -> bool) -> Self {
+        DeepEq {
+            same,
+            assumed: HashSet::new(),
+            assumed_order: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Compare all pairs, returns `false` as soon as one pair differs.
+    fn eq_all(&mut self, pairs: Vec<(Value<'js>, Value<'js>)>) -> Result<bool> {
+        // Pairs already on the stack belong to an outer comparison and are left alone.
+        let base = self.stack.len();
+        self.stack.extend(pairs);
+        while self.stack.len() > base {
+            let (a, b) = self.stack.pop().expect("stack is not empty");
+            if (self.same)(&a, &b) {
+                continue;
+            }
+            let eq = match (a.as_object(), b.as_object()) {
+                (Some(a), Some(b)) => {
+                    // Objects which are already being compared are assumed to be equal, this
+                    // makes isomorphic cyclic structures compare equal.
+                    let key = unsafe { (a.get_ptr() as usize, b.get_ptr() as usize) };
+                    if self.assumed.insert(key) {
+                        self.assumed_order.push(key);
+                        self.eq_shallow(a, b)?
+                    } else {
+                        true
+                    }
+                }
+                _ => false,
+            };
+            if !eq {
+                self.stack.truncate(base);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Compare pairs without failing the whole comparison if they differ.
+    fn try_eq_all(&mut self, pairs: Vec<(Value<'js>, Value<'js>)>) -> Result<bool> {
+        // Pairs assumed equal while comparing might not be equal after all.
+        let mark = self.assumed_order.len();
+        let res = self.eq_all(pairs)?;
+        if !res {
+            for key in self.assumed_order.drain(mark..) {
+                self.assumed.remove(&key);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Compare two objects without their nested values, which are pushed to the stack instead.
+    fn eq_shallow(&mut self, a: &Object<'js>, b: &Object<'js>) -> Result<bool> {
+        let (a_raw, b_raw) = (a.as_js_value(), b.as_js_value());
+        let same_kind = unsafe {
+            a.is_array() == b.is_array()
+                && !a.is_function()
+                && !b.is_function()
+                && qjs::JS_IsDate(a_raw) == qjs::JS_IsDate(b_raw)
+                && qjs::JS_IsRegExp(a_raw) == qjs::JS_IsRegExp(b_raw)
+                && qjs::JS_IsMap(a_raw) == qjs::JS_IsMap(b_raw)
+                && qjs::JS_IsSet(a_raw) == qjs::JS_IsSet(b_raw)
+        };
+        if !same_kind || !opt_same(a.get_prototype(), b.get_prototype()) {
+            return Ok(false);
+        }
+
+        unsafe {
+            if qjs::JS_IsDate(a_raw) {
+                let get_time = |obj: &Object<'js>| -> Result<Value<'js>> {
+                    obj.get::<_, Function>("getTime")?
+                        .call((This(obj.clone()),))
+                };
+                self.stack.push((get_time(a)?, get_time(b)?));
+            } else if qjs::JS_IsRegExp(a_raw) {
+                let (a, b) = (RegExp(a.clone()), RegExp(b.clone()));
+                if a.source()? != b.source()? || a.flags()? != b.flags()? {
+                    return Ok(false);
+                }
+            } else if (qjs::JS_IsMap(a_raw) || qjs::JS_IsSet(a_raw))
+                && !self.eq_collections(a, b, qjs::JS_IsMap(a_raw))?
+            {
+                return Ok(false);
+            }
+        }
+
+        self.eq_properties(a, b)
+    }
+
+    /// Compare the own enumerable string keyed properties of two objects.
+    fn eq_properties(&mut self, a: &Object<'js>, b: &Object<'js>) -> Result<bool> {
+        let keys = a
+            .own_keys::<Atom>(Filter::default())
+            .collect::<Result<Vec<_>>>()?;
+        if keys.len() != b.own_keys::<Atom>(Filter::default()).len() {
+            return Ok(false);
+        }
+
+        // Push in reverse so properties are compared in their original order.
+        for key in keys.into_iter().rev() {
+            let (Some(a_prop), Some(b_prop)) = (
+                a.own_property_descriptor(key.clone())?,
+                b.own_property_descriptor(key)?,
+            ) else {
+                return Ok(false);
+            };
+            match (a_prop.value, b_prop.value) {
+                (Some(a), Some(b)) => self.stack.push((a, b)),
+                (None, None) if a_prop.get == b_prop.get && a_prop.set == b_prop.set => {}
+                _ => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Compare the entries of two `Map` or two `Set` objects.
+    fn eq_collections(&mut self, a: &Object<'js>, b: &Object<'js>, map: bool) -> Result<bool> {
+        let a_entries = collection_entries(a)?;
+        let mut b_entries = collection_entries(b)?;
+        if a_entries.len() != b_entries.len() {
+            return Ok(false);
+        }
+
+        'outer: for (a_key, a_value) in a_entries {
+            // Try to find an entry with the same key first, only fall back to a structural
+            // search for keys which are objects.
+            if let Some(idx) = b_entries
+                .iter()
+                .position(|(b_key, _)| a_key.same_value_zero(b_key))
+            {
+                let (_, b_value) = b_entries.swap_remove(idx);
+                if map {
+                    self.stack.push((a_value, b_value));
+                }
+                continue;
+            }
+            if !a_key.is_object() {
+                return Ok(false);
+            }
+            for idx in 0..b_entries.len() {
+                let (b_key, b_value) = &b_entries[idx];
+                let mut pairs = vec![(a_key.clone(), b_key.clone())];
+                if map {
+                    pairs.push((a_value.clone(), b_value.clone()));
+                }
+                if self.try_eq_all(pairs)? {
+                    b_entries.swap_remove(idx);
+                    continue 'outer;
+                }
+            }
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+/// Returns the `[key, value]` pairs of a `Map` or `[value, value]` pairs of a `Set`.
+pub(super) fn collection_entries<'js>(obj: &Object<'js>) -> Result<Vec<(Value<'js>, Value<'js>)>> {
+    let ctx = obj.ctx().clone();
+    let entries = Rc::new(RefCell::new(Vec::new()));
+    let entries_clone = entries.clone();
+    let callback = Function::new(
+        ctx,
+        move |value: Value<'js>, key: Value<'js>, _: Rest<Value<'js>>| {
+            entries_clone.borrow_mut().push((key, value));
+        },
+    )?;
+    let for_each: Function = obj.get("forEach")?;
+    for_each.call::<_, ()>((This(obj.clone()), callback))?;
+    Ok(entries.take())
+}
+
+fn opt_same<'js>(a: Option<Object<'js>>, b: Option<Object<'js>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.same_value_zero(&b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+impl<'js> Value<'js> {
+    /// Check whether two values are structurally equal.
+    ///
+    /// Primitive values and objects which are the same object are compared with the
+    /// `SameValueZero` algorithm, thus `NaN` equals `NaN` and `+0` equals `-0`.
+    /// Other objects are equal when they have the same prototype, are of the same kind and their
+    /// own enumerable string keyed properties are recursively equal. Arrays are only equal to
+    /// arrays, `Date` objects are compared by their time and `RegExp` objects by their source and
+    /// flags. `Map` objects are compared by their entries and `Set` objects by their elements,
+    /// matching keys by `SameValueZero` first and structurally otherwise. Functions are only
+    /// equal to themselves.
+    ///
+    /// Properties are read through their descriptors, so getters are never invoked; two accessor
+    /// properties are equal when they have the same getter and setter functions. The contents of
+    /// `Map` and `Set` objects are read by calling their `forEach` method.
+    ///
+    /// Cyclic structures are supported, two cyclic structures are equal if they are isomorphic.
+    /// The values are compared iteratively so deeply nested values don't overflow the stack.
+    pub fn deep_eq(&self, other: &Value<'js>) -> Result<bool> {
+        DeepEq::new(Value::same_value_zero).eq_all(vec![(self.clone(), other.clone())])
+    }
+
+    /// Check whether two values are structurally equal like [`Value::deep_eq`], but compare
+    /// primitive values with [`Value::strict_eq`] (`===`), thus `NaN` never equals `NaN`.
+    ///
+    /// Returns `false` if the comparison throws, the exception is cleared from the context.
+    pub fn deep_equal(&self, other: &Value<'js>) -> bool {
+        DeepEq::new(Value::strict_eq)
+            .eq_all(vec![(self.clone(), other.clone())])
+            .unwrap_or_else(|_| {
+                self.ctx.catch();
+                false
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn deep_eq(ctx: &Ctx, a: &str, b: &str) -> bool {
+        let a: Value = ctx.eval(a).unwrap();
+        let b: Value = ctx.eval(b).unwrap();
+        a.deep_eq(&b).unwrap()
+    }
+
+    #[test]
+    fn primitives() {
+        test_with(|ctx| {
+            assert!(deep_eq(&ctx, "1", "1.0"));
+            assert!(deep_eq(&ctx, "NaN", "NaN"));
+            assert!(deep_eq(&ctx, "0", "-0"));
+            assert!(deep_eq(&ctx, "'foo'", "'f' + 'oo'"));
+            assert!(deep_eq(&ctx, "undefined", "undefined"));
+            assert!(!deep_eq(&ctx, "null", "undefined"));
+            assert!(!deep_eq(&ctx, "1", "'1'"));
+            assert!(!deep_eq(&ctx, "1n", "1"));
+        })
+    }
+
+    #[test]
+    fn objects_and_arrays() {
+        test_with(|ctx| {
+            assert!(deep_eq(
+                &ctx,
+                "({a: 1, b: [1, {c: 2}]})",
+                "({b: [1, {c: 2}], a: 1})"
+            ));
+            assert!(!deep_eq(&ctx, "({a: 1})", "({a: 1, b: 2})"));
+            assert!(!deep_eq(&ctx, "({a: 1})", "({a: 2})"));
+            assert!(!deep_eq(&ctx, "({0: 1, 1: 2})", "[1, 2]"));
+            assert!(!deep_eq(&ctx, "[1, 2]", "[1, 2, 3]"));
+            assert!(!deep_eq(&ctx, "({})", "Object.create(null)"));
+            assert!(!deep_eq(&ctx, "(() => 1)", "(() => 1)"));
+        })
+    }
+
+    #[test]
+    fn getters_are_not_invoked() {
+        test_with(|ctx| {
+            let a: Value = ctx
+                .eval(
+                    r#"
+                    globalThis.calls = 0;
+                    globalThis.getter = function() { calls += 1; return 1 };
+                    ({ get a() { return getter() } })
+                "#,
+                )
+                .unwrap();
+            let b: Value = ctx.eval("({ get a() { return getter() } })").unwrap();
+            assert!(!a.deep_eq(&b).unwrap());
+            assert!(a.deep_eq(&a.clone()).unwrap());
+            let calls: i32 = ctx.globals().get("calls").unwrap();
+            assert_eq!(calls, 0);
+        })
+    }
+
+    #[test]
+    fn maps_and_sets() {
+        test_with(|ctx| {
+            assert!(deep_eq(
+                &ctx,
+                "new Map([[1, {a: 1}], ['b', [2]]])",
+                "new Map([['b', [2]], [1, {a: 1}]])"
+            ));
+            assert!(!deep_eq(&ctx, "new Map([[1, 2]])", "new Map([[1, 3]])"));
+            assert!(!deep_eq(&ctx, "new Map([[1, 2]])", "new Set([1])"));
+            assert!(deep_eq(
+                &ctx,
+                "new Set([1, NaN, {a: 1}])",
+                "new Set([{a: 1}, NaN, 1])"
+            ));
+            assert!(!deep_eq(&ctx, "new Set([{a: 1}])", "new Set([{a: 2}])"));
+            assert!(!deep_eq(&ctx, "new Set([1, 2])", "new Set([1, 2, 3])"));
+        })
+    }
+
+    #[test]
+    fn dates_and_regexps() {
+        test_with(|ctx| {
+            assert!(deep_eq(&ctx, "new Date(5)", "new Date(5)"));
+            assert!(!deep_eq(&ctx, "new Date(5)", "new Date(6)"));
+            assert!(!deep_eq(&ctx, "new Date(5)", "({})"));
+            assert!(deep_eq(&ctx, "/a/g", "/a/g"));
+            assert!(!deep_eq(&ctx, "/a/g", "/a/i"));
+            assert!(!deep_eq(&ctx, "/a/g", "/b/g"));
+        })
+    }
+
+    #[test]
+    fn deeply_nested() {
+        test_with(|ctx| {
+            let nested =
+                "(() => { let a = []; for (let i = 0; i < 100000; i++) a = [a]; return a })()";
+            assert!(deep_eq(&ctx, nested, nested));
+        })
+    }
+
+    #[test]
+    fn deep_equal() {
+        test_with(|ctx| {
+            let eq = |a: &str, b: &str| -> bool {
+                let a: Value = ctx.eval(a).unwrap();
+                let b: Value = ctx.eval(b).unwrap();
+                a.deep_equal(&b)
+            };
+            assert!(!eq("NaN", "NaN"));
+            assert!(!eq("[NaN]", "[NaN]"));
+            assert!(eq("0", "-0"));
+            assert!(!eq("1", "'1'"));
+            assert!(eq("({ a: [1, { b: 2 }] })", "({ a: [1, { b: 2 }] })"));
+            assert!(!eq("({ a: [1, { b: 2 }] })", "({ a: [1, { b: 3 }] })"));
+        })
+    }
+
+    #[test]
+    fn errors() {
+        test_with(|ctx| {
+            let a: Value = ctx.eval("new Map([[1, 1]])").unwrap();
+            let b: Value = ctx
+                .eval("const b = new Map([[1, 1]]); b.forEach = () => { throw new Error('x') }; b")
+                .unwrap();
+            assert!(matches!(a.deep_eq(&b), Err(Error::Exception)));
+            ctx.catch();
+            assert!(!a.deep_equal(&b));
+            assert!(!ctx.catch().is_object());
+        })
+    }
+
+    #[test]
+    fn cyclic_structures() {
+        test_with(|ctx| {
+            assert!(deep_eq(
+                &ctx,
+                "(() => { let a = { v: 1 }; a.self = a; return a })()",
+                "(() => { let b = { v: 1 }; b.self = b; return b })()"
+            ));
+            assert!(deep_eq(
+                &ctx,
+                "(() => { let a = { v: 1 }; let b = { v: 2, next: a }; a.next = b; return a })()",
+                "(() => { let a = { v: 1 }; let b = { v: 2, next: a }; a.next = b; return a })()"
+            ));
+            assert!(deep_eq(
+                &ctx,
+                "(() => { let a = []; a.push(a); return a })()",
+                "(() => { let b = []; b.push(b); return b })()"
+            ));
+            assert!(!deep_eq(
+                &ctx,
+                "(() => { let a = { v: 1 }; a.self = a; return a })()",
+                "(() => { let b = { v: 2 }; b.self = b; return b })()"
+            ));
+            assert!(deep_eq(
+                &ctx,
+                "(() => { let m = new Map(); m.set('m', m); return m })()",
+                "(() => { let m = new Map(); m.set('m', m); return m })()"
+            ));
+        })
+    }
+}
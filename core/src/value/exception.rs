@@ -67,6 +67,69 @@ impl<'js> Exception<'js> {
         Ok(Exception(obj))
     }
 
+    /// Creates a new exception which is an instance of the given error class like `TypeError`
+    /// without throwing it.
+    ///
+    /// The error is created with the intrinsic error classes of the context, changes to the
+    /// global error constructors by scripts don't affect it.
+    fn new_native(
+        ctx: &Ctx<'js>,
+        throw: fn(&Ctx<'js>, &str) -> Error,
+        message: &str,
+    ) -> Result<Self> {
+        throw(ctx, message);
+        let value = ctx.catch();
+        value
+            .into_object()
+            .and_then(Self::from_object)
+            .ok_or_else(|| Error::new_from_js("value", "exception"))
+    }
+
+    /// Creates a new `SyntaxError` with a given message without throwing it.
+    pub fn syntax_error(ctx: &Ctx<'js>, message: &str) -> Result<Self> {
+        Self::new_native(ctx, Self::throw_syntax, message)
+    }
+
+    /// Creates a new `TypeError` with a given message without throwing it.
+    ///
+    /// Properties can be added to the error before it is thrown with [`Exception::throw`]:
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Exception, Function, Result, Ctx, CatchResultExt};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let open = Function::new(ctx.clone(), |ctx: Ctx| -> Result<()> {
+    ///     let error = Exception::type_error(&ctx, "file not found")?;
+    ///     error.set("code", "ENOENT")?;
+    ///     Err(error.throw())
+    /// })
+    /// .unwrap();
+    /// ctx.globals().set("open", open).unwrap();
+    /// let code: String = ctx
+    ///     .eval("try { open() } catch (e) { e instanceof TypeError && e.code }")
+    ///     .unwrap();
+    /// assert_eq!(code, "ENOENT");
+    /// # });
+    /// ```
+    pub fn type_error(ctx: &Ctx<'js>, message: &str) -> Result<Self> {
+        Self::new_native(ctx, Self::throw_type, message)
+    }
+
+    /// Creates a new `ReferenceError` with a given message without throwing it.
+    pub fn reference_error(ctx: &Ctx<'js>, message: &str) -> Result<Self> {
+        Self::new_native(ctx, Self::throw_reference, message)
+    }
+
+    /// Creates a new `RangeError` with a given message without throwing it.
+    pub fn range_error(ctx: &Ctx<'js>, message: &str) -> Result<Self> {
+        Self::new_native(ctx, Self::throw_range, message)
+    }
+
+    /// Creates a new `InternalError` with a given message without throwing it.
+    pub fn internal_error(ctx: &Ctx<'js>, message: &str) -> Result<Self> {
+        Self::new_native(ctx, Self::throw_internal, message)
+    }
+
     /// Returns the message of the error.
     ///
     /// Same as retrieving `error.message` in JavaScript.
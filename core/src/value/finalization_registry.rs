@@ -0,0 +1,190 @@
+use crate::{
+    function::This, value::Constructor, Ctx, Error, FromJs, Function, IntoJs, JsLifetime, Object,
+    Result, Value,
+};
+use core::{fmt, ops::Deref};
+
+/// Rust representation of a JavaScript `FinalizationRegistry`.
+///
+/// The registry calls its callback with the held value of a registered target some time after
+/// the target has been garbage collected. The callbacks are run as pending jobs, so the runtime
+/// must execute its jobs for them to be called.
+///
+/// Requires the `WeakRef` intrinsic to be available in the context.
+#[derive(PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct FinalizationRegistry<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for FinalizationRegistry<'js> {
+    type Changed<'to> = FinalizationRegistry<'to>;
+}
+
+impl<'js> fmt::Debug for FinalizationRegistry<'js> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("FinalizationRegistry")
+            .field(&self.0)
+            .finish()
+    }
+}
+
+impl<'js> FinalizationRegistry<'js> {
+    /// Create a new registry which calls `callback` with the held value of collected targets.
+    pub fn new(ctx: Ctx<'js>, callback: Function<'js>) -> Result<Self> {
+        let constructor = Self::constructor(&ctx)?;
+        constructor.construct((callback,))
+    }
+
+    /// Register a target object with the registry.
+    ///
+    /// `held_value` is passed to the callback once `target` is collected. If `unregister_token`
+    /// is given the registration can be removed again with [`FinalizationRegistry::unregister`].
+    pub fn register(
+        &self,
+        target: Object<'js>,
+        held_value: Value<'js>,
+        unregister_token: Option<Value<'js>>,
+    ) -> Result<()> {
+        let register: Function = self.0.get("register")?;
+        register.call((This(self.0.clone()), target, held_value, unregister_token))
+    }
+
+    /// Remove all registrations made with the given token.
+    ///
+    /// Returns whether any registration was removed.
+    pub fn unregister(&self, token: Value<'js>) -> Result<bool> {
+        let unregister: Function = self.0.get("unregister")?;
+        unregister.call((This(self.0.clone()), token))
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Constructor<'js>> {
+        ctx.globals().get("FinalizationRegistry")
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_finalization_registry().then_some(Self(object))
+    }
+}
+
+impl<'js> Deref for FinalizationRegistry<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for FinalizationRegistry<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for FinalizationRegistry<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for FinalizationRegistry<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        Self::from_value(value).ok_or_else(|| Error::new_from_js(ty_name, "FinalizationRegistry"))
+    }
+}
+
+impl<'js> IntoJs<'js> for FinalizationRegistry<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of the global `FinalizationRegistry`
+    /// constructor.
+    pub fn is_finalization_registry(&self) -> bool {
+        FinalizationRegistry::constructor(self.ctx())
+            .map(|constructor| self.is_instance_of(constructor))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+    #[test]
+    fn callback_after_collection() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let held = Rc::new(RefCell::new(Vec::new()));
+
+        ctx.with(|ctx| {
+            let held = held.clone();
+            let callback =
+                Function::new(ctx.clone(), move |value: i32| held.borrow_mut().push(value))
+                    .unwrap();
+            let registry = FinalizationRegistry::new(ctx.clone(), callback).unwrap();
+
+            let targets = [
+                Object::new(ctx.clone()).unwrap(),
+                Object::new(ctx.clone()).unwrap(),
+            ];
+            let token = Object::new(ctx.clone()).unwrap();
+            registry
+                .register(targets[0].clone(), 1.into_js(&ctx).unwrap(), None)
+                .unwrap();
+            registry
+                .register(
+                    targets[1].clone(),
+                    2.into_js(&ctx).unwrap(),
+                    Some(token.clone().into_value()),
+                )
+                .unwrap();
+            assert!(registry.unregister(token.clone().into_value()).unwrap());
+            assert!(!registry.unregister(token.into_value()).unwrap());
+
+            ctx.globals().set("registry", registry).unwrap();
+            let registry: FinalizationRegistry = ctx.globals().get("registry").unwrap();
+            assert!(registry.is_finalization_registry());
+            assert!(ctx
+                .globals()
+                .get::<_, FinalizationRegistry>("Object")
+                .is_err());
+        });
+
+        rt.run_gc();
+        while rt.execute_pending_job().unwrap() {}
+        assert_eq!(*held.borrow(), [1]);
+    }
+}
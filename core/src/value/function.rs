@@ -4,23 +4,30 @@ use crate::{
     atom::PredefinedAtom,
     class::{Class, JsClass},
     function::ffi::RustFunc,
-    qjs, Ctx, Error, FromJs, IntoJs, Object, Result, Value,
+    qjs, Array, Coerced, Ctx, Error, Exception, FromJs, IntoJs, Object, Result, Value,
 };
 
 mod args;
 mod ffi;
 mod into_func;
+mod memoize;
 mod params;
 mod types;
 
-use alloc::{borrow::ToOwned as _, boxed::Box};
+use alloc::{borrow::ToOwned as _, boxed::Box, vec::Vec};
 pub use args::{Args, IntoArg, IntoArgs};
+use core::cell::Cell;
 pub use ffi::RustFunction;
+pub use memoize::{memoize, MemoizedFunction};
 pub use params::{FromParam, FromParams, ParamRequirement, Params, ParamsAccessor};
 #[cfg(feature = "futures")]
 pub use types::Async;
 pub use types::{Exhaustive, Flat, Func, FuncArg, MutFn, Null, OnceFn, Opt, Rest, This};
 
+/// The maximum number of arguments [`Function::apply`] accepts, the same limit QuickJS uses for
+/// `Function.prototype.apply`.
+pub const MAX_APPLY_ARGS: usize = 65535;
+
 /// A trait for converting a Rust function to a JavaScript function.
 pub trait IntoJsFunc<'js, P> {
     /// Returns the requirements this function has for the set of arguments used to call this
@@ -58,6 +65,33 @@ impl<'js> Function<'js> {
         Function(cls.into_inner()).with_length(F::param_requirements().min())
     }
 
+    /// Create a new function from a Rust closure which can only be called once.
+    ///
+    /// The closure is moved out of the function on the first call, so it can take ownership of
+    /// the values it captured. The closure is called with the `this` value and all arguments the
+    /// function was called with. Any subsequent call throws a `TypeError`.
+    pub fn new_once<F>(ctx: Ctx<'js>, f: F) -> Result<Self>
+    where
+        F: FnOnce(Ctx<'js>, Value<'js>, Rest<Value<'js>>) -> Result<Value<'js>> + 'js,
+    {
+        let f = Cell::new(Some(f));
+        let func = Box::new(move |params: Params<'_, 'js>| {
+            let ctx = params.ctx().clone();
+            let f = f
+                .take()
+                .ok_or_else(|| Exception::throw_type(&ctx, "function can only be called once"))?;
+            let this = params.this();
+            let args = (0..params.len())
+                .filter_map(|idx| params.arg(idx))
+                .collect::<Vec<_>>();
+            f(ctx, this, Rest(args))
+        }) as Box<dyn RustFunc<'js> + 'js>;
+
+        let cls = Class::instance(ctx, RustFunction(func))?;
+        debug_assert!(cls.is_function());
+        Ok(Function(cls.into_inner()))
+    }
+
     /// Call the function with given arguments.
     pub fn call<A, R>(&self, args: A) -> Result<R>
     where
@@ -79,6 +113,44 @@ impl<'js> Function<'js> {
         args.apply(self)
     }
 
+    /// Call the function with the elements of an array as arguments, like
+    /// `Function.prototype.apply` in JavaScript.
+    pub fn apply<R>(&self, this: Value<'js>, args: Array<'js>) -> Result<R>
+    where
+        R: FromJs<'js>,
+    {
+        self.apply_array_like(this, args.into_object())
+    }
+
+    /// Call the function with the elements of an array-like object as arguments.
+    ///
+    /// The number of arguments is read from the `length` property of the object, missing elements
+    /// are passed as `undefined`. Throws a `RangeError` if the object has more than
+    /// [`MAX_APPLY_ARGS`] elements.
+    pub fn apply_array_like<R>(&self, this: Value<'js>, args: Object<'js>) -> Result<R>
+    where
+        R: FromJs<'js>,
+    {
+        let ctx = self.ctx();
+        let len = args
+            .get::<_, Coerced<i64>>(PredefinedAtom::Length)?
+            .0
+            .max(0) as u64;
+        if len > MAX_APPLY_ARGS as u64 {
+            return Err(Exception::throw_range(
+                ctx,
+                "too many arguments in function call",
+            ));
+        }
+        let len = len as usize;
+        let mut accum_args = Args::new(ctx.clone(), len);
+        accum_args.this(this)?;
+        for idx in 0..len {
+            accum_args.push_arg(args.get::<_, Value>(idx as u32)?)?;
+        }
+        self.call_arg(accum_args)
+    }
+
     /// Defer call the function with given arguments.
     ///
     /// Calling a function with defer is equivalent to calling a JavaScript function with
@@ -201,11 +273,15 @@ impl<'js> Constructor<'js> {
             let this = params.this();
             let ctx = params.ctx().clone();
 
-            // get the prototype of thie class from itself or the inate class prototype.
-            let proto = this
-                .into_function()
-                .map(|func| func.get(PredefinedAtom::Prototype))
-                .unwrap_or_else(|| Class::<C>::prototype(&ctx))?;
+            // get the prototype of the class from `new.target`, which is a script subclass when
+            // called through `super()`, falling back to the inate class prototype.
+            let proto = match this.into_function() {
+                Some(func) => func
+                    .get::<_, Value>(PredefinedAtom::Prototype)?
+                    .into_object()
+                    .map_or_else(|| Class::<C>::prototype(&ctx), |x| Ok(Some(x))),
+                None => Class::<C>::prototype(&ctx),
+            }?;
 
             let res = f.call(params)?;
             res.as_object()
@@ -577,6 +653,40 @@ mod test {
         })
     }
 
+    #[test]
+    fn once_callback_moves_captured_data() {
+        test_with(|ctx| {
+            let data = vec![1, 2, 3];
+            let f = Function::new_once(ctx.clone(), move |ctx, this, args| {
+                let mut data = data;
+                data.extend(args.0.iter().map(|x| x.as_int().unwrap()));
+                if let Some(x) = this.as_int() {
+                    data.push(x);
+                }
+                data.into_js(&ctx)
+            })
+            .unwrap();
+            ctx.globals().set("once", f).unwrap();
+
+            let res: Vec<i32> = ctx.eval("once.call(6, 4, 5)").unwrap();
+            assert_eq!(res, [1, 2, 3, 4, 5, 6]);
+
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                    try {
+                        once();
+                        "no error"
+                    } catch (e) {
+                        e instanceof TypeError ? e.message : "wrong error"
+                    }
+                    "#,
+                )
+                .unwrap();
+            assert_eq!(res, "function can only be called once");
+        })
+    }
+
     #[test]
     fn multiple_const_callbacks() {
         test_with(|ctx| {
@@ -817,4 +927,36 @@ mod test {
             assert_eq!(n, 3);
         });
     }
+
+    #[test]
+    fn apply_array() {
+        test_with(|ctx| {
+            let f: Function = ctx
+                .eval("(function(...args) { return [this.name, ...args].join(','); })")
+                .unwrap();
+            let this: Value = ctx.eval("({ name: 'self' })").unwrap();
+
+            let args: Array = ctx.eval("[1, 'a', true]").unwrap();
+            let res: StdString = f.apply(this.clone(), args).unwrap();
+            assert_eq!(res, "self,1,a,true");
+
+            let args: Object = ctx.eval("({ length: 3, 0: 'x', 2: 'z' })").unwrap();
+            let res: StdString = f.apply_array_like(this.clone(), args).unwrap();
+            assert_eq!(res, "self,x,,z");
+
+            let count: Function = ctx.eval("(...args) => args.length").unwrap();
+            let args: Array = ctx.eval("new Array(65535).fill(0)").unwrap();
+            let res: usize = count
+                .apply(Value::new_undefined(ctx.clone()), args)
+                .unwrap();
+            assert_eq!(res, function::MAX_APPLY_ARGS);
+
+            let args: Array = ctx.eval("new Array(65536)").unwrap();
+            let err = count
+                .apply::<usize>(Value::new_undefined(ctx.clone()), args)
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("too many arguments"));
+        });
+    }
 }
@@ -129,6 +129,8 @@ impl<'js> Args<'js> {
     where
         R: FromJs<'js>,
     {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::call(func.as_value(), "js");
         let val = unsafe {
             let val = qjs::JS_Call(
                 self.ctx.as_ptr(),
@@ -63,6 +63,8 @@ impl<'js> JsClass<'js> for RustFunction<'js> {
     }
 
     fn call<'a>(this: &JsCell<'js, Self>, params: Params<'a, 'js>) -> Result<Value<'js>> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::call(&params.function(), "rust");
         this.borrow().0.call(params)
     }
 }
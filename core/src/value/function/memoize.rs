@@ -0,0 +1,293 @@
+use core::{cell::RefCell, hash::Hash, num::NonZeroUsize};
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    atom::PredefinedAtom,
+    class::{Class, JsCell, JsClass, Readable, Trace, Tracer},
+    function::{FromParams, Params, Rest, This},
+    value::Constructor,
+    Ctx, Error, FromJs, Function, IntoJs, JsLifetime, Object, Result, Value,
+};
+
+/// The class of functions created with [`Function::memoize`].
+///
+/// Calling it converts the arguments into the key type of the cache and only calls the wrapped
+/// function if no result is cached for the key, the least recently used result is dropped once
+/// the cache is full.
+pub struct MemoizedFunction<'js> {
+    func: Function<'js>,
+    cache: Box<dyn MemoCache<'js> + 'js>,
+}
+
+impl<'js> MemoizedFunction<'js> {
+    /// Removes all cached results of a function created with [`Function::memoize`].
+    ///
+    /// Returns an error if the object isn't a memoized function.
+    pub fn invalidate_cache(obj: &Object<'js>) -> Result<()> {
+        let this = Class::<Self>::from_object(obj)
+            .ok_or_else(|| Error::new_from_js("object", Self::NAME))?;
+        this.borrow().cache.clear();
+        Ok(())
+    }
+
+    /// Returns the number of results cached by a function created with [`Function::memoize`].
+    ///
+    /// Returns an error if the object isn't a memoized function.
+    pub fn cache_len(obj: &Object<'js>) -> Result<usize> {
+        let this = Class::<Self>::from_object(obj)
+            .ok_or_else(|| Error::new_from_js("object", Self::NAME))?;
+        let len = this.borrow().cache.len();
+        Ok(len)
+    }
+}
+
+unsafe impl<'js> JsLifetime<'js> for MemoizedFunction<'js> {
+    type Changed<'to> = MemoizedFunction<'to>;
+}
+
+impl<'js> Trace<'js> for MemoizedFunction<'js> {
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        self.func.trace(tracer);
+        self.cache.trace(tracer);
+    }
+}
+
+impl<'js> JsClass<'js> for MemoizedFunction<'js> {
+    const NAME: &'static str = "MemoizedFunction";
+
+    type Mutable = Readable;
+
+    const CALLABLE: bool = true;
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        Ok(Some(Function::prototype(ctx.clone())))
+    }
+
+    fn constructor(_ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Ok(None)
+    }
+
+    fn call<'a>(this: &JsCell<'js, Self>, params: Params<'a, 'js>) -> Result<Value<'js>> {
+        let this = this.borrow();
+        this.cache.call(&this.func, params)
+    }
+}
+
+/// The type erased cache of a memoized function.
+trait MemoCache<'js> {
+    fn call<'a>(&self, func: &Function<'js>, params: Params<'a, 'js>) -> Result<Value<'js>>;
+
+    fn clear(&self);
+
+    fn len(&self) -> usize;
+
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>);
+}
+
+/// The results cached by the `this` value and the arguments of a call, `None` if the capacity
+/// is zero.
+struct LruCache<'js, K, V>(RefCell<Option<lru::LruCache<(Value<'js>, K), V>>>);
+
+impl<'js, K, V> MemoCache<'js> for LruCache<'js, K, V>
+where
+    K: FromParams<'js> + Hash + Eq + 'static,
+    V: FromJs<'js> + IntoJs<'js> + Clone + 'static,
+{
+    fn call<'a>(&self, func: &Function<'js>, params: Params<'a, 'js>) -> Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+        let this = params.this();
+        let args = (0..params.len())
+            .filter_map(|idx| params.arg(idx))
+            .collect::<Vec<_>>();
+        let key = (this.clone(), params.parse::<K>()?);
+        // The cache is not borrowed during the call so the function can call itself.
+        let cached = self
+            .0
+            .borrow_mut()
+            .as_mut()
+            .and_then(|cache| cache.get(&key).cloned());
+        if let Some(value) = cached {
+            return value.into_js(&ctx);
+        }
+        let value: V = func.call((This(this), Rest(args)))?;
+        if let Some(cache) = self.0.borrow_mut().as_mut() {
+            cache.put(key, value.clone());
+        }
+        value.into_js(&ctx)
+    }
+
+    fn clear(&self) {
+        if let Some(cache) = self.0.borrow_mut().as_mut() {
+            cache.clear();
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.0.borrow().as_ref().map_or(0, |cache| cache.len())
+    }
+
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        if let Some(cache) = self.0.borrow().as_ref() {
+            for ((this, _), _) in cache.iter() {
+                this.trace(tracer);
+            }
+        }
+    }
+}
+
+impl<'js> Function<'js> {
+    /// Wraps the function in a function which caches the results for up to `capacity` different
+    /// arguments.
+    ///
+    /// The arguments are converted into the key `K` like the arguments of a Rust callback, for
+    /// example `(i32,)` for a function taking a single number, and the result is converted into
+    /// `V` before it is cached. Results are cached separately for every `this` value the
+    /// function is called with and only computed once per key, so the function should not have
+    /// side effects. Once the cache is full the least recently used result is removed.
+    ///
+    /// The cache can be cleared with [`MemoizedFunction::invalidate_cache`]. The key and result
+    /// types can't hold JavaScript values, as they are converted from the arguments and the
+    /// result on every call anyway.
+    ///
+    /// ```
+    /// # use rquickjs::{Context, Function, Runtime};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let square: Function = ctx
+    ///     .eval("globalThis.calls = 0; (x) => { calls += 1; return x * x }")
+    ///     .unwrap();
+    /// let square = square.memoize::<(i32,), i32>(16).unwrap();
+    /// ctx.globals().set("square", square).unwrap();
+    /// let res: i32 = ctx.eval("square(3) + square(3)").unwrap();
+    /// assert_eq!(res, 18);
+    /// let calls: i32 = ctx.globals().get("calls").unwrap();
+    /// assert_eq!(calls, 1);
+    /// # });
+    /// ```
+    pub fn memoize<K, V>(&self, capacity: usize) -> Result<Function<'js>>
+    where
+        K: FromParams<'js> + Hash + Eq + 'static,
+        V: FromJs<'js> + IntoJs<'js> + Clone + 'static,
+    {
+        let cache = NonZeroUsize::new(capacity).map(lru::LruCache::new);
+        let cache = LruCache::<K, V>(RefCell::new(cache));
+        let memoized = MemoizedFunction {
+            func: self.clone(),
+            cache: Box::new(cache),
+        };
+        let cls = Class::instance(self.ctx().clone(), memoized)?;
+        let res = Function(cls.into_inner());
+        if let Some(len) = self.get::<_, Option<usize>>(PredefinedAtom::Length)? {
+            res.set_length(len)?;
+        }
+        Ok(res)
+    }
+}
+
+/// Wraps a function in a function which caches the results for up to `capacity` different
+/// arguments, the same as [`Function::memoize`].
+pub fn memoize<'js, K, V>(func: Function<'js>, capacity: usize) -> Result<Function<'js>>
+where
+    K: FromParams<'js> + Hash + Eq + 'static,
+    V: FromJs<'js> + IntoJs<'js> + Clone + 'static,
+{
+    func.memoize::<K, V>(capacity)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        function::{self, MemoizedFunction},
+        test_with, CatchResultExt, Function, Object,
+    };
+
+    #[test]
+    fn memoize() {
+        test_with(|ctx| {
+            let fib: Function = ctx
+                .eval("globalThis.calls = 0; (n) => { calls += 1; return n < 2 ? n : fib(n - 1) + fib(n - 2) }")
+                .unwrap();
+            let fib = fib.memoize::<(u32,), f64>(64).unwrap();
+            ctx.globals().set("fib", fib.clone()).unwrap();
+            let res: f64 = ctx.eval("fib(50)").catch(&ctx).unwrap();
+            assert_eq!(res, 12586269025.0);
+            let calls: u32 = ctx.globals().get("calls").unwrap();
+            assert_eq!(calls, 51);
+            assert_eq!(MemoizedFunction::cache_len(&fib).unwrap(), 51);
+            let len: u32 = ctx.eval("fib.length").unwrap();
+            assert_eq!(len, 1);
+
+            MemoizedFunction::invalidate_cache(&fib).unwrap();
+            assert_eq!(MemoizedFunction::cache_len(&fib).unwrap(), 0);
+            let res: f64 = ctx.eval("fib(2)").unwrap();
+            assert_eq!(res, 1.0);
+            let calls: u32 = ctx.globals().get("calls").unwrap();
+            assert_eq!(calls, 54);
+
+            assert!(
+                MemoizedFunction::invalidate_cache(&Object::new(ctx.clone()).unwrap()).is_err()
+            );
+        })
+    }
+
+    #[test]
+    fn memoize_eviction() {
+        test_with(|ctx| {
+            let join: Function = ctx
+                .eval("globalThis.calls = 0; function join(a, b) { calls += 1; return this.prefix + a + b }; join")
+                .unwrap();
+            let join = join.memoize::<(String, String), String>(2).unwrap();
+            ctx.globals().set("join", join).unwrap();
+            let res: String = ctx
+                .eval(
+                    r#"
+                    const o = { prefix: "-", join };
+                    [o.join("a", "b"), o.join("a", "c"), o.join("a", "b"), o.join("d", "e"), o.join("a", "b"), o.join("a", "c")].join(",")
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "-ab,-ac,-ab,-de,-ab,-ac");
+            let calls: u32 = ctx.globals().get("calls").unwrap();
+            assert_eq!(calls, 4);
+
+            let res = ctx.eval::<(), _>("join(1)").catch(&ctx).unwrap_err();
+            assert!(res.to_string().contains("argument"), "{res}");
+        })
+    }
+
+    #[test]
+    fn memoize_this() {
+        test_with(|ctx| {
+            let name: Function = ctx
+                .eval("globalThis.calls = 0; (function (suffix) { calls += 1; return this.name + suffix })")
+                .unwrap();
+            let name = function::memoize::<(String,), String>(name, 4).unwrap();
+            ctx.globals().set("name", name.clone()).unwrap();
+            let res: String = ctx
+                .eval(
+                    r#"
+                    (() => {
+                        const a = { name: "a", get: name };
+                        const b = { name: "b", get: name };
+                        return [a.get("!"), b.get("!"), a.get("!"), b.get("?")].join(",");
+                    })()
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "a!,b!,a!,b?");
+            let calls: u32 = ctx.globals().get("calls").unwrap();
+            assert_eq!(calls, 3);
+            assert_eq!(MemoizedFunction::cache_len(&name).unwrap(), 3);
+
+            // The receivers are only kept alive by the cache now.
+            ctx.run_gc();
+            assert_eq!(MemoizedFunction::cache_len(&name).unwrap(), 3);
+            MemoizedFunction::invalidate_cache(&name).unwrap();
+            ctx.run_gc();
+        })
+    }
+}
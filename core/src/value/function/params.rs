@@ -24,7 +24,7 @@ impl<'a, 'js> Params<'a, 'js> {
         this: qjs::JSValue,
         argc: qjs::c_int,
         argv: *mut qjs::JSValue,
-        _flags: qjs::c_int,
+        flags: qjs::c_int,
     ) -> Self {
         let args = if argv.is_null() {
             assert_eq!(
@@ -42,7 +42,7 @@ impl<'a, 'js> Params<'a, 'js> {
             function,
             this,
             args,
-            is_constructor: false,
+            is_constructor: flags & qjs::JS_CALL_FLAG_CONSTRUCTOR as qjs::c_int != 0,
         }
     }
 
@@ -102,6 +102,13 @@ impl<'a, 'js> Params<'a, 'js> {
         self.is_constructor
     }
 
+    /// Converts the params into typed arguments, the same way closures passed to
+    /// [`Function::new`](crate::Function::new) receive them.
+    pub fn parse<T: FromParams<'js>>(self) -> Result<T> {
+        self.check_params(T::param_requirements())?;
+        T::from_params(&mut self.access())
+    }
+
     /// Turns the params into an accessor object for extracting the arguments.
     pub fn access(self) -> ParamsAccessor<'a, 'js> {
         ParamsAccessor {
@@ -67,6 +67,9 @@ pub struct Exhaustive;
 
 #[cfg(feature = "futures")]
 /// Helper type for creating a function from a closure which returns a future.
+///
+/// Without the `"parallel"` feature the runtime is only ever used from a single thread, so the
+/// closure and the futures it returns don't need to be [`Send`] and can hold `Rc` based state.
 pub struct Async<T>(pub T);
 
 /// Helper type for creating a function from a closure which implements [`FnMut`]
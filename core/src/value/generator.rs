@@ -0,0 +1,267 @@
+use crate::{
+    atom::PredefinedAtom, function::This, Ctx, Error, FromJs, Function, IntoJs, JsLifetime, Object,
+    Result, Value,
+};
+use core::{fmt, ops::Deref};
+
+/// The result of resuming a generator, i.e. the `{ value, done }` object returned by its methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorResult<T> {
+    /// The yielded value, or the returned value if the generator is done.
+    pub value: T,
+    /// Whether the generator finished.
+    pub done: bool,
+}
+
+impl<'js, T: FromJs<'js>> FromJs<'js> for GeneratorResult<T> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        let object = value
+            .into_object()
+            .ok_or_else(|| Error::new_from_js(ty_name, "GeneratorResult"))?;
+        Ok(GeneratorResult {
+            value: object.get(PredefinedAtom::Value)?,
+            done: object.get(PredefinedAtom::Done)?,
+        })
+    }
+}
+
+/// Rust representation of a JavaScript generator object, which is returned by calling a
+/// generator function.
+///
+/// The generator runs until its next `yield` every time it is resumed, which allows driving it
+/// stepwise from Rust, for example as a coroutine. Any object with `next`, `return` and `throw`
+/// methods is accepted.
+#[derive(PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct Generator<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for Generator<'js> {
+    type Changed<'to> = Generator<'to>;
+}
+
+impl<'js> fmt::Debug for Generator<'js> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Generator").field(&self.0).finish()
+    }
+}
+
+impl<'js> Generator<'js> {
+    fn resume<A, T>(&self, method: PredefinedAtom, arg: A) -> Result<GeneratorResult<T>>
+    where
+        A: IntoJs<'js>,
+        T: FromJs<'js>,
+    {
+        let method: Function = self.0.get(method)?;
+        method.call((This(self.0.clone()), arg))
+    }
+
+    /// Resume the generator, the argument becomes the value of the `yield` expression it is
+    /// suspended at.
+    ///
+    /// A finished generator returns `done: true` with an undefined value.
+    pub fn next<A, T>(&self, arg: A) -> Result<GeneratorResult<T>>
+    where
+        A: IntoJs<'js>,
+        T: FromJs<'js>,
+    {
+        self.resume(PredefinedAtom::Next, arg)
+    }
+
+    /// Finish the generator as if a `return` statement with the argument was at the point it is
+    /// suspended at.
+    ///
+    /// The `finally` blocks around the suspension point are run and may still yield values.
+    pub fn return_<A, T>(&self, arg: A) -> Result<GeneratorResult<T>>
+    where
+        A: IntoJs<'js>,
+        T: FromJs<'js>,
+    {
+        self.resume(PredefinedAtom::Return, arg)
+    }
+
+    /// Resume the generator by throwing the argument at the point it is suspended at.
+    ///
+    /// If the generator doesn't catch the exception [`Error::Exception`] is returned and the
+    /// thrown value can be retrieved with [`Ctx::catch`].
+    pub fn throw<A, T>(&self, arg: A) -> Result<GeneratorResult<T>>
+    where
+        A: IntoJs<'js>,
+        T: FromJs<'js>,
+    {
+        self.resume(PredefinedAtom::Throw, arg)
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object, returns `None` if it doesn't have the generator methods.
+    ///
+    /// An exception thrown while looking up the methods, for example by a getter, is caught
+    /// and the object is not considered a generator.
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        let is_generator = [
+            PredefinedAtom::Next,
+            PredefinedAtom::Return,
+            PredefinedAtom::Throw,
+        ]
+        .into_iter()
+        .all(|method| match object.get::<_, Value>(method) {
+            Ok(method) => method.is_function(),
+            Err(error) => {
+                if error.is_exception() {
+                    object.ctx().catch();
+                }
+                false
+            }
+        });
+        is_generator.then_some(Self(object))
+    }
+}
+
+impl<'js> Deref for Generator<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for Generator<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for Generator<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for Generator<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        Self::from_value(value).ok_or_else(|| Error::new_from_js(ty_name, "Generator"))
+    }
+}
+
+impl<'js> IntoJs<'js> for Generator<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn drive_stepwise() {
+        test_with(|ctx| {
+            let generator: Generator = ctx
+                .eval(
+                    r#"
+                    (function* () {
+                        let received = [];
+                        try {
+                            received.push(yield 1);
+                            received.push(yield 2);
+                        } catch (e) {
+                            received.push("caught " + e);
+                            yield 3;
+                        }
+                        return received.join(",");
+                    })()
+                    "#,
+                )
+                .unwrap();
+
+            let res: GeneratorResult<i32> = generator.next(()).unwrap();
+            assert_eq!(
+                res,
+                GeneratorResult {
+                    value: 1,
+                    done: false
+                }
+            );
+            let res: GeneratorResult<i32> = generator.next("a").unwrap();
+            assert_eq!(
+                res,
+                GeneratorResult {
+                    value: 2,
+                    done: false
+                }
+            );
+            let res: GeneratorResult<i32> = generator.throw("b").unwrap();
+            assert_eq!(
+                res,
+                GeneratorResult {
+                    value: 3,
+                    done: false
+                }
+            );
+            let res: GeneratorResult<StdString> = generator.next(()).unwrap();
+            assert_eq!(res.value, "a,caught b");
+            assert!(res.done);
+
+            // A finished generator stays done.
+            let res: GeneratorResult<Value> = generator.next(()).unwrap();
+            assert!(res.done && res.value.is_undefined());
+            assert!(generator.throw::<_, Value>(7).is_err());
+            assert_eq!(ctx.catch().as_int(), Some(7));
+        })
+    }
+
+    #[test]
+    fn return_early() {
+        test_with(|ctx| {
+            let generator: Generator = ctx
+                .eval("(function* () { try { yield 1; yield 2; } finally { globalThis.cleaned = true; } })()")
+                .unwrap();
+            assert!(!generator.next::<_, i32>(()).unwrap().done);
+            let res: GeneratorResult<i32> = generator.return_(5).unwrap();
+            assert_eq!(
+                res,
+                GeneratorResult {
+                    value: 5,
+                    done: true
+                }
+            );
+            assert!(ctx.globals().get::<_, bool>("cleaned").unwrap());
+
+            assert!(ctx.eval::<Generator, _>("({ next() {} })").is_err());
+            let object: Object = ctx
+                .eval("({ next() {}, return() {}, get throw() { throw new Error('getter') } })")
+                .unwrap();
+            assert!(Generator::from_object(object).is_none());
+            assert!(!unsafe { crate::qjs::JS_HasException(ctx.as_ptr()) });
+        })
+    }
+}
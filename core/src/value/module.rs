@@ -2,17 +2,24 @@
 
 use alloc::{ffi::CString, vec::Vec};
 use core::{
+    cell::RefCell,
     ffi::CStr,
     marker::PhantomData,
     mem::MaybeUninit,
-    ptr::{self, null_mut, NonNull},
+    ptr::{self, NonNull},
     slice,
 };
 
 use crate::{
-    atom::PredefinedAtom, qjs, Atom, Ctx, Error, FromAtom, FromJs, IntoAtom, IntoJs, Object,
-    Promise, Result, Value,
+    object::ObjectIter, qjs, Atom, Ctx, Error, FromAtom, FromJs, IntoAtom, IntoJs, Object, Promise,
+    Result, StdString, Value,
 };
+#[cfg(feature = "futures")]
+use crate::{CatchResultExt, CaughtResult};
+
+mod native;
+pub(crate) use native::NativeExport;
+pub use native::{ModuleBuilder, NativeModule};
 
 #[derive(Default)]
 pub enum WriteOptionsEndianness {
@@ -110,12 +117,50 @@ pub type ModuleLoadFn =
     unsafe extern "C" fn(*mut qjs::JSContext, *const qjs::c_char) -> *mut qjs::JSModuleDef;
 
 /// A class which can be used to declare rust-native JavaScript modules.
+///
+/// Native modules are defined in two steps. [`ModuleDef::declare`] is called when the module is
+/// created, with [`Module::declare_def`] or by a loader, and names all exports of the module.
+/// [`ModuleDef::evaluate`] is called when the module is evaluated, after its importers were
+/// linked to it, and sets the value of every declared export.
+///
+/// The names have to match: setting an export which wasn't declared returns
+/// [`Error::InvalidExport`], declaring a name twice returns [`Error::DuplicateExports`] and an
+/// export which was declared but not set fails the evaluation with [`Error::MissingExport`].
+///
+/// ```
+/// # use rquickjs::{module::{Declarations, Exports, ModuleDef}, Ctx, Function, Module, Result};
+/// struct MathModule;
+///
+/// impl ModuleDef for MathModule {
+///     fn declare<'js>(decl: &Declarations<'js>) -> Result<()> {
+///         decl.declare("pi")?.declare("square")?;
+///         Ok(())
+///     }
+///
+///     fn evaluate<'js>(ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
+///         exports
+///             .export("pi", std::f64::consts::PI)?
+///             .export("square", Function::new(ctx.clone(), |x: f64| x * x)?)?;
+///         Ok(())
+///     }
+/// }
+/// # let rt = rquickjs::Runtime::new().unwrap();
+/// # let ctx = rquickjs::Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// Module::declare_def::<MathModule, _>(ctx.clone(), "math")?;
+/// let math = Module::import(&ctx, "math")?.finish::<rquickjs::Object>()?;
+/// assert_eq!(math.get::<_, Function>("square")?.call::<_, f64>((3,))?, 9.0);
+/// # Ok::<_, rquickjs::Error>(())
+/// # }).unwrap();
+/// ```
 pub trait ModuleDef {
+    /// Declare the names of the exports of the module.
     fn declare<'js>(decl: &Declarations<'js>) -> Result<()> {
         let _ = decl;
         Ok(())
     }
 
+    /// Set the values of the declared exports, called when the module is evaluated.
     fn evaluate<'js>(ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
         let _ = (exports, ctx);
         Ok(())
@@ -140,13 +185,24 @@ impl<'js> Declarations<'js> {
     /// This function avoids an extra allocation, having to convert from a rust string into a
     /// null-terminated CStr.
     pub fn declare_c_str(&self, name: &CStr) -> Result<&Self> {
-        unsafe { qjs::JS_AddModuleExport(self.0.ctx.as_ptr(), self.0.as_ptr(), name.as_ptr()) };
+        let res =
+            unsafe { qjs::JS_AddModuleExport(self.0.ctx.as_ptr(), self.0.as_ptr(), name.as_ptr()) };
+        if res < 0 {
+            // QuickJS throws a syntax error for the duplicate name.
+            self.0.ctx.catch();
+            return Err(Error::DuplicateExports {
+                name: name.to_string_lossy().into(),
+            });
+        }
         Ok(self)
     }
 }
 
-/// A struct used for setting the value of previously declared exporsts of a module.
-pub struct Exports<'js>(Module<'js, Declared>);
+/// A struct used for setting the value of previously declared exports of a module.
+pub struct Exports<'js> {
+    module: Module<'js, Declared>,
+    exported: RefCell<Vec<StdString>>,
+}
 
 impl<'js> Exports<'js> {
     /// Set the value of an exported entry.
@@ -159,21 +215,35 @@ impl<'js> Exports<'js> {
     ///
     /// This function avoids a possible conversion from a rust string into a CStr
     pub fn export_c_str<T: IntoJs<'js>>(&self, name: &CStr, value: T) -> Result<&Self> {
-        let value = value.into_js(&self.0.ctx)?;
+        let value = value.into_js(&self.module.ctx)?;
         let res = unsafe {
             qjs::JS_SetModuleExport(
-                self.0.ctx.as_ptr(),
-                self.0.as_ptr(),
+                self.module.ctx.as_ptr(),
+                self.module.as_ptr(),
                 name.as_ptr(),
                 value.into_js_value(),
             )
         };
+        let name = StdString::from(name.to_string_lossy());
         if res < 0 {
-            return Err(Error::InvalidExport);
+            return Err(Error::InvalidExport { name });
         }
+        self.exported.borrow_mut().push(name);
 
         Ok(self)
     }
+
+    /// Returns an error for the first declared export which wasn't set.
+    fn check_missing(&self) -> Result<()> {
+        let exported = self.exported.borrow();
+        for name in self.module.namespace()?.keys::<StdString>() {
+            let name = name?;
+            if !exported.contains(&name) {
+                return Err(Error::MissingExport { name });
+            }
+        }
+        Ok(())
+    }
 }
 
 /// A marker struct used to indicate that a module is possibly not yet evaluated.
@@ -215,8 +285,11 @@ impl<'js, T> Module<'js, T> {
         // Should never be null
         let ptr = NonNull::new(ptr).unwrap();
         let module = unsafe { Module::from_ptr(ctx.clone(), ptr) };
-        let exports = Exports(module);
-        match D::evaluate(&ctx, &exports) {
+        let exports = Exports {
+            module,
+            exported: RefCell::new(Vec::new()),
+        };
+        match D::evaluate(&ctx, &exports).and_then(|_| exports.check_missing()) {
             Ok(_) => 0,
             Err(error) => {
                 error.throw(&ctx);
@@ -360,6 +433,12 @@ impl<'js> Module<'js, Declared> {
     ///
     /// Returns the module as being evaluated and a promise which resolves when the module has finished evaluating.
     /// The return value of the promise is the JavaScript value undefined.
+    ///
+    /// If the module body contains a top-level `await` the promise only settles once the module
+    /// body has run to completion, and is rejected if it throws after an `await`. The namespace
+    /// of the returned module can be accessed before the promise has settled, but exports which
+    /// are only initialized after an `await` are then still uninitialized, and reading them
+    /// throws a `ReferenceError`.
     pub fn eval(self) -> Result<(Module<'js, Evaluated>, Promise<'js>)> {
         let ret = unsafe {
             // JS_EvalFunction `free's` the module so we should dup first
@@ -379,6 +458,22 @@ impl<'js> Module<'js, Declared> {
         ))
     }
 
+    /// Evaluate the module and wait until it has finished evaluating.
+    ///
+    /// Unlike [`Module::eval`] this also waits for any top-level `await` in the module body to
+    /// complete. Returns the evaluated module, or the error with which evaluation was rejected.
+    ///
+    /// The returned future only makes progress if the job queue is driven while awaiting it, as
+    /// it is within [`async_with!`](crate::async_with).
+    #[cfg(feature = "futures")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+    pub async fn eval_async(self) -> CaughtResult<'js, Module<'js, Evaluated>> {
+        let ctx = self.ctx.clone();
+        let (module, promise) = self.eval().catch(&ctx)?;
+        promise.into_future::<()>().await?;
+        Ok(module)
+    }
+
     /// A function for loading a Rust module from C.
     ///
     /// # Safety
@@ -405,25 +500,75 @@ impl<'js> Module<'js, Declared> {
     /// Import and evaluate a module
     ///
     /// This will work similar to an `import(specifier)` statement in JavaScript returning a promise with the result of the imported module.
+    ///
+    /// The specifier is resolved relative to the calling script or module. If called from Rust
+    /// outside of any JavaScript code it is resolved relative to the
+    /// [`source_name`](crate::context::EvalOptions::source_name) of the script currently being
+    /// evaluated, if any.
     pub fn import<S: Into<Vec<u8>>>(ctx: &Ctx<'js>, specifier: S) -> Result<Promise<'js>> {
         let specifier = CString::new(specifier)?;
+        let base_name = match ctx.script_or_module_name(1) {
+            Some(name) => CString::new(name.to_string()?)?,
+            None => unsafe { ctx.get_opaque() }
+                .current_source_name()
+                .unwrap_or_default(),
+        };
         unsafe {
-            let base_name = ctx
-                .script_or_module_name(1)
-                .unwrap_or_else(|| Atom::from_predefined(ctx.clone(), PredefinedAtom::Empty));
-
-            let base_name_c_str =
-                qjs::JS_AtomToCStringLen(ctx.as_ptr(), null_mut(), base_name.atom);
-
-            let res = qjs::JS_LoadModule(ctx.as_ptr(), base_name_c_str, specifier.as_ptr());
-
-            qjs::JS_FreeCString(ctx.as_ptr(), base_name_c_str);
-
+            let res = qjs::JS_LoadModule(ctx.as_ptr(), base_name.as_ptr(), specifier.as_ptr());
             let res = ctx.handle_exception(res)?;
-
             Ok(Promise::from_js_value(ctx.clone(), res))
         }
     }
+
+    /// Import and evaluate a module, loading it with the asynchronous loader of the runtime.
+    ///
+    /// Works like [`Module::import`] but first fetches the module and all of its static imports
+    /// with the loader set with
+    /// [`AsyncRuntime::set_async_loader`](crate::AsyncRuntime::set_async_loader).
+    #[cfg(all(feature = "loader", feature = "futures"))]
+    #[cfg_attr(
+        feature = "doc-cfg",
+        doc(cfg(all(feature = "loader", feature = "futures")))
+    )]
+    pub async fn import_async<S: Into<Vec<u8>>>(
+        ctx: &Ctx<'js>,
+        specifier: S,
+    ) -> Result<Promise<'js>> {
+        let specifier = specifier.into();
+        crate::loader::load_async(ctx, || Self::import(ctx, specifier.clone())).await
+    }
+
+    /// Declares a new module and evaluates it, loading its imports with the asynchronous loader
+    /// of the runtime.
+    ///
+    /// Works like [`Module::evaluate`] but first fetches all static imports with the loader set
+    /// with [`AsyncRuntime::set_async_loader`](crate::AsyncRuntime::set_async_loader).
+    #[cfg(all(feature = "loader", feature = "futures"))]
+    #[cfg_attr(
+        feature = "doc-cfg",
+        doc(cfg(all(feature = "loader", feature = "futures")))
+    )]
+    pub async fn evaluate_async<N, S>(ctx: Ctx<'js>, name: N, source: S) -> Result<Promise<'js>>
+    where
+        N: Into<Vec<u8>>,
+        S: Into<Vec<u8>>,
+    {
+        let (name, source) = (name.into(), source.into());
+        crate::loader::load_async(&ctx, || {
+            Self::evaluate(ctx.clone(), name.clone(), source.clone())
+        })
+        .await
+    }
+
+    /// Import and evaluate a module, blocking until the import has finished.
+    ///
+    /// Executes pending jobs until the promise returned by [`Module::import`] is settled and
+    /// returns the namespace object of the imported module. Returns [`Error::WouldBlock`] if the
+    /// import can't finish without further progress from outside the runtime, like a pending
+    /// future.
+    pub fn import_sync<S: Into<Vec<u8>>>(ctx: &Ctx<'js>, specifier: S) -> Result<Object<'js>> {
+        Self::import(ctx, specifier)?.finish()
+    }
 }
 
 impl<'js, Evaluated> Module<'js, Evaluated> {
@@ -469,6 +614,38 @@ impl<'js, Evaluated> Module<'js, Evaluated> {
         Ok(obj)
     }
 
+    /// Returns the specifiers of the modules statically imported by the module, in source order
+    /// and without duplicates.
+    ///
+    /// The specifiers are returned as written in the source, before they are resolved. Dynamic
+    /// `import()` calls are not included as they are only known when they are evaluated. Modules
+    /// re-exported with `export ... from` are included. Native modules don't have imports.
+    pub fn imports(&self) -> Result<Vec<StdString>> {
+        let ptr = self.as_ptr();
+        let count = unsafe { qjs::rquickjs_get_module_requested_count(ptr) };
+        (0..count)
+            .map(|idx| unsafe {
+                let atom = qjs::rquickjs_get_module_requested(self.ctx.as_ptr(), ptr, idx);
+                Atom::from_atom_val(self.ctx.clone(), atom).to_string()
+            })
+            .collect()
+    }
+
+    /// Returns the names of the exports declared by the module, including `default`.
+    ///
+    /// The names exported with `export * from` are not included as they are only known once the
+    /// re-exported modules are loaded. The module doesn't need to be evaluated.
+    pub fn export_names(&self) -> Result<Vec<StdString>> {
+        let ptr = self.as_ptr();
+        let count = unsafe { qjs::rquickjs_get_module_export_count(ptr) };
+        (0..count)
+            .map(|idx| unsafe {
+                let atom = qjs::rquickjs_get_module_export_name(self.ctx.as_ptr(), ptr, idx);
+                Atom::from_atom_val(self.ctx.clone(), atom).to_string()
+            })
+            .collect()
+    }
+
     /// Return the `import.meta` object of a module
     pub fn meta(&self) -> Result<Object<'js>> {
         unsafe {
@@ -490,6 +667,8 @@ impl<'js, Evaluated> Module<'js, Evaluated> {
     }
 
     /// Return exported value by name
+    ///
+    /// Exports are live bindings, the value is read from the module at the time of the call.
     pub fn get<N, T>(&self, name: N) -> Result<T>
     where
         N: IntoAtom<'js>,
@@ -498,6 +677,19 @@ impl<'js, Evaluated> Module<'js, Evaluated> {
         self.namespace()?.get(name)
     }
 
+    /// Returns an iterator over the names and values of all exports of the module.
+    ///
+    /// Exports are iterated in the order of their names. Like with [`Module::get`] each value is
+    /// read from the module only when the iterator reaches it, reading an export which isn't
+    /// initialized yet returns an error.
+    pub fn exports<K, V>(&self) -> Result<ObjectIter<'js, K, V>>
+    where
+        K: FromAtom<'js>,
+        V: FromJs<'js>,
+    {
+        Ok(self.namespace()?.props())
+    }
+
     /// Change the module back to being only declared.
     ///
     /// This is always safe to do since calling eval again on an already evaluated module is safe.
@@ -557,6 +749,73 @@ mod test {
         })
     }
 
+    #[test]
+    fn export_mismatch() {
+        struct Duplicate;
+        impl ModuleDef for Duplicate {
+            fn declare(decl: &Declarations) -> Result<()> {
+                decl.declare("a")?.declare("a")?;
+                Ok(())
+            }
+        }
+
+        struct Undeclared;
+        impl ModuleDef for Undeclared {
+            fn declare(decl: &Declarations) -> Result<()> {
+                decl.declare("a")?;
+                Ok(())
+            }
+
+            fn evaluate<'js>(_ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
+                exports.export("a", 1)?.export("b", 2)?;
+                Ok(())
+            }
+        }
+
+        struct Unset;
+        impl ModuleDef for Unset {
+            fn declare(decl: &Declarations) -> Result<()> {
+                decl.declare("a")?.declare("b")?;
+                Ok(())
+            }
+
+            fn evaluate<'js>(_ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
+                exports.export("a", 1)?;
+                Ok(())
+            }
+        }
+
+        test_with(|ctx| {
+            let err = Module::declare_def::<Duplicate, _>(ctx.clone(), "duplicate").unwrap_err();
+            assert!(matches!(err, Error::DuplicateExports { ref name } if name == "a"));
+            assert!(ctx.catch().type_of().is_void());
+
+            Module::declare_def::<Undeclared, _>(ctx.clone(), "undeclared").unwrap();
+            let err = Module::import(&ctx, "undeclared")
+                .unwrap()
+                .finish::<()>()
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("Tried to set the export 'b' which was not previously declared"),
+                "{err}"
+            );
+
+            Module::declare_def::<Unset, _>(ctx.clone(), "unset").unwrap();
+            let err = Module::import(&ctx, "unset")
+                .unwrap()
+                .finish::<()>()
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("The declared export 'b' was not set"),
+                "{err}"
+            );
+        })
+    }
+
     #[test]
     fn import_native() {
         test_with(|ctx| {
@@ -629,6 +888,136 @@ mod test {
         })
     }
 
+    #[test]
+    fn import_sync() {
+        test_with(|ctx| {
+            Module::declare_def::<RustModule, _>(ctx.clone(), "rust_mod").unwrap();
+            let val = Module::import_sync(&ctx, "rust_mod").unwrap();
+            let hello: StdString = val.get("hello").unwrap();
+            assert_eq!(&hello, "world");
+
+            let err = Module::import_sync(&ctx, "missing_mod")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("missing_mod"));
+        })
+    }
+
+    #[cfg(feature = "futures")]
+    #[tokio::test]
+    async fn eval_top_level_await() {
+        use crate::{function::Async, prelude::Func, promise::PromiseState};
+
+        async fn set_timeout<'js>(cb: Function<'js>, ms: f64) -> Result<()> {
+            tokio::time::sleep(core::time::Duration::from_secs_f64(ms / 1000.0)).await;
+            cb.call::<_, ()>(())
+        }
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            ctx.globals()
+                .set("setTimeout", Func::from(Async(set_timeout)))
+                .unwrap();
+
+            let module = Module::declare(
+                ctx.clone(),
+                "tla",
+                r#"
+                export let state = "pending";
+                await new Promise((resolve) => setTimeout(resolve, 10));
+                state = "done";
+                export const late = 42;
+            "#,
+            )
+            .unwrap();
+            let (module, promise) = module.eval().unwrap();
+            assert!(promise.state() == PromiseState::Pending);
+            assert_eq!(module.get::<_, StdString>("state").unwrap(), "pending");
+            let err = module.get::<_, i32>("late").catch(&ctx).unwrap_err();
+            assert!(err.to_string().contains("not initialized"));
+
+            promise.into_future::<()>().await.unwrap();
+            assert_eq!(module.get::<_, StdString>("state").unwrap(), "done");
+            assert_eq!(module.get::<_, i32>("late").unwrap(), 42);
+
+            let module = Module::declare(
+                ctx.clone(),
+                "tla2",
+                r#"
+                export let state = "pending";
+                await new Promise((resolve) => setTimeout(resolve, 10));
+                state = "done";
+            "#,
+            )
+            .unwrap();
+            let module = module.eval_async().await.unwrap();
+            assert_eq!(module.get::<_, StdString>("state").unwrap(), "done");
+
+            let module = Module::declare(
+                ctx.clone(),
+                "tla_error",
+                r#"
+                await new Promise((resolve) => setTimeout(resolve, 10));
+                throw new Error("failed after await");
+            "#,
+            )
+            .unwrap();
+            let err = module.eval_async().await.unwrap_err();
+            assert!(err.to_string().contains("failed after await"));
+        })
+        .await
+    }
+
+    #[test]
+    #[cfg(feature = "loader")]
+    fn import_base_name() {
+        use crate::{
+            context::EvalOptions,
+            loader::{ModuleLoader, Resolver},
+        };
+        use alloc::rc::Rc;
+        use core::cell::RefCell;
+
+        struct BaseResolver(Rc<RefCell<Vec<StdString>>>);
+
+        impl Resolver for BaseResolver {
+            fn resolve<'js>(
+                &mut self,
+                _ctx: &Ctx<'js>,
+                base: &str,
+                name: &str,
+            ) -> Result<StdString> {
+                self.0.borrow_mut().push(base.into());
+                Ok(name.into())
+            }
+        }
+
+        let bases = Rc::new(RefCell::new(Vec::new()));
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            (BaseResolver(bases.clone()),),
+            (ModuleLoader::default().with_module("rust_mod", RustModule),),
+        );
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            fn import_rust<'js>(ctx: Ctx<'js>) -> Result<Object<'js>> {
+                Module::import_sync(&ctx, "rust_mod")
+            }
+            let import = Function::new(ctx.clone(), import_rust).unwrap();
+            ctx.globals().set("importRust", import).unwrap();
+            let options = EvalOptions {
+                source_name: Some("dir/main.js".into()),
+                ..Default::default()
+            };
+            let _: Object = ctx
+                .eval_with_options("[0].map(() => importRust())[0]", options)
+                .unwrap();
+        });
+        assert_eq!(bases.borrow().as_slice(), ["dir/main.js"]);
+    }
+
     #[test]
     #[should_panic(expected = "kaboom")]
     fn import_crashing() {
@@ -699,6 +1088,92 @@ mod test {
         });
     }
 
+    #[test]
+    fn exports_live_bindings() {
+        test_with(|ctx| {
+            let (module, promise) = Module::declare(
+                ctx.clone(),
+                "live",
+                r#"
+                export let count = 0;
+                export const name = "counter";
+                export function tick() {
+                    Promise.resolve().then(() => { count += 1; });
+                }
+            "#,
+            )
+            .unwrap()
+            .eval()
+            .unwrap();
+            promise.finish::<()>().unwrap();
+
+            assert_eq!(module.get::<_, i32>("count").unwrap(), 0);
+            let exports = module.exports::<StdString, Value>().unwrap();
+            assert_eq!(exports.len(), 3);
+
+            module
+                .get::<_, Function>("tick")
+                .unwrap()
+                .call::<_, ()>(())
+                .unwrap();
+            assert_eq!(module.get::<_, i32>("count").unwrap(), 0);
+            while ctx.execute_pending_job() {}
+            assert_eq!(module.get::<_, i32>("count").unwrap(), 1);
+
+            // The iterator created before the update reads the current value.
+            let exports = exports.collect::<Result<Vec<_>>>().unwrap();
+            let names = exports
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(names, ["count", "name", "tick"]);
+            assert_eq!(exports[0].1.as_int(), Some(1));
+            assert_eq!(
+                exports[1].1.as_string().unwrap().to_string().unwrap(),
+                "counter"
+            );
+            assert!(exports[2].1.is_function());
+        })
+    }
+
+    #[test]
+    fn imports_and_exports() {
+        test_with(|ctx| {
+            for (name, source) in [
+                ("a", "export const x = 1; export default 2;"),
+                ("b", "export const y = 3;"),
+                ("c", "export const z = 4;"),
+            ] {
+                Module::declare(ctx.clone(), name, source).unwrap();
+            }
+            let module = Module::declare(
+                ctx.clone(),
+                "main",
+                r#"
+                import def, { x } from "a";
+                import * as b from "b";
+                import "a";
+                export { z as renamed } from "c";
+                export * from "b";
+                export const local = def + x;
+                export default function () {
+                    return import("dynamic");
+                }
+                "#,
+            )
+            .unwrap();
+            assert_eq!(module.imports().unwrap(), ["a", "b", "c"]);
+            assert_eq!(
+                module.export_names().unwrap(),
+                ["renamed", "local", "default"]
+            );
+
+            let native = Module::declare_def::<RustModule, _>(ctx.clone(), "rust").unwrap();
+            assert!(native.imports().unwrap().is_empty());
+            assert_eq!(native.export_names().unwrap(), ["hello"]);
+        })
+    }
+
     #[test]
     fn from_javascript() {
         test_with(|ctx| {
@@ -0,0 +1,371 @@
+use core::{cell::RefCell, fmt, ptr::NonNull};
+
+use alloc::{boxed::Box, ffi::CString, vec::Vec};
+
+use crate::{
+    class::JsClass,
+    function::IntoJsFunc,
+    markers::{ParallelSend, ParallelSync},
+    module::{Declarations, Declared, Exports},
+    qjs, Class, Ctx, Error, Function, IntoJs, Module, Ref, Result, StdString, Value,
+};
+
+#[cfg(not(feature = "parallel"))]
+type ExportFn = Box<dyn for<'js> Fn(&Ctx<'js>) -> Result<Value<'js>>>;
+
+#[cfg(feature = "parallel")]
+type ExportFn = Box<dyn for<'js> Fn(&Ctx<'js>) -> Result<Value<'js>> + Send + Sync>;
+
+/// An export of a [`NativeModule`], creating its value when the module is evaluated.
+pub(crate) struct NativeExport {
+    name: StdString,
+    value: ExportFn,
+}
+
+/// A native module defined with closures instead of implementing
+/// [`ModuleDef`](super::ModuleDef).
+///
+/// The module declares its exports when it is loaded and creates their values when it is
+/// evaluated, so it can be loaded in any number of contexts. With the `"parallel"` feature the
+/// export closures have to be `Send + Sync`, as the runtime keeps the exports of declared modules
+/// until they are evaluated. Register it with
+/// [`ModuleLoader::with_native`](crate::loader::ModuleLoader::with_native) or declare it
+/// directly with [`NativeModule::declare`].
+///
+/// ```
+/// # use rquickjs::{module::NativeModule, Context, Module, Object, Runtime};
+/// let module = NativeModule::builder("greeting")
+///     .export_value("VERSION", "1.0")
+///     .export_fn("hello", |name: String| format!("Hello, {name}!"))
+///     .finish()
+///     .unwrap();
+///
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     module.declare(ctx.clone()).unwrap();
+///     let greeting: Object = Module::import(&ctx, "greeting").unwrap().finish().unwrap();
+///     assert_eq!(greeting.get::<_, String>("VERSION").unwrap(), "1.0");
+/// });
+/// ```
+pub struct NativeModule {
+    name: StdString,
+    exports: Ref<[NativeExport]>,
+}
+
+impl fmt::Debug for NativeModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeModule")
+            .field("name", &self.name)
+            .field(
+                "exports",
+                &self.exports.iter().map(|x| &x.name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl NativeModule {
+    /// Start building a native module with the given name.
+    pub fn builder<N: Into<StdString>>(name: N) -> ModuleBuilder {
+        ModuleBuilder {
+            name: name.into(),
+            exports: Vec::new(),
+        }
+    }
+
+    /// Returns the name of the module.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Declare the module in the context but don't evaluate it.
+    pub fn declare<'js>(&self, ctx: Ctx<'js>) -> Result<Module<'js, Declared>> {
+        let name = CString::new(self.name.as_str())?;
+        let ptr = unsafe { qjs::JS_NewCModule(ctx.as_ptr(), name.as_ptr(), Some(eval_fn)) };
+        let ptr = NonNull::new(ptr).ok_or(Error::Unknown)?;
+        let decl = Declarations(unsafe { Module::from_ptr(ctx.clone(), ptr) });
+        for export in self.exports.iter() {
+            decl.declare(export.name.as_str())?;
+        }
+        unsafe {
+            ctx.get_opaque()
+                .insert_native_module(ptr.as_ptr(), self.exports.clone())
+        };
+        Ok(decl.0)
+    }
+}
+
+unsafe extern "C" fn eval_fn(ctx: *mut qjs::JSContext, ptr: *mut qjs::JSModuleDef) -> qjs::c_int {
+    let ctx = Ctx::from_ptr(ctx);
+    // Should never be null
+    let ptr = NonNull::new(ptr).unwrap();
+    let native = ctx.get_opaque().take_native_module(ptr.as_ptr());
+    let exports = Exports {
+        module: Module::from_ptr(ctx.clone(), ptr),
+        exported: RefCell::new(Vec::new()),
+    };
+    let res = native.ok_or(Error::Unknown).and_then(|native| {
+        for export in native.iter() {
+            exports.export(export.name.as_str(), (export.value)(&ctx)?)?;
+        }
+        Ok(())
+    });
+    match res {
+        Ok(_) => 0,
+        Err(error) => {
+            error.throw(&ctx);
+            -1
+        }
+    }
+}
+
+/// A builder for a [`NativeModule`].
+#[must_use]
+pub struct ModuleBuilder {
+    name: StdString,
+    exports: Vec<NativeExport>,
+}
+
+impl ModuleBuilder {
+    /// Export a value, which is converted into a JavaScript value in every context.
+    pub fn export_value<N, V>(self, name: N, value: V) -> Self
+    where
+        N: Into<StdString>,
+        V: for<'js> IntoJs<'js> + Clone + ParallelSend + ParallelSync + 'static,
+    {
+        self.export_with(name, move |ctx| value.clone().into_js(ctx))
+    }
+
+    /// Export a function created from a Rust function, named like the export.
+    pub fn export_fn<N, F, P>(self, name: N, func: F) -> Self
+    where
+        N: Into<StdString>,
+        F: for<'js> IntoJsFunc<'js, P> + Clone + ParallelSend + ParallelSync + 'static,
+    {
+        let name = name.into();
+        let func_name = name.clone();
+        self.export_with(name, move |ctx| {
+            let func = Function::new(ctx.clone(), func.clone())?.with_name(&func_name)?;
+            Ok(func.into_value())
+        })
+    }
+
+    /// Export the constructor of a Rust class under the name of the class.
+    ///
+    /// Evaluating the module fails if the class has no constructor.
+    pub fn export_class<C>(self) -> Self
+    where
+        C: for<'js> JsClass<'js> + 'static,
+    {
+        self.export_with(C::NAME, |ctx| {
+            let constructor = Class::<C>::create_constructor(ctx)?.ok_or_else(|| {
+                Error::new_into_js_message(C::NAME, "Constructor", "The class has no constructor")
+            })?;
+            Ok(constructor.into_value())
+        })
+    }
+
+    /// Export a value created by the function when the module is evaluated.
+    pub fn export_with<N, F>(mut self, name: N, value: F) -> Self
+    where
+        N: Into<StdString>,
+        F: for<'js> Fn(&Ctx<'js>) -> Result<Value<'js>> + ParallelSend + ParallelSync + 'static,
+    {
+        self.exports.push(NativeExport {
+            name: name.into(),
+            value: Box::new(value),
+        });
+        self
+    }
+
+    /// Finish building the module.
+    ///
+    /// Returns [`Error::DuplicateExports`] if a name was exported twice.
+    pub fn finish(self) -> Result<NativeModule> {
+        for (i, export) in self.exports.iter().enumerate() {
+            if self.exports[..i].iter().any(|x| x.name == export.name) {
+                return Err(Error::DuplicateExports {
+                    name: export.name.clone(),
+                });
+            }
+        }
+        Ok(NativeModule {
+            name: self.name,
+            exports: self.exports.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "loader")]
+    use crate::{
+        class::{Trace, Tracer, Writable},
+        function::Constructor,
+        JsLifetime,
+    };
+    use crate::{CatchResultExt, Context, Runtime};
+
+    #[cfg(feature = "loader")]
+    #[derive(Clone, Copy)]
+    struct Counter {
+        count: i32,
+    }
+
+    #[cfg(feature = "loader")]
+    impl<'js> Trace<'js> for Counter {
+        fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+    }
+
+    #[cfg(feature = "loader")]
+    unsafe impl<'js> JsLifetime<'js> for Counter {
+        type Changed<'to> = Counter;
+    }
+
+    #[cfg(feature = "loader")]
+    impl<'js> JsClass<'js> for Counter {
+        const NAME: &'static str = "Counter";
+
+        type Mutable = Writable;
+
+        fn prototype(ctx: &Ctx<'js>) -> Result<Option<crate::Object<'js>>> {
+            let proto = crate::Object::new(ctx.clone())?;
+            proto.set(
+                "increment",
+                Function::new(
+                    ctx.clone(),
+                    |this: crate::function::This<Class<'js, Counter>>| {
+                        let mut counter = this.borrow_mut();
+                        counter.count += 1;
+                        counter.count
+                    },
+                )?,
+            )?;
+            Ok(Some(proto))
+        }
+
+        fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+            let constructor = Constructor::new_class::<Counter, _, _>(
+                ctx.clone(),
+                |ctx: Ctx<'js>, count: i32| Class::instance(ctx, Counter { count }),
+            )?;
+            Ok(Some(constructor))
+        }
+    }
+
+    #[cfg(feature = "loader")]
+    #[test]
+    fn builder() {
+        use crate::loader::{BuiltinResolver, ModuleLoader};
+
+        let module = NativeModule::builder("my:mod")
+            .export_value("VERSION", 2)
+            .export_fn("hello", |name: StdString| alloc::format!("Hello, {name}!"))
+            .export_class::<Counter>()
+            .finish()
+            .unwrap();
+        assert_eq!(module.name(), "my:mod");
+
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            BuiltinResolver::default().with_module("my:mod"),
+            ModuleLoader::default().with_native(module),
+        );
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let (module, promise) = Module::declare(
+                ctx.clone(),
+                "main",
+                r#"
+                    import { VERSION, hello, Counter } from "my:mod";
+                    const counter = new Counter(VERSION);
+                    counter.increment();
+                    export const greeting = hello("world");
+                    export const name = hello.name;
+                    export const count = counter.increment();
+                "#,
+            )
+            .catch(&ctx)
+            .unwrap()
+            .eval()
+            .catch(&ctx)
+            .unwrap();
+            promise.finish::<()>().catch(&ctx).unwrap();
+            assert_eq!(
+                module.get::<_, StdString>("greeting").unwrap(),
+                "Hello, world!"
+            );
+            assert_eq!(module.get::<_, StdString>("name").unwrap(), "hello");
+            assert_eq!(module.get::<_, i32>("count").unwrap(), 4);
+        });
+    }
+
+    #[cfg(feature = "loader")]
+    #[test]
+    fn loaded_in_many_contexts() {
+        use crate::loader::{BuiltinResolver, ModuleLoader};
+
+        let module = NativeModule::builder("shared")
+            .export_value("VALUE", 7)
+            .finish()
+            .unwrap();
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            BuiltinResolver::default().with_module("shared"),
+            ModuleLoader::default().with_native(module),
+        );
+        for _ in 0..2 {
+            let ctx = Context::full(&rt).unwrap();
+            ctx.with(|ctx| {
+                let shared: crate::Object = Module::import(&ctx, "shared")
+                    .catch(&ctx)
+                    .unwrap()
+                    .finish()
+                    .catch(&ctx)
+                    .unwrap();
+                assert_eq!(shared.get::<_, i32>("VALUE").unwrap(), 7);
+            });
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<NativeModule>();
+    }
+
+    #[test]
+    fn duplicate_export() {
+        let res = NativeModule::builder("dup")
+            .export_value("a", 1)
+            .export_fn("b", || ())
+            .export_value("a", 2)
+            .finish();
+        assert!(matches!(res, Err(Error::DuplicateExports { name }) if name == "a"));
+    }
+
+    #[test]
+    fn failing_export() {
+        let module = NativeModule::builder("failing")
+            .export_with("value", |ctx| {
+                Err(crate::Exception::throw_message(ctx, "cannot create"))
+            })
+            .finish()
+            .unwrap();
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            // Modules can be declared in every context.
+            module.declare(ctx.clone()).unwrap();
+            let err = Module::import(&ctx, "failing")
+                .unwrap()
+                .finish::<()>()
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("cannot create"), "{err}");
+        });
+    }
+}
@@ -1,13 +1,18 @@
 //! Module for types dealing with JS objects.
 
 use crate::{
-    convert::FromIteratorJs, qjs, Array, Atom, Ctx, FromAtom, FromJs, IntoAtom, IntoJs, Result,
-    Value,
+    convert::FromIteratorJs, qjs, Array, Atom, Ctx, Error, FromAtom, FromJs, IntoAtom, IntoJs,
+    Result, Value,
 };
+use alloc::vec::Vec;
 use core::{iter::FusedIterator, marker::PhantomData, mem};
 
+mod function_list;
+mod opaque;
 mod property;
-pub use property::{Accessor, AsProperty, Property, PropertyFlags};
+pub use function_list::{FunctionListEntry, RawFunction};
+pub use opaque::OpaqueRef;
+pub use property::{Accessor, AsProperty, Property, PropertyDescriptor, PropertyFlags};
 
 /// Rust representation of a JavaScript object.
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
@@ -34,6 +39,31 @@ impl<'js> Object<'js> {
         })
     }
 
+    /// Get a value, returning `None` if it is absent, undefined or of a different type.
+    ///
+    /// Only errors which aren't caused by the type of the value, like an exception thrown by a
+    /// getter, are returned.
+    pub fn try_get_typed<K: IntoAtom<'js>, V: FromJs<'js>>(&self, k: K) -> Result<Option<V>> {
+        let value: Value = self.get(k)?;
+        if value.is_undefined() {
+            return Ok(None);
+        }
+        match V::from_js(self.ctx(), value) {
+            Ok(x) => Ok(Some(x)),
+            Err(Error::FromJs { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get a value, returning the default value of the type if it is absent or undefined.
+    pub fn get_or_default<K: IntoAtom<'js>, V: FromJs<'js> + Default>(&self, k: K) -> Result<V> {
+        let value: Value = self.get(k)?;
+        if value.is_undefined() {
+            return Ok(V::default());
+        }
+        V::from_js(self.ctx(), value)
+    }
+
     /// check whether the object contains a certain key.
     pub fn contains_key<K>(&self, k: K) -> Result<bool>
     where
@@ -138,6 +168,131 @@ impl<'js> Object<'js> {
         }
     }
 
+    /// Copy all own enumerable string and symbol properties from `source` onto this object.
+    ///
+    /// Equivalent to `Object.assign(this, source)`, so getters on the source and setters on this
+    /// object are invoked. The copy is shallow: nested objects are shared with the source, see
+    /// [`Object::merge_deep`] to merge them instead.
+    pub fn assign_from(&self, source: &Object<'js>) -> Result<()> {
+        for key in source.own_keys::<Atom>(Filter::new().string().symbol().enum_only()) {
+            let key = key?;
+            let value: Value = source.get(key.clone())?;
+            self.set(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Recursively merge the own enumerable string and symbol properties of `source` into this
+    /// object, for example to layer configuration objects.
+    ///
+    /// Properties are copied like with [`Object::assign_from`], except that plain objects, whose
+    /// prototype is `Object.prototype` or `null`, are merged into the plain object of the target
+    /// with the same key. Plain objects of the source without a counterpart are merged into a new
+    /// object, so the source is never modified through the target. Arrays and all other values,
+    /// including class instances, replace the value of the target and are shared with the source.
+    /// Circular references of the source are shared as well.
+    pub fn merge_deep(&self, source: &Object<'js>) -> Result<()> {
+        let object_proto = Object::new(self.ctx().clone())?.get_prototype();
+        self.merge_deep_inner(source, object_proto.as_ref(), &mut Vec::new())
+    }
+
+    fn merge_deep_inner(
+        &self,
+        source: &Object<'js>,
+        object_proto: Option<&Object<'js>>,
+        stack: &mut Vec<Object<'js>>,
+    ) -> Result<()> {
+        let is_plain = |object: &Object<'js>| {
+            !object.is_array()
+                && !object.is_function()
+                && match object.get_prototype() {
+                    Some(proto) => Some(&proto) == object_proto,
+                    None => true,
+                }
+        };
+
+        stack.push(source.clone());
+        for key in source.own_keys::<Atom>(Filter::new().string().symbol().enum_only()) {
+            let key = key?;
+            let value: Value = source.get(key.clone())?;
+            let nested = match value.as_object() {
+                Some(object) if is_plain(object) && !stack.contains(object) => object,
+                _ => {
+                    self.set(key, value)?;
+                    continue;
+                }
+            };
+            let target = match self.get::<_, Value>(key.clone())?.into_object() {
+                Some(target) if is_plain(&target) => target,
+                _ => {
+                    let target = Object::new(self.ctx().clone())?;
+                    self.set(key, target.clone())?;
+                    target
+                }
+            };
+            target.merge_deep_inner(nested, object_proto, stack)?;
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    /// Copy all own enumerable properties from each of the sources onto this object in order.
+    ///
+    /// Equivalent to `Object.assign(this, ...sources)`.
+    pub fn assign_from_many(&self, sources: &[&Object<'js>]) -> Result<()> {
+        sources
+            .iter()
+            .try_for_each(|source| self.assign_from(source))
+    }
+
+    /// Copy all own enumerable properties from each of the sources onto the target and return
+    /// the target.
+    ///
+    /// Equivalent to `Object.assign(target, ...sources)`.
+    pub fn assign(target: Object<'js>, sources: &[&Object<'js>]) -> Result<Object<'js>> {
+        target.assign_from_many(sources)?;
+        Ok(target)
+    }
+
+    /// Create a new plain object with the own enumerable properties of this object.
+    ///
+    /// Equivalent to `Object.assign({}, this)`.
+    pub fn clone_shallow(&self) -> Result<Object<'js>> {
+        Self::assign(Object::new(self.ctx().clone())?, &[self])
+    }
+
+    /// Find the first own string enumerable property whose name matches the predicate.
+    ///
+    /// Returns the key together with the value of the property. The names are passed to the
+    /// predicate without allocating, which makes it suitable for case-insensitive lookups:
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let headers: Object = ctx.eval(r#"({ "Content-Type": "text/plain" })"#).unwrap();
+    /// let (_, value) = headers
+    ///     .find_key(|name| name.eq_ignore_ascii_case("content-type"))
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(value.as_string().unwrap().to_string().unwrap(), "text/plain");
+    /// # })
+    /// ```
+    pub fn find_key<F>(&self, mut predicate: F) -> Result<Option<(Atom<'js>, Value<'js>)>>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        for key in self.keys::<Atom>() {
+            let key = key?;
+            if key.with_str(&mut predicate)? {
+                let value = self.get(key.clone())?;
+                return Ok(Some((key, value)));
+            }
+        }
+        Ok(None)
+    }
+
     /// Get an object prototype
     ///
     /// Objects can have no prototype, in this case this function will return null.
@@ -801,4 +956,140 @@ mod test {
             );
         })
     }
+
+    #[test]
+    fn find_key() {
+        test_with(|ctx| {
+            let object: Object = ctx
+                .eval(
+                    r#"
+                    const object = { "Content-Type": "text/plain", 1: "one" };
+                    Object.defineProperty(object, "Hidden", { value: 1, enumerable: false });
+                    object[Symbol("accept")] = "symbol";
+                    object
+                    "#,
+                )
+                .unwrap();
+
+            let (key, value) = object
+                .find_key(|name| name.eq_ignore_ascii_case("content-type"))
+                .unwrap()
+                .unwrap();
+            assert_eq!(key.to_string().unwrap(), "Content-Type");
+            assert_eq!(StdString::from_js(&ctx, value).unwrap(), "text/plain");
+
+            let (_, value) = object.find_key(|name| name == "1").unwrap().unwrap();
+            assert_eq!(StdString::from_js(&ctx, value).unwrap(), "one");
+
+            assert!(object.find_key(|name| name == "Hidden").unwrap().is_none());
+            assert!(object.find_key(|name| name == "accept").unwrap().is_none());
+        })
+    }
+
+    #[test]
+    fn assign() {
+        test_with(|ctx| {
+            let sources: Vec<Object> = ctx
+                .eval(
+                    r#"
+                    globalThis.sym = Symbol("sym");
+                    const first = { a: 1, b: 1, [sym]: "symbol" };
+                    Object.defineProperty(first, "hidden", { value: 1, enumerable: false });
+                    const second = { b: 2, get c() { return this.b + 1 } };
+                    [first, second]
+                    "#,
+                )
+                .unwrap();
+            let (first, second) = (&sources[0], &sources[1]);
+
+            let target: Object = ctx
+                .eval("globalThis.target = { set a(v) { this.seen = v } }; target")
+                .unwrap();
+            let res = Object::assign(target, &[first, second]).unwrap();
+            let state: StdString = ctx
+                .eval("[target.seen, target.b, target.c, target[sym], 'hidden' in target].join()")
+                .unwrap();
+            assert_eq!(state, "1,2,3,symbol,false");
+            assert!(res.contains_key("seen").unwrap());
+
+            let copy = second.clone_shallow().unwrap();
+            assert_ne!(&copy, second);
+            let desc = copy.own_property_descriptor("c").unwrap().unwrap();
+            assert_eq!(desc.value.unwrap().as_int(), Some(3));
+            assert_eq!(copy.keys::<StdString>().count(), 2);
+        })
+    }
+
+    #[test]
+    fn merge_deep() {
+        test_with(|ctx| {
+            let objects: Vec<Object> = ctx
+                .eval(
+                    r#"
+                    globalThis.sym = Symbol("sym");
+                    globalThis.base = {
+                        server: { host: "localhost", port: 80, tls: { enabled: false } },
+                        list: [1, 2],
+                        date: new Date(0),
+                    };
+                    globalThis.layer = {
+                        server: { port: 8080, tls: { enabled: true }, [sym]: "symbol" },
+                        list: [3],
+                        date: { plain: true },
+                        extra: { nested: { deep: 1 } },
+                    };
+                    layer.self = layer;
+                    [base, layer]
+                    "#,
+                )
+                .unwrap();
+            objects[0].merge_deep(&objects[1]).unwrap();
+
+            let state: StdString = ctx
+                .eval(
+                    r#"[
+                        base.server.host, base.server.port, base.server.tls.enabled,
+                        base.server[sym], base.server !== layer.server, base.list.join("|"),
+                        base.list === layer.list, base.date.plain, base.date instanceof Date,
+                        base.extra.nested.deep, base.extra !== layer.extra, base.self === layer,
+                        layer.server.host === undefined,
+                    ].join()"#,
+                )
+                .unwrap();
+            assert_eq!(
+                state,
+                "localhost,8080,true,symbol,true,3,true,true,false,1,true,true,true"
+            );
+        })
+    }
+
+    #[test]
+    fn typed_get() {
+        test_with(|ctx| {
+            let object: Object = ctx
+                .eval(
+                    r#"({
+                        name: "x",
+                        count: 3,
+                        missing: undefined,
+                        get broken() { throw new Error("broken") },
+                    })"#,
+                )
+                .unwrap();
+
+            let name: Option<StdString> = object.try_get_typed("name").unwrap();
+            assert_eq!(name.as_deref(), Some("x"));
+            assert_eq!(object.try_get_typed::<_, i32>("name").unwrap(), None);
+            assert_eq!(object.try_get_typed::<_, i32>("count").unwrap(), Some(3));
+            assert_eq!(object.try_get_typed::<_, i32>("missing").unwrap(), None);
+            assert_eq!(object.try_get_typed::<_, i32>("absent").unwrap(), None);
+            assert!(object.try_get_typed::<_, i32>("broken").is_err());
+            ctx.catch();
+
+            assert_eq!(object.get_or_default::<_, i32>("count").unwrap(), 3);
+            assert_eq!(object.get_or_default::<_, i32>("missing").unwrap(), 0);
+            assert_eq!(object.get_or_default::<_, Vec<i32>>("absent").unwrap(), []);
+            assert!(object.get_or_default::<_, i32>("name").is_err());
+        })
+    }
 }
@@ -0,0 +1,258 @@
+use crate::{qjs, Object, Result};
+use core::ffi::{c_int, CStr};
+
+/// The raw C function type of a [`FunctionListEntry`], `JSCFunction` in QuickJS.
+pub type RawFunction = unsafe extern "C" fn(
+    ctx: *mut qjs::JSContext,
+    this: qjs::JSValue,
+    argc: c_int,
+    argv: *mut qjs::JSValue,
+) -> qjs::JSValue;
+
+const FUNCTION_FLAGS: u8 = (qjs::JS_PROP_WRITABLE | qjs::JS_PROP_CONFIGURABLE) as u8;
+const CONSTANT_FLAGS: u8 = qjs::JS_PROP_CONFIGURABLE as u8;
+
+/// An entry of a list of properties defined at once with [`Object::set_function_list`].
+///
+/// The constructors are `const` so a list can be created statically, see [`function_list!`].
+/// Functions are writable and configurable like the methods of built-in objects, constants are
+/// only configurable. The flags can be changed with the builder methods.
+///
+/// [`function_list!`]: crate::function_list
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct FunctionListEntry(qjs::JSCFunctionListEntry);
+
+// SAFETY: The entry only points to static names and functions.
+unsafe impl Send for FunctionListEntry {}
+unsafe impl Sync for FunctionListEntry {}
+
+impl FunctionListEntry {
+    const fn new(
+        name: &'static CStr,
+        def_type: u32,
+        u: qjs::JSCFunctionListEntry__bindgen_ty_1,
+    ) -> Self {
+        Self(qjs::JSCFunctionListEntry {
+            name: name.as_ptr(),
+            prop_flags: CONSTANT_FLAGS,
+            def_type: def_type as u8,
+            magic: 0,
+            u,
+        })
+    }
+
+    /// A function with the number of arguments it expects, which becomes its `length`.
+    ///
+    /// The function has to follow the contract described in the safety section of
+    /// [`Object::set_function_list`].
+    pub const fn function(name: &'static CStr, length: u8, func: RawFunction) -> Self {
+        let mut entry = Self::new(
+            name,
+            qjs::JS_DEF_CFUNC,
+            qjs::JSCFunctionListEntry__bindgen_ty_1 {
+                func: qjs::JSCFunctionListEntry__bindgen_ty_1__bindgen_ty_1 {
+                    length,
+                    cproto: qjs::JSCFunctionEnum_JS_CFUNC_generic as u8,
+                    cfunc: qjs::JSCFunctionType {
+                        generic: Some(func),
+                    },
+                },
+            },
+        );
+        entry.0.prop_flags = FUNCTION_FLAGS;
+        entry
+    }
+
+    /// An integer constant.
+    pub const fn int32(name: &'static CStr, value: i32) -> Self {
+        Self::new(
+            name,
+            qjs::JS_DEF_PROP_INT32,
+            qjs::JSCFunctionListEntry__bindgen_ty_1 { i32_: value },
+        )
+    }
+
+    /// A number constant.
+    pub const fn float64(name: &'static CStr, value: f64) -> Self {
+        Self::new(
+            name,
+            qjs::JS_DEF_PROP_DOUBLE,
+            qjs::JSCFunctionListEntry__bindgen_ty_1 { f64_: value },
+        )
+    }
+
+    /// A string constant.
+    pub const fn string(name: &'static CStr, value: &'static CStr) -> Self {
+        Self::new(
+            name,
+            qjs::JS_DEF_PROP_STRING,
+            qjs::JSCFunctionListEntry__bindgen_ty_1 {
+                str_: value.as_ptr(),
+            },
+        )
+    }
+
+    const fn with_flag(mut self, flag: u32, set: bool) -> Self {
+        if set {
+            self.0.prop_flags |= flag as u8;
+        } else {
+            self.0.prop_flags &= !(flag as u8);
+        }
+        self
+    }
+
+    /// Set whether the property is writable.
+    #[must_use]
+    pub const fn writable(self, writable: bool) -> Self {
+        self.with_flag(qjs::JS_PROP_WRITABLE, writable)
+    }
+
+    /// Set whether the property is configurable.
+    #[must_use]
+    pub const fn configurable(self, configurable: bool) -> Self {
+        self.with_flag(qjs::JS_PROP_CONFIGURABLE, configurable)
+    }
+
+    /// Set whether the property is enumerable.
+    #[must_use]
+    pub const fn enumerable(self, enumerable: bool) -> Self {
+        self.with_flag(qjs::JS_PROP_ENUMERABLE, enumerable)
+    }
+}
+
+/// Create a static list of [`FunctionListEntry`] from `(name, length, function)` tuples.
+///
+/// ```
+/// # use rquickjs::{function_list, qjs, Context, Object, Runtime};
+/// unsafe extern "C" fn answer(
+///     _ctx: *mut qjs::JSContext,
+///     _this: qjs::JSValue,
+///     _argc: qjs::c_int,
+///     _argv: *mut qjs::JSValue,
+/// ) -> qjs::JSValue {
+///     qjs::JS_NewFloat64(42.0)
+/// }
+///
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let object = Object::new(ctx.clone()).unwrap();
+/// // SAFETY: `answer` returns an owned value and doesn't touch its arguments.
+/// unsafe {
+///     object.set_function_list(function_list![(c"answer", 0, answer), (c"alsoAnswer", 0, answer)])
+/// }
+/// .unwrap();
+/// ctx.globals().set("object", object).unwrap();
+/// assert_eq!(ctx.eval::<i32, _>("object.answer() + object.alsoAnswer()").unwrap(), 84);
+/// # })
+/// ```
+#[macro_export]
+macro_rules! function_list {
+    ($(($name:expr, $length:expr, $func:expr)),* $(,)?) => {{
+        const LIST: &[$crate::object::FunctionListEntry] = &[
+            $($crate::object::FunctionListEntry::function($name, $length, $func)),*
+        ];
+        LIST
+    }};
+}
+
+impl<'js> Object<'js> {
+    /// Define all properties of the list with a single call into QuickJS.
+    ///
+    /// This is faster than setting many functions one by one, for example on the prototype of a
+    /// class with lots of methods. Functions are created lazily the first time they are accessed,
+    /// which is why the list has to be static.
+    ///
+    /// # Safety
+    /// QuickJS calls the functions of the list directly, so every [`FunctionListEntry::function`]
+    /// must be a valid `JSCFunction`:
+    /// - it is called with the context, `this` and `argc` arguments at `argv`, all of which are
+    ///   borrowed and must not be freed or kept past the call without duplicating them,
+    /// - it must return a value it owns, or `JS_EXCEPTION` after throwing on the context,
+    /// - it must not unwind, panics have to be caught before returning to QuickJS.
+    pub unsafe fn set_function_list(&self, entries: &'static [FunctionListEntry]) -> Result<()> {
+        let ctx = self.ctx();
+        let res = qjs::JS_SetPropertyFunctionList(
+            ctx.as_ptr(),
+            self.0.as_js_value(),
+            entries.as_ptr().cast(),
+            entries.len() as c_int,
+        );
+        if res < 0 {
+            return Err(ctx.raise_exception());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    unsafe extern "C" fn add(
+        ctx: *mut qjs::JSContext,
+        _this: qjs::JSValue,
+        argc: c_int,
+        argv: *mut qjs::JSValue,
+    ) -> qjs::JSValue {
+        let mut sum = 0.0;
+        for i in 0..argc as usize {
+            let mut value = 0.0;
+            if qjs::JS_ToFloat64(ctx, &mut value, *argv.add(i)) < 0 {
+                return qjs::JS_EXCEPTION;
+            }
+            sum += value;
+        }
+        qjs::JS_NewFloat64(sum)
+    }
+
+    unsafe extern "C" fn fail(
+        ctx: *mut qjs::JSContext,
+        _this: qjs::JSValue,
+        _argc: c_int,
+        _argv: *mut qjs::JSValue,
+    ) -> qjs::JSValue {
+        qjs::JS_ThrowTypeError(ctx, c"failed".as_ptr())
+    }
+
+    static MATH: &[FunctionListEntry] = &[
+        FunctionListEntry::function(c"add", 2, add),
+        FunctionListEntry::function(c"fail", 0, fail).enumerable(true),
+        FunctionListEntry::int32(c"ANSWER", 42),
+        FunctionListEntry::float64(c"HALF", 0.5).writable(true),
+        FunctionListEntry::string(c"NAME", c"math"),
+    ];
+
+    #[test]
+    fn set_function_list() {
+        test_with(|ctx| {
+            let math = Object::new(ctx.clone()).unwrap();
+            unsafe {
+                math.set_function_list(MATH).unwrap();
+                math.set_function_list(function_list![(c"sum", 0, add)])
+                    .unwrap();
+            }
+            ctx.globals().set("math", math).unwrap();
+
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                    math.HALF = 1.5;
+                    [
+                        math.add(1, 2), math.add.length, math.sum(1, 2, 3), math.sum.name,
+                        math.ANSWER, Object.getOwnPropertyDescriptor(math, "ANSWER").writable,
+                        math.HALF, math.NAME, Object.keys(math),
+                    ].join()
+                    "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "3,2,6,sum,42,false,1.5,math,fail");
+
+            let err = ctx.eval::<(), _>("math.fail()").catch(&ctx).unwrap_err();
+            assert!(err.to_string().contains("failed"), "{err}");
+        })
+    }
+}
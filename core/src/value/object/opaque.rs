@@ -0,0 +1,156 @@
+use alloc::boxed::Box;
+use core::{any::Any, marker::PhantomData, ops::Deref};
+
+use crate::{
+    class::{JsClass, OwnedBorrow, Readable, Trace, Tracer},
+    markers::ParallelSend,
+    qjs,
+    value::Constructor,
+    Class, Ctx, JsLifetime, Object, Result, Symbol, Value,
+};
+
+use super::Property;
+
+#[cfg(not(feature = "parallel"))]
+type AnyBox = Box<dyn Any>;
+#[cfg(feature = "parallel")]
+type AnyBox = Box<dyn Any + Send>;
+
+/// The hidden class instance which owns the Rust value attached to an object.
+pub(crate) struct ObjectData(AnyBox);
+
+unsafe impl<'js> JsLifetime<'js> for ObjectData {
+    type Changed<'to> = ObjectData;
+}
+
+impl<'js> Trace<'js> for ObjectData {
+    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+}
+
+impl<'js> JsClass<'js> for ObjectData {
+    const NAME: &'static str = "ObjectData";
+
+    type Mutable = Readable;
+
+    fn prototype(_ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        Ok(None)
+    }
+
+    fn constructor(_ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Ok(None)
+    }
+}
+
+/// A reference to a Rust value attached to an object with [`Object::set_opaque`].
+///
+/// Keeps the value alive even if it is replaced on the object while the reference is held.
+pub struct OpaqueRef<'js, T> {
+    data: OwnedBorrow<'js, ObjectData>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: 'static> Deref for OpaqueRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.data
+            .0
+            .downcast_ref()
+            .expect("type of the opaque value was checked on creation")
+    }
+}
+
+/// Returns the symbol under which the data of objects is stored, unique to the runtime.
+fn data_key<'js>(ctx: &Ctx<'js>) -> Result<Symbol<'js>> {
+    let opaque = unsafe { ctx.get_opaque() };
+    if let Some(key) = opaque.object_data_key() {
+        return Ok(key);
+    }
+    let key = unsafe {
+        let v = qjs::JS_NewSymbol(ctx.as_ptr(), c"rquickjs.opaque".as_ptr(), false);
+        let v = ctx.handle_exception(v)?;
+        Value::from_js_value(ctx.clone(), v).into_symbol().unwrap()
+    };
+    opaque.set_object_data_key(key.clone());
+    Ok(key)
+}
+
+impl<'js> Object<'js> {
+    /// Attach a Rust value to the object, replacing the value attached before.
+    ///
+    /// The value is dropped when the object is garbage collected. It is stored in a
+    /// non-enumerable property keyed by a symbol unique to the runtime which is only reachable
+    /// from JavaScript through reflection like `Object.getOwnPropertySymbols`.
+    ///
+    /// Returns an error if the object is not extensible.
+    pub fn set_opaque<T: ParallelSend + 'static>(&self, data: T) -> Result<()> {
+        let ctx = self.ctx();
+        let key = data_key(ctx)?;
+        let data = Class::instance(ctx.clone(), ObjectData(Box::new(data)))?;
+        self.prop(key, Property::from(data).configurable())
+    }
+
+    /// Returns the Rust value attached to the object with [`Object::set_opaque`].
+    ///
+    /// Returns `None` if no value is attached or if the attached value is not of type `T`.
+    pub fn get_opaque<T: 'static>(&self) -> Option<OpaqueRef<'js, T>> {
+        let key = data_key(self.ctx()).ok()?;
+        let desc = self.own_property_descriptor(key).ok()??;
+        let data = Class::<ObjectData>::from_value(&desc.value?).ok()?;
+        if !data.borrow().0.is::<T>() {
+            return None;
+        }
+        Some(OpaqueRef {
+            data: OwnedBorrow::from_class(data),
+            _marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test_with, Object};
+
+    #[test]
+    fn set_get_opaque() {
+        test_with(|ctx| {
+            let obj: Object = ctx.eval("({ a: 1 })").unwrap();
+            assert!(obj.get_opaque::<u32>().is_none());
+
+            obj.set_opaque(42u32).unwrap();
+            assert_eq!(*obj.get_opaque::<u32>().unwrap(), 42);
+            // Guarded against type confusion.
+            assert!(obj.get_opaque::<i32>().is_none());
+
+            let data = obj.get_opaque::<u32>().unwrap();
+            obj.set_opaque("replaced").unwrap();
+            assert_eq!(*data, 42);
+            assert_eq!(*obj.get_opaque::<&str>().unwrap(), "replaced");
+            assert!(obj.get_opaque::<u32>().is_none());
+
+            // Hidden from normal property enumeration.
+            ctx.globals().set("obj", obj).unwrap();
+            let keys: String = ctx.eval("JSON.stringify(Object.keys(obj))").unwrap();
+            assert_eq!(keys, r#"["a"]"#);
+        })
+    }
+
+    #[test]
+    #[cfg(not(feature = "parallel"))]
+    fn opaque_dropped_with_object() {
+        use alloc::rc::Rc;
+
+        test_with(|ctx| {
+            let data = Rc::new(());
+            let obj = Object::new(ctx.clone()).unwrap();
+            obj.set_opaque(data.clone()).unwrap();
+            assert_eq!(Rc::strong_count(&data), 2);
+            drop(obj);
+            ctx.run_gc();
+            assert_eq!(Rc::strong_count(&data), 1);
+
+            let frozen: crate::Value = ctx.eval("Object.freeze({})").unwrap();
+            assert!(frozen.as_object().unwrap().set_opaque(1).is_err());
+        })
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
-    function::IntoJsFunc, qjs, Ctx, Function, IntoAtom, IntoJs, Object, Result, Undefined, Value,
+    function::{IntoJsFunc, This},
+    qjs, Atom, Ctx, Filter, FromAtom, FromJs, Function, IntoAtom, IntoJs, Object, Result,
+    Undefined, Value,
 };
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
 
 impl<'js> Object<'js> {
     /// Define a property of an object
@@ -51,6 +55,156 @@ impl<'js> Object<'js> {
         }
         Ok(())
     }
+
+    /// Define an accessor property with a getter backed by a Rust closure.
+    ///
+    /// The closure is called with the object the property was read from, which may be an object
+    /// inheriting from this one. The property is enumerable and configurable, like a getter in an
+    /// object literal, and a setter defined with [`Object::define_setter`] is kept.
+    ///
+    /// The closure is owned by the getter function, which doesn't trace it for the garbage
+    /// collector, so it can't capture JS values: a getter capturing the object it is defined on
+    /// would keep it alive forever. Read JS state through the `this` argument instead, or capture
+    /// a [`Persistent`](crate::Persistent) and restore it in the closure.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let obj = Object::new(ctx.clone()).unwrap();
+    /// obj.set("a", 1).unwrap();
+    /// obj.define_getter("double", |_ctx, this| {
+    ///     let a: i32 = this.get::<Object>()?.get("a")?;
+    ///     Ok(a * 2)
+    /// })
+    /// .unwrap();
+    /// assert_eq!(obj.get::<_, i32>("double").unwrap(), 2);
+    /// # })
+    /// ```
+    pub fn define_getter<K, F, R>(&self, key: K, getter: F) -> Result<()>
+    where
+        K: IntoAtom<'js>,
+        F: Fn(Ctx<'js>, Value<'js>) -> Result<R> + 'static,
+        R: IntoJs<'js> + 'js,
+    {
+        self.prop(
+            key,
+            Accessor::new_get(move |ctx: Ctx<'js>, this: This<Value<'js>>| getter(ctx, this.0))
+                .enumerable()
+                .configurable(),
+        )
+    }
+
+    /// Define an accessor property with a setter backed by a Rust closure.
+    ///
+    /// The closure is called with the object the property was assigned on and the new value. The
+    /// property is enumerable and configurable and a getter defined with
+    /// [`Object::define_getter`] is kept. Like the getter, the closure can't capture JS values.
+    pub fn define_setter<K, F, V>(&self, key: K, setter: F) -> Result<()>
+    where
+        K: IntoAtom<'js>,
+        F: Fn(Ctx<'js>, Value<'js>, V) -> Result<()> + 'static,
+        V: FromJs<'js> + 'js,
+    {
+        self.prop(
+            key,
+            Accessor::new_set(move |ctx: Ctx<'js>, this: This<Value<'js>>, value: V| {
+                setter(ctx, this.0, value)
+            })
+            .enumerable()
+            .configurable(),
+        )
+    }
+
+    /// Get the descriptor of an own property without invoking any accessors.
+    ///
+    /// Returns `None` if the object doesn't have an own property with the given key.
+    pub fn own_property_descriptor<K>(&self, key: K) -> Result<Option<PropertyDescriptor<'js>>>
+    where
+        K: IntoAtom<'js>,
+    {
+        let ctx = self.ctx();
+        let key = key.into_atom(ctx)?;
+        let mut desc = MaybeUninit::<qjs::JSPropertyDescriptor>::uninit();
+        let res = unsafe {
+            qjs::JS_GetOwnProperty(
+                ctx.as_ptr(),
+                desc.as_mut_ptr(),
+                self.0.as_js_value(),
+                key.atom,
+            )
+        };
+        if res < 0 {
+            return Err(ctx.raise_exception());
+        }
+        if res == 0 {
+            return Ok(None);
+        }
+        let desc = unsafe { desc.assume_init() };
+        Ok(Some(unsafe { PropertyDescriptor::from_ffi(ctx, desc) }))
+    }
+
+    /// Get the own properties of an object together with their descriptors.
+    ///
+    /// Unlike [`Object::own_props`] this doesn't invoke accessors, which makes it suitable for
+    /// inspecting or faithfully copying objects.
+    pub fn own_properties_full<K: FromAtom<'js>>(
+        &self,
+        filter: Filter,
+    ) -> Result<Vec<(K, PropertyDescriptor<'js>)>> {
+        let mut res = Vec::new();
+        for key in self.own_keys::<Atom>(filter) {
+            let key = key?;
+            // Properties can be removed while enumerating, e.g. by a proxy.
+            if let Some(desc) = self.own_property_descriptor(key.clone())? {
+                res.push((K::from_atom(key)?, desc));
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// The descriptor of an own property, like the one returned by
+/// `Object.getOwnPropertyDescriptor`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyDescriptor<'js> {
+    /// The value of a data property, `None` for accessor properties.
+    pub value: Option<Value<'js>>,
+    /// The getter of an accessor property, if any.
+    pub get: Option<Function<'js>>,
+    /// The setter of an accessor property, if any.
+    pub set: Option<Function<'js>>,
+    /// Whether a data property can be assigned, `None` for accessor properties.
+    pub writable: Option<bool>,
+    /// Whether the property shows up during enumeration.
+    pub enumerable: bool,
+    /// Whether the property can be deleted or reconfigured.
+    pub configurable: bool,
+}
+
+impl<'js> PropertyDescriptor<'js> {
+    /// Returns true if the property is an accessor property.
+    pub fn is_accessor(&self) -> bool {
+        self.writable.is_none()
+    }
+
+    /// Takes ownership of the values in the descriptor.
+    unsafe fn from_ffi(ctx: &Ctx<'js>, desc: qjs::JSPropertyDescriptor) -> Self {
+        let value = Value::from_js_value(ctx.clone(), desc.value);
+        let get = Value::from_js_value(ctx.clone(), desc.getter);
+        let set = Value::from_js_value(ctx.clone(), desc.setter);
+        let flag = |flag: u32| desc.flags & flag as qjs::c_int != 0;
+        let accessor = flag(qjs::JS_PROP_GETSET);
+        PropertyDescriptor {
+            value: (!accessor).then_some(value),
+            get: get.into_function(),
+            set: set.into_function(),
+            writable: (!accessor).then(|| flag(qjs::JS_PROP_WRITABLE)),
+            enumerable: flag(qjs::JS_PROP_ENUMERABLE),
+            configurable: flag(qjs::JS_PROP_CONFIGURABLE),
+        }
+    }
 }
 
 pub type PropertyFlags = qjs::c_int;
@@ -431,4 +585,94 @@ mod test {
             assert_eq!(val.lock().clone(), "");
         });
     }
+
+    #[test]
+    fn define_getter_and_setter() {
+        test_with(|ctx| {
+            let state = Ref::new(Mut::new(1));
+            let obj = Object::new(ctx.clone()).unwrap();
+            obj.define_getter("computed", {
+                let state = state.clone();
+                move |_ctx, this| {
+                    let scale: i32 = this.get::<Object>()?.get("scale")?;
+                    Ok(*state.lock() * scale)
+                }
+            })
+            .unwrap();
+            obj.define_setter("computed", {
+                let state = state.clone();
+                move |_ctx, _this, value: i32| {
+                    *state.lock() = value;
+                    Ok(())
+                }
+            })
+            .unwrap();
+            obj.set("scale", 10).unwrap();
+            ctx.globals().set("obj", obj.clone()).unwrap();
+
+            let read = || ctx.eval::<i32, _>("obj.computed").unwrap();
+            assert_eq!(read(), 10);
+            *state.lock() = 2;
+            assert_eq!(read(), 20);
+            ctx.eval::<(), _>("obj.computed = 3").unwrap();
+            assert_eq!(*state.lock(), 3);
+            assert_eq!(read(), 30);
+
+            // Inheriting objects are passed as `this`.
+            let inherited: i32 = ctx
+                .eval("Object.assign(Object.create(obj), { scale: 100 }).computed")
+                .unwrap();
+            assert_eq!(inherited, 300);
+            let keys: Vec<StdString> = ctx.eval("Object.keys(obj)").unwrap();
+            assert_eq!(keys, ["computed", "scale"]);
+        });
+    }
+
+    #[test]
+    fn own_properties_full() {
+        test_with(|ctx| {
+            let obj: Object = ctx
+                .eval(
+                    r#"
+                    let called = false;
+                    const obj = { a: 1 };
+                    Object.defineProperty(obj, "b", { value: "x", enumerable: false });
+                    Object.defineProperty(obj, "c", {
+                        get() { called = true; return 3; },
+                        enumerable: true,
+                        configurable: true,
+                    });
+                    obj
+                    "#,
+                )
+                .unwrap();
+
+            let props = obj
+                .own_properties_full::<StdString>(Filter::new().string())
+                .unwrap();
+            let keys: Vec<_> = props.iter().map(|(k, _)| k.as_str()).collect();
+            assert_eq!(keys, ["a", "b", "c"]);
+
+            let a = &props[0].1;
+            assert_eq!(a.value.as_ref().unwrap().as_int(), Some(1));
+            assert_eq!(a.writable, Some(true));
+            assert!(a.enumerable && a.configurable && !a.is_accessor());
+
+            let b = &props[1].1;
+            assert_eq!(b.writable, Some(false));
+            assert!(!b.enumerable && !b.configurable);
+
+            let c = &props[2].1;
+            assert!(c.is_accessor());
+            assert!(c.value.is_none() && c.set.is_none());
+            assert_eq!(c.get.as_ref().unwrap().call::<_, i32>(()).unwrap(), 3);
+            assert!(c.enumerable && c.configurable);
+
+            let props = obj
+                .own_properties_full::<StdString>(Filter::default())
+                .unwrap();
+            assert_eq!(props.len(), 2);
+            assert!(obj.own_property_descriptor("d").unwrap().is_none());
+        })
+    }
 }
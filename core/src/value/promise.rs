@@ -1,10 +1,11 @@
 //! Javascript promises and future integration.
+#[cfg(feature = "futures")]
+use crate::CaughtResult;
 use crate::{
-    atom::PredefinedAtom, qjs, Ctx, Error, FromJs, Function, IntoJs, Object, Result, Value,
+    atom::PredefinedAtom, function::This, qjs, CatchResultExt, CaughtError, Ctx, Error, FromJs,
+    Function, IntoJs, Object, Result, Value,
 };
 #[cfg(feature = "futures")]
-use crate::{function::This, CatchResultExt, CaughtError};
-#[cfg(feature = "futures")]
 use alloc::rc::Rc;
 #[cfg(feature = "futures")]
 use core::{
@@ -17,15 +18,22 @@ use core::{
 #[cfg(all(feature = "std", feature = "futures"))]
 use std::println;
 
-/// The execution state of a promise.
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
-pub enum PromiseState {
+/// The execution state of a promise, together with its result once it settled.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum PromiseState<'js> {
     /// The promise has not yet completed.
     Pending,
-    /// The promise completed succefully.
-    Resolved,
-    /// The promise completed with an error.
-    Rejected,
+    /// The promise completed succefully with the value.
+    Fulfilled(Value<'js>),
+    /// The promise completed with an error, the value is the rejection reason.
+    Rejected(Value<'js>),
+}
+
+impl PromiseState<'_> {
+    /// Returns whether the promise has not yet completed.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, PromiseState::Pending)
+    }
 }
 
 /// The type of promise event.
@@ -37,6 +45,28 @@ pub enum PromiseHookType {
     Resolve,
 }
 
+/// Resolve or reject a promise with the result.
+pub(crate) fn settle<'js>(
+    ctx: &Ctx<'js>,
+    resolve: &Function<'js>,
+    reject: &Function<'js>,
+    res: Result<Value<'js>>,
+) -> Result<()> {
+    match res.catch(ctx) {
+        Ok(x) => resolve.call::<_, ()>((x,)),
+        Err(e) => match e {
+            CaughtError::Exception(e) => reject.call::<_, ()>((e,)),
+            CaughtError::Value(e) => reject.call::<_, ()>((e,)),
+            CaughtError::Error(e) => {
+                let is_exception = unsafe { qjs::JS_IsException(e.throw(ctx)) };
+                debug_assert!(is_exception);
+                let e = ctx.catch();
+                reject.call::<_, ()>((e,))
+            }
+        },
+    }
+}
+
 /// A JavaScript promise.
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
 #[repr(transparent)]
@@ -53,23 +83,9 @@ impl<'js> Promise<'js> {
         let (promise, resolve, reject) = ctx.promise()?;
         let ctx_clone = ctx.clone();
         let future = async move {
-            let res = future.await.into_js(&ctx_clone).catch(&ctx_clone);
-
-            let err = match res {
-                Ok(x) => resolve.call::<_, ()>((x,)),
-                Err(e) => match e {
-                    CaughtError::Exception(e) => reject.call::<_, ()>((e,)),
-                    CaughtError::Value(e) => reject.call::<_, ()>((e,)),
-                    CaughtError::Error(e) => {
-                        let is_exception = unsafe { qjs::JS_IsException(e.throw(&ctx_clone)) };
-                        debug_assert!(is_exception);
-                        let e = ctx_clone.catch();
-                        reject.call::<_, ()>((e,))
-                    }
-                },
-            };
+            let res = future.await.into_js(&ctx_clone);
             // TODO figure out something better to do here.
-            if let Err(_e) = err {
+            if let Err(_e) = settle(&ctx_clone, &resolve, &reject, res) {
                 #[cfg(feature = "std")]
                 println!("promise handle function returned error:{}", _e);
             }
@@ -78,18 +94,43 @@ impl<'js> Promise<'js> {
         Ok(promise)
     }
 
+    /// Create a promise which is already resolved with the value, or rejected with the error.
+    ///
+    /// Errors which aren't JavaScript exceptions are converted into one, like errors returned
+    /// from functions called by JavaScript.
+    pub fn from_result<T>(ctx: &Ctx<'js>, res: Result<T>) -> Result<Self>
+    where
+        T: IntoJs<'js>,
+    {
+        let (promise, resolve, reject) = ctx.promise()?;
+        settle(ctx, &resolve, &reject, res.and_then(|x| x.into_js(ctx)))?;
+        Ok(promise)
+    }
+
     /// Create a new JavaScript promise along with its resolve and reject functions.
     pub fn new(ctx: &Ctx<'js>) -> Result<(Self, Function<'js>, Function<'js>)> {
         ctx.promise()
     }
 
-    /// Returns the state of the promise, either pending,resolved or rejected.
-    pub fn state(&self) -> PromiseState {
-        let v = unsafe { qjs::JS_PromiseState(self.ctx().as_ptr(), self.as_js_value()) };
+    /// Returns the state of the promise, either pending, or fulfilled or rejected together with
+    /// the result.
+    ///
+    /// This neither runs pending jobs nor has any other side effects, which allows taking a fast
+    /// path for promises which already settled. In particular reading the reason of a rejected
+    /// promise doesn't mark the rejection as handled, so it is still reported to the
+    /// [rejection tracker](crate::Runtime::set_host_promise_rejection_tracker) unless a handler
+    /// is attached.
+    pub fn state(&self) -> PromiseState<'js> {
+        let ctx = self.ctx();
+        let v = unsafe { qjs::JS_PromiseState(ctx.as_ptr(), self.as_js_value()) };
+        let result = || unsafe {
+            let v = qjs::JS_PromiseResult(ctx.as_ptr(), self.as_js_value());
+            Value::from_js_value(ctx.clone(), v)
+        };
         match v {
             qjs::JSPromiseStateEnum_JS_PROMISE_PENDING => PromiseState::Pending,
-            qjs::JSPromiseStateEnum_JS_PROMISE_FULFILLED => PromiseState::Resolved,
-            qjs::JSPromiseStateEnum_JS_PROMISE_REJECTED => PromiseState::Rejected,
+            qjs::JSPromiseStateEnum_JS_PROMISE_FULFILLED => PromiseState::Fulfilled(result()),
+            qjs::JSPromiseStateEnum_JS_PROMISE_REJECTED => PromiseState::Rejected(result()),
             _ => unreachable!(),
         }
     }
@@ -104,6 +145,36 @@ impl<'js> Promise<'js> {
         self.0.get(PredefinedAtom::Catch)
     }
 
+    /// Attach callbacks for the fulfillment and the rejection of the promise, like
+    /// `promise.then(onFulfilled, onRejected)` in JavaScript.
+    ///
+    /// Returns the promise resolving with the result of the called callback. A missing callback
+    /// passes the value or the rejection through.
+    pub fn then_with(
+        &self,
+        on_fulfilled: Option<Function<'js>>,
+        on_rejected: Option<Function<'js>>,
+    ) -> Result<Promise<'js>> {
+        self.then()?
+            .call((This(self.clone()), on_fulfilled, on_rejected))
+    }
+
+    /// Attach a callback for the rejection of the promise, like `promise.catch(onRejected)` in
+    /// JavaScript.
+    pub fn catch_with(&self, on_rejected: Function<'js>) -> Result<Promise<'js>> {
+        self.catch()?.call((This(self.clone()), on_rejected))
+    }
+
+    /// Attach a callback called when the promise settles, like `promise.finally(onFinally)` in
+    /// JavaScript.
+    ///
+    /// Returns a promise settling like this promise once the callback completed.
+    pub fn finally(&self, on_finally: Function<'js>) -> Result<Promise<'js>> {
+        self.0
+            .get::<_, Function>(PredefinedAtom::Finally)?
+            .call((This(self.clone()), on_finally))
+    }
+
     /// Returns the result of the future if there is one.
     ///
     /// Returns None if the promise has not yet been completed, Ok if the promise was resolved, and
@@ -112,18 +183,8 @@ impl<'js> Promise<'js> {
     pub fn result<T: FromJs<'js>>(&self) -> Option<Result<T>> {
         match self.state() {
             PromiseState::Pending => None,
-            PromiseState::Resolved => {
-                let v = unsafe { qjs::JS_PromiseResult(self.ctx().as_ptr(), self.as_js_value()) };
-                let v = unsafe { Value::from_js_value(self.ctx().clone(), v) };
-                Some(FromJs::from_js(self.ctx(), v))
-            }
-            PromiseState::Rejected => {
-                unsafe {
-                    let v = qjs::JS_PromiseResult(self.ctx().as_ptr(), self.as_js_value());
-                    qjs::JS_Throw(self.ctx().as_ptr(), v);
-                };
-                Some(Err(Error::Exception))
-            }
+            PromiseState::Fulfilled(v) => Some(FromJs::from_js(self.ctx(), v)),
+            PromiseState::Rejected(v) => Some(Err(self.ctx().throw(v))),
         }
     }
 
@@ -133,7 +194,8 @@ impl<'js> Promise<'js> {
     /// jobs before the promise can be resolved, this function returns [`Error::WouldBlock`]
     /// indicating that no more work can be done at the moment.
     ///
-    /// This function only drives the quickjs job queue, futures are not polled.
+    /// This function only drives the quickjs job queue, futures are not polled. Together with
+    /// [`Promise::then_with`] it allows driving promises from programs without an async runtime.
     pub fn finish<T: FromJs<'js>>(&self) -> Result<T> {
         loop {
             if let Some(x) = self.result() {
@@ -147,6 +209,14 @@ impl<'js> Promise<'js> {
     }
 
     /// Wrap the promise into a struct which can be polled as a rust future.
+    ///
+    /// The future resolves with the value of the promise converted to `T`. If the promise is
+    /// rejected the future returns a [`CaughtError`] containing the rejection reason, whose
+    /// message and stack can be retrieved with [`CaughtError::message`] and
+    /// [`CaughtError::stack`].
+    ///
+    /// Dropping the future before the promise is settled releases the waker it registered, the
+    /// reaction callbacks attached to the promise then do nothing.
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
     #[cfg(feature = "futures")]
     pub fn into_future<T>(self) -> PromiseFuture<'js, T>
@@ -167,6 +237,8 @@ impl<'js> Promise<'js> {
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 #[derive(Debug)]
 pub struct PromiseFuture<'js, T> {
+    // The callbacks attached to the promise only hold a weak reference so the waker is released
+    // when the future is dropped.
     state: Option<Rc<RefCell<Waker>>>,
     promise: Promise<'js>,
     _marker: PhantomData<T>,
@@ -181,38 +253,41 @@ impl<'js, T> Future for PromiseFuture<'js, T>
 where
     T: FromJs<'js>,
 {
-    type Output = Result<T>;
+    type Output = CaughtResult<'js, T>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
+        let ctx = this.promise.ctx().clone();
 
         if let Some(x) = this.promise.result() {
-            return Poll::Ready(x);
+            return Poll::Ready(x.catch(&ctx));
         }
 
-        if this.state.is_none() {
-            let inner = Rc::new(RefCell::new(cx.waker().clone()));
-            this.state = Some(inner.clone());
+        if let Some(state) = this.state.as_ref() {
+            state.borrow_mut().clone_from(cx.waker());
+            return Poll::Pending;
+        }
 
-            let resolve = Function::new(this.promise.ctx.clone(), move || {
-                inner.borrow().wake_by_ref();
-            })?;
+        let inner = Rc::new(RefCell::new(cx.waker().clone()));
+        let weak = Rc::downgrade(&inner);
+        this.state = Some(inner);
 
+        let res = Function::new(ctx.clone(), move || {
+            if let Some(waker) = weak.upgrade() {
+                waker.borrow().wake_by_ref();
+            }
+        })
+        .and_then(|callback| {
             this.promise.then()?.call::<_, ()>((
                 This(this.promise.clone()),
-                resolve.clone(),
-                resolve,
-            ))?;
-            return Poll::Pending;
+                callback.clone(),
+                callback,
+            ))
+        });
+        match res.catch(&ctx) {
+            Ok(()) => Poll::Pending,
+            Err(e) => Poll::Ready(Err(e)),
         }
-
-        this.state
-            .as_ref()
-            .unwrap()
-            .borrow_mut()
-            .clone_from(cx.waker());
-
-        Poll::Pending
     }
 }
 
@@ -282,13 +357,13 @@ impl<'js> MaybePromise<'js> {
         self.0.ctx()
     }
 
-    /// Returns [`PromiseState::Resolved`] if the wrapped value isn't a promise, otherwise calls
-    /// [`Promise::state`] on the promise and returns it's value.
-    pub fn state(&self) -> PromiseState {
+    /// Returns [`PromiseState::Fulfilled`] with the wrapped value if it isn't a promise,
+    /// otherwise calls [`Promise::state`] on the promise and returns it's value.
+    pub fn state(&self) -> PromiseState<'js> {
         if let Some(x) = self.0.as_promise() {
             x.state()
         } else {
-            PromiseState::Resolved
+            PromiseState::Fulfilled(self.0.clone())
         }
     }
 
@@ -345,11 +420,13 @@ impl<'js, T> Future for MaybePromiseFuture<'js, T>
 where
     T: FromJs<'js>,
 {
-    type Output = Result<T>;
+    type Output = CaughtResult<'js, T>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
         match self.get_mut().0 {
-            MaybePromiseFutureInner::Ready(ref x) => Poll::Ready(T::from_js(x.ctx(), x.clone())),
+            MaybePromiseFutureInner::Ready(ref x) => {
+                Poll::Ready(T::from_js(x.ctx(), x.clone()).catch(x.ctx()))
+            }
             MaybePromiseFutureInner::Future(ref mut x) => Pin::new(x).poll(cx),
         }
     }
@@ -357,7 +434,10 @@ where
 
 #[cfg(test)]
 mod test {
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    };
     #[cfg(feature = "futures")]
     use std::time::Duration;
 
@@ -372,6 +452,67 @@ mod test {
         Runtime,
     };
 
+    #[test]
+    fn state() {
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        let rt = Runtime::new().unwrap();
+        let log = handled.clone();
+        rt.set_host_promise_rejection_tracker(Some(Box::new(move |_, _, _, is_handled| {
+            log.lock().unwrap().push(is_handled)
+        })));
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let (pending, ..) = Promise::new(&ctx).unwrap();
+            assert!(pending.state().is_pending());
+
+            let promise: Promise = ctx.eval("Promise.resolve(42)").unwrap();
+            assert!(
+                matches!(promise.state(), PromiseState::Fulfilled(v) if v.as_int() == Some(42))
+            );
+
+            let promise: Promise = ctx.eval("Promise.reject(7)").unwrap();
+            assert_eq!(*handled.lock().unwrap(), [false]);
+            assert!(matches!(promise.state(), PromiseState::Rejected(v) if v.as_int() == Some(7)));
+            // Reading the reason doesn't mark the rejection as handled, attaching a handler does.
+            assert_eq!(*handled.lock().unwrap(), [false]);
+            let ignore: Function = ctx.eval("() => {}").unwrap();
+            promise.catch_with(ignore).unwrap();
+            assert_eq!(*handled.lock().unwrap(), [false, true]);
+        })
+    }
+
+    #[test]
+    fn chaining() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let double: Function = ctx.eval("(x) => x * 2").unwrap();
+            let recover: Function = ctx.eval("(e) => e.message.length").unwrap();
+            let settled: Function = ctx.eval("() => { globalThis.settled = true; }").unwrap();
+
+            let promise: Promise = ctx.eval("Promise.resolve(21)").unwrap();
+            let chained = promise.then_with(Some(double.clone()), None).unwrap();
+            assert_eq!(chained.state(), PromiseState::Pending);
+            assert_eq!(chained.finish::<i32>().unwrap(), 42);
+
+            let promise: Promise = ctx.eval("Promise.reject(new Error('abc'))").unwrap();
+            let chained = promise
+                .then_with(Some(double), None)
+                .unwrap()
+                .catch_with(recover)
+                .unwrap()
+                .finally(settled)
+                .unwrap();
+            assert_eq!(chained.finish::<i32>().unwrap(), 3);
+            assert!(ctx.globals().get::<_, bool>("settled").unwrap());
+
+            let promise: Promise = ctx.eval("Promise.reject(7)").unwrap();
+            let chained = promise.then_with(None, None).unwrap();
+            assert!(chained.finish::<()>().is_err());
+            assert_eq!(ctx.catch().as_int(), Some(7));
+        })
+    }
+
     #[cfg(feature = "futures")]
     async fn set_timeout<'js>(cb: Function<'js>, number: f64) -> Result<()> {
         tokio::time::sleep(Duration::from_secs_f64(number / 1000.0)).await;
@@ -402,7 +543,7 @@ mod test {
                 .catch(&ctx)
                 .unwrap();
             let promise: Promise = func.call(()).unwrap();
-            assert_eq!(promise.into_future::<i32>().await.catch(&ctx).unwrap(), 42);
+            assert_eq!(promise.into_future::<i32>().await.unwrap(), 42);
 
             let func = ctx
                 .eval::<Function, _>(
@@ -419,7 +560,7 @@ mod test {
                 .catch(&ctx)
                 .unwrap();
             let promise: Promise = func.call(()).unwrap();
-            let err = promise.into_future::<()>().await.catch(&ctx);
+            let err = promise.into_future::<()>().await;
             match err {
                 Err(CaughtError::Value(v)) => {
                     assert_eq!(v.as_int().unwrap(), 42)
@@ -483,6 +624,77 @@ mod test {
         .await
     }
 
+    #[cfg(feature = "futures")]
+    #[tokio::test]
+    async fn promise_rejection() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            let promise: Promise = ctx.eval("Promise.reject({ code: 7 })").unwrap();
+            let err = promise.into_future::<()>().await.unwrap_err();
+            assert!(err.is_js_error());
+            let reason = err.value().unwrap().into_object().unwrap();
+            assert_eq!(reason.get::<_, i32>("code").unwrap(), 7);
+
+            let promise: Promise = ctx
+                .eval("Promise.resolve().then(() => { throw new RangeError('boom') })")
+                .unwrap();
+            let err = promise.into_future::<()>().await.unwrap_err();
+            assert!(err.is_exception());
+            assert_eq!(err.message().as_deref(), Some("boom"));
+            assert!(err.stack().is_some());
+
+            let promise: Promise = ctx.eval("Promise.resolve('text')").unwrap();
+            let err = promise.into_future::<i32>().await.unwrap_err();
+            assert!(!err.is_js_error());
+        })
+        .await
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn promise_future_drop() {
+        use std::{
+            future::Future,
+            pin::pin,
+            sync::Arc,
+            task::{Context as TaskContext, Poll, Wake, Waker},
+        };
+
+        struct Noop;
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let noop = Arc::new(Noop);
+            let waker = Waker::from(noop.clone());
+            let (promise, resolve, _) = Promise::new(&ctx).unwrap();
+            {
+                let mut future = pin!(promise.clone().into_future::<i32>());
+                let mut cx = TaskContext::from_waker(&waker);
+                assert!(future.as_mut().poll(&mut cx).is_pending());
+                assert!(future.as_mut().poll(&mut cx).is_pending());
+                assert_eq!(Arc::strong_count(&noop), 3);
+            }
+            // Dropping the future releases the registered waker right away.
+            assert_eq!(Arc::strong_count(&noop), 2);
+
+            // Settling the promise afterwards runs the callbacks which now do nothing.
+            resolve.call::<_, ()>((1,)).unwrap();
+            while ctx.execute_pending_job() {}
+            assert!(matches!(promise.state(), PromiseState::Fulfilled(v) if v.as_int() == Some(1)));
+
+            let mut future = pin!(promise.into_future::<i32>());
+            let mut cx = TaskContext::from_waker(&waker);
+            assert!(matches!(future.as_mut().poll(&mut cx), Poll::Ready(Ok(1))));
+        });
+        rt.run_gc();
+    }
+
     #[test]
     fn promise_then() {
         static DID_EXECUTE: AtomicBool = AtomicBool::new(false);
@@ -509,7 +721,7 @@ mod test {
                 .unwrap();
 
             resolve.call::<_, ()>(("FOO",)).unwrap();
-            assert_eq!(promise.state(), PromiseState::Resolved);
+            assert!(matches!(promise.state(), PromiseState::Fulfilled(v) if v.get::<String>().unwrap() == "FOO"));
 
             while ctx.execute_pending_job() {}
 
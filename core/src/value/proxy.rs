@@ -0,0 +1,399 @@
+use crate::{
+    qjs, value::Constructor, Ctx, Error, FromJs, Function, IntoJs, JsLifetime, Object, Result,
+    Value,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt, ops::Deref};
+
+type GetTrap<'js> =
+    Box<dyn Fn(Ctx<'js>, Object<'js>, Value<'js>, Value<'js>) -> Result<Value<'js>> + 'js>;
+type SetTrap<'js> =
+    Box<dyn Fn(Ctx<'js>, Object<'js>, Value<'js>, Value<'js>, Value<'js>) -> Result<bool> + 'js>;
+type KeyTrap<'js> = Box<dyn Fn(Ctx<'js>, Object<'js>, Value<'js>) -> Result<bool> + 'js>;
+type OwnKeysTrap<'js> = Box<dyn Fn(Ctx<'js>, Object<'js>) -> Result<Vec<Value<'js>>> + 'js>;
+
+/// The traps of a [`Proxy`] implemented in Rust.
+///
+/// Each trap is optional, operations without a trap are forwarded to the target as usual. A trap
+/// receives the target of the proxy as its first argument after the context and can throw by
+/// returning an error.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Object, Value, Proxy, ProxyHandler};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let handler = ProxyHandler::new().get(|ctx, target, key, receiver| {
+///     if target.contains_key(key.clone())? {
+///         target.get(key)
+///     } else {
+///         Ok(Value::new_int(ctx, 0))
+///     }
+/// });
+/// let proxy = Proxy::new(ctx.clone(), Object::new(ctx.clone()).unwrap(), handler).unwrap();
+/// ctx.globals().set("counts", proxy).unwrap();
+/// let count: i32 = ctx.eval("counts.a += 1; counts.a").unwrap();
+/// assert_eq!(count, 1);
+/// # });
+/// ```
+#[derive(Default)]
+pub struct ProxyHandler<'js> {
+    get: Option<GetTrap<'js>>,
+    set: Option<SetTrap<'js>>,
+    has: Option<KeyTrap<'js>>,
+    delete_property: Option<KeyTrap<'js>>,
+    own_keys: Option<OwnKeysTrap<'js>>,
+}
+
+impl<'js> fmt::Debug for ProxyHandler<'js> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ProxyHandler")
+            .field("get", &self.get.is_some())
+            .field("set", &self.set.is_some())
+            .field("has", &self.has.is_some())
+            .field("delete_property", &self.delete_property.is_some())
+            .field("own_keys", &self.own_keys.is_some())
+            .finish()
+    }
+}
+
+impl<'js> ProxyHandler<'js> {
+    /// Create a handler without any traps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the trap for reading a property, called with the target, the property key and the
+    /// receiver of the access.
+    pub fn get<F>(mut self, trap: F) -> Self
+    where
+        F: Fn(Ctx<'js>, Object<'js>, Value<'js>, Value<'js>) -> Result<Value<'js>> + 'js,
+    {
+        self.get = Some(Box::new(trap));
+        self
+    }
+
+    /// Set the trap for writing a property, called with the target, the property key, the new
+    /// value and the receiver of the assignment.
+    ///
+    /// Returning `false` makes the assignment throw a `TypeError` in strict mode code.
+    pub fn set<F>(mut self, trap: F) -> Self
+    where
+        F: Fn(Ctx<'js>, Object<'js>, Value<'js>, Value<'js>, Value<'js>) -> Result<bool> + 'js,
+    {
+        self.set = Some(Box::new(trap));
+        self
+    }
+
+    /// Set the trap for the `in` operator, called with the target and the property key.
+    pub fn has<F>(mut self, trap: F) -> Self
+    where
+        F: Fn(Ctx<'js>, Object<'js>, Value<'js>) -> Result<bool> + 'js,
+    {
+        self.has = Some(Box::new(trap));
+        self
+    }
+
+    /// Set the trap for the `delete` operator, called with the target and the property key.
+    pub fn delete_property<F>(mut self, trap: F) -> Self
+    where
+        F: Fn(Ctx<'js>, Object<'js>, Value<'js>) -> Result<bool> + 'js,
+    {
+        self.delete_property = Some(Box::new(trap));
+        self
+    }
+
+    /// Set the trap for listing the own property keys, called with the target.
+    ///
+    /// The returned keys must be strings or symbols.
+    pub fn own_keys<F>(mut self, trap: F) -> Self
+    where
+        F: Fn(Ctx<'js>, Object<'js>) -> Result<Vec<Value<'js>>> + 'js,
+    {
+        self.own_keys = Some(Box::new(trap));
+        self
+    }
+
+    /// Create the JavaScript handler object with a function for each trap.
+    fn into_object(self, ctx: &Ctx<'js>) -> Result<Object<'js>> {
+        let handler = Object::new(ctx.clone())?;
+        if let Some(trap) = self.get {
+            let func = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'js>, target: Object<'js>, key: Value<'js>, receiver: Value<'js>| {
+                    trap(ctx, target, key, receiver)
+                },
+            )?;
+            handler.set("get", func)?;
+        }
+        if let Some(trap) = self.set {
+            let func = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'js>,
+                      target: Object<'js>,
+                      key: Value<'js>,
+                      value: Value<'js>,
+                      receiver: Value<'js>| trap(ctx, target, key, value, receiver),
+            )?;
+            handler.set("set", func)?;
+        }
+        if let Some(trap) = self.has {
+            let func = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'js>, target: Object<'js>, key: Value<'js>| trap(ctx, target, key),
+            )?;
+            handler.set("has", func)?;
+        }
+        if let Some(trap) = self.delete_property {
+            let func = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'js>, target: Object<'js>, key: Value<'js>| trap(ctx, target, key),
+            )?;
+            handler.set("deleteProperty", func)?;
+        }
+        if let Some(trap) = self.own_keys {
+            let func = Function::new(ctx.clone(), move |ctx: Ctx<'js>, target: Object<'js>| {
+                trap(ctx, target)
+            })?;
+            handler.set("ownKeys", func)?;
+        }
+        Ok(handler)
+    }
+}
+
+/// Rust representation of a JavaScript `Proxy`.
+///
+/// Requires the `Proxy` intrinsic to be available in the context.
+#[derive(PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct Proxy<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for Proxy<'js> {
+    type Changed<'to> = Proxy<'to>;
+}
+
+impl<'js> fmt::Debug for Proxy<'js> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Proxy").field(&self.0).finish()
+    }
+}
+
+impl<'js> Proxy<'js> {
+    /// Create a new proxy for `target` whose operations are intercepted by the traps of
+    /// `handler`.
+    pub fn new(ctx: Ctx<'js>, target: Object<'js>, handler: ProxyHandler<'js>) -> Result<Self> {
+        let handler = handler.into_object(&ctx)?;
+        Self::from_handler(ctx, target, handler)
+    }
+
+    /// Create a new proxy for `target` with a JavaScript handler object.
+    pub fn from_handler(ctx: Ctx<'js>, target: Object<'js>, handler: Object<'js>) -> Result<Self> {
+        let constructor: Constructor = ctx.globals().get("Proxy")?;
+        constructor.construct((target, handler))
+    }
+
+    /// Returns the target of the proxy, `None` if the proxy was revoked.
+    pub fn target(&self) -> Result<Option<Object<'js>>> {
+        let ctx = self.0.ctx();
+        let value = unsafe {
+            let value = qjs::JS_GetProxyTarget(ctx.as_ptr(), self.0.as_js_value());
+            let value = ctx.handle_exception(value)?;
+            Value::from_js_value(ctx.clone(), value)
+        };
+        Ok(value.into_object())
+    }
+
+    /// Returns the handler object of the proxy, `None` if the proxy was revoked.
+    pub fn handler(&self) -> Result<Option<Object<'js>>> {
+        let ctx = self.0.ctx();
+        let value = unsafe {
+            let value = qjs::JS_GetProxyHandler(ctx.as_ptr(), self.0.as_js_value());
+            let value = ctx.handle_exception(value)?;
+            Value::from_js_value(ctx.clone(), value)
+        };
+        Ok(value.into_object())
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_proxy().then_some(Self(object))
+    }
+}
+
+impl<'js> Deref for Proxy<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for Proxy<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for Proxy<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for Proxy<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        Self::from_value(value).ok_or_else(|| Error::new_from_js(ty_name, "Proxy"))
+    }
+}
+
+impl<'js> IntoJs<'js> for Proxy<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is a proxy.
+    pub fn is_proxy(&self) -> bool {
+        unsafe { qjs::JS_IsProxy(self.as_js_value()) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::{cell::RefCell, rc::Rc, string::String as StdString, vec::Vec};
+
+    #[test]
+    fn rust_traps() {
+        test_with(|ctx| {
+            let store = Rc::new(RefCell::new(Vec::<(StdString, i32)>::new()));
+            let writes = Rc::new(RefCell::new(Vec::new()));
+
+            let handler = ProxyHandler::new()
+                .get({
+                    let store = store.clone();
+                    move |ctx, _target, key, receiver| {
+                        assert!(receiver.as_object().unwrap().is_proxy());
+                        let key = StdString::from_js(&ctx, key)?;
+                        let store = store.borrow();
+                        match store.iter().find(|(k, _)| *k == key) {
+                            Some((_, v)) => Ok(Value::new_int(ctx, *v)),
+                            None => Ok(Value::new_undefined(ctx)),
+                        }
+                    }
+                })
+                .set({
+                    let store = store.clone();
+                    let writes = writes.clone();
+                    move |ctx, _target, key, value, _receiver| {
+                        let key = StdString::from_js(&ctx, key)?;
+                        let Some(value) = value.as_int() else {
+                            return Err(Exception::throw_type(&ctx, "only integers can be stored"));
+                        };
+                        writes.borrow_mut().push(key.clone());
+                        let mut store = store.borrow_mut();
+                        store.retain(|(k, _)| *k != key);
+                        store.push((key, value));
+                        Ok(true)
+                    }
+                })
+                .has({
+                    let store = store.clone();
+                    move |ctx, _target, key| {
+                        let key = StdString::from_js(&ctx, key)?;
+                        Ok(store.borrow().iter().any(|(k, _)| *k == key))
+                    }
+                })
+                .delete_property({
+                    let store = store.clone();
+                    move |ctx, _target, key| {
+                        let key = StdString::from_js(&ctx, key)?;
+                        let mut store = store.borrow_mut();
+                        let len = store.len();
+                        store.retain(|(k, _)| *k != key);
+                        Ok(store.len() != len)
+                    }
+                })
+                .own_keys({
+                    let store = store.clone();
+                    move |ctx, _target| {
+                        store
+                            .borrow()
+                            .iter()
+                            .map(|(k, _)| k.as_str().into_js(&ctx))
+                            .collect()
+                    }
+                });
+
+            let target = Object::new(ctx.clone()).unwrap();
+            let proxy = Proxy::new(ctx.clone(), target.clone(), handler).unwrap();
+            assert_eq!(proxy.target().unwrap(), Some(target.clone()));
+            assert!(proxy.handler().unwrap().is_some());
+            ctx.globals().set("store", proxy).unwrap();
+
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    store.a = 1;
+                    store.b = 2;
+                    store.a += 10;
+                    delete store.b;
+                    [
+                        String(store.a),
+                        String("a" in store),
+                        String("b" in store),
+                        String(store.b),
+                        Reflect.ownKeys(store).join(","),
+                    ]
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, ["11", "true", "false", "undefined", "a"]);
+            assert_eq!(*writes.borrow(), ["a", "b", "a"]);
+            // The traps intercepted all writes to the target.
+            assert!(target.keys::<StdString>().next().is_none());
+
+            let err = ctx
+                .eval::<(), _>("store.c = 'text'")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("only integers can be stored"));
+
+            let proxy: Proxy = ctx.globals().get("store").unwrap();
+            assert!(proxy.is_proxy());
+            assert!(ctx.globals().get::<_, Proxy>("Object").is_err());
+        })
+    }
+}
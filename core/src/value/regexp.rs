@@ -0,0 +1,212 @@
+use crate::{
+    atom::PredefinedAtom, function::This, qjs, value::Constructor, Array, Ctx, Error, FromJs,
+    Function, IntoJs, JsLifetime, Object, Result, StdString, Value,
+};
+use core::{fmt, mem, ops::Deref};
+
+/// Rust representation of a JavaScript regular expression.
+///
+/// Requires the `RegExp` intrinsic to be available in the context.
+#[derive(PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct RegExp<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for RegExp<'js> {
+    type Changed<'to> = RegExp<'to>;
+}
+
+impl<'js> fmt::Debug for RegExp<'js> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("RegExp").field(&self.0).finish()
+    }
+}
+
+impl<'js> RegExp<'js> {
+    /// Create a new regular expression from a pattern and flags like `"gi"`.
+    ///
+    /// Returns an error if the pattern or the flags are invalid.
+    pub fn new(ctx: Ctx<'js>, pattern: &str, flags: &str) -> Result<Self> {
+        let constructor: Constructor = ctx.globals().get(PredefinedAtom::RegExp)?;
+        constructor.construct((pattern, flags))
+    }
+
+    /// Returns whether the regular expression matches the input.
+    ///
+    /// Like in JavaScript this advances `lastIndex` for global and sticky expressions.
+    pub fn test(&self, input: &str) -> Result<bool> {
+        let test: Function = self.0.get("test")?;
+        test.call((This(self.0.clone()), input))
+    }
+
+    /// Execute the regular expression on the input.
+    ///
+    /// Returns the match array, which contains the matched string followed by the captures and
+    /// has the `index`, `input` and `groups` properties, or `None` if there is no match.
+    pub fn exec(&self, input: &str) -> Result<Option<Array<'js>>> {
+        let exec: Function = self.0.get("exec")?;
+        exec.call((This(self.0.clone()), input))
+    }
+
+    /// Returns the source text of the pattern.
+    pub fn source(&self) -> Result<StdString> {
+        self.0.get("source")
+    }
+
+    /// Returns the flags of the regular expression.
+    pub fn flags(&self) -> Result<StdString> {
+        self.0.get("flags")
+    }
+
+    /// Returns the index at which the next match starts for global and sticky expressions.
+    pub fn last_index(&self) -> Result<usize> {
+        self.0.get("lastIndex")
+    }
+
+    /// Set the index at which the next match starts for global and sticky expressions.
+    pub fn set_last_index(&self, index: usize) -> Result<()> {
+        self.0.set("lastIndex", index)
+    }
+
+    /// Returns whether the `g` flag is set.
+    pub fn global(&self) -> Result<bool> {
+        self.0.get("global")
+    }
+
+    /// Returns whether the `i` flag is set.
+    pub fn ignore_case(&self) -> Result<bool> {
+        self.0.get("ignoreCase")
+    }
+
+    /// Returns whether the `m` flag is set.
+    pub fn multiline(&self) -> Result<bool> {
+        self.0.get("multiline")
+    }
+
+    /// Returns whether the `y` flag is set.
+    pub fn sticky(&self) -> Result<bool> {
+        self.0.get("sticky")
+    }
+
+    /// Returns whether the `u` flag is set.
+    pub fn unicode(&self) -> Result<bool> {
+        self.0.get("unicode")
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_regexp().then_some(Self(object))
+    }
+}
+
+impl<'js> Deref for RegExp<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for RegExp<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for RegExp<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for RegExp<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        Self::from_value(value).ok_or_else(|| Error::new_from_js(ty_name, "RegExp"))
+    }
+}
+
+impl<'js> IntoJs<'js> for RegExp<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is a regular expression.
+    pub fn is_regexp(&self) -> bool {
+        unsafe { qjs::JS_IsRegExp(self.0.as_js_value()) }
+    }
+
+    /// Turn the object into a regular expression if it is one.
+    pub fn as_regexp(&self) -> Option<&RegExp<'js>> {
+        // SAFETY: RegExp is a transparent wrapper around an object.
+        self.is_regexp()
+            .then(|| unsafe { mem::transmute::<&Object<'js>, &RegExp<'js>>(self) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn exec_and_flags() {
+        test_with(|ctx| {
+            let re = RegExp::new(ctx.clone(), r"(\w+)@(?<host>\w+)", "gi").unwrap();
+            assert_eq!(re.source().unwrap(), r"(\w+)@(?<host>\w+)");
+            assert_eq!(re.flags().unwrap(), "gi");
+            assert!(re.global().unwrap() && re.ignore_case().unwrap());
+            assert!(!re.multiline().unwrap() && !re.sticky().unwrap() && !re.unicode().unwrap());
+
+            let m = re.exec("mail a@b and c@d").unwrap().unwrap();
+            assert_eq!(m.get::<StdString>(0).unwrap(), "a@b");
+            assert_eq!(m.get::<StdString>(1).unwrap(), "a");
+            assert_eq!(m.as_object().get::<_, usize>("index").unwrap(), 5);
+            let groups: Object = m.as_object().get("groups").unwrap();
+            assert_eq!(groups.get::<_, StdString>("host").unwrap(), "b");
+            assert_eq!(re.last_index().unwrap(), 8);
+
+            assert!(re.test("mail a@b and c@d").unwrap());
+            assert!(re.exec("mail a@b and c@d").unwrap().is_none());
+            re.set_last_index(0).unwrap();
+            assert!(re.test("x@y").unwrap());
+
+            assert!(RegExp::new(ctx.clone(), "(", "").is_err());
+            assert!(RegExp::new(ctx.clone(), "a", "q").is_err());
+
+            let re: RegExp = ctx.eval("/a.c/s").unwrap();
+            assert!(re.test("a\nc").unwrap());
+            assert!(ctx.eval::<RegExp, _>("({ source: 'a' })").is_err());
+            assert!(Object::new(ctx).unwrap().as_regexp().is_none());
+        })
+    }
+}
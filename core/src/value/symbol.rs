@@ -1,4 +1,5 @@
-use crate::{qjs, Atom, Ctx, Result, Value};
+use crate::{qjs, Atom, Ctx, Result, StdString, Value};
+use alloc::ffi::CString;
 
 /// Rust representation of a JavaScript symbol.
 #[derive(Debug, Clone, PartialEq, Hash)]
@@ -6,6 +7,47 @@ use crate::{qjs, Atom, Ctx, Result, Value};
 pub struct Symbol<'js>(pub(crate) Value<'js>);
 
 impl<'js> Symbol<'js> {
+    /// Create a new unique symbol with the given description, like `Symbol(description)`.
+    pub fn new(ctx: Ctx<'js>, description: &str) -> Result<Self> {
+        Self::new_raw(ctx, description, false)
+    }
+
+    /// Get the symbol registered for the key in the global symbol registry, creating it if it
+    /// doesn't exist yet, like `Symbol.for(key)`.
+    ///
+    /// Unlike symbols created with [`Symbol::new`], a symbol for a key is shared with all scripts
+    /// and contexts of the runtime.
+    pub fn for_key(ctx: Ctx<'js>, key: &str) -> Result<Self> {
+        Self::new_raw(ctx, key, true)
+    }
+
+    fn new_raw(ctx: Ctx<'js>, description: &str, is_global: bool) -> Result<Self> {
+        let description = CString::new(description)?;
+        unsafe {
+            let val = qjs::JS_NewSymbol(ctx.as_ptr(), description.as_ptr(), is_global);
+            let val = ctx.handle_exception(val)?;
+            Ok(Symbol(Value::from_js_value(ctx, val)))
+        }
+    }
+
+    /// Returns the key of the symbol in the global symbol registry, like `Symbol.keyFor(symbol)`.
+    ///
+    /// Returns `None` for symbols which aren't registered, like unique and well-known symbols.
+    pub fn key_for(&self) -> Result<Option<StdString>> {
+        let ctx = self.0.ctx();
+        let key = unsafe {
+            let val = qjs::rquickjs_symbol_key_for(ctx.as_ptr(), self.0.as_js_value());
+            let val = ctx.handle_exception(val)?;
+            Value::from_js_value(ctx.clone(), val)
+        };
+        key.get()
+    }
+
+    /// Returns if the symbol is registered in the global symbol registry.
+    pub fn is_registered(&self) -> Result<bool> {
+        self.key_for().map(|x| x.is_some())
+    }
+
     /// Get the symbol description
     pub fn description(&self) -> Result<Value<'js>> {
         let atom = Atom::from_str(self.0.ctx.clone(), "description")?;
@@ -89,4 +131,43 @@ mod test {
             assert!(s.description().unwrap().is_undefined());
         });
     }
+
+    #[test]
+    fn registry() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let bar = ctx.with(|ctx| {
+            let registered = Symbol::for_key(ctx.clone(), "foo").unwrap();
+            let script: Symbol = ctx.eval("Symbol.for('foo')").unwrap();
+            assert_eq!(registered, script);
+            assert_eq!(registered.key_for().unwrap().as_deref(), Some("foo"));
+            assert!(registered.is_registered().unwrap());
+
+            let unique = Symbol::new(ctx.clone(), "foo").unwrap();
+            assert_ne!(unique, registered);
+            assert_eq!(unique.key_for().unwrap(), None);
+            assert!(!Symbol::iterator(ctx.clone()).is_registered().unwrap());
+
+            // Scripts replacing `Symbol.keyFor` don't change the result.
+            ctx.eval::<(), _>("Symbol.keyFor = () => 'bar'").unwrap();
+            assert_eq!(registered.key_for().unwrap().as_deref(), Some("foo"));
+            assert_eq!(unique.key_for().unwrap(), None);
+
+            let obj: Object = ctx.eval("({ [Symbol.for('foo')]: 42 })").unwrap();
+            assert_eq!(obj.get::<_, i32>(registered).unwrap(), 42);
+            obj.set(Symbol::for_key(ctx.clone(), "bar").unwrap(), 1)
+                .unwrap();
+            ctx.globals().set("obj", obj).unwrap();
+            assert_eq!(ctx.eval::<i32, _>("obj[Symbol.for('bar')]").unwrap(), 1);
+            Persistent::save(&ctx, Symbol::for_key(ctx.clone(), "bar").unwrap())
+        });
+
+        // The registry is shared between the contexts of a runtime.
+        let other = Context::full(&rt).unwrap();
+        other.with(|ctx| {
+            let bar = bar.restore(&ctx).unwrap();
+            let script: Symbol = ctx.eval("Symbol.for('bar')").unwrap();
+            assert_eq!(bar, script);
+        });
+    }
 }
@@ -192,6 +192,34 @@ impl<'js, T> TypedArray<'js, T> {
         ArrayBuffer::from_js(&ctx, buf)
     }
 
+    /// Get the underlying ArrayBuffer together with the byte offset and byte length of the view
+    /// into it.
+    ///
+    /// Returns an error if the buffer is detached or the view is out of bounds.
+    pub fn buffer(&self) -> Result<(ArrayBuffer<'js>, usize, usize)> {
+        let ctx = self.ctx().clone();
+        let mut off = MaybeUninit::<qjs::size_t>::uninit();
+        let mut len = MaybeUninit::<qjs::size_t>::uninit();
+        let buf = unsafe {
+            let val = qjs::JS_GetTypedArrayBuffer(
+                ctx.as_ptr(),
+                self.0.as_js_value(),
+                off.as_mut_ptr(),
+                len.as_mut_ptr(),
+                null_mut(),
+            );
+            let val = ctx.handle_exception(val)?;
+            Value::from_js_value(ctx.clone(), val)
+        };
+        let off: usize = unsafe { off.assume_init() }
+            .try_into()
+            .expect(qjs::SIZE_T_ERROR);
+        let len: usize = unsafe { len.assume_init() }
+            .try_into()
+            .expect(qjs::SIZE_T_ERROR);
+        Ok((ArrayBuffer::from_js(&ctx, buf)?, off, len))
+    }
+
     /// Convert from an ArrayBuffer
     pub fn from_arraybuffer(arraybuffer: ArrayBuffer<'js>) -> Result<Self>
     where
@@ -464,4 +492,27 @@ mod test {
             assert!(!obj.is_typed_array::<u8>());
         });
     }
+
+    #[test]
+    fn buffer_of_subarray() {
+        test_with(|ctx| {
+            let val: TypedArray<u16> = ctx
+                .eval(
+                    r#"
+                        globalThis.arr = new Uint16Array([1, 2, 3, 4, 5]);
+                        arr.subarray(1, 3)
+                    "#,
+                )
+                .unwrap();
+            let (buffer, offset, len) = val.buffer().unwrap();
+            assert_eq!((offset, len), (2, 4));
+            assert_eq!(buffer.len(), 10);
+            let bytes = &buffer.as_bytes().unwrap()[offset..offset + len];
+            assert_eq!(bytes, val.as_bytes().unwrap());
+
+            let _: Value = ctx.eval("arr.buffer.transfer()").unwrap();
+            assert!(val.buffer().is_err());
+            ctx.catch();
+        });
+    }
 }
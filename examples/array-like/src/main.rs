@@ -0,0 +1,151 @@
+//! Expose a Rust `Vec` to JavaScript as an array-like object, without copying its elements, by
+//! using the exotic property hooks of [`JsClass`].
+
+use rquickjs::{
+    class::{JsCell, JsClass, Trace, Tracer, Writable},
+    Array, Atom, CatchResultExt, Class, Context, Ctx, Exception, FromJs, IntoJs, JsLifetime,
+    Object, Result, Runtime, Value,
+};
+
+/// A vector which can be indexed from JavaScript like an array.
+///
+/// Reading an index converts the element on access and assigning an index, or `length` to
+/// truncate, modifies the vector in place. The prototype inherits from `Array.prototype` so the
+/// generic array methods like `map` or `join` work on it.
+pub struct RustVecClass<T>(pub Vec<T>);
+
+impl<'js, T> Trace<'js> for RustVecClass<T> {
+    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+}
+
+unsafe impl<'js, T: 'static> JsLifetime<'js> for RustVecClass<T> {
+    type Changed<'to> = RustVecClass<T>;
+}
+
+/// Returns the index if the property name is a canonical array index.
+fn parse_index(name: &str) -> Option<usize> {
+    let index: usize = name.parse().ok()?;
+    (index.to_string() == name).then_some(index)
+}
+
+impl<'js, T> JsClass<'js> for RustVecClass<T>
+where
+    T: IntoJs<'js> + FromJs<'js> + Clone + 'static,
+{
+    const NAME: &'static str = "RustVec";
+
+    const EXOTIC: bool = true;
+
+    type Mutable = Writable;
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        let proto = Object::new(ctx.clone())?;
+        let array_proto = Array::new(ctx.clone())?.as_object().get_prototype();
+        proto.set_prototype(array_proto.as_ref())?;
+        Ok(Some(proto))
+    }
+
+    fn constructor(_ctx: &Ctx<'js>) -> Result<Option<rquickjs::function::Constructor<'js>>> {
+        Ok(None)
+    }
+
+    fn get_own_property(
+        this: &JsCell<'js, Self>,
+        ctx: &Ctx<'js>,
+        atom: Atom<'js>,
+    ) -> Result<Option<Value<'js>>> {
+        let name = atom.to_string()?;
+        let this = this.borrow();
+        if name == "length" {
+            return this.0.len().into_js(ctx).map(Some);
+        }
+        let Some(index) = parse_index(&name) else {
+            return Ok(None);
+        };
+        this.0
+            .get(index)
+            .cloned()
+            .map(|x| x.into_js(ctx))
+            .transpose()
+    }
+
+    fn get_own_property_names(this: &JsCell<'js, Self>, ctx: &Ctx<'js>) -> Result<Vec<Atom<'js>>> {
+        (0..this.borrow().0.len())
+            .map(|index| Atom::from_u32(ctx.clone(), index as u32))
+            .collect()
+    }
+
+    fn delete_property(this: &JsCell<'js, Self>, ctx: &Ctx<'js>, atom: Atom<'js>) -> Result<bool> {
+        // Removing an element would leave a hole.
+        Self::has_property(this, ctx, atom).map(|x| !x)
+    }
+
+    fn define_own_property(
+        this: &JsCell<'js, Self>,
+        ctx: &Ctx<'js>,
+        atom: Atom<'js>,
+        value: Value<'js>,
+    ) -> Result<bool> {
+        let name = atom.to_string()?;
+        let mut this = this.borrow_mut();
+        if name == "length" {
+            let len: usize = value.get()?;
+            if len > this.0.len() {
+                return Err(Exception::throw_range(
+                    ctx,
+                    "a RustVec can't grow by length",
+                ));
+            }
+            this.0.truncate(len);
+            return Ok(true);
+        }
+        let Some(index) = parse_index(&name) else {
+            return Ok(false);
+        };
+        let value = T::from_js(ctx, value)?;
+        match index.cmp(&this.0.len()) {
+            std::cmp::Ordering::Less => this.0[index] = value,
+            std::cmp::Ordering::Equal => this.0.push(value),
+            std::cmp::Ordering::Greater => {
+                return Err(Exception::throw_range(ctx, "a RustVec can't have holes"));
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn main() -> Result<()> {
+    let rt = Runtime::new()?;
+    let ctx = Context::full(&rt)?;
+
+    ctx.with(|ctx| -> Result<()> {
+        let numbers = Class::instance(ctx.clone(), RustVecClass(vec![1.5f64, 2.0, 4.0]))?;
+        ctx.globals().set("numbers", numbers.clone())?;
+
+        let res: String = ctx
+            .eval(
+                r#"
+                numbers[1] = 3;
+                numbers.push(8);
+                const doubled = numbers.map((x) => x * 2);
+                `${numbers.length} [${numbers.join(", ")}] [${doubled}] ${Object.keys(numbers)}`
+                "#,
+            )
+            .catch(&ctx)
+            .unwrap();
+        println!("{res}");
+        assert_eq!(res, "4 [1.5, 3, 4, 8] [3,6,8,16] 0,1,2,3");
+
+        // The changes were made to the Rust vector.
+        assert_eq!(numbers.borrow().0, [1.5, 3.0, 4.0, 8.0]);
+
+        let err = ctx
+            .eval::<(), _>("numbers[10] = 1")
+            .catch(&ctx)
+            .unwrap_err();
+        println!("{err}");
+        Ok(())
+    })?;
+
+    Ok(())
+}
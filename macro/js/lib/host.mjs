@@ -0,0 +1,3 @@
+import { version } from "host";
+
+export const hostVersion = version;
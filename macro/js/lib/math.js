@@ -0,0 +1,9 @@
+import { name } from "../main.js";
+
+export function add(a, b) {
+  return a + b;
+}
+
+export function importer() {
+  return name;
+}
@@ -0,0 +1,7 @@
+import { add } from "./lib/math.js";
+
+export const name = "main";
+
+export function sum(a, b) {
+  return add(a, b);
+}
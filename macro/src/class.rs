@@ -1,8 +1,9 @@
 use proc_macro2::{Ident, TokenStream};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     fold::Fold,
     parse::{Parse, ParseStream},
+    parse_quote,
     punctuated::{Pair, Punctuated},
     spanned::Spanned,
     Error, ItemEnum, ItemStruct, LitStr, Result, Token,
@@ -20,13 +21,20 @@ pub(crate) struct ClassConfig {
     pub crate_: Option<String>,
     pub rename: Option<String>,
     pub rename_all: Option<Case>,
+    pub allow_non_static: bool,
+    pub callable: bool,
+    pub constructible: bool,
 }
 
 pub(crate) enum ClassOption {
     Frozen(FlagOption<kw::frozen>),
     Crate(ValueOption<Token![crate], LitStr>),
     Rename(ValueOption<kw::rename, LitStr>),
+    Name(ValueOption<kw::name, LitStr>),
     RenameAll(ValueOption<kw::rename_all, Case>),
+    AllowNonStatic(FlagOption<kw::allow_non_static>),
+    Callable(FlagOption<kw::callable>),
+    Constructible(FlagOption<kw::constructible>),
 }
 
 impl Parse for ClassOption {
@@ -37,8 +45,16 @@ impl Parse for ClassOption {
             input.parse().map(Self::Crate)
         } else if input.peek(kw::rename) {
             input.parse().map(Self::Rename)
+        } else if input.peek(kw::name) {
+            input.parse().map(Self::Name)
         } else if input.peek(kw::rename_all) {
             input.parse().map(Self::RenameAll)
+        } else if input.peek(kw::allow_non_static) {
+            input.parse().map(Self::AllowNonStatic)
+        } else if input.peek(kw::callable) {
+            input.parse().map(Self::Callable)
+        } else if input.peek(kw::constructible) {
+            input.parse().map(Self::Constructible)
         } else {
             Err(syn::Error::new(input.span(), "invalid class attribute"))
         }
@@ -57,9 +73,21 @@ impl ClassConfig {
             ClassOption::Rename(ref x) => {
                 self.rename = Some(x.value.value());
             }
+            ClassOption::Name(ref x) => {
+                self.rename = Some(x.value.value());
+            }
             ClassOption::RenameAll(ref x) => {
                 self.rename_all = Some(x.value);
             }
+            ClassOption::AllowNonStatic(ref x) => {
+                self.allow_non_static = x.is_true();
+            }
+            ClassOption::Callable(ref x) => {
+                self.callable = x.is_true();
+            }
+            ClassOption::Constructible(ref x) => {
+                self.constructible = x.is_true();
+            }
         }
     }
 
@@ -334,6 +362,41 @@ impl Class {
     }
 
     pub fn expand(self) -> Result<TokenStream> {
+        let reexpand = self.reexpand();
+        let impls = self.expand_impls(None)?;
+        Ok(quote! {
+            #reexpand
+            #impls
+        })
+    }
+
+    /// Returns the bounds of the derived implementations.
+    ///
+    /// Apart from the `'js` lifetime the type must be `'static`, unless allowed otherwise.
+    fn static_bounds(&self) -> Result<Option<syn::WhereClause>> {
+        let mut generics = self.generics().clone();
+        if self.config().allow_non_static {
+            return Ok(generics.where_clause);
+        }
+        if let Some(lt) = generics.lifetimes().find(|lt| lt.lifetime.ident != "js") {
+            return Err(Error::new(
+                lt.span(),
+                "JsClass can only be derived for types which are 'static apart from the 'js \
+                 lifetime, add `#[qjs(allow_non_static)]` to allow other lifetimes",
+            ));
+        }
+        let params = generics
+            .type_params()
+            .map(|x| x.ident.clone())
+            .collect::<Vec<_>>();
+        let where_clause = generics.make_where_clause();
+        for param in params {
+            where_clause.predicates.push(parse_quote!(#param: 'static));
+        }
+        Ok(Some(where_clause.clone()))
+    }
+
+    pub fn expand_impls(self, where_clause: Option<syn::WhereClause>) -> Result<TokenStream> {
         let crate_name = format_ident!("{}", self.config().crate_name()?);
         let class_name = self.ident().clone();
         let javascript_name = self.javascript_name();
@@ -341,21 +404,30 @@ impl Class {
 
         let generics = self.generics().clone();
         let generics_with_lifetimes = add_js_lifetime(&generics);
+        let (_, generics, _) = generics.split_for_impl();
 
         let mutability = self.mutability();
+        // Instances which can be called with `new` must also be callable as functions.
+        let callable = self.config().callable || self.config().constructible;
+        let constructible = self.config().constructible;
         let props = self.expand_props(&crate_name);
-        let reexpand = self.reexpand();
+        let predicates = where_clause
+            .as_ref()
+            .map(|x| x.predicates.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
 
         let res = quote! {
-            #reexpand
-
             #[allow(non_snake_case)]
             mod #module_name{
                 pub use super::*;
 
-                impl #generics_with_lifetimes #crate_name::class::JsClass<'js> for #class_name #generics{
+                impl #generics_with_lifetimes #crate_name::class::JsClass<'js> for #class_name #generics #where_clause {
                     const NAME: &'static str = #javascript_name;
 
+                    const CALLABLE: bool = #callable;
+
+                    const CONSTRUCTIBLE: bool = #constructible;
+
                     type Mutable = #crate_name::class::#mutability;
 
                     fn prototype(ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<Option<#crate_name::Object<'js>>>{
@@ -374,9 +446,16 @@ impl Class {
                         let implementor = #crate_name::class::impl_::ConstructorCreate::<Self>::new();
                         (&implementor).create_constructor(ctx)
                     }
+
+                    fn call<'a>(this: &#crate_name::class::JsCell<'js, Self>, params: #crate_name::function::Params<'a, 'js>) -> #crate_name::Result<#crate_name::Value<'js>>{
+                        use #crate_name::class::impl_::CallImplementor;
+
+                        let implementor = #crate_name::class::impl_::CallImpl::<Self>::new();
+                        (&implementor).call(this, params)
+                    }
                 }
 
-                impl #generics_with_lifetimes #crate_name::IntoJs<'js> for #class_name #generics{
+                impl #generics_with_lifetimes #crate_name::IntoJs<'js> for #class_name #generics #where_clause {
                     fn into_js(self,ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<#crate_name::Value<'js>>{
                         let cls = #crate_name::class::Class::<Self>::instance(ctx.clone(),self)?;
                         #crate_name::IntoJs::into_js(cls, ctx)
@@ -385,7 +464,8 @@ impl Class {
 
                 impl #generics_with_lifetimes #crate_name::FromJs<'js> for #class_name #generics
                 where
-                    for<'a> #crate_name::class::impl_::CloneWrapper<'a,Self>: #crate_name::class::impl_::CloneTrait<Self>,
+                    for<'__clone> #crate_name::class::impl_::CloneWrapper<'__clone,Self>: #crate_name::class::impl_::CloneTrait<Self>,
+                    #(#predicates,)*
                 {
                     fn from_js(ctx: &#crate_name::Ctx<'js>, value: #crate_name::Value<'js>) -> #crate_name::Result<Self>{
                         use #crate_name::class::impl_::CloneTrait;
@@ -405,3 +485,10 @@ impl Class {
 pub(crate) fn expand(options: OptionList<ClassOption>, item: syn::Item) -> Result<TokenStream> {
     Class::from_proc_macro_input(options, item)?.expand()
 }
+
+pub(crate) fn derive(input: syn::DeriveInput) -> Result<TokenStream> {
+    let item: syn::Item = syn::parse2(input.into_token_stream())?;
+    let class = Class::from_proc_macro_input(OptionList(Vec::new()), item)?;
+    let where_clause = class.static_bounds()?;
+    class.expand_impls(where_clause)
+}
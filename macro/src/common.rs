@@ -125,6 +125,11 @@ impl<'a> Fold for SelfReplacer<'a> {
 
 pub(crate) mod kw {
     syn::custom_keyword!(frozen);
+    syn::custom_keyword!(name);
+    syn::custom_keyword!(allow_non_static);
+    syn::custom_keyword!(callable);
+    syn::custom_keyword!(constructible);
+    syn::custom_keyword!(call);
     syn::custom_keyword!(skip_trace);
     syn::custom_keyword!(rename);
     syn::custom_keyword!(rename_all);
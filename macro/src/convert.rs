@@ -0,0 +1,349 @@
+use convert_case::Casing;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Data, DataEnum, DeriveInput, Error, Ident, LitStr, Result, Token, Type,
+};
+
+use crate::{
+    attrs::{take_attributes, OptionList, ValueOption},
+    class::ClassOption,
+    common::{add_js_lifetime, crate_ident, kw, Case},
+    fields::{FieldConfig, Fields},
+};
+
+/// The key of the object property holding the variant name of data carrying enums.
+const TAG: &str = "type";
+/// The key of the object property holding the fields of tuple variants.
+const VALUE: &str = "value";
+
+#[derive(Default)]
+pub(crate) struct ConvertConfig {
+    pub(crate) crate_: Option<String>,
+    pub(crate) rename_all: Option<Case>,
+}
+
+impl ConvertConfig {
+    pub fn apply(&mut self, option: &ConvertOption) {
+        match option {
+            ConvertOption::Crate(x) => {
+                self.crate_ = Some(x.value.value());
+            }
+            ConvertOption::RenameAll(x) => {
+                self.rename_all = Some(x.value);
+            }
+            // Options of other derives, which share the `qjs` attribute.
+            ConvertOption::Class => {}
+        }
+    }
+
+    pub fn crate_name(&self) -> Result<Ident> {
+        if let Some(x) = self.crate_.as_ref() {
+            Ok(format_ident!("{x}"))
+        } else {
+            Ok(format_ident!("{}", crate_ident()?))
+        }
+    }
+}
+
+pub(crate) enum ConvertOption {
+    Crate(ValueOption<Token![crate], LitStr>),
+    RenameAll(ValueOption<kw::rename_all, Case>),
+    Class,
+}
+
+impl Parse for ConvertOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![crate]) {
+            input.parse().map(Self::Crate)
+        } else if input.peek(kw::rename_all) {
+            input.parse().map(Self::RenameAll)
+        } else {
+            input
+                .parse::<ClassOption>()
+                .map(|_| Self::Class)
+                .map_err(|e| syn::Error::new(e.span(), "invalid conversion attribute"))
+        }
+    }
+}
+
+enum VariantFields {
+    Unit,
+    Named(Vec<(Ident, String)>),
+    Unnamed(Vec<Type>),
+}
+
+struct Variant {
+    ident: Ident,
+    name: String,
+    fields: VariantFields,
+}
+
+struct Enum {
+    config: ConvertConfig,
+    ident: Ident,
+    variants: Vec<Variant>,
+}
+
+impl Enum {
+    fn parse(input: DeriveInput, derive: &str) -> Result<Self> {
+        let DeriveInput {
+            ident,
+            data,
+            mut attrs,
+            ..
+        } = input;
+
+        let mut config = ConvertConfig::default();
+        take_attributes(&mut attrs, |attr| {
+            if !attr.path().is_ident("qjs") {
+                return Ok(false);
+            }
+
+            let options: OptionList<ConvertOption> = attr.parse_args()?;
+            options.0.iter().for_each(|x| config.apply(x));
+            Ok(true)
+        })?;
+
+        let variants = match data {
+            Data::Enum(DataEnum { variants, .. }) => variants,
+            Data::Struct(x) => {
+                return Err(Error::new(
+                    x.struct_token.span(),
+                    format_args!("{derive} can only be derived for enums"),
+                ))
+            }
+            Data::Union(x) => {
+                return Err(Error::new(
+                    x.union_token.span(),
+                    format_args!("{derive} can only be derived for enums"),
+                ))
+            }
+        };
+
+        let mut res = Vec::new();
+        for mut variant in variants {
+            let variant_config = FieldConfig::from_attributes(&mut variant.attrs)?;
+            let name = if let Some(rename) = variant_config.rename {
+                rename
+            } else if let Some(case) = config.rename_all {
+                variant.ident.to_string().to_case(case.to_convert_case())
+            } else {
+                variant.ident.to_string()
+            };
+
+            let fields = match Fields::from_fields(variant.fields)? {
+                Fields::Unit => VariantFields::Unit,
+                Fields::Unnamed(x) if x.is_empty() => VariantFields::Unit,
+                Fields::Unnamed(x) => VariantFields::Unnamed(x.into_iter().map(|x| x.ty).collect()),
+                Fields::Named(x) => {
+                    let mut fields = Vec::new();
+                    for x in x {
+                        let ident = x.ident.unwrap();
+                        let name = x.config.rename.unwrap_or_else(|| ident.to_string());
+                        if name == TAG {
+                            return Err(Error::new(
+                                ident.span(),
+                                format_args!(
+                                    "field name `{TAG}` is reserved for the variant name, rename the field with `#[qjs(rename = \"..\")]`"
+                                ),
+                            ));
+                        }
+                        fields.push((ident, name));
+                    }
+                    VariantFields::Named(fields)
+                }
+            };
+
+            res.push(Variant {
+                ident: variant.ident,
+                name,
+                fields,
+            });
+        }
+
+        Ok(Enum {
+            config,
+            ident,
+            variants: res,
+        })
+    }
+
+    /// Returns whether all variants are fieldless and the enum can be represented as a string.
+    fn is_unit(&self) -> bool {
+        self.variants
+            .iter()
+            .all(|x| matches!(x.fields, VariantFields::Unit))
+    }
+
+    fn expand_unknown_variant(&self, crate_name: &Ident) -> TokenStream {
+        let ident = self.ident.to_string();
+        let names = self.variants.iter().map(|x| &x.name);
+        quote! {
+            Err(#crate_name::Error::new_unknown_variant(#ident, __name, &[#(#names),*]))
+        }
+    }
+}
+
+pub(crate) fn expand_from_js(input: DeriveInput) -> Result<TokenStream> {
+    let generics = input.generics.clone();
+    let enum_ = Enum::parse(input, "FromJs")?;
+
+    let lifetime_generics = add_js_lifetime(&generics);
+    let crate_name = enum_.config.crate_name()?;
+    let ident = &enum_.ident;
+    let type_name = ident.to_string();
+    let unknown = enum_.expand_unknown_variant(&crate_name);
+
+    let body = if enum_.is_unit() {
+        let arms = enum_.variants.iter().map(|x| {
+            let variant = &x.ident;
+            let name = &x.name;
+            quote!(#name => Ok(Self::#variant),)
+        });
+
+        quote! {
+            let __name = value
+                .as_string()
+                .ok_or_else(|| #crate_name::Error::new_from_js(value.type_name(), #type_name))?
+                .to_string()?;
+            let __name = __name.as_str();
+            match __name {
+                #(#arms)*
+                _ => #unknown,
+            }
+        }
+    } else {
+        let arms = enum_.variants.iter().map(|x| {
+            let variant = &x.ident;
+            let name = &x.name;
+            let construct = match x.fields {
+                VariantFields::Unit => quote!(Self::#variant),
+                VariantFields::Named(ref fields) => {
+                    let fields = fields.iter().map(|(ident, name)| {
+                        quote!(#ident: __object.get(#name)?)
+                    });
+                    quote!(Self::#variant{ #(#fields),* })
+                }
+                VariantFields::Unnamed(ref types) if types.len() == 1 => {
+                    quote!(Self::#variant(__object.get(#VALUE)?))
+                }
+                VariantFields::Unnamed(ref types) => {
+                    let bindings = (0..types.len())
+                        .map(|idx| format_ident!("__field{idx}"))
+                        .collect::<Vec<_>>();
+                    quote!({
+                        let #crate_name::convert::List((#(#bindings,)*)): #crate_name::convert::List<(#(#types,)*)> =
+                            __object.get(#VALUE)?;
+                        Self::#variant(#(#bindings),*)
+                    })
+                }
+            };
+            quote!(#name => Ok(#construct),)
+        });
+
+        quote! {
+            let __type = value.type_name();
+            let __object = value
+                .into_object()
+                .ok_or_else(|| #crate_name::Error::new_from_js(__type, #type_name))?;
+            let __name: #crate_name::String = __object.get(#TAG)?;
+            let __name = __name.to_string()?;
+            let __name = __name.as_str();
+            match __name {
+                #(#arms)*
+                _ => #unknown,
+            }
+        }
+    };
+
+    Ok(quote! {
+        impl #lifetime_generics #crate_name::FromJs<'js> for #ident #generics {
+            fn from_js(_ctx: &#crate_name::Ctx<'js>, value: #crate_name::Value<'js>) -> #crate_name::Result<Self> {
+                #body
+            }
+        }
+    })
+}
+
+pub(crate) fn expand_into_js(input: DeriveInput) -> Result<TokenStream> {
+    let generics = input.generics.clone();
+    let enum_ = Enum::parse(input, "IntoJs")?;
+
+    let lifetime_generics = add_js_lifetime(&generics);
+    let crate_name = enum_.config.crate_name()?;
+    let ident = &enum_.ident;
+
+    let body = if enum_.is_unit() {
+        let arms = enum_.variants.iter().map(|x| {
+            let variant = &x.ident;
+            let name = &x.name;
+            quote!(Self::#variant => #name,)
+        });
+
+        quote! {
+            let __name = match self {
+                #(#arms)*
+            };
+            #crate_name::String::from_str(ctx.clone(), __name).map(|x| x.into_value())
+        }
+    } else {
+        let arms = enum_.variants.iter().map(|x| {
+            let variant = &x.ident;
+            let name = &x.name;
+            match x.fields {
+                VariantFields::Unit => quote! {
+                    Self::#variant => {
+                        __object.set(#TAG, #name)?;
+                    }
+                },
+                VariantFields::Named(ref fields) => {
+                    let idents = fields.iter().map(|x| &x.0);
+                    let sets = fields
+                        .iter()
+                        .map(|(ident, name)| quote!(__object.set(#name, #ident)?;));
+                    quote! {
+                        Self::#variant{ #(#idents),* } => {
+                            __object.set(#TAG, #name)?;
+                            #(#sets)*
+                        }
+                    }
+                }
+                VariantFields::Unnamed(ref types) => {
+                    let bindings = (0..types.len())
+                        .map(|idx| format_ident!("__field{idx}"))
+                        .collect::<Vec<_>>();
+                    let value = if bindings.len() == 1 {
+                        quote!(#(#bindings)*)
+                    } else {
+                        quote!(#crate_name::convert::List((#(#bindings,)*)))
+                    };
+                    quote! {
+                        Self::#variant(#(#bindings),*) => {
+                            __object.set(#TAG, #name)?;
+                            __object.set(#VALUE, #value)?;
+                        }
+                    }
+                }
+            }
+        });
+
+        quote! {
+            let __object = #crate_name::Object::new(ctx.clone())?;
+            match self {
+                #(#arms)*
+            }
+            Ok(__object.into_value())
+        }
+    };
+
+    Ok(quote! {
+        impl #lifetime_generics #crate_name::IntoJs<'js> for #ident #generics {
+            fn into_js(self, ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<#crate_name::Value<'js>> {
+                #body
+            }
+        }
+    })
+}
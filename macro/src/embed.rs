@@ -1,9 +1,12 @@
-use std::{env, path::Path};
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+};
 
 use crate::common::crate_ident;
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
-use rquickjs_core::{Context, Module, Result as JsResult, Runtime, WriteOptions};
+use rquickjs_core::compile;
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
@@ -41,6 +44,65 @@ impl Parse for EmbedModules {
     }
 }
 
+/// The parsing struct for an embedded directory.
+pub struct EmbedDir {
+    pub path: LitStr,
+}
+
+impl Parse for EmbedDir {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(EmbedDir { path })
+    }
+}
+
+/// Returns the absolute path of a path relative to the crate manifest.
+fn manifest_path(path: &str, span: Span) -> Result<PathBuf> {
+    let path = Path::new(path);
+    if !path.is_relative() {
+        return Ok(path.to_owned());
+    }
+    let full_path =
+        Path::new(&env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR should be set"))
+            .join(path);
+    full_path.canonicalize().map_err(|e| {
+        Error::new(
+            span,
+            format_args!(
+                "Error loading embedded js module from path `{}`: {}",
+                full_path.display(),
+                e
+            ),
+        )
+    })
+}
+
+fn read_source(path: &Path, span: Span) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            span,
+            format_args!(
+                "Error loading embedded js module from path `{}`: {}",
+                path.display(),
+                e
+            ),
+        )
+    })
+}
+
+/// Compiles the `(name, source)` pairs to module bytecode.
+fn compile(files: Vec<(String, String)>) -> Result<Vec<(String, Vec<u8>)>> {
+    let names = files.iter().map(|f| f.0.clone()).collect::<Vec<_>>();
+    let modules = compile::compile_modules_raw(files).map_err(|e| {
+        Error::new(
+            Span::call_site(),
+            format_args!("Error compiling embedded js module: {}", e),
+        )
+    })?;
+    Ok(names.into_iter().zip(modules).collect())
+}
+
 /// Implementation of the macro
 pub fn embed(modules: EmbedModules) -> Result<TokenStream> {
     let mut files = Vec::new();
@@ -50,77 +112,72 @@ pub fn embed(modules: EmbedModules) -> Result<TokenStream> {
             .as_ref()
             .map(|x| x.1.value())
             .unwrap_or_else(|| f.name.value());
+        let path = manifest_path(&path, f.name.span())?;
+        let source = read_source(&path, f.name.span())?;
+        files.push((f.name.value(), source));
+    }
 
-        let path = Path::new(&path);
+    let res = to_entries(compile(files)?.into_iter());
 
-        let path = if path.is_relative() {
-            let full_path = Path::new(
-                &env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR should be set"),
-            )
-            .join(path);
-            match full_path.canonicalize() {
-                Ok(x) => x,
-                Err(e) => {
-                    return Err(Error::new(
-                        f.name.span(),
-                        format_args!(
-                            "Error loading embedded js module from path `{}`: {}",
-                            full_path.display(),
-                            e
-                        ),
-                    ));
-                }
-            }
-        } else {
-            path.to_owned()
-        };
+    expand(&res)
+}
 
-        let source = match std::fs::read_to_string(&path) {
-            Ok(x) => x,
-            Err(e) => {
-                return Err(Error::new(
-                    f.name.span(),
-                    format_args!(
-                        "Error loading embedded js module from path `{}`: {}",
-                        path.display(),
-                        e
-                    ),
-                ));
-            }
-        };
-        files.push((f.name.value(), source));
+/// Collects the `.js` and `.mjs` files in the directory and its subdirectories together with
+/// their path relative to `root`, which is their module name.
+fn collect_modules(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_modules(root, &path, files)?;
+            continue;
+        }
+        let is_module = path
+            .extension()
+            .is_some_and(|ext| ext == "js" || ext == "mjs");
+        if !is_module {
+            continue;
+        }
+        let name = path
+            .strip_prefix(root)
+            .expect("the path should be inside the root directory")
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push((name, path));
     }
+    Ok(())
+}
 
-    let res = (|| -> JsResult<Vec<(String, Vec<u8>)>> {
-        let rt = Runtime::new()?;
-        let ctx = Context::full(&rt)?;
-
-        let mut modules = Vec::new();
-
-        ctx.with(|ctx| -> JsResult<()> {
-            for f in files.into_iter() {
-                let bc = Module::declare(ctx.clone(), f.0.clone(), f.1)?
-                    .write(WriteOptions::default())?;
-                modules.push((f.0, bc));
-            }
-            Ok(())
-        })?;
-        Ok(modules)
-    })();
-
-    let res = match res {
-        Ok(x) => x,
-        Err(e) => {
-            return Err(Error::new(
-                Span::call_site(),
-                format_args!("Error compiling embedded js module: {}", e),
-            ));
-        }
-    };
+/// Implementation of the directory macro
+pub fn embed_dir(dir: EmbedDir) -> Result<TokenStream> {
+    let span = dir.path.span();
+    let root = manifest_path(&dir.path.value(), span)?;
+    let mut paths = Vec::new();
+    collect_modules(&root, &root, &mut paths).map_err(|e| {
+        Error::new(
+            span,
+            format_args!(
+                "Error reading embedded js directory `{}`: {}",
+                root.display(),
+                e
+            ),
+        )
+    })?;
+    paths.sort();
 
-    let res = to_entries(res.into_iter());
+    let mut files = Vec::new();
+    for (name, path) in paths.iter() {
+        files.push((name.clone(), read_source(path, span)?));
+    }
+    let bundle = expand(&to_entries(compile(files)?.into_iter()))?;
 
-    expand(&res)
+    // Including the files makes cargo rebuild the crate when one of them changes.
+    let paths = paths.iter().map(|(_, path)| path.to_string_lossy());
+    Ok(quote! {{
+        #(const _: &[u8] = include_bytes!(#paths);)*
+        #bundle
+    }})
 }
 
 fn to_entries(modules: impl Iterator<Item = (String, Vec<u8>)>) -> Vec<(String, TokenStream)> {
@@ -168,7 +225,7 @@ pub fn expand(modules: &[(String, TokenStream)]) -> Result<TokenStream> {
 
 #[cfg(test)]
 mod test {
-    use super::{expand, to_entries, EmbedModules};
+    use super::{collect_modules, expand, to_entries, EmbedDir, EmbedModules};
     use quote::quote;
 
     #[cfg(feature = "phf")]
@@ -220,4 +277,21 @@ mod test {
         assert!(b.path.is_none());
         assert!(iter.next().is_none());
     }
+
+    #[test]
+    fn collect_dir() {
+        let dir = syn::parse2::<EmbedDir>(quote! { "js", }).unwrap();
+        assert_eq!(dir.path.value(), "js");
+
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("js");
+        let mut files = Vec::new();
+        collect_modules(&root, &root, &mut files).unwrap();
+        files.sort();
+        let names = files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["lib/host.mjs", "lib/math.js", "main.js"]);
+        assert!(files.iter().all(|(_, path)| path.is_file()));
+    }
 }
@@ -233,6 +233,46 @@ impl JsFunction {
         }
     }
 
+    /// Expands the body of a [`JsClass::call`](rquickjs_core::class::JsClass::call)
+    /// implementation which calls this function with the called instance as `self`.
+    pub fn expand_to_class_call_body(&self, lib_crate: &Ident) -> TokenStream {
+        let args = self.params.params.iter().filter(|x| !x.is_this);
+        let arg_type_requirements = args.clone().map(|x| {
+            let ty = x.expand_type(lib_crate);
+            quote! {
+                .combine(<#ty as #lib_crate::function::FromParam>::param_requirement())
+            }
+        });
+        let arg_extract = args.map(|x| x.expand_extract(lib_crate));
+        let this_extract = self
+            .params
+            .params
+            .iter()
+            .filter(|x| x.is_this)
+            .map(|x| x.expand_extract_this(lib_crate));
+        let arg_apply = self.params.params.iter().map(|x| {
+            if x.is_this {
+                x.expand_apply_this()
+            } else {
+                x.expand_apply()
+            }
+        });
+        let rust_function = &self.rust_function;
+
+        quote! {
+            let ctx = params.ctx().clone();
+            params.check_params(
+                #lib_crate::function::ParamRequirement::none()
+                #(#arg_type_requirements)*
+            )?;
+            let mut _params = params.access();
+            #(#arg_extract)*
+            #(#this_extract)*
+            let res = #rust_function(#(#arg_apply),*);
+            #lib_crate::IntoJs::into_js(res,&ctx)
+        }
+    }
+
     pub fn expand_to_js_function_impl(&self, prefix: &str, lib_crate: &Ident) -> TokenStream {
         let body = self.expand_to_js_function_body(lib_crate);
         let arg_types = self.params.expand_type(lib_crate);
@@ -327,6 +367,16 @@ impl JsParam {
         }
     }
 
+    /// Expands the application of `self` borrowed from the `this` cell of a class call.
+    pub fn expand_apply_this(&self) -> TokenStream {
+        let t = format_ident!("tmp_{}", self.number);
+        match self.kind {
+            ParamKind::Value => quote!(::core::clone::Clone::clone(&*#t)),
+            ParamKind::Borrow => quote!(&*#t),
+            ParamKind::BorrowMut => quote!(&mut *#t),
+        }
+    }
+
     pub fn expand_type(&self, lib_crate: &Ident) -> TokenStream {
         let t = &self.tokens;
         let ty = match self.kind {
@@ -343,6 +393,18 @@ impl JsParam {
         }
     }
 
+    /// Expands borrowing `self` from the `this` cell of a class call.
+    pub fn expand_extract_this(&self, lib_crate: &Ident) -> TokenStream {
+        let binding = self.expand_binding();
+        let borrow = match self.kind {
+            ParamKind::BorrowMut => quote!(try_borrow_mut),
+            ParamKind::Value | ParamKind::Borrow => quote!(try_borrow),
+        };
+        quote! {
+            let #binding = this.#borrow().map_err(#lib_crate::Error::ClassBorrow)?;
+        }
+    }
+
     pub fn expand_extract(&self, lib_crate: &Ident) -> TokenStream {
         let ty = self.expand_type(lib_crate);
         let binding = self.expand_binding();
@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::{
     spanned::Spanned as _, visit::Visit, Data, DeriveInput, Error, Field, Fields, GenericArgument,
     GenericParam, Generics, Lifetime, Result, Type,
@@ -156,23 +156,22 @@ pub(crate) fn expand(mut input: DeriveInput) -> Result<TokenStream> {
 
     let types = extract_types_need_checking(lt, &input.data)?;
 
-    let const_name = format_ident!("__{}__LT_TYPE_CHECK", name.to_string().to_uppercase());
+    let asserts = types.iter().map(|ty| {
+        quote_spanned! {ty.span()=>
+            assert_field_is_js_lifetime::<#lt, #ty>();
+        }
+    });
 
     let res = quote! {
-        const #const_name: () = const {
-            trait ValidJsLifetimeImpl{};
-
-            impl<#lt> ValidJsLifetimeImpl for #name<#lt>
-                where
-                    #(
-                        #types: #crate_name::JsLifetime<#lt>
-                    ),*
-            {
+        const _: () = {
+            // Fields which contain the `'js` lifetime or `'static` must be JsLifetime for the
+            // `'js` lifetime, otherwise they would keep JavaScript data alive past its lifetime.
+            const fn assert_field_is_js_lifetime<#lt, T: #crate_name::JsLifetime<#lt> + ?Sized>() {}
+
+            #[allow(dead_code)]
+            const fn assert_fields<#lt>() {
+                #(#asserts)*
             }
-
-            const fn assert_js_lifetime_impl_is_valid<T: ValidJsLifetimeImpl>(){}
-
-            assert_js_lifetime_impl_is_valid::<#name>()
         };
 
         unsafe impl<#lt> #crate_name::JsLifetime<#lt> for #name #generics
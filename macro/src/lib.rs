@@ -22,6 +22,7 @@ macro_rules! assert_eq_tokens {
 mod attrs;
 mod class;
 mod common;
+mod convert;
 mod embed;
 mod fields;
 mod function;
@@ -44,8 +45,11 @@ mod trace;
 /// |--------------|-----------|-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------|
 /// | `crate`      | String    | Changes the name from which the attribute tries to use rquickjs types. Use when the name behind which the rquickjs crate is declared is not properly resolved by the macro.             |
 /// | `rename`     | String    | Changes the name of the implemented class on the JavaScript side.                                                                                                                       |
+/// | `name`       | String    | Same as `rename`.                                                                                                                                                                       |
 /// | `rename_all` | Casing    | Converts the case of all the fields of this struct which have implement accessors. Can be one of `lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`,`snake_case`, or `SCREAMING_SNAKE` |
 /// | `frozen`     | Flag      | Changes the class implementation to only allow borrowing immutably.  Trying to borrow mutably will result in an error.                                                                  |
+/// | `callable`   | Flag      | Makes instances of the class callable as functions, calls are handled by the method marked with `#[qjs(call)]` in the [`macro@methods`] impl block and throw a `TypeError` without one. |
+/// | `constructible` | Flag   | Makes instances of the class callable with `new` as well, which is handled by the same method. Implies `callable`.                                                                      |
 ///
 /// # Field options
 ///
@@ -187,6 +191,7 @@ pub fn function(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
 /// | `rename`       | String or [`PredefinedAtom`](rquickjs_core::atom::PredefinedAtom) | Changes the name of the field getter and/or setter to the specified name in JavaScript.         |
 /// | `static`       | Flag                                                              | Makes the method a static method i.e. defined on the type constructor instead of the prototype. |
 /// | `constructor`  | Flag                                                              | Marks this method a the constructor for this type.                                              |
+/// | `call`         | Flag                                                              | Marks this method as the handler for calling instances of a `callable` class as a function.     |
 /// | `skip`         | Flag                                                              | Skips defining this method on the JavaScript class.                                             |
 ///
 /// # Example
@@ -488,6 +493,62 @@ pub fn module(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
     }
 }
 
+/// A macro for deriving [`JsClass`](rquickjs_core::class::JsClass) for a Rust type.
+///
+/// Generates the same implementation as the [`class`](macro@class) attribute, taking the options
+/// from `#[qjs(...)]` attributes on the type and its fields. Methods and the constructor are
+/// registered with the [`methods`](macro@methods) attribute, as with the `class` attribute.
+///
+/// The JavaScript name of the class can be set with either `#[qjs(rename = "Name")]` or
+/// `#[qjs(name = "Name")]`. Apart from the `'js` lifetime the type must be `'static`, which is
+/// required by type parameters. Add `#[qjs(allow_non_static)]` to allow other lifetimes and
+/// non-`'static` type parameters.
+///
+/// # Example
+/// ```
+/// use rquickjs::{class::{JsClass, Trace}, CatchResultExt, Context, JsLifetime, Runtime};
+///
+/// #[derive(Trace, JsLifetime, JsClass)]
+/// #[qjs(name = "Counter")]
+/// pub struct RustCounter {
+///     #[qjs(get)]
+///     count: u32,
+/// }
+///
+/// #[rquickjs::methods]
+/// impl RustCounter {
+///     #[qjs(constructor)]
+///     pub fn new(count: u32) -> Self {
+///         RustCounter { count }
+///     }
+///
+///     pub fn increment(&mut self) {
+///         self.count += 1;
+///     }
+/// }
+///
+/// fn main() {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///     ctx.with(|ctx| {
+///         rquickjs::Class::<RustCounter>::define(&ctx.globals()).unwrap();
+///         let count: u32 = ctx
+///             .eval("const c = new Counter(1); c.increment(); c.count")
+///             .catch(&ctx)
+///             .unwrap();
+///         assert_eq!(count, 2);
+///     });
+/// }
+/// ```
+#[proc_macro_derive(JsClass, attributes(qjs))]
+pub fn js_class(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    match class::derive(derive_input) {
+        Ok(x) => x.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
 /// A macro for auto deriving the trace trait.
 #[proc_macro_derive(Trace, attributes(qjs))]
 pub fn trace(stream: TokenStream1) -> TokenStream1 {
@@ -547,6 +608,78 @@ pub fn embed(item: TokenStream1) -> TokenStream1 {
     }
 }
 
+/// A macro for embedding a directory of JavaScript modules into a binary.
+///
+/// Compiles every `.js` and `.mjs` file in the directory and its subdirectories to bytecode like
+/// [`embed!`], which returns the same [`Bundle`](rquickjs_core::loader::Bundle) resolver and
+/// loader. The name of each module is its path relative to the directory, like
+/// `lib/math.js`, so relative imports between the embedded modules resolve among them.
+/// Specifiers which don't name an embedded module fail to resolve, so the bundle can be combined
+/// with other resolvers and loaders in a tuple which handle the remaining modules.
+///
+/// The directory path is relative to the crate manifest file. Changing one of the files
+/// rebuilds the crate, adding a file requires touching the crate source to be picked up.
+///
+/// # Usage
+///
+/// ```
+/// use rquickjs::{
+///     embed_dir,
+///     loader::{BuiltinResolver, Bundle, ModuleLoader},
+///     module::NativeModule,
+///     CatchResultExt, Context, Module, Object, Runtime,
+/// };
+///
+/// // `main.js` and `lib/math.js` import each other, `lib/host.mjs` imports the `host` module
+/// static BUNDLE: Bundle = embed_dir!("js");
+///
+/// fn main() {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///
+///     let host = NativeModule::builder("host")
+///         .export_value("version", 3)
+///         .finish()
+///         .unwrap();
+///     rt.set_loader(
+///         (BUNDLE, BuiltinResolver::default().with_module("host")),
+///         (BUNDLE, ModuleLoader::default().with_native(host)),
+///     );
+///     ctx.with(|ctx| {
+///         let main: Object = Module::import(&ctx, "main.js")
+///             .unwrap()
+///             .finish()
+///             .catch(&ctx)
+///             .unwrap();
+///         let sum: rquickjs::Function = main.get("sum").unwrap();
+///         assert_eq!(sum.call::<_, i32>((1, 2)).unwrap(), 3);
+///
+///         let math: Object = Module::import(&ctx, "lib/math.js")
+///             .unwrap()
+///             .finish()
+///             .catch(&ctx)
+///             .unwrap();
+///         let importer: rquickjs::Function = math.get("importer").unwrap();
+///         assert_eq!(importer.call::<_, String>(()).unwrap(), "main");
+///
+///         let host: Object = Module::import(&ctx, "lib/host.mjs")
+///             .unwrap()
+///             .finish()
+///             .catch(&ctx)
+///             .unwrap();
+///         assert_eq!(host.get::<_, i32>("hostVersion").unwrap(), 3);
+///     })
+/// }
+/// ```
+#[proc_macro]
+pub fn embed_dir(item: TokenStream1) -> TokenStream1 {
+    let embed_dir: embed::EmbedDir = parse_macro_input!(item);
+    match embed::embed_dir(embed_dir) {
+        Ok(x) => x.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
 /// A Macro for auto deriving the JsLifetime trait.
 #[proc_macro_derive(JsLifetime, attributes(qjs))]
 pub fn js_lifetime(stream: TokenStream1) -> TokenStream1 {
@@ -556,3 +689,82 @@ pub fn js_lifetime(stream: TokenStream1) -> TokenStream1 {
         Err(e) => e.into_compile_error().into(),
     }
 }
+
+/// A macro for deriving the `FromJs` trait for enums.
+///
+/// Enums of which no variant has fields are converted from strings containing the variant
+/// name. Other enums are converted from objects with a `type` property containing the variant
+/// name. The fields of struct variants are taken from the properties of the same name, the field
+/// of a tuple variant from the `value` property, and multiple fields of a tuple variant from an
+/// array in the `value` property.
+///
+/// Converting a string which doesn't name a variant returns an error listing the valid names.
+///
+/// # Attribute options
+///
+/// | **Option**   | **Value** | **Description**                                                  |
+/// |--------------|-----------|------------------------------------------------------------------|
+/// | `crate`      | String    | Changes the name from which the attribute tries to use rquickjs types. Use when the name behind which the rquickjs crate is declared is not properly resolved by the macro. |
+/// | `rename_all` | Casing    | Converts the case of all variant names.                          |
+///
+/// Variants and the fields of struct variants can be renamed with `#[qjs(rename = "...")]`.
+///
+/// # Example
+/// ```
+/// use rquickjs::{Context, FromJs, IntoJs, Runtime};
+///
+/// #[derive(FromJs, IntoJs, Debug, PartialEq)]
+/// #[qjs(rename_all = "camelCase")]
+/// enum Mode {
+///     ReadOnly,
+///     #[qjs(rename = "rw")]
+///     ReadWrite,
+/// }
+///
+/// #[derive(FromJs, IntoJs, Debug, PartialEq)]
+/// enum Shape {
+///     Circle { radius: f64 },
+///     Square(f64),
+///     Empty,
+/// }
+///
+/// fn main() {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///     ctx.with(|ctx| {
+///         let mode: Mode = ctx.eval(r#""rw""#).unwrap();
+///         assert_eq!(mode, Mode::ReadWrite);
+///
+///         let shape: Shape = ctx.eval(r#"({ type: "Circle", radius: 2 })"#).unwrap();
+///         assert_eq!(shape, Shape::Circle { radius: 2.0 });
+///
+///         ctx.globals().set("shape", Shape::Square(3.0)).unwrap();
+///         let side: f64 = ctx.eval("shape.type === 'Square' ? shape.value : 0").unwrap();
+///         assert_eq!(side, 3.0);
+///
+///         let err = ctx.eval::<Mode, _>(r#""writeOnly""#).unwrap_err();
+///         assert!(err.to_string().contains("expected one of `readOnly`, `rw`"));
+///     });
+/// }
+/// ```
+#[proc_macro_derive(FromJs, attributes(qjs))]
+pub fn from_js(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    match convert::expand_from_js(derive_input) {
+        Ok(x) => x.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// A macro for deriving the `IntoJs` trait for enums.
+///
+/// Uses the same representation and options as the [`FromJs`](macro@FromJs) derive, so values
+/// round-trip between both.
+#[proc_macro_derive(IntoJs, attributes(qjs))]
+pub fn into_js(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    match convert::expand_into_js(derive_input) {
+        Ok(x) => x.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
@@ -134,6 +134,7 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> Result<
     let mut accessors = HashMap::new();
     let mut functions = Vec::new();
     let mut constructor: Option<Method> = None;
+    let mut call: Option<Method> = None;
     let mut static_span: Option<Span> = None;
     //let mut consts = Vec::new();
 
@@ -160,6 +161,17 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> Result<
                         error.extend(Error::new(first_span, "First constructor defined here"));
                         return Err(error);
                     }
+                } else if function.config.call {
+                    if function.function.is_async {
+                        return Err(Error::new(span, "A call handler can't be async"));
+                    }
+                    if let Some(first) = call.replace(function) {
+                        let first_span = first.attr_span;
+                        let mut error =
+                            Error::new(span, "A class can only have a single call handler");
+                        error.extend(Error::new(first_span, "First call handler defined here"));
+                        return Err(error);
+                    }
                 } else {
                     if static_span.is_none() && function.config.r#static {
                         static_span = Some(function.attr_span);
@@ -185,6 +197,7 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> Result<
     let function_impls = functions.iter().map(|func| func.expand_impl());
     let accessor_impls = accessors.values().map(|access| access.expand_impl());
     let constructor_impl = constructor.as_ref().map(|constr| constr.expand_impl());
+    let call_impl = call.as_ref().map(|call| call.expand_impl());
 
     let function_js_impls = functions
         .iter()
@@ -203,7 +216,7 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> Result<
     let proto_ident = format_ident!("_proto");
     let function_apply_proto = functions
         .iter()
-        .filter(|&func| (!func.config.r#static))
+        .filter(|&func| !func.config.r#static)
         .map(|func| {
             func.expand_apply_to_object(&prefix, &self_ty, &proto_ident, config.rename_all)
         });
@@ -244,6 +257,22 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> Result<
         TokenStream::new()
     };
 
+    let call_create = if let Some(c) = call.as_ref() {
+        let body = c.function.expand_to_class_call_body(&crate_name);
+        let js_added_generics = add_js_lifetime(&generics);
+
+        quote! {
+            impl #js_added_generics #crate_name::class::impl_::CallImplementor<'js,#self_ty> for #crate_name::class::impl_::CallImpl<#self_ty> {
+                fn call<'a>(&self, this: &#crate_name::class::JsCell<'js,#self_ty>, params: #crate_name::function::Params<'a,'js>) -> #crate_name::Result<#crate_name::Value<'js>>{
+                    let _ = this;
+                    #body
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
     let class_name = get_class_name(&self_ty);
     let impl_mod_name = format_ident!("__impl_methods_{class_name}__");
 
@@ -253,6 +282,7 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> Result<
             #(#function_impls)*
             #(#accessor_impls)*
             #constructor_impl
+            #call_impl
         }
 
 
@@ -276,6 +306,8 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> Result<
             }
 
             #constructor_create
+
+            #call_create
         }
     };
 
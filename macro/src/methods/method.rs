@@ -16,6 +16,7 @@ use crate::{
 #[derive(Default, Clone)]
 pub(crate) struct MethodConfig {
     pub constructor: bool,
+    pub call: bool,
     pub skip: bool,
     pub r#static: bool,
     pub configurable: bool,
@@ -31,6 +32,9 @@ impl MethodConfig {
             MethodOption::Constructor(x) => {
                 self.constructor = x.is_true();
             }
+            MethodOption::Call(x) => {
+                self.call = x.is_true();
+            }
             MethodOption::Static(x) => {
                 self.r#static = x.is_true();
             }
@@ -58,6 +62,7 @@ impl MethodConfig {
 
 pub(crate) enum MethodOption {
     Constructor(FlagOption<kw::constructor>),
+    Call(FlagOption<kw::call>),
     Static(FlagOption<Token![static]>),
     Skip(FlagOption<kw::skip>),
     Configurable(FlagOption<kw::configurable>),
@@ -71,6 +76,8 @@ impl Parse for MethodOption {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         if input.peek(kw::constructor) {
             input.parse().map(Self::Constructor)
+        } else if input.peek(kw::call) {
+            input.parse().map(Self::Call)
         } else if input.peek(Token![static]) {
             input.parse().map(Self::Static)
         } else if input.peek(kw::skip) {
@@ -120,6 +127,17 @@ impl MethodConfig {
             ));
         }
 
+        if self.call && (self.constructor || self.get || self.set || self.r#static) {
+            return Err(Error::new(
+                span,
+                "a call handler can't also be a constructor, getter, setter or static method.",
+            ));
+        }
+
+        if self.call && self.rename.is_some() {
+            return Err(Error::new(span, "Can't rename a call handler"));
+        }
+
         if self.configurable && !(self.get || self.set) {
             return Err(Error::new(
                 span,
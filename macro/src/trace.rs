@@ -8,6 +8,7 @@ use syn::{
 
 use crate::{
     attrs::{take_attributes, OptionList, ValueOption},
+    class::ClassOption,
     common::{add_js_lifetime, crate_ident},
     fields::Fields,
 };
@@ -23,12 +24,15 @@ impl ImplConfig {
             TraceOption::Crate(x) => {
                 self.crate_ = Some(x.value.value());
             }
+            // Options of the `JsClass` derive, which shares the `qjs` attribute.
+            TraceOption::Class => {}
         }
     }
 }
 
 pub(crate) enum TraceOption {
     Crate(ValueOption<Token![crate], LitStr>),
+    Class,
 }
 
 impl Parse for TraceOption {
@@ -36,7 +40,10 @@ impl Parse for TraceOption {
         if input.peek(Token![crate]) {
             input.parse().map(Self::Crate)
         } else {
-            Err(syn::Error::new(input.span(), "invalid impl attribute"))
+            input
+                .parse::<ClassOption>()
+                .map(|_| Self::Class)
+                .map_err(|e| syn::Error::new(e.span(), "invalid impl attribute"))
         }
     }
 }
@@ -145,7 +152,7 @@ pub(crate) fn expand(input: DeriveInput) -> Result<TokenStream> {
                         let names = f
                             .iter()
                             .enumerate()
-                            .filter(|&(_idx, f)| (!f.config.skip_trace))
+                            .filter(|&(_idx, f)| !f.config.skip_trace)
                             .map(|(idx, _f)| {
                                 let ident = format_ident!("tmp_{idx}");
                                 Some(ident)
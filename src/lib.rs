@@ -6,6 +6,24 @@
 //! [QuickJS](https://bellard.org/quickjs/) JavaScript engine. This crate is heavily inspired by
 //! the [rlua](https://crates.io/crates/rlua) crate.
 //!
+//! The [`prelude`] module contains the types, traits and macros most programs need:
+//!
+//! ```
+//! use rquickjs::prelude::*;
+//!
+//! fn main() -> rquickjs::Result<()> {
+//!     let rt = Runtime::new()?;
+//!     let ctx = Context::full(&rt)?;
+//!     ctx.with(|ctx| {
+//!         let greet = Function::new(ctx.clone(), |name: String| format!("Hello, {name}!"))?;
+//!         ctx.globals().set("greet", greet)?;
+//!         let msg: String = ctx.eval("greet('world')")?;
+//!         assert_eq!(msg, "Hello, world!");
+//!         Ok(())
+//!     })
+//! }
+//! ```
+//!
 //! # The `Runtime` and `Context` objects
 //!
 //! The main entry point of this library is the [`Runtime`] struct. It represents the interpreter
@@ -124,7 +142,20 @@ pub use rquickjs_core::*;
 
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "macro")))]
 #[cfg(feature = "macro")]
-pub use rquickjs_macro::{class, embed, function, methods, module, JsLifetime};
+pub use rquickjs_macro::{
+    class, embed, embed_dir, function, methods, module, FromJs, IntoJs, JsLifetime,
+};
+
+pub mod prelude {
+    //! A group of often used types, traits and macros.
+
+    pub use rquickjs_core::prelude::*;
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "macro")))]
+    #[cfg(feature = "macro")]
+    pub use rquickjs_macro::{
+        class, function, methods, module, FromJs, IntoJs, JsClass, JsLifetime, Trace,
+    };
+}
 
 pub mod class {
     //! JavaScript classes defined from Rust.
@@ -132,7 +163,7 @@ pub mod class {
     pub use rquickjs_core::class::*;
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "macro")))]
     #[cfg(feature = "macro")]
-    pub use rquickjs_macro::Trace;
+    pub use rquickjs_macro::{JsClass, Trace};
 }
 
 // The following imports needed to linking docs
@@ -10,6 +10,10 @@ use std::{
 const WASI_SDK_VERSION_MAJOR: usize = 24;
 const WASI_SDK_VERSION_MINOR: usize = 0;
 
+// The first WASI SDK release with sysroots for the `wasm32-wasip1` and `wasm32-wasip2` targets.
+const WASI_SDK_PREVIEW2_VERSION_MAJOR: usize = 22;
+const WASI_SDK_PREVIEW2_VERSION_MINOR: usize = 0;
+
 fn download_wasi_sdk() -> PathBuf {
     let mut wasi_sdk_dir: PathBuf = env::var("OUT_DIR").unwrap().into();
     wasi_sdk_dir.push("wasi-sdk");
@@ -89,6 +93,19 @@ fn get_wasi_sdk_path() -> PathBuf {
         .unwrap_or_else(download_wasi_sdk)
 }
 
+/// Returns the clang target for a WASI rust target.
+///
+/// The legacy `wasm32-wasi` target is kept as is, as older WASI SDKs only know that name.
+fn wasi_clang_target(target: &str) -> &'static str {
+    match target {
+        "wasm32-wasip2" => "wasm32-wasip2",
+        "wasm32-wasip1" => "wasm32-wasip1",
+        "wasm32-wasip1-threads" => "wasm32-wasip1-threads",
+        "wasm32-wasi-threads" => "wasm32-wasi-threads",
+        _ => "wasm32-wasi",
+    }
+}
+
 fn main() {
     #[cfg(feature = "logging")]
     pretty_env_logger::init();
@@ -143,8 +160,22 @@ fn main() {
         "xsum.c",
     ];
 
+    // Includes `quickjs.c`, so it is compiled instead of it.
+    let ext_file = "rquickjs.c";
+    println!("cargo:rerun-if-changed={}", ext_file);
+
     let mut defines: Vec<(String, Option<&str>)> = vec![("_GNU_SOURCE".into(), None)];
 
+    // QuickJS only compiles in dump support for debug builds, make sure it is available when
+    // any of the dump features is enabled as the dumps are selected at runtime with dump flags.
+    if features
+        .iter()
+        .filter(|feature| feature.starts_with("dump-"))
+        .any(|feature| env::var(feature_to_cargo(feature)).is_ok())
+    {
+        defines.push(("ENABLE_DUMPS".into(), Some("")));
+    }
+
     let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
     let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap();
 
@@ -205,6 +236,7 @@ fn main() {
             .expect("Unable to copy source; try 'git submodule update --init'");
     }
     fs::copy("quickjs.bind.h", out_dir.join("quickjs.bind.h")).expect("Unable to copy source");
+    fs::copy(ext_file, out_dir.join(ext_file)).expect("Unable to copy source");
 
     if target_os == "wasi" {
         let wasi_sdk_path = get_wasi_sdk_path();
@@ -216,11 +248,28 @@ fn main() {
         }
         env::set_var("CC", wasi_sdk_path.join("bin/clang").to_str().unwrap());
         env::set_var("AR", wasi_sdk_path.join("bin/ar").to_str().unwrap());
-        let sysroot = format!(
-            "--sysroot={}",
-            wasi_sdk_path.join("share/wasi-sysroot").display()
-        );
-        env::set_var("CFLAGS", &sysroot);
+
+        let clang_target = wasi_clang_target(&env::var("TARGET").unwrap());
+        let sysroot_path = wasi_sdk_path.join("share/wasi-sysroot");
+        // The libraries of each target live in `lib/<target>` of the sysroot.
+        if !sysroot_path
+            .join("lib")
+            .join(clang_target)
+            .try_exists()
+            .unwrap()
+        {
+            panic!(
+                "wasi-sdk in {} has no sysroot for the {} target, the wasm32-wasip1 and wasm32-wasip2 targets require wasi-sdk {}.{} or later",
+                wasi_sdk_path.display(),
+                clang_target,
+                WASI_SDK_PREVIEW2_VERSION_MAJOR,
+                WASI_SDK_PREVIEW2_VERSION_MINOR,
+            );
+        }
+        let target = format!("--target={clang_target}");
+        let sysroot = format!("--sysroot={}", sysroot_path.display());
+        env::set_var("CFLAGS", format!("{target} {sysroot}"));
+        bindgen_cflags.push(target);
         bindgen_cflags.push(sysroot);
     }
 
@@ -236,9 +285,10 @@ fn main() {
         builder.define(name, *value);
     }
 
-    for src in &source_files {
+    for src in source_files.iter().filter(|src| **src != "quickjs.c") {
         builder.file(out_dir.join(src));
     }
+    builder.file(out_dir.join(ext_file));
 
     builder.compile("libquickjs.a");
 }
@@ -0,0 +1,226 @@
+// The QuickJS library with a few additions rquickjs needs which QuickJS doesn't export.
+//
+// This file is compiled instead of `quickjs.c` so the additions can use its internals. They are
+// declared in `src/ext.rs` as the bindings are generated from `quickjs.h` only.
+
+#include "quickjs.c"
+
+// Read a property with a receiver, like `Reflect.get`.
+JSValue rquickjs_get_property_receiver(JSContext *ctx, JSValueConst obj,
+                                       JSAtom prop, JSValueConst receiver)
+{
+    return JS_GetPropertyInternal(ctx, obj, prop, receiver, false);
+}
+
+// Assign a property with a receiver, like `Reflect.set` but taking the `JS_PROP_THROW*` flags
+// of the exotic `set_property` method. Frees `val`.
+int rquickjs_set_property_receiver(JSContext *ctx, JSValueConst obj,
+                                   JSAtom prop, JSValue val,
+                                   JSValueConst receiver, int flags)
+{
+    return JS_SetPropertyInternal2(ctx, obj, prop, val, receiver, flags);
+}
+
+// The job of a dynamic `import()`, which loads the module with the synchronous module loader.
+//
+// The arguments are the resolve and reject functions of the promise returned by `import()`, the
+// name of the importing script or module and the specifier.
+JSValue rquickjs_dynamic_import_job(JSContext *ctx, int argc, JSValueConst *argv)
+{
+    return js_dynamic_import_job(ctx, argc, argv);
+}
+
+// Execute the next pending job like `JS_ExecutePendingJob`, but run the job of a dynamic
+// `import()` with `dynamic_import` instead, if it isn't NULL. It is called with the same
+// arguments as `rquickjs_dynamic_import_job`.
+int rquickjs_execute_pending_job(JSRuntime *rt, JSContext **pctx,
+                                 JSJobFunc *dynamic_import)
+{
+    JSJobEntry *e;
+
+    if (dynamic_import && !list_empty(&rt->job_list)) {
+        e = list_entry(rt->job_list.next, JSJobEntry, link);
+        if (e->job_func == js_dynamic_import_job)
+            e->job_func = dynamic_import;
+    }
+    return JS_ExecutePendingJob(rt, pctx);
+}
+
+// The number of modules requested by the static imports and re-exports of a module.
+int rquickjs_get_module_requested_count(JSModuleDef *m)
+{
+    return m->req_module_entries_count;
+}
+
+// The specifier of a module requested by a module, as written in the source.
+JSAtom rquickjs_get_module_requested(JSContext *ctx, JSModuleDef *m, int idx)
+{
+    return JS_DupAtom(ctx, m->req_module_entries[idx].module_name);
+}
+
+// The number of exports of a module, not including the ones of `export * from`.
+int rquickjs_get_module_export_count(JSModuleDef *m)
+{
+    return m->export_entries_count;
+}
+
+// The name of an export of a module.
+JSAtom rquickjs_get_module_export_name(JSContext *ctx, JSModuleDef *m, int idx)
+{
+    return JS_DupAtom(ctx, m->export_entries[idx].export_name);
+}
+
+// The `eval` function the context was created with, even if the global was removed or replaced.
+JSValue rquickjs_get_intrinsic_eval(JSContext *ctx)
+{
+    return js_dup(ctx->eval_obj);
+}
+
+// The key of a symbol in the global symbol registry like `Symbol.keyFor`, or undefined if the
+// symbol isn't registered, without looking up the global `Symbol` object.
+JSValue rquickjs_symbol_key_for(JSContext *ctx, JSValueConst symbol)
+{
+    return js_symbol_keyFor(ctx, JS_UNDEFINED, 1, &symbol);
+}
+
+// The state of the allocator of runtimes created with `rquickjs_new_runtime`, which wraps the
+// allocator the runtime was created with.
+typedef struct RquickjsMallocState {
+    JSMallocFunctions mf;
+    void *opaque;
+    // NULL while the runtime is created.
+    JSRuntime *rt;
+    // Set if QuickJS threw its out of memory error.
+    bool out_of_memory;
+    // The live memory allocated while the error was created, which includes the error object.
+    void *oom_allocs[8];
+    int oom_alloc_count;
+} RquickjsMallocState;
+
+static void rquickjs_record_alloc(RquickjsMallocState *s, void *ptr)
+{
+    if (!ptr || !s->rt || !s->rt->in_out_of_memory)
+        return;
+    s->out_of_memory = true;
+    if (s->oom_alloc_count == countof(s->oom_allocs)) {
+        // Drop the oldest allocation, which belongs to an earlier error.
+        memmove(s->oom_allocs, s->oom_allocs + 1,
+                sizeof(s->oom_allocs) - sizeof(s->oom_allocs[0]));
+        s->oom_alloc_count--;
+    }
+    s->oom_allocs[s->oom_alloc_count++] = ptr;
+}
+
+static void rquickjs_forget_alloc(RquickjsMallocState *s, void *ptr)
+{
+    int i;
+
+    for (i = 0; i < s->oom_alloc_count; i++) {
+        if (s->oom_allocs[i] == ptr) {
+            memmove(s->oom_allocs + i, s->oom_allocs + i + 1,
+                    (--s->oom_alloc_count - i) * sizeof(s->oom_allocs[0]));
+            return;
+        }
+    }
+}
+
+static void *rquickjs_calloc(void *opaque, size_t count, size_t size)
+{
+    RquickjsMallocState *s = opaque;
+    void *ptr = s->mf.js_calloc(s->opaque, count, size);
+
+    rquickjs_record_alloc(s, ptr);
+    return ptr;
+}
+
+static void *rquickjs_malloc(void *opaque, size_t size)
+{
+    RquickjsMallocState *s = opaque;
+    void *ptr = s->mf.js_malloc(s->opaque, size);
+
+    rquickjs_record_alloc(s, ptr);
+    return ptr;
+}
+
+static void rquickjs_free(void *opaque, void *ptr)
+{
+    RquickjsMallocState *s = opaque;
+
+    rquickjs_forget_alloc(s, ptr);
+    s->mf.js_free(s->opaque, ptr);
+}
+
+static void *rquickjs_realloc(void *opaque, void *ptr, size_t size)
+{
+    RquickjsMallocState *s = opaque;
+    void *res = s->mf.js_realloc(s->opaque, ptr, size);
+
+    if (res) {
+        rquickjs_forget_alloc(s, ptr);
+        rquickjs_record_alloc(s, res);
+    }
+    return res;
+}
+
+// Create a runtime like `JS_NewRuntime2`, using the default allocator if `mf` is NULL, which
+// records when QuickJS runs out of memory, see `rquickjs_is_out_of_memory_error`.
+//
+// The runtime must be freed with `rquickjs_free_runtime`.
+JSRuntime *rquickjs_new_runtime(const JSMallocFunctions *mf, void *opaque)
+{
+    RquickjsMallocState *s;
+    JSMallocFunctions wrapper;
+    JSRuntime *rt;
+
+    if (!mf)
+        mf = &def_malloc_funcs;
+    s = mf->js_calloc(opaque, 1, sizeof(*s));
+    if (!s)
+        return NULL;
+    s->mf = *mf;
+    s->opaque = opaque;
+    wrapper = (JSMallocFunctions){
+        rquickjs_calloc,
+        rquickjs_malloc,
+        rquickjs_free,
+        rquickjs_realloc,
+        mf->js_malloc_usable_size,
+    };
+    rt = JS_NewRuntime2(&wrapper, s);
+    if (!rt) {
+        mf->js_free(opaque, s);
+        return NULL;
+    }
+    s->rt = rt;
+    return rt;
+}
+
+// Free a runtime created with `rquickjs_new_runtime`.
+void rquickjs_free_runtime(JSRuntime *rt)
+{
+    RquickjsMallocState *s = rt->malloc_state.opaque;
+
+    JS_FreeRuntime(rt);
+    s->mf.js_free(s->opaque, s);
+}
+
+// Returns whether `val` is the error QuickJS threw when it ran out of memory. Only the first
+// check after running out of memory can succeed.
+bool rquickjs_is_out_of_memory_error(JSRuntime *rt, JSValueConst val)
+{
+    RquickjsMallocState *s = rt->malloc_state.opaque;
+    bool res = false;
+    int i;
+
+    if (!s->out_of_memory)
+        return false;
+    if (JS_VALUE_GET_TAG(val) == JS_TAG_OBJECT) {
+        for (i = 0; i < s->oom_alloc_count; i++) {
+            if (s->oom_allocs[i] == JS_VALUE_GET_PTR(val))
+                res = true;
+        }
+    }
+    s->out_of_memory = false;
+    s->oom_alloc_count = 0;
+    return res;
+}
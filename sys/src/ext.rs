@@ -0,0 +1,94 @@
+// Functions added to QuickJS by `rquickjs.c`, which are not part of the generated bindings.
+extern "C" {
+    /// Read a property with a receiver, like `Reflect.get`.
+    pub fn rquickjs_get_property_receiver(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        prop: JSAtom,
+        receiver: JSValue,
+    ) -> JSValue;
+
+    /// Assign a property with a receiver, like `Reflect.set`, taking ownership of `val`.
+    ///
+    /// `flags` are the `JS_PROP_THROW*` flags passed to the exotic `set_property` method.
+    pub fn rquickjs_set_property_receiver(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        prop: JSAtom,
+        val: JSValue,
+        receiver: JSValue,
+        flags: c_int,
+    ) -> c_int;
+
+    /// The job of a dynamic `import()`, which loads the module with the synchronous module
+    /// loader.
+    ///
+    /// The arguments are the resolve and reject functions of the promise returned by `import()`,
+    /// the name of the importing script or module and the specifier.
+    pub fn rquickjs_dynamic_import_job(
+        ctx: *mut JSContext,
+        argc: c_int,
+        argv: *mut JSValue,
+    ) -> JSValue;
+
+    /// Execute the next pending job like `JS_ExecutePendingJob`, but run the job of a dynamic
+    /// `import()` with `dynamic_import` instead, if it is set.
+    ///
+    /// `dynamic_import` is called with the same arguments as [`rquickjs_dynamic_import_job`].
+    pub fn rquickjs_execute_pending_job(
+        rt: *mut JSRuntime,
+        pctx: *mut *mut JSContext,
+        dynamic_import: JSJobFunc,
+    ) -> c_int;
+
+    /// The number of modules requested by the static imports and re-exports of a module.
+    pub fn rquickjs_get_module_requested_count(m: *mut JSModuleDef) -> c_int;
+
+    /// The specifier of a module requested by a module, as written in the source.
+    ///
+    /// Returns a new atom, `idx` must be less than [`rquickjs_get_module_requested_count`].
+    pub fn rquickjs_get_module_requested(
+        ctx: *mut JSContext,
+        m: *mut JSModuleDef,
+        idx: c_int,
+    ) -> JSAtom;
+
+    /// The number of exports of a module, not including the ones of `export * from`.
+    pub fn rquickjs_get_module_export_count(m: *mut JSModuleDef) -> c_int;
+
+    /// The name of an export of a module.
+    ///
+    /// Returns a new atom, `idx` must be less than [`rquickjs_get_module_export_count`].
+    pub fn rquickjs_get_module_export_name(
+        ctx: *mut JSContext,
+        m: *mut JSModuleDef,
+        idx: c_int,
+    ) -> JSAtom;
+
+    /// The `eval` function the context was created with, even if the global was removed or
+    /// replaced.
+    ///
+    /// Returns a new reference.
+    pub fn rquickjs_get_intrinsic_eval(ctx: *mut JSContext) -> JSValue;
+
+    /// Returns the key of the symbol in the global symbol registry like `Symbol.keyFor`, or
+    /// undefined if the symbol isn't registered.
+    ///
+    /// Returns a new reference.
+    pub fn rquickjs_symbol_key_for(ctx: *mut JSContext, symbol: JSValue) -> JSValue;
+
+    /// Create a runtime like `JS_NewRuntime2`, using the default allocator if `mf` is null,
+    /// which records when QuickJS runs out of memory, see [`rquickjs_is_out_of_memory_error`].
+    ///
+    /// The runtime must be freed with [`rquickjs_free_runtime`].
+    pub fn rquickjs_new_runtime(mf: *const JSMallocFunctions, opaque: *mut c_void)
+        -> *mut JSRuntime;
+
+    /// Free a runtime created with [`rquickjs_new_runtime`].
+    pub fn rquickjs_free_runtime(rt: *mut JSRuntime);
+
+    /// Returns whether `val` is the error QuickJS threw when it ran out of memory.
+    ///
+    /// Only the first check after running out of memory can succeed.
+    pub fn rquickjs_is_out_of_memory_error(rt: *mut JSRuntime, val: JSValue) -> bool;
+}
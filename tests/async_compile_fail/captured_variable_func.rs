@@ -7,9 +7,9 @@ pub async fn test() {
     let mut var = 1u32;
     let var_ref = &mut var;
     async_with!(ctx => |ctx|{
-        ctx.spawn(async move {
+        let _ = ctx.spawn(async move {
             *var_ref += 1;
-        })
+        });
     })
     .await
 }
@@ -1,7 +1,23 @@
+#[cfg(target_arch = "wasm32")]
+#[path = "macros/pass_callable.rs"]
+pub mod pass_callable;
+
 #[cfg(target_arch = "wasm32")]
 #[path = "macros/pass_class.rs"]
 pub mod pass_class;
 
+#[cfg(target_arch = "wasm32")]
+#[path = "macros/pass_convert.rs"]
+pub mod pass_convert;
+
+#[cfg(target_arch = "wasm32")]
+#[path = "macros/pass_js_class.rs"]
+pub mod pass_js_class;
+
+#[cfg(target_arch = "wasm32")]
+#[path = "macros/pass_js_lifetime.rs"]
+pub mod pass_js_lifetime;
+
 #[cfg(target_arch = "wasm32")]
 #[path = "macros/pass_method.rs"]
 pub mod pass_method;
@@ -17,7 +33,10 @@ pub mod pass_trace;
 #[cfg(feature = "macro")]
 mod macro_tests {
     #[cfg(target_arch = "wasm32")]
-    use crate::{pass_class, pass_method, pass_module, pass_trace};
+    use crate::{
+        pass_callable, pass_class, pass_convert, pass_js_class, pass_js_lifetime, pass_method,
+        pass_module, pass_trace,
+    };
 
     #[cfg(not(target_arch = "wasm32"))]
     #[test]
@@ -26,18 +45,44 @@ mod macro_tests {
         t.pass("tests/macros/pass_*.rs");
         #[cfg(feature = "compile-tests")]
         t.compile_fail("tests/compile_fail/*.rs");
+        #[cfg(all(not(feature = "parallel"), feature = "compile-tests"))]
+        t.compile_fail("tests/non_parallel_compile_fail/*.rs");
         #[cfg(all(feature = "futures", feature = "compile-tests"))]
         t.compile_fail("tests/async_compile_fail/*.rs");
         #[cfg(all(feature = "futures", feature = "parallel", feature = "compile-tests"))]
         t.compile_fail("tests/async_parallel_compile_fail/*.rs");
     }
 
+    #[cfg(target_arch = "wasm32")]
+    #[test]
+    fn macros_pass_callable() {
+        pass_callable::main();
+    }
+
     #[cfg(target_arch = "wasm32")]
     #[test]
     fn macros_pass_class() {
         pass_class::main();
     }
 
+    #[cfg(target_arch = "wasm32")]
+    #[test]
+    fn macros_pass_convert() {
+        pass_convert::main();
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[test]
+    fn macros_pass_js_class() {
+        pass_js_class::main();
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[test]
+    fn macros_pass_js_lifetime() {
+        pass_js_lifetime::main();
+    }
+
     #[cfg(target_arch = "wasm32")]
     #[test]
     fn macros_pass_method() {
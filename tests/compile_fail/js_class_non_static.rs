@@ -0,0 +1,9 @@
+use rquickjs::{class::JsClass, Value};
+
+#[derive(JsClass)]
+pub struct Borrowed<'js, 'a> {
+    value: Value<'js>,
+    name: &'a str,
+}
+
+fn main() {}
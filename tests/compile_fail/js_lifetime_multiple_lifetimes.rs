@@ -0,0 +1,9 @@
+use rquickjs::{JsLifetime, Value};
+
+#[derive(JsLifetime)]
+pub struct Borrowed<'js, 'a> {
+    value: Value<'js>,
+    name: &'a str,
+}
+
+fn main() {}
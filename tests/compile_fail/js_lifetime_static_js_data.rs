@@ -0,0 +1,9 @@
+use rquickjs::{JsLifetime, Value};
+
+#[derive(JsLifetime)]
+pub struct Escaped<'js> {
+    value: Value<'js>,
+    escaped: Value<'static>,
+}
+
+fn main() {}
@@ -0,0 +1,109 @@
+use rquickjs::{class::Trace, prelude::Opt, CatchResultExt, Context, JsLifetime, Runtime};
+
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class(callable)]
+pub struct Counter {
+    #[qjs(get)]
+    count: u32,
+}
+
+#[rquickjs::methods]
+impl Counter {
+    #[qjs(call)]
+    pub fn increment(&mut self, by: Opt<u32>) -> u32 {
+        self.count += by.0.unwrap_or(1);
+        self.count
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class(constructible)]
+pub struct Factory {
+    #[qjs(get)]
+    prefix: String,
+}
+
+#[rquickjs::methods]
+impl Factory {
+    #[qjs(call)]
+    pub fn create(&self, name: String) -> Counter {
+        Counter {
+            count: (self.prefix.len() + name.len()) as u32,
+        }
+    }
+}
+
+/// A callable class without a `#[qjs(call)]` method, calling it throws.
+#[derive(Trace, JsLifetime)]
+#[rquickjs::class(callable)]
+pub struct Uncallable {}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        ctx.globals().set("counter", Counter { count: 0 }).unwrap();
+        ctx.globals()
+            .set(
+                "factory",
+                Factory {
+                    prefix: "ab".to_string(),
+                },
+            )
+            .unwrap();
+        ctx.globals().set("uncallable", Uncallable {}).unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            if(typeof counter !== "function"){
+                throw new Error(1)
+            }
+            if(counter() !== 1 || counter(2) !== 3){
+                throw new Error(2)
+            }
+            if(counter.count !== 3){
+                throw new Error(3)
+            }
+            counter.reset();
+            if(counter.count !== 0){
+                throw new Error(4)
+            }
+            let threw = false;
+            try {
+                new counter();
+            } catch(e) {
+                threw = true;
+            }
+            if(!threw){
+                throw new Error(5)
+            }
+            if(factory("c").count !== 3){
+                throw new Error(6)
+            }
+            let created = new factory("cd");
+            if(created.count !== 4 || created() !== 5){
+                throw new Error(7)
+            }
+            if(factory.prefix !== "ab"){
+                throw new Error(8)
+            }
+            let error = null;
+            try {
+                uncallable();
+            } catch(e) {
+                error = e;
+            }
+            if(!(error instanceof TypeError) || error.message !== "Uncallable is not a function"){
+                throw new Error(9)
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+    });
+}
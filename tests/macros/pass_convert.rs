@@ -0,0 +1,108 @@
+use rquickjs::{CatchResultExt, Context, FromJs, IntoJs, Runtime, Value};
+
+#[derive(FromJs, IntoJs, Debug, PartialEq, Clone)]
+#[qjs(rename_all = "camelCase")]
+pub enum Mode {
+    ReadOnly,
+    WriteOnly,
+    #[qjs(rename = "rw")]
+    ReadWrite,
+}
+
+#[derive(FromJs, IntoJs, Debug, PartialEq, Clone)]
+pub enum Shape {
+    Circle {
+        radius: f64,
+    },
+    Rect {
+        #[qjs(rename = "w")]
+        width: f64,
+        #[qjs(rename = "h")]
+        height: f64,
+    },
+    Square(f64),
+    Line(f64, f64, f64, f64),
+    Empty,
+}
+
+#[derive(FromJs, IntoJs, Debug, PartialEq)]
+pub enum Wrapper<'js> {
+    Value(Value<'js>),
+    Nothing,
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+    ctx.with(|ctx| {
+        let mode: Mode = ctx.eval(r#""writeOnly""#).catch(&ctx).unwrap();
+        assert_eq!(mode, Mode::WriteOnly);
+        let mode: Mode = ctx.eval(r#""rw""#).catch(&ctx).unwrap();
+        assert_eq!(mode, Mode::ReadWrite);
+
+        let err = ctx.eval::<Mode, _>(r#""ReadOnly""#).unwrap_err();
+        assert!(err.is_from_js());
+        assert!(err
+            .to_string()
+            .contains("unknown variant `ReadOnly`, expected one of `readOnly`, `writeOnly`, `rw`"));
+        assert!(ctx.eval::<Mode, _>("1").unwrap_err().is_from_js());
+
+        for mode in [Mode::ReadOnly, Mode::WriteOnly, Mode::ReadWrite] {
+            let value = mode.clone().into_js(&ctx).unwrap();
+            assert!(value.is_string());
+            assert_eq!(Mode::from_js(&ctx, value).unwrap(), mode);
+        }
+
+        let shape: Shape = ctx
+            .eval(r#"({ type: "Rect", w: 1, h: 2 })"#)
+            .catch(&ctx)
+            .unwrap();
+        assert_eq!(
+            shape,
+            Shape::Rect {
+                width: 1.0,
+                height: 2.0
+            }
+        );
+        let shape: Shape = ctx
+            .eval(r#"({ type: "Line", value: [0, 1, 2, 3] })"#)
+            .catch(&ctx)
+            .unwrap();
+        assert_eq!(shape, Shape::Line(0.0, 1.0, 2.0, 3.0));
+
+        let err = ctx.eval::<Shape, _>(r#"({ type: "Triangle" })"#).unwrap_err();
+        assert!(err.to_string().contains(
+            "unknown variant `Triangle`, expected one of `Circle`, `Rect`, `Square`, `Line`, `Empty`"
+        ));
+
+        for shape in [
+            Shape::Circle { radius: 1.0 },
+            Shape::Rect {
+                width: 1.0,
+                height: 2.0,
+            },
+            Shape::Square(3.0),
+            Shape::Line(0.0, 1.0, 2.0, 3.0),
+            Shape::Empty,
+        ] {
+            let value = shape.clone().into_js(&ctx).unwrap();
+            assert!(value.is_object());
+            assert_eq!(Shape::from_js(&ctx, value).unwrap(), shape);
+        }
+
+        ctx.globals().set("square", Shape::Square(3.0)).unwrap();
+        let valid: bool = ctx
+            .eval(r#"square.type === "Square" && square.value === 3"#)
+            .unwrap();
+        assert!(valid);
+
+        let wrapper: Wrapper = ctx
+            .eval(r#"({ type: "Value", value: "foo" })"#)
+            .catch(&ctx)
+            .unwrap();
+        let Wrapper::Value(value) = wrapper else {
+            panic!("expected the value variant")
+        };
+        assert_eq!(value.as_string().unwrap().to_string().unwrap(), "foo");
+    })
+}
@@ -0,0 +1,75 @@
+use rquickjs::{
+    class::{JsClass, Trace, Tracer},
+    CatchResultExt, Class, Context, JsLifetime, Object, Runtime,
+};
+
+#[derive(Trace, JsLifetime, JsClass)]
+#[qjs(name = "Point", rename_all = "camelCase")]
+pub struct RustPoint {
+    #[qjs(get, set)]
+    x_pos: f64,
+    #[qjs(get, set)]
+    y_pos: f64,
+}
+
+#[rquickjs::methods]
+impl RustPoint {
+    #[qjs(constructor)]
+    pub fn new(x_pos: f64, y_pos: f64) -> Self {
+        RustPoint { x_pos, y_pos }
+    }
+
+    pub fn length(&self) -> f64 {
+        (self.x_pos * self.x_pos + self.y_pos * self.y_pos).sqrt()
+    }
+}
+
+#[derive(Trace, JsLifetime, JsClass)]
+pub struct Holder<'js> {
+    #[qjs(get)]
+    object: Object<'js>,
+}
+
+#[derive(JsClass)]
+#[qjs(frozen)]
+pub struct Wrapper<T: Clone + Default> {
+    value: T,
+}
+
+impl<T: Clone + Default> Trace<'_> for Wrapper<T> {
+    fn trace<'a>(&self, _tracer: Tracer<'a, '_>) {}
+}
+
+unsafe impl<'js, T: Clone + Default + 'static> JsLifetime<'js> for Wrapper<T> {
+    type Changed<'to> = Wrapper<T>;
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        assert_eq!(<RustPoint as JsClass>::NAME, "Point");
+        assert_eq!(<Wrapper<u32> as JsClass>::NAME, "Wrapper");
+
+        Class::<RustPoint>::define(&ctx.globals()).unwrap();
+        let len: f64 = ctx
+            .eval("const p = new Point(3, 0); p.yPos = 4; p.length()")
+            .catch(&ctx)
+            .unwrap();
+        assert_eq!(len, 5.0);
+
+        let holder = Holder {
+            object: Object::new(ctx.clone()).unwrap(),
+        };
+        ctx.globals().set("holder", holder).unwrap();
+        let is_object: bool = ctx
+            .eval("typeof holder.object === 'object'")
+            .catch(&ctx)
+            .unwrap();
+        assert!(is_object);
+
+        let wrapper = Class::instance(ctx.clone(), Wrapper { value: 7u32 }).unwrap();
+        assert_eq!(wrapper.borrow().value, 7);
+    })
+}
@@ -0,0 +1,54 @@
+use rquickjs::{Context, Function, JsLifetime, Object, Persistent, Runtime, Value};
+
+#[derive(JsLifetime)]
+pub struct Callback<'js> {
+    func: Function<'js>,
+    obj: Object<'js>,
+    calls: usize,
+}
+
+#[derive(JsLifetime)]
+pub enum Slot<'js> {
+    Empty,
+    Value(Value<'js>),
+    Callbacks(Vec<Callback<'js>>),
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    let (callback, slot) = ctx.with(|ctx| {
+        let callback = Callback {
+            func: ctx.eval("(obj) => obj.value * 2").unwrap(),
+            obj: ctx.eval("({ value: 21 })").unwrap(),
+            calls: 0,
+        };
+        let slot = Slot::Value(ctx.eval("'stored'").unwrap());
+        (
+            Persistent::save(&ctx, callback),
+            Persistent::save(&ctx, slot),
+        )
+    });
+
+    ctx.with(|ctx| {
+        let mut callback = callback.restore(&ctx).unwrap();
+        let res: i32 = callback.func.call((callback.obj.clone(),)).unwrap();
+        callback.calls += 1;
+        assert_eq!(res, 42);
+        assert_eq!(callback.calls, 1);
+
+        let Slot::Value(value) = slot.restore(&ctx).unwrap() else {
+            panic!("restored the wrong variant");
+        };
+        assert_eq!(value.as_string().unwrap().to_string().unwrap(), "stored");
+
+        let slot = Persistent::save(&ctx, Slot::Callbacks(vec![callback]));
+        assert!(matches!(
+            slot.restore(&ctx).unwrap(),
+            Slot::Callbacks(callbacks) if callbacks.len() == 1
+        ));
+        let empty = Persistent::save(&ctx, Slot::Empty);
+        assert!(matches!(empty.restore(&ctx).unwrap(), Slot::Empty));
+    });
+}
@@ -0,0 +1,3 @@
+use rquickjs::class::JsClassSend;
+
+fn main() {}
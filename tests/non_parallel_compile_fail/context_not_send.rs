@@ -0,0 +1,15 @@
+use rquickjs::Context;
+
+trait AssertSend {
+    fn assert_send(self);
+}
+
+impl<T: Send> AssertSend for T {
+    fn assert_send(self) {}
+}
+
+fn check(ctx: Context) {
+    ctx.assert_send();
+}
+
+fn main() {}
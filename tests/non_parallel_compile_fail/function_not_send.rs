@@ -0,0 +1,15 @@
+use rquickjs::Function;
+
+trait AssertSend {
+    fn assert_send(self);
+}
+
+impl<T: Send> AssertSend for T {
+    fn assert_send(self) {}
+}
+
+fn check(func: Function<'_>) {
+    func.assert_send();
+}
+
+fn main() {}
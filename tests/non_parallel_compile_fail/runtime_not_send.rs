@@ -0,0 +1,15 @@
+use rquickjs::Runtime;
+
+trait AssertSend {
+    fn assert_send(self);
+}
+
+impl<T: Send> AssertSend for T {
+    fn assert_send(self) {}
+}
+
+fn check(rt: Runtime) {
+    rt.assert_send();
+}
+
+fn main() {}